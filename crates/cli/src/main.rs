@@ -0,0 +1,321 @@
+// The `muse` command, a thin shell around `muse-parser`: `muse parse`
+// prints the ESTree AST or the tokens of a file as JSON, and `muse check`
+// reports the syntax errors of a set of files the way compilers do.
+
+use std::{
+    fs,
+    io::{self, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand};
+use muse_parser::{
+    parse, parse_with_errors, read_tokenizer, to_json, EcmaVersion, Options, ParserError,
+    RenderStyle, SourceLocation, SourceType, Token, TokenValue,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// The input has syntax errors.
+const EXIT_SYNTAX_ERROR: u8 = 1;
+/// A file could not be read, or a pattern matched nothing.
+const EXIT_IO_ERROR: u8 = 2;
+
+#[derive(Parser)]
+#[command(name = "muse", version, about = "A small JavaScript parser")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the ESTree AST of a file as JSON.
+    Parse {
+        /// The file to parse, or `-` for the standard input.
+        file: PathBuf,
+        /// Prints the tokens instead, one JSON object per line.
+        #[arg(long)]
+        tokens: bool,
+        #[command(flatten)]
+        options: OptionArgs,
+    },
+    /// Reports the syntax errors of files.
+    Check {
+        /// The files to check, or glob patterns like `src/**/*.js`.
+        #[arg(required = true)]
+        patterns: Vec<String>,
+        #[command(flatten)]
+        options: OptionArgs,
+    },
+}
+
+#[derive(Args)]
+struct OptionArgs {
+    /// The ECMAScript version: 3, 5, 6 to 13, 2015 to 2022 or latest.
+    ///
+    /// The AST printed by `parse` always has the fields of the latest
+    /// version, like `optional` and `await`, which acorn leaves out for
+    /// older versions.
+    #[arg(long, value_name = "VERSION", default_value = "latest", value_parser = parse_ecma_version)]
+    ecma: EcmaVersion,
+    /// Parses the input as a module instead of a script.
+    #[arg(long)]
+    module: bool,
+    /// Adds `loc` line and column information to nodes and tokens.
+    #[arg(long)]
+    locations: bool,
+    /// Adds `range` offset pairs to nodes and tokens.
+    #[arg(long)]
+    ranges: bool,
+}
+
+impl OptionArgs {
+    fn to_options(&self, source_file: &str) -> Options {
+        Options {
+            ecma_version: self.ecma,
+            source_type: if self.module {
+                SourceType::Module
+            } else {
+                SourceType::Script
+            },
+            locations: self.locations,
+            ranges: self.ranges,
+            source_file: Some(source_file.to_owned()),
+            ..Default::default()
+        }
+    }
+}
+
+fn parse_ecma_version(value: &str) -> Result<EcmaVersion, String> {
+    Ok(match value {
+        "3" => EcmaVersion::Ecma3,
+        "5" => EcmaVersion::Ecma5,
+        "6" | "2015" => EcmaVersion::Ecma2015,
+        "7" | "2016" => EcmaVersion::Ecma2016,
+        "8" | "2017" => EcmaVersion::Ecma2017,
+        "9" | "2018" => EcmaVersion::Ecma2018,
+        "10" | "2019" => EcmaVersion::Ecma2019,
+        "11" | "2020" => EcmaVersion::Ecma2020,
+        "12" | "2021" => EcmaVersion::Ecma2021,
+        "13" | "2022" => EcmaVersion::Ecma2022,
+        "latest" => EcmaVersion::Latest,
+        _ => return Err(format!("unknown ECMAScript version `{:}`", value)),
+    })
+}
+
+fn render_style() -> RenderStyle {
+    if std::env::var_os("NO_COLOR").is_none() && io::stderr().is_terminal() {
+        RenderStyle::Ansi
+    } else {
+        RenderStyle::Plain
+    }
+}
+
+fn print_error(error: &ParserError, input: &str, name: &str) {
+    eprint!("{:}", error.render(input, Some(name), render_style()));
+}
+
+/// Reads `file`, or the standard input for `-`, along with the name to
+/// show in errors.
+fn read_input(file: &Path) -> io::Result<(String, String)> {
+    if file == Path::new("-") {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(("<stdin>".to_owned(), input))
+    } else {
+        Ok((file.display().to_string(), fs::read_to_string(file)?))
+    }
+}
+
+/// A token as printed by `muse parse --tokens`, with its type reduced to
+/// its label.
+#[derive(Serialize)]
+struct TokenJson<'a> {
+    #[serde(rename = "type")]
+    token_type: &'a str,
+    value: Value,
+    start: i32,
+    end: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    loc: Option<&'a SourceLocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    range: Option<(i32, i32)>,
+}
+
+impl<'a> From<&'a Token> for TokenJson<'a> {
+    fn from(token: &'a Token) -> Self {
+        TokenJson {
+            token_type: &token.token_type.label,
+            value: match &token.value {
+                TokenValue::Null => Value::Null,
+                TokenValue::String(value) | TokenValue::BigInt(value) => json!(value),
+                TokenValue::Number(value) => json!(value),
+                TokenValue::Regex { pattern, flags } => {
+                    json!({ "pattern": pattern, "flags": flags })
+                }
+            },
+            start: token.start,
+            end: token.end,
+            loc: token.loc.as_ref(),
+            range: token.range,
+        }
+    }
+}
+
+/// Prints the tokens of `file` as JSON as soon as they are read, stopping
+/// at the first error.
+fn run_tokens(file: &Path, options: &OptionArgs) -> ExitCode {
+    let (name, reader): (_, Box<dyn Read>) = if file == Path::new("-") {
+        ("<stdin>".to_owned(), Box::new(io::stdin()))
+    } else {
+        match fs::File::open(file) {
+            Ok(reader) => (file.display().to_string(), Box::new(reader)),
+            Err(error) => {
+                eprintln!("muse: {:}: {:}", file.display(), error);
+                return ExitCode::from(EXIT_IO_ERROR);
+            }
+        }
+    };
+    let options = options.to_options(&name);
+    let mut out = io::stdout().lock();
+    let mut tokens = read_tokenizer(reader, &options);
+    for token in &mut tokens {
+        match token {
+            Ok(token) => {
+                // Numbers are written like in the AST, `1` rather than `1.0`.
+                let json = to_json(&TokenJson::from(&token)).expect("a token always serializes");
+                let _ = writeln!(out, "{:}", json);
+            }
+            Err(ParserError::Io(error)) => {
+                eprintln!("muse: {:}: {:}", file.display(), error);
+                return ExitCode::from(EXIT_IO_ERROR);
+            }
+            Err(error) => {
+                print_error(&error, tokens.input(), &name);
+                return ExitCode::from(EXIT_SYNTAX_ERROR);
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn run_parse(file: &Path, tokens: bool, options: &OptionArgs) -> ExitCode {
+    if tokens {
+        return run_tokens(file, options);
+    }
+    let (name, input) = match read_input(file) {
+        Ok(result) => result,
+        Err(error) => {
+            eprintln!("muse: {:}: {:}", file.display(), error);
+            return ExitCode::from(EXIT_IO_ERROR);
+        }
+    };
+    let options = options.to_options(&name);
+    let result = parse(&input, &options).map(|program| {
+        let json = to_json(&program).expect("an AST always serializes");
+        let _ = writeln!(io::stdout().lock(), "{:}", json);
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            print_error(&error, &input, &name);
+            ExitCode::from(EXIT_SYNTAX_ERROR)
+        }
+    }
+}
+
+/// Expands the patterns to the files they match. Paths of existing files
+/// are taken as they are, even when they look like patterns.
+fn expand_patterns(patterns: &[String]) -> (Vec<PathBuf>, bool) {
+    let mut files = vec![];
+    let mut failed = false;
+    for pattern in patterns {
+        let path = Path::new(pattern);
+        if path.is_file() {
+            files.push(path.to_path_buf());
+            continue;
+        }
+        let matches = match glob::glob(pattern) {
+            Ok(paths) => paths,
+            Err(error) => {
+                eprintln!("muse: invalid pattern `{:}`: {:}", pattern, error);
+                failed = true;
+                continue;
+            }
+        };
+        let count = files.len();
+        for entry in matches {
+            match entry {
+                Ok(path) if path.is_file() => files.push(path),
+                Ok(_) => {}
+                Err(error) => {
+                    eprintln!("muse: {:}", error);
+                    failed = true;
+                }
+            }
+        }
+        if files.len() == count {
+            eprintln!("muse: {:}: no matching files", pattern);
+            failed = true;
+        }
+    }
+    (files, failed)
+}
+
+fn run_check(patterns: &[String], options: &OptionArgs) -> ExitCode {
+    let (files, mut failed) = expand_patterns(patterns);
+    let mut error_count = 0;
+    for file in &files {
+        let name = file.display().to_string();
+        let input = match fs::read_to_string(file) {
+            Ok(input) => input,
+            Err(error) => {
+                eprintln!("muse: {:}: {:}", name, error);
+                failed = true;
+                continue;
+            }
+        };
+        let options = Options {
+            collect_errors: true,
+            ..options.to_options(&name)
+        };
+        let errors = match parse_with_errors(&input, &options) {
+            Ok((_, errors)) => errors,
+            Err(errors) => errors,
+        };
+        for error in &errors {
+            print_error(error, &input, &name);
+            eprintln!();
+        }
+        error_count += errors.len();
+    }
+    if error_count > 0 {
+        eprintln!(
+            "muse: {:} error{:} in {:} file{:} checked",
+            error_count,
+            if error_count == 1 { "" } else { "s" },
+            files.len(),
+            if files.len() == 1 { "" } else { "s" }
+        );
+    }
+    if failed {
+        ExitCode::from(EXIT_IO_ERROR)
+    } else if error_count > 0 {
+        ExitCode::from(EXIT_SYNTAX_ERROR)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Parse {
+            file,
+            tokens,
+            options,
+        } => run_parse(&file, tokens, &options),
+        Command::Check { patterns, options } => run_check(&patterns, &options),
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn muse(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_muse"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn prints_numbers_of_tokens_like_the_ast() {
+    let output = muse(&["parse", "--tokens", "-"], "1 + 0x10 + 1.5");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        r#"{"type":"num","value":1,"start":0,"end":1}
+{"type":"+/-","value":"+","start":2,"end":3}
+{"type":"num","value":16,"start":4,"end":8}
+{"type":"+/-","value":"+","start":9,"end":10}
+{"type":"num","value":1.5,"start":11,"end":14}
+"#
+    );
+}
+
+#[test]
+fn checks_every_error_of_a_file() {
+    let dir = std::env::temp_dir().join(format!("muse-check-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("errors.js");
+    std::fs::write(&file, "let a; let a; var b = 1__0; @\n").unwrap();
+    let output = muse(&["check", file.to_str().unwrap()], "");
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let codes: Vec<&str> = stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix("error["))
+        .filter_map(|line| line.split(']').next())
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            "DuplicateBinding",
+            "InvalidNumericSeparator",
+            "UnexpectedCharacter"
+        ]
+    );
+    assert!(
+        stderr.ends_with("muse: 3 errors in 1 file checked\n"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,68 @@
+// The generated program the benchmarks parse. It repeats a chunk covering
+// the common syntax, with fresh names in every copy so that scopes grow
+// the way they do in real code.
+
+const CHUNK: &str = r#"
+import { helper_N as imported_N } from "./module_N.js";
+
+const config_N = { name: "item_N", retries: 3, ratio: 0.5e-3, pattern: /^[a-z]+\d*$/giu };
+
+export class Store_N extends Base {
+    #items = new Map();
+    static count = 0;
+
+    constructor(options = {}) {
+        super(options);
+        this.options = { ...config_N, ...options };
+    }
+
+    get size() {
+        return this.#items.size;
+    }
+
+    async load(keys) {
+        for (const key of keys) {
+            const { value, meta: [first, ...rest] = [] } = await imported_N(key);
+            if (value == null || typeof value !== "object") {
+                continue;
+            }
+            this.#items.set(key, { value, first, rest });
+        }
+        return this;
+    }
+
+    *entries() {
+        yield* this.#items.entries();
+    }
+}
+
+export function format_N(value, width = 8) {
+    let text = `${value}`.padStart(width, " ");
+    switch (typeof value) {
+        case "number":
+            text = value.toFixed(2) + (value > 1e6 ? "M" : value > 1e3 ? "k" : "");
+            break;
+        default:
+            text = String(value ?? "");
+    }
+    try {
+        return text.replace(/\s+/g, " ").trim();
+    } catch (error) {
+        throw new Error(`cannot format ${value}: ${error.message}`);
+    } finally {
+        Store_N.count++;
+    }
+}
+
+const handlers_N = [1, 2, 3].map((n) => (event) => event.detail?.value * n);
+var total_N = handlers_N.reduce((sum, handler, index) => sum + handler({ detail: { value: index } }), 0);
+label_N: while (total_N > 0) {
+    if (total_N-- % 7 === 0) break label_N;
+}
+"#;
+
+pub fn source(copies: usize) -> String {
+    (0..copies)
+        .map(|index| CHUNK.replace("_N", &format!("_{:}", index)))
+        .collect()
+}
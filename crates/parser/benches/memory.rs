@@ -0,0 +1,88 @@
+// Counts the allocations a parse makes and the memory its AST holds on
+// to, with and without locations.
+//
+//     cargo bench -p muse-parser --bench memory
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use muse_parser::{parse, Options, SourceType};
+
+mod common;
+
+use common::source;
+
+struct Counter;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counter {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        LIVE.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        LIVE.fetch_add(new_size, Ordering::Relaxed);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counter = Counter;
+
+fn measure(name: &str, input: &str, options: &Options) {
+    // Warms up the lazily built tables, which are not part of a parse.
+    drop(parse(input, options).unwrap());
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    let live = LIVE.load(Ordering::Relaxed);
+    let started = Instant::now();
+    let program = parse(input, options).unwrap();
+    let elapsed = started.elapsed();
+    println!(
+        "{:<12} {:>9} allocations {:>8.1} MB allocated {:>7.1} MB held by the AST {:>8.1?}",
+        name,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        (ALLOCATED.load(Ordering::Relaxed) - allocated) as f64 / 1e6,
+        (LIVE.load(Ordering::Relaxed) - live) as f64 / 1e6,
+        elapsed
+    );
+    drop(program);
+}
+
+fn main() {
+    let input = source(1000);
+    println!("parsing {:} bytes", input.len());
+    let options = Options {
+        source_type: SourceType::Module,
+        ..Options::default()
+    };
+    measure("plain", &input, &options);
+    measure(
+        "locations",
+        &input,
+        &Options {
+            locations: true,
+            ranges: true,
+            source_file: Some("bundle.js".to_owned()),
+            direct_source_file: Some("bundle.js".to_owned()),
+            ..options.clone()
+        },
+    );
+}
@@ -0,0 +1,47 @@
+// Parses and tokenizes generated programs of a few sizes.
+//
+//     cargo bench -p muse-parser --bench parse
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use muse_parser::{parse, tokenizer, Options, SourceType};
+
+mod common;
+
+use common::source;
+
+fn bench_parse(c: &mut Criterion) {
+    let options = Options {
+        source_type: SourceType::Module,
+        ..Options::default()
+    };
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    for copies in [10, 100, 1000] {
+        let input = source(copies);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(input.len()),
+            &input,
+            |b, input| b.iter(|| parse(black_box(input), &options).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn bench_tokenize(c: &mut Criterion) {
+    let options = Options {
+        source_type: SourceType::Module,
+        ..Options::default()
+    };
+    let input = source(1000);
+    let mut group = c.benchmark_group("tokenize");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function(BenchmarkId::from_parameter(input.len()), |b| {
+        b.iter(|| tokenizer(black_box(&input), &options).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_tokenize);
+criterion_main!(benches);
@@ -0,0 +1,111 @@
+// ### Atoms
+//
+// Identifier names, the values and raw text of literals and template
+// elements, and source file names are interned while parsing: all the
+// occurrences of a text in a program share one allocation, and copying one
+// to a node only bumps a reference count.
+
+use std::{borrow::Borrow, collections::HashSet, fmt, ops::Deref, sync::Arc};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An immutable, cheaply cloned string.
+#[derive(Clone, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Atom(Arc<str>);
+
+impl Atom {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Atom {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Atom {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Atom {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Atom {
+    fn from(text: &str) -> Self {
+        Atom(Arc::from(text))
+    }
+}
+
+impl From<String> for Atom {
+    fn from(text: String) -> Self {
+        Atom(Arc::from(text))
+    }
+}
+
+impl PartialEq<str> for Atom {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Atom {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Atom {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl fmt::Debug for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl Serialize for Atom {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Atom {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Atom::from)
+    }
+}
+
+/// The atoms of a parse, so that equal strings share their allocation.
+#[derive(Debug, Default)]
+pub struct Interner {
+    atoms: HashSet<Atom>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, text: &str) -> Atom {
+        if let Some(atom) = self.atoms.get(text) {
+            return atom.clone();
+        }
+        let atom = Atom::from(text);
+        self.atoms.insert(atom.clone());
+        atom
+    }
+}
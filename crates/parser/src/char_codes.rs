@@ -1,3 +1,6 @@
+// Not every character code of the table is referenced yet.
+#![allow(dead_code)]
+
 pub(crate) const BACK_SPACE: i32 = 0x08; // '<BS>'
 pub(crate) const TAB: i32 = 0x09; //  '\t'
 pub(crate) const LINE_FEED: i32 = 0x0a; //  '\n'
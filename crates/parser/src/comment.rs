@@ -0,0 +1,194 @@
+// ### Comments
+//
+// The tokenizer skips comments like whitespace. When `on_comment` is set
+// in the options, it also reports each of them, in the shape acorn gives
+// to its `onComment` option. `attach_comments` then hangs the collected
+// comments on the nodes around them, as `leadingComments` and
+// `trailingComments`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    location::{OffsetConverter, SourceLocation},
+    node::{Program, Spanned},
+    options::{OffsetUnit, Sink},
+    visit::VisitControl,
+    visit_mut::{NodeMut, VisitMut},
+    whitespace::{is_new_line, is_white_space},
+};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CommentKind {
+    /// A `/* */` comment.
+    Block,
+    /// A `//` comment, or a hashbang or HTML-like comment.
+    Line,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    #[serde(rename = "type")]
+    pub kind: CommentKind,
+    /// The text of the comment, without the delimiters.
+    pub value: String,
+    pub start: i32,
+    pub end: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loc: Option<SourceLocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<(i32, i32)>,
+}
+
+/// Where the comments found during a parse are reported.
+pub type OnComment = Sink<Comment>;
+
+/// A comment waiting to be attached, with its range extended over the
+/// whitespace and comments around it, up to the neighbouring tokens.
+struct PendingComment {
+    comment: Comment,
+    extended_start: i32,
+    extended_end: i32,
+    /// Whether the comment ends the line of the token before it.
+    ends_line: bool,
+}
+
+struct CommentAttacher {
+    pending: Vec<PendingComment>,
+    cursor: usize,
+    leading: bool,
+    /// Whether only the comments that end a line are attached.
+    ends_line_only: bool,
+}
+
+impl CommentAttacher {
+    /// Takes the pending comments whose extended range touches `pos` from
+    /// the side being attached. Comments that can no longer be attached on
+    /// that side are passed over.
+    fn take_comments(&mut self, pos: i32) -> Vec<Comment> {
+        let mut comments = vec![];
+        while let Some(pending) = self.pending.get(self.cursor) {
+            let edge = if self.leading {
+                pending.extended_end
+            } else {
+                pending.extended_start
+            };
+            if edge > pos {
+                break;
+            }
+            if edge == pos && (pending.ends_line || !self.ends_line_only) {
+                comments.push(self.pending.remove(self.cursor).comment);
+            } else {
+                self.cursor += 1;
+            }
+        }
+        comments
+    }
+}
+
+impl VisitMut for CommentAttacher {
+    fn enter(&mut self, mut node: NodeMut<'_>) -> VisitControl {
+        if self.leading {
+            let comments = self.take_comments(node.span().start);
+            if !comments.is_empty() {
+                let span = node.span_mut();
+                let attached = span.comments.get_or_insert_with(Default::default);
+                attached.leading.extend(comments);
+            }
+        }
+        VisitControl::Continue
+    }
+
+    fn leave(&mut self, mut node: NodeMut<'_>) {
+        if !self.leading {
+            let comments = self.take_comments(node.span().end);
+            if !comments.is_empty() {
+                let span = node.span_mut();
+                let attached = span.comments.get_or_insert_with(Default::default);
+                attached.trailing.extend(comments);
+            }
+        }
+    }
+}
+
+/// Attaches comments collected with `on_comment` from `input` to the nodes
+/// of its `program`. A comment on the line of the end of a node, with a
+/// line break after it, becomes a trailing comment of the innermost such
+/// node. Otherwise, a comment followed only by whitespace and comments up
+/// to the start of a node becomes a leading comment of the outermost such
+/// node. The remaining comments that follow the end of a node the same way
+/// become trailing comments of the innermost one. Comments inside an empty
+/// block or list are not attached.
+///
+/// `unit` is the `offset_unit` the comments and the program were parsed
+/// with.
+pub fn attach_comments(program: &mut Program, comments: &[Comment], input: &str, unit: OffsetUnit) {
+    let offsets = match unit {
+        OffsetUnit::Utf8 => OffsetConverter::default(),
+        _ => OffsetConverter::new(input),
+    };
+    let to_byte = |offset: i32| offsets.convert(offset, unit, OffsetUnit::Utf8) as usize;
+    let from_byte = |offset: usize| offsets.convert(offset as i32, OffsetUnit::Utf8, unit);
+    let mut pending: Vec<PendingComment> = comments
+        .iter()
+        .map(|comment| PendingComment {
+            comment: comment.clone(),
+            extended_start: comment.start,
+            extended_end: comment.end,
+            ends_line: false,
+        })
+        .collect();
+    for index in (0..pending.len()).rev() {
+        let mut end = to_byte(pending[index].comment.end);
+        while let Some(ch) = input[end..].chars().next() {
+            if !is_white_space(ch) {
+                break;
+            }
+            end += ch.len_utf8();
+        }
+        let end = from_byte(end);
+        pending[index].extended_end = match pending.get(index + 1) {
+            Some(next) if next.comment.start == end => next.extended_end,
+            _ => end,
+        };
+    }
+    for index in 0..pending.len() {
+        let mut start = to_byte(pending[index].comment.start);
+        while let Some(ch) = input[..start].chars().next_back() {
+            if !is_white_space(ch) {
+                break;
+            }
+            start -= ch.len_utf8();
+        }
+        let start = from_byte(start);
+        pending[index].extended_start = match index.checked_sub(1).map(|i| &pending[i]) {
+            Some(previous) if previous.comment.end == start => previous.extended_start,
+            _ => start,
+        };
+    }
+
+    for pending in &mut pending {
+        let before = &input[to_byte(pending.extended_start)..to_byte(pending.comment.start)];
+        let after_start = to_byte(pending.comment.end);
+        let after_end = to_byte(pending.extended_end);
+        let after = &input[after_start..after_end];
+        let has_new_line = |text: &str| text.chars().any(|ch| is_new_line(ch as i32));
+        pending.ends_line = pending.extended_start > 0
+            && !has_new_line(before)
+            && (after_end == input.len() || has_new_line(after));
+    }
+
+    let mut attacher = CommentAttacher {
+        pending,
+        cursor: 0,
+        leading: false,
+        ends_line_only: true,
+    };
+    attacher.visit_program(program);
+    attacher.cursor = 0;
+    attacher.leading = true;
+    attacher.ends_line_only = false;
+    attacher.visit_program(program);
+    attacher.cursor = 0;
+    attacher.leading = false;
+    attacher.visit_program(program);
+}
@@ -1,20 +1,182 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::location::Position;
+use crate::{location::Position, options::OffsetUnit};
+
+/// A stable identifier for each kind of error the parser raises. Messages
+/// may be reworded, the codes are not.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum ErrorCode {
+    // Tokenizer errors.
+    UnexpectedCharacter,
+    UnterminatedComment,
+    UnterminatedString,
+    UnterminatedTemplate,
+    UnterminatedRegex,
+    InvalidRegex,
+    InvalidRegexFlag,
+    DuplicateRegexFlag,
+    InvalidNumber,
+    IdentifierAfterNumber,
+    InvalidNumericSeparator,
+    InvalidEscape,
+    OctalEscape,
+    BadTemplateEscape,
+    EscapedKeyword,
+
+    // Parser errors.
+    UnexpectedToken,
+    ReservedWord,
+    AwaitIdentifier,
+    AwaitOutsideAsync,
+    YieldIdentifier,
+    ArgumentsInClassField,
+    InvalidInStaticBlock,
+    InvalidParameterDefault,
+    MixedCoalesce,
+    InvalidPrivateIn,
+    StrictDelete,
+    DeletePrivateField,
+    InvalidOptionalChain,
+    SuperOutsideMethod,
+    SuperCallOutsideConstructor,
+    InvalidImportCall,
+    InvalidMetaProperty,
+    ImportMetaOutsideModule,
+    NewTargetOutsideFunction,
+    RestTrailingComma,
+    InvalidAccessorParams,
+    UseStrictWithNonSimpleParams,
+    UndeclaredPrivateName,
+    InvalidAssignmentTarget,
+    InvalidDestructuring,
+    StrictModeBinding,
+    LetInLexicalBinding,
+    DuplicateParameter,
+    DuplicateBinding,
+    DuplicatePrivateName,
+    DuplicateProto,
+    DuplicateProperty,
+    UndefinedExport,
+    DuplicateExport,
+    InvalidExportName,
+    ImportExportNotTopLevel,
+    ImportExportOutsideModule,
+    UnsyntacticBreakContinue,
+    LetInForOf,
+    ForInOfInitializer,
+    ReturnOutsideFunction,
+    DuplicateDefaultClause,
+    NewlineAfterThrow,
+    MissingCatchOrFinally,
+    StrictWith,
+    DuplicateLabel,
+    DuplicateConstructor,
+    InvalidConstructor,
+    StaticPrototype,
+
+    // Errors that are not about the input.
+    InvalidOffset,
+    Io,
+    Internal,
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// A span of the input pointed at by an error besides its own, like the
+/// first declaration of a redeclared name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub start: i32,
+    pub end: i32,
+    pub message: String,
+}
 
 #[derive(Error, Debug)]
 pub enum ParserError {
-    #[error("fancy_regex::Error")]
+    #[error("{0}")]
     FancyRegexError(#[from] fancy_regex::Error),
 
-    #[error("{message:?}")]
+    /// An error in the input. The message ends with the line and column of
+    /// `pos`, like acorn's; `pos` and `end` delimit the offending text,
+    /// which is empty when it is not known.
+    #[error("{message}")]
     SyntaxError {
+        code: ErrorCode,
         message: String,
         pos: i32,
+        end: i32,
         loc: Position,
         raised_at: i32,
+        labels: Vec<Label>,
+        notes: Vec<String>,
+        /// What `pos`, `end`, `raised_at`, the column of `loc` and the
+        /// labels count.
+        unit: OffsetUnit,
     },
 
-    #[error("UnKnown error")]
-    UnKnown,
+    /// Raised internally while reading a template element that contains an
+    /// invalid escape sequence, so that the tokenizer can fall back to
+    /// reading it as an invalid template token.
+    #[error("Invalid template escape")]
+    InvalidTemplateEscape,
+
+    /// The offset passed to `parse_expression_at` is out of bounds or not on
+    /// a character boundary of the input.
+    #[error("Invalid offset {0}")]
+    InvalidOffset(i32),
+
+    /// Reading the input of a streaming tokenizer failed, or it was not
+    /// UTF-8.
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    /// A bug in the parser rather than an error in the input.
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl ParserError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ParserError::SyntaxError { code, .. } => *code,
+            ParserError::InvalidOffset(_) => ErrorCode::InvalidOffset,
+            ParserError::Io(_) => ErrorCode::Io,
+            _ => ErrorCode::Internal,
+        }
+    }
+
+    /// Sets where the offending text of a syntax error ends.
+    pub fn with_end(mut self, new_end: i32) -> Self {
+        if let ParserError::SyntaxError { end, .. } = &mut self {
+            *end = new_end;
+        }
+        self
+    }
+
+    /// Points a syntax error at another span of the input.
+    pub fn with_label(mut self, start: i32, end: i32, message: &str) -> Self {
+        if let ParserError::SyntaxError { labels, .. } = &mut self {
+            labels.push(Label {
+                start,
+                end,
+                message: message.to_owned(),
+            });
+        }
+        self
+    }
+
+    /// Adds a note, printed after the source of a syntax error.
+    pub fn with_note(mut self, note: &str) -> Self {
+        if let ParserError::SyntaxError { notes, .. } = &mut self {
+            notes.push(note.to_owned());
+        }
+        self
+    }
 }
@@ -0,0 +1,492 @@
+// ### ESTree JSON
+//
+// The AST serializes to the same JSON that `JSON.stringify` produces for
+// an acorn AST: every node is an object that starts with its `type`,
+// `start` and `end`, followed by `loc`, `sourceFile` and `range` when the
+// options asked for them, and then by its fields in the order acorn
+// assigns them. Absent optional children are `null`. Fields that acorn
+// only adds from some ECMAScript version on, like `optional` or `await`,
+// are always written, so the output matches acorn for the versions that
+// define all of them.
+//
+// The `type` of a node is not always the name of its struct. A `Function`
+// is a `FunctionDeclaration` or a `FunctionExpression` depending on where
+// it appears, so nodes held by one of the node enums take their type from
+// the name of the variant, and `EstreeNode::TYPE` is only used for nodes
+// that are stored directly in a field. Deserialization goes the other way
+// around and picks the variant from the `type` of the JSON object.
+
+use std::io;
+
+use serde::{
+    de::{self, DeserializeOwned},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{ser::Formatter, Value};
+
+use crate::{
+    atom::Atom, comment::Comment, location::SourceLocation, node::*, utils::number_to_string,
+};
+
+/// A node with its ESTree fields. The fields shared by all nodes are
+/// written by `serialize_node` from the node's `Span`.
+pub(crate) trait EstreeNode: Spanned {
+    /// The ESTree type of the node when it is not held by a node enum.
+    const TYPE: &'static str;
+
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error>;
+}
+
+/// A value that can be held by a variant of a node enum: either a boxed
+/// node, or another node enum.
+pub(crate) trait EstreeVariant: Sized {
+    /// Whether a node of type `node_type` belongs in the variant `variant`.
+    fn accepts(variant: &str, node_type: &str) -> bool;
+
+    fn serialize_variant<S: Serializer>(
+        &self,
+        variant: &'static str,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+
+    fn from_value(value: Value) -> serde_json::Result<Self>;
+}
+
+impl<T: EstreeNode + DeserializeOwned> EstreeVariant for Box<T> {
+    fn accepts(variant: &str, node_type: &str) -> bool {
+        variant == node_type
+    }
+
+    fn serialize_variant<S: Serializer>(
+        &self,
+        variant: &'static str,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_node(serializer, variant, &**self)
+    }
+
+    fn from_value(value: Value) -> serde_json::Result<Self> {
+        serde_json::from_value(value).map(Box::new)
+    }
+}
+
+/// Reads the `type` of a JSON node.
+pub(crate) fn node_type(value: &Value) -> Result<String, &'static str> {
+    value
+        .get("type")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or("missing node type")
+}
+
+pub(crate) fn serialize_node<S: Serializer, N: EstreeNode>(
+    serializer: S,
+    node_type: &str,
+    node: &N,
+) -> Result<S::Ok, S::Error> {
+    let span = node.span();
+    let mut map = serializer.serialize_map(None)?;
+    map.serialize_entry("type", node_type)?;
+    map.serialize_entry("start", &span.start)?;
+    map.serialize_entry("end", &span.end)?;
+    if let Some(loc) = &span.loc {
+        map.serialize_entry("loc", loc)?;
+    }
+    if let Some(source_file) = &span.source_file {
+        map.serialize_entry("sourceFile", source_file)?;
+    }
+    if let Some((start, end)) = span.range {
+        map.serialize_entry("range", &[start, end])?;
+    }
+    node.serialize_fields(&mut map)?;
+    if let Some(comments) = &span.comments {
+        if !comments.leading.is_empty() {
+            map.serialize_entry("leadingComments", &comments.leading)?;
+        }
+        if !comments.trailing.is_empty() {
+            map.serialize_entry("trailingComments", &comments.trailing)?;
+        }
+    }
+    map.end()
+}
+
+/// The fields of a JSON node that make up its `Span`.
+#[derive(Deserialize)]
+pub(crate) struct SpanFields {
+    start: i32,
+    end: i32,
+    loc: Option<SourceLocation>,
+    range: Option<(i32, i32)>,
+    #[serde(rename = "sourceFile")]
+    source_file: Option<Atom>,
+    #[serde(rename = "leadingComments", default)]
+    leading_comments: Vec<Comment>,
+    #[serde(rename = "trailingComments", default)]
+    trailing_comments: Vec<Comment>,
+}
+
+impl From<SpanFields> for Span {
+    fn from(fields: SpanFields) -> Self {
+        let comments = if fields.leading_comments.is_empty() && fields.trailing_comments.is_empty()
+        {
+            None
+        } else {
+            Some(Box::new(NodeComments {
+                leading: fields.leading_comments,
+                trailing: fields.trailing_comments,
+            }))
+        };
+        Span {
+            start: fields.start,
+            end: fields.end,
+            loc: fields.loc,
+            range: fields.range,
+            source_file: fields.source_file,
+            comments,
+        }
+    }
+}
+
+macro_rules! impl_serialize {
+    ($($node:ident),* $(,)?) => {
+        $(
+            impl Serialize for $node {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize_node(serializer, Self::TYPE, self)
+                }
+            }
+        )*
+    };
+}
+
+/// Implements `EstreeNode` and `Serialize` for nodes whose ESTree fields
+/// map one to one to struct fields.
+macro_rules! estree_nodes {
+    ($($node:ident { $($key:literal => $field:ident),* $(,)? })*) => {
+        $(
+            impl EstreeNode for $node {
+                const TYPE: &'static str = stringify!($node);
+
+                #[allow(unused_variables)]
+                fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+                    $(map.serialize_entry($key, &self.$field)?;)*
+                    Ok(())
+                }
+            }
+
+            impl_serialize!($node);
+        )*
+    };
+}
+
+estree_nodes! {
+    Program { "body" => body, "sourceType" => source_type }
+    BlockStatement { "body" => body }
+    EmptyStatement {}
+    DebuggerStatement {}
+    WithStatement { "object" => object, "body" => body }
+    ReturnStatement { "argument" => argument }
+    LabeledStatement { "body" => body, "label" => label }
+    BreakStatement { "label" => label }
+    ContinueStatement { "label" => label }
+    IfStatement { "test" => test, "consequent" => consequent, "alternate" => alternate }
+    SwitchStatement { "discriminant" => discriminant, "cases" => cases }
+    SwitchCase { "consequent" => consequent, "test" => test }
+    ThrowStatement { "argument" => argument }
+    TryStatement { "block" => block, "handler" => handler, "finalizer" => finalizer }
+    CatchClause { "param" => param, "body" => body }
+    WhileStatement { "test" => test, "body" => body }
+    DoWhileStatement { "body" => body, "test" => test }
+    ForStatement { "init" => init, "test" => test, "update" => update, "body" => body }
+    ForInStatement { "left" => left, "right" => right, "body" => body }
+    ForOfStatement { "await" => is_await, "left" => left, "right" => right, "body" => body }
+    VariableDeclaration { "declarations" => declarations, "kind" => kind }
+    VariableDeclarator { "id" => id, "init" => init }
+    ClassBody { "body" => body }
+    MethodDefinition {
+        "static" => is_static,
+        "computed" => computed,
+        "key" => key,
+        "kind" => kind,
+        "value" => value,
+    }
+    PropertyDefinition {
+        "static" => is_static,
+        "computed" => computed,
+        "key" => key,
+        "value" => value,
+    }
+    StaticBlock { "body" => body }
+    Identifier { "name" => name }
+    PrivateIdentifier { "name" => name }
+    ThisExpression {}
+    Super {}
+    ArrayExpression { "elements" => elements }
+    SpreadElement { "argument" => argument }
+    ObjectExpression { "properties" => properties }
+    TemplateLiteral { "expressions" => expressions, "quasis" => quasis }
+    TemplateElement { "value" => value, "tail" => tail }
+    TaggedTemplateExpression { "tag" => tag, "quasi" => quasi }
+    MemberExpression {
+        "object" => object,
+        "property" => property,
+        "computed" => computed,
+        "optional" => optional,
+    }
+    CallExpression { "callee" => callee, "arguments" => arguments, "optional" => optional }
+    NewExpression { "callee" => callee, "arguments" => arguments }
+    ChainExpression { "expression" => expression }
+    SequenceExpression { "expressions" => expressions }
+    UpdateExpression { "operator" => operator, "prefix" => prefix, "argument" => argument }
+    BinaryExpression { "left" => left, "operator" => operator, "right" => right }
+    LogicalExpression { "left" => left, "operator" => operator, "right" => right }
+    AssignmentExpression { "operator" => operator, "left" => left, "right" => right }
+    ConditionalExpression {
+        "test" => test,
+        "consequent" => consequent,
+        "alternate" => alternate,
+    }
+    YieldExpression { "delegate" => delegate, "argument" => argument }
+    AwaitExpression { "argument" => argument }
+    MetaProperty { "meta" => meta, "property" => property }
+    ImportExpression { "source" => source }
+    ParenthesizedExpression { "expression" => expression }
+    ObjectPattern { "properties" => properties }
+    ArrayPattern { "elements" => elements }
+    RestElement { "argument" => argument }
+    AssignmentPattern { "left" => left, "right" => right }
+    ImportDeclaration { "specifiers" => specifiers, "source" => source }
+    ImportSpecifier { "imported" => imported, "local" => local }
+    ImportDefaultSpecifier { "local" => local }
+    ImportNamespaceSpecifier { "local" => local }
+    ExportNamedDeclaration {
+        "declaration" => declaration,
+        "specifiers" => specifiers,
+        "source" => source,
+    }
+    ExportSpecifier { "local" => local, "exported" => exported }
+    ExportDefaultDeclaration { "declaration" => declaration }
+    ExportAllDeclaration { "exported" => exported, "source" => source }
+}
+
+// The nodes below have fields that acorn sets although they carry no
+// information in our AST, or fields whose order depends on the node.
+
+impl EstreeNode for ExpressionStatement {
+    const TYPE: &'static str = "ExpressionStatement";
+
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry("expression", &self.expression)?;
+        if let Some(directive) = &self.directive {
+            map.serialize_entry("directive", directive)?;
+        }
+        Ok(())
+    }
+}
+
+impl EstreeNode for Function {
+    const TYPE: &'static str = "FunctionExpression";
+
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry("id", &self.id)?;
+        map.serialize_entry("expression", &false)?;
+        map.serialize_entry("generator", &self.generator)?;
+        map.serialize_entry("async", &self.is_async)?;
+        map.serialize_entry("params", &self.params)?;
+        map.serialize_entry("body", &self.body)
+    }
+}
+
+impl EstreeNode for ArrowFunctionExpression {
+    const TYPE: &'static str = "ArrowFunctionExpression";
+
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        let expression = matches!(self.body, ArrowFunctionBody::Expression(_));
+        map.serialize_entry("id", &())?;
+        map.serialize_entry("expression", &expression)?;
+        map.serialize_entry("generator", &false)?;
+        map.serialize_entry("async", &self.is_async)?;
+        map.serialize_entry("params", &self.params)?;
+        map.serialize_entry("body", &self.body)
+    }
+}
+
+impl EstreeNode for Class {
+    const TYPE: &'static str = "ClassExpression";
+
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry("id", &self.id)?;
+        map.serialize_entry("superClass", &self.super_class)?;
+        map.serialize_entry("body", &self.body)
+    }
+}
+
+impl EstreeNode for UnaryExpression {
+    const TYPE: &'static str = "UnaryExpression";
+
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry("operator", &self.operator)?;
+        map.serialize_entry("prefix", &true)?;
+        map.serialize_entry("argument", &self.argument)
+    }
+}
+
+// Acorn assigns `kind` before `value` for methods, accessors and shorthand
+// properties, and after it for `key: value` properties.
+
+impl EstreeNode for Property {
+    const TYPE: &'static str = "Property";
+
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry("method", &self.method)?;
+        map.serialize_entry("shorthand", &self.shorthand)?;
+        map.serialize_entry("computed", &self.computed)?;
+        map.serialize_entry("key", &self.key)?;
+        if self.method || self.shorthand || self.kind != PropertyKind::Init {
+            map.serialize_entry("kind", &self.kind)?;
+            map.serialize_entry("value", &self.value)
+        } else {
+            map.serialize_entry("value", &self.value)?;
+            map.serialize_entry("kind", &self.kind)
+        }
+    }
+}
+
+impl EstreeNode for AssignmentProperty {
+    const TYPE: &'static str = "Property";
+
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry("method", &false)?;
+        map.serialize_entry("shorthand", &self.shorthand)?;
+        map.serialize_entry("computed", &self.computed)?;
+        map.serialize_entry("key", &self.key)?;
+        if self.shorthand {
+            map.serialize_entry("kind", &PropertyKind::Init)?;
+            map.serialize_entry("value", &self.value)
+        } else {
+            map.serialize_entry("value", &self.value)?;
+            map.serialize_entry("kind", &PropertyKind::Init)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RegexValue {
+    pattern: Atom,
+    flags: Atom,
+}
+
+/// The largest integer below which every integer is exactly representable
+/// as an `f64`.
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+impl EstreeNode for Literal {
+    const TYPE: &'static str = "Literal";
+
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        match &self.value {
+            LiteralValue::Null => map.serialize_entry("value", &())?,
+            LiteralValue::Boolean(value) => map.serialize_entry("value", value)?,
+            // Integers are written without a fractional part, as
+            // JavaScript does, even by a plain `serde_json` serializer.
+            LiteralValue::Number(value)
+                if value.fract() == 0.0 && value.abs() <= MAX_SAFE_INTEGER =>
+            {
+                map.serialize_entry("value", &(*value as i64))?
+            }
+            LiteralValue::Number(value) => map.serialize_entry("value", value)?,
+            LiteralValue::String(value) => map.serialize_entry("value", value)?,
+            // `JSON.stringify` cannot represent a `BigInt`, the digits are
+            // in the `bigint` field.
+            LiteralValue::BigInt(_) => map.serialize_entry("value", &())?,
+            // A `RegExp` object has no enumerable properties.
+            LiteralValue::Regex { .. } => map.serialize_entry("value", &serde_json::Map::new())?,
+        }
+        map.serialize_entry("raw", &self.raw)?;
+        match &self.value {
+            LiteralValue::BigInt(value) => map.serialize_entry("bigint", value),
+            LiteralValue::Regex { pattern, flags } => map.serialize_entry(
+                "regex",
+                &RegexValue {
+                    pattern: pattern.clone(),
+                    flags: flags.clone(),
+                },
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl_serialize!(
+    ExpressionStatement,
+    Function,
+    ArrowFunctionExpression,
+    Class,
+    UnaryExpression,
+    Property,
+    AssignmentProperty,
+    Literal,
+);
+
+impl<'de> Deserialize<'de> for Literal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct EstreeLiteral {
+            #[serde(flatten)]
+            span: Span,
+            #[serde(default)]
+            value: Value,
+            raw: Atom,
+            regex: Option<RegexValue>,
+            bigint: Option<Atom>,
+        }
+
+        let literal = EstreeLiteral::deserialize(deserializer)?;
+        let value = if let Some(RegexValue { pattern, flags }) = literal.regex {
+            LiteralValue::Regex { pattern, flags }
+        } else if let Some(bigint) = literal.bigint {
+            LiteralValue::BigInt(bigint)
+        } else {
+            match literal.value {
+                Value::Null => LiteralValue::Null,
+                Value::Bool(value) => LiteralValue::Boolean(value),
+                Value::Number(value) => LiteralValue::Number(value.as_f64().unwrap_or(f64::NAN)),
+                Value::String(value) => LiteralValue::String(value.into()),
+                _ => return Err(de::Error::custom("invalid literal value")),
+            }
+        };
+        Ok(Literal {
+            span: literal.span,
+            value,
+            raw: literal.raw,
+        })
+    }
+}
+
+/// Formats numbers the way `JSON.stringify` does.
+struct EstreeFormatter;
+
+impl Formatter for EstreeFormatter {
+    fn write_f32<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: f32) -> io::Result<()> {
+        self.write_f64(writer, value as f64)
+    }
+
+    fn write_f64<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: f64) -> io::Result<()> {
+        writer.write_all(number_to_string(value).as_bytes())
+    }
+}
+
+/// Serializes a node, or any value holding nodes, to the JSON that
+/// `JSON.stringify` produces for the acorn AST of the same source.
+///
+/// The fields that acorn only adds from some `ecma_version` on, like
+/// `optional`, `await`, `async` or `computed`, are always written, so for
+/// older versions the output has fields that acorn's does not.
+pub fn to_json<T: ?Sized + Serialize>(value: &T) -> serde_json::Result<String> {
+    let mut writer = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut writer, EstreeFormatter);
+    value.serialize(&mut serializer)?;
+    // The serializer only writes valid UTF-8.
+    Ok(String::from_utf8(writer).unwrap())
+}
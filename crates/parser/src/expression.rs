@@ -1,41 +1,2285 @@
+// A recursive descent parser operates by defining functions for all
+// syntactic elements, and recursively calling those, each function
+// advancing the input stream and returning an AST node. Precedence
+// of constructs (for example, the fact that `!x[1]` means `!(x[1])`
+// instead of `(!x)[1]` is handled by the fact that the parser
+// function that parses unary prefix operators is called first, and
+// in turn calls the function that parses `[]` subscripts — that
+// way, it'll receive the node for `x[1]` already parsed, and wraps
+// *that* in the unary operator node.
+//
+// Acorn uses an [operator precedence parser][opp] to handle binary
+// operator precedence, because it is much more compact than using
+// the technique outlined above, which uses different, nesting
+// functions to specify precedence, for all of the ten binary
+// precedence levels that JavaScript defines.
+//
+// [opp]: http://en.wikipedia.org/wiki/Operator-precedence_parser
+
+use std::collections::{HashMap, HashSet};
+
 use crate::{
-    errors::ParserError, location::Position, node::Node, parser::Parser, utils::DestructuringErrors,
+    atom::Atom,
+    char_codes::DOT,
+    errors::{ErrorCode, ParserError},
+    location::{LocationParser, Position},
+    lval::LvalParser,
+    node::{
+        ArrayExpression, ArrowFunctionBody, ArrowFunctionExpression, AssignmentExpression,
+        AssignmentOperator, AssignmentProperty, AwaitExpression, BinaryExpression, BinaryOperator,
+        BlockStatement, CallExpression, ChainExpression, ConditionalExpression, Expression,
+        ExpressionOrSpread, Function, Identifier, ImportExpression, Literal, LiteralValue,
+        LogicalExpression, LogicalOperator, MemberExpression, MetaProperty, NewExpression,
+        NodeParser, ObjectExpression, ObjectPattern, ParenthesizedExpression, Pattern,
+        PrivateIdentifier, Property, PropertyKind, PropertyOrRest, PropertyOrSpread, RestElement,
+        SequenceExpression, Span, Spanned, SpreadElement, Super, TaggedTemplateExpression,
+        TemplateElement, TemplateElementValue, TemplateLiteral, ThisExpression, UnaryExpression,
+        UnaryOperator, UpdateExpression, UpdateOperator, YieldExpression,
+    },
+    options::SourceType,
+    parser::Parser,
+    scope::{
+        function_flags, ScopeParser, BIND_NONE, BIND_OUTSIDE, BIND_VAR, SCOPE_ARROW,
+        SCOPE_DIRECT_SUPER, SCOPE_SUPER,
+    },
+    statement::StatementParser,
+    token::{
+        context::{get_token_context_types, TokenContextParser},
+        types::{get_token_types, TokenType},
+        TokenParser, TokenValue,
+    },
+    utils::{number_to_string, DestructuringErrors, UtilsParser},
+    whitespace::get_line_break_regex,
 };
 
+pub type AfterLeftParse = Box<dyn Fn(Expression, &Option<i32>, &Option<Position>) -> Expression>;
+
+/// The kinds of each property name seen so far in an object literal, used
+/// to detect redefinitions.
+#[derive(Debug, Default)]
+pub struct PropHash {
+    proto: bool,
+    kinds: HashMap<String, PropKinds>,
+}
+
+#[derive(Debug, Default)]
+struct PropKinds {
+    init: bool,
+    get: bool,
+    set: bool,
+}
+
+/// Maps the value of an operator token to the operator of a node. The
+/// tokenizer only produces operator tokens with known values.
+pub(crate) fn operator_from_token<T>(value: &str, from_token: fn(&str) -> Option<T>) -> T {
+    from_token(value).unwrap_or_else(|| unreachable!("unknown operator '{:}'", value))
+}
+
+fn is_private_field_access(expr: &Expression) -> bool {
+    match expr {
+        Expression::MemberExpression(member) => {
+            matches!(member.property, Expression::PrivateIdentifier(_))
+        }
+        Expression::ChainExpression(chain) => is_private_field_access(&chain.expression),
+        Expression::ParenthesizedExpression(paren) => is_private_field_access(&paren.expression),
+        _ => false,
+    }
+}
+
 pub trait ExpressionParser {
+    fn check_prop_clash(
+        &mut self,
+        prop: &Property,
+        prop_hash: &mut PropHash,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<(), ParserError>;
+    fn parse_expression(
+        &mut self,
+        for_init: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<Expression, ParserError>;
     fn parse_maybe_assign(
+        &mut self,
+        for_init: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        after_left_parse: Option<AfterLeftParse>,
+    ) -> Result<Expression, ParserError>;
+    fn parse_maybe_conditional(
+        &mut self,
+        for_init: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<Expression, ParserError>;
+    fn parse_expr_ops(
+        &mut self,
+        for_init: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<Expression, ParserError>;
+    fn parse_expr_op(
+        &mut self,
+        left: Expression,
+        left_start_pos: i32,
+        left_start_loc: &Option<Position>,
+        min_prec: i32,
+        for_init: bool,
+    ) -> Result<Expression, ParserError>;
+    fn build_binary(
         &self,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        left: Expression,
+        right: Expression,
+        operator: &str,
+        is_logical: bool,
+    ) -> Result<Expression, ParserError>;
+    fn parse_maybe_unary(
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        saw_unary: bool,
+        is_inc_dec: bool,
+        for_init: bool,
+    ) -> Result<Expression, ParserError>;
+    fn parse_expr_subscripts(
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        for_init: bool,
+    ) -> Result<Expression, ParserError>;
+    fn parse_subscripts(
+        &mut self,
+        base: Expression,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        no_calls: bool,
         for_init: bool,
-        destructuring_errors: &Option<DestructuringErrors>,
-        after_left_parse: Option<Box<dyn Fn(&Node, &Option<i32>, &Option<Position>) -> Node>>,
-    ) -> Result<Node, ParserError>;
+    ) -> Result<Expression, ParserError>;
+    fn should_parse_async_arrow(&mut self) -> Result<bool, ParserError>;
+    #[allow(clippy::too_many_arguments)]
+    fn parse_subscript(
+        &mut self,
+        base: Expression,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        no_calls: bool,
+        maybe_async_arrow: bool,
+        optional_chained: bool,
+        for_init: bool,
+    ) -> Result<(Expression, bool), ParserError>;
+    fn parse_expr_atom(
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        for_init: bool,
+    ) -> Result<Expression, ParserError>;
+    fn parse_expr_import(&mut self) -> Result<Expression, ParserError>;
+    fn parse_dynamic_import(&mut self, span: Span) -> Result<ImportExpression, ParserError>;
+    fn parse_import_meta(
+        &mut self,
+        span: Span,
+        meta: Identifier,
+    ) -> Result<MetaProperty, ParserError>;
+    fn parse_literal(&mut self, value: LiteralValue) -> Result<Literal, ParserError>;
+    fn parse_paren_expression(&mut self) -> Result<Expression, ParserError>;
+    fn parse_paren_and_distinguish_expression(
+        &mut self,
+        can_be_arrow: bool,
+        for_init: bool,
+    ) -> Result<Expression, ParserError>;
+    fn parse_new(&mut self) -> Result<Expression, ParserError>;
+    fn parse_template_element(&mut self, is_tagged: bool) -> Result<TemplateElement, ParserError>;
+    fn parse_template(&mut self, is_tagged: bool) -> Result<TemplateLiteral, ParserError>;
     fn parse_obj(
-        &self,
-        is_pattern: bool,
-        destructuring_errors: &Option<DestructuringErrors>,
-    ) -> Result<Node, ParserError>;
-    fn parse_ident(&self, is_liberal: bool) -> Result<Node, ParserError>;
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<ObjectExpression, ParserError>;
+    fn parse_obj_pattern(&mut self) -> Result<ObjectPattern, ParserError>;
+    fn parse_property(
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<PropertyOrSpread, ParserError>;
+    fn parse_property_pattern(&mut self) -> Result<PropertyOrRest, ParserError>;
+    fn parse_getter_setter(
+        &mut self,
+        span: Span,
+        kind: PropertyKind,
+    ) -> Result<Property, ParserError>;
+    #[allow(clippy::too_many_arguments)]
+    fn parse_property_value(
+        &mut self,
+        span: Span,
+        computed: bool,
+        key: Expression,
+        is_generator: bool,
+        is_async: bool,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        contains_esc: bool,
+    ) -> Result<Property, ParserError>;
+    fn parse_property_name(&mut self) -> Result<(bool, Expression), ParserError>;
+    fn is_async_prop(&self, computed: bool, key: &Expression) -> Result<bool, ParserError>;
+    fn parse_method(
+        &mut self,
+        is_generator: bool,
+        is_async: bool,
+        allow_direct_super: bool,
+    ) -> Result<Function, ParserError>;
+    fn parse_arrow_expression(
+        &mut self,
+        span: Span,
+        params: Vec<ExpressionOrSpread>,
+        rest: Option<RestElement>,
+        is_async: bool,
+        for_init: bool,
+    ) -> Result<ArrowFunctionExpression, ParserError>;
+    fn parse_function_body(
+        &mut self,
+        start: i32,
+        id: Option<&Identifier>,
+        params: &[Pattern],
+        is_arrow_function: bool,
+        is_method: bool,
+    ) -> Result<BlockStatement, ParserError>;
+    fn is_simple_param_list(&self, params: &[Pattern]) -> bool;
+    fn check_params(
+        &mut self,
+        params: &[Pattern],
+        allow_duplicates: bool,
+    ) -> Result<(), ParserError>;
+    fn parse_expr_list(
+        &mut self,
+        close: &TokenType,
+        allow_trailing_comma: bool,
+        allow_empty: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<Vec<Option<ExpressionOrSpread>>, ParserError>;
+    fn check_unreserved(&mut self, id: &Identifier) -> Result<(), ParserError>;
+    fn parse_ident(&mut self, is_liberal: bool) -> Result<Identifier, ParserError>;
+    fn parse_private_ident(&mut self) -> Result<PrivateIdentifier, ParserError>;
+    fn parse_yield(&mut self, for_init: bool) -> Result<YieldExpression, ParserError>;
+    fn parse_await(&mut self, for_init: bool) -> Result<AwaitExpression, ParserError>;
 }
 
 impl ExpressionParser for Parser {
+    /// Check if property name clashes with already added.
+    /// Object/class getters and setters are not allowed to clash —
+    /// either with each other or with an init property — and in
+    /// strict mode, init properties are also not allowed to be repeated.
+    fn check_prop_clash(
+        &mut self,
+        prop: &Property,
+        prop_hash: &mut PropHash,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<(), ParserError> {
+        let ecma_version = self.options.get_ecma_version_number();
+        if ecma_version >= 6 && (prop.computed || prop.method || prop.shorthand) {
+            return Ok(());
+        }
+        let key = &prop.key;
+        let name = match key {
+            Expression::Identifier(id) => id.name.to_string(),
+            Expression::Literal(literal) => match &literal.value {
+                LiteralValue::String(value) => value.to_string(),
+                LiteralValue::BigInt(value) => value.to_string(),
+                LiteralValue::Number(value) => number_to_string(*value),
+                _ => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+        let key_start = key.span().start;
+        if ecma_version >= 6 {
+            if name == "__proto__" && prop.kind == PropertyKind::Init {
+                if prop_hash.proto {
+                    match destructuring_errors {
+                        Some(destructuring_errors) => {
+                            if destructuring_errors.double_proto < 0 {
+                                destructuring_errors.double_proto = key_start;
+                            }
+                        }
+                        None => self.raise_recoverable(
+                            key_start,
+                            ErrorCode::DuplicateProto,
+                            "Redefinition of __proto__ property",
+                        )?,
+                    }
+                }
+                prop_hash.proto = true;
+            }
+            return Ok(());
+        }
+        let other = prop_hash.kinds.entry(name).or_default();
+        let redefinition = match prop.kind {
+            PropertyKind::Init => self.is_strict && other.init || other.get || other.set,
+            PropertyKind::Get => other.init || other.get,
+            PropertyKind::Set => other.init || other.set,
+        };
+        if redefinition {
+            self.raise_recoverable(
+                key_start,
+                ErrorCode::DuplicateProperty,
+                "Redefinition of property",
+            )?;
+        }
+        match prop.kind {
+            PropertyKind::Init => other.init = true,
+            PropertyKind::Get => other.get = true,
+            PropertyKind::Set => other.set = true,
+        }
+        Ok(())
+    }
+
+    /// Parse a full expression. The arguments are used to forbid the `in`
+    /// operator (in for loops initalization expressions) and provide
+    /// reference for storing '=' operator inside shorthand property
+    /// assignment in contexts where both object expression and object
+    /// pattern might appear (so it's possible to raise delayed syntax
+    /// error at correct position).
+    fn parse_expression(
+        &mut self,
+        for_init: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<Expression, ParserError> {
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        let expr = self.parse_maybe_assign(for_init, destructuring_errors, None)?;
+        let token_types = get_token_types();
+        if self.cur_token_type.eq(&token_types.comma) {
+            let span = self.start_node_at(start_pos, &start_loc);
+            let mut expressions = vec![expr];
+            while self.eat(&token_types.comma)? {
+                expressions.push(self.parse_maybe_assign(for_init, destructuring_errors, None)?);
+            }
+            return Ok(Expression::SequenceExpression(Box::new(
+                SequenceExpression {
+                    span: self.finish_node(span),
+                    expressions,
+                },
+            )));
+        }
+        Ok(expr)
+    }
+
+    /// Parse an assignment expression. This includes applications of
+    /// operators like `+=`.
     fn parse_maybe_assign(
+        &mut self,
+        for_init: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        after_left_parse: Option<AfterLeftParse>,
+    ) -> Result<Expression, ParserError> {
+        if self.is_contextual("yield") {
+            if self.in_generator() {
+                return Ok(Expression::YieldExpression(Box::new(
+                    self.parse_yield(for_init)?,
+                )));
+            }
+            // The tokenizer will assume an expression is allowed after
+            // `yield`, but this isn't that kind of yield
+            self.expr_allowed = false;
+        }
+
+        let own_destructuring_errors = destructuring_errors.is_none();
+        let mut own_errors = Some(DestructuringErrors::default());
+        let destructuring_errors = if own_destructuring_errors {
+            &mut own_errors
+        } else {
+            destructuring_errors
+        };
+        let (old_parenthesized_assign, old_trailing_comma, old_double_proto) =
+            match destructuring_errors.as_mut() {
+                Some(errors) => {
+                    let old = (
+                        errors.parenthesized_assign,
+                        errors.trailing_comma,
+                        errors.double_proto,
+                    );
+                    errors.parenthesized_assign = -1;
+                    errors.trailing_comma = -1;
+                    old
+                }
+                None => (-1, -1, -1),
+            };
+
+        let token_types = get_token_types();
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        if self.cur_token_type.eq(&token_types.paren_l) || self.cur_token_type.eq(&token_types.name)
+        {
+            self.potential_arrow_at = start_pos;
+            // The flag is raised by the `for await` statement for its init
+            // expression, and only survives while parsing that expression.
+            self.is_potential_arrow_in_for_await = for_init && self.is_potential_arrow_in_for_await;
+        }
+        let mut left = self.parse_maybe_conditional(for_init, destructuring_errors)?;
+        if let Some(after_left_parse) = after_left_parse {
+            left = after_left_parse(left, &Some(start_pos), &start_loc);
+        }
+        let cur_token_type = self.cur_token_type.clone();
+        if cur_token_type.is_assign {
+            let span = self.start_node_at(start_pos, &start_loc);
+            let operator = operator_from_token(
+                self.cur_token_value.as_str(),
+                AssignmentOperator::from_token,
+            );
+            let is_eq = cur_token_type.eq(&token_types.eq);
+            let left_start = left.span().start;
+            let left = if is_eq {
+                self.to_assignable(left, false, destructuring_errors)?
+            } else {
+                self.check_lval_simple(&left, BIND_NONE, &mut None)?;
+                self.to_simple_assignable(left)?
+            };
+            if let Some(errors) = destructuring_errors.as_mut() {
+                if !own_destructuring_errors {
+                    errors.parenthesized_assign = -1;
+                    errors.trailing_comma = -1;
+                    errors.double_proto = -1;
+                }
+                // reset because shorthand default was used correctly
+                if errors.shorthand_assign >= left_start {
+                    errors.shorthand_assign = -1;
+                }
+            }
+            if is_eq {
+                self.check_lval_pattern(&left, BIND_NONE, &mut None)?;
+            }
+            self.next(false)?;
+            let right = self.parse_maybe_assign(for_init, &mut None, None)?;
+            if old_double_proto > -1 {
+                if let Some(errors) = destructuring_errors.as_mut() {
+                    errors.double_proto = old_double_proto;
+                }
+            }
+            return Ok(Expression::AssignmentExpression(Box::new(
+                AssignmentExpression {
+                    span: self.finish_node(span),
+                    operator,
+                    left,
+                    right,
+                },
+            )));
+        } else if own_destructuring_errors {
+            self.check_expression_errors(destructuring_errors, true)?;
+        }
+        if let Some(errors) = destructuring_errors.as_mut() {
+            if old_parenthesized_assign > -1 {
+                errors.parenthesized_assign = old_parenthesized_assign;
+            }
+            if old_trailing_comma > -1 {
+                errors.trailing_comma = old_trailing_comma;
+            }
+        }
+        Ok(left)
+    }
+
+    /// Parse a ternary conditional (`?:`) operator.
+    fn parse_maybe_conditional(
+        &mut self,
+        for_init: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<Expression, ParserError> {
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        let expr = self.parse_expr_ops(for_init, destructuring_errors)?;
+        if self.check_expression_errors(destructuring_errors, false)? {
+            return Ok(expr);
+        }
+        let token_types = get_token_types();
+        if self.eat(&token_types.question)? {
+            let span = self.start_node_at(start_pos, &start_loc);
+            let consequent = self.parse_maybe_assign(false, &mut None, None)?;
+            self.expect(&token_types.colon)?;
+            let alternate = self.parse_maybe_assign(for_init, &mut None, None)?;
+            return Ok(Expression::ConditionalExpression(Box::new(
+                ConditionalExpression {
+                    span: self.finish_node(span),
+                    test: expr,
+                    consequent,
+                    alternate,
+                },
+            )));
+        }
+        Ok(expr)
+    }
+
+    /// Start the precedence parser.
+    fn parse_expr_ops(
+        &mut self,
+        for_init: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<Expression, ParserError> {
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        let expr = self.parse_maybe_unary(destructuring_errors, false, false, for_init)?;
+        if self.check_expression_errors(destructuring_errors, false)?
+            || (expr.span().start == start_pos
+                && matches!(expr, Expression::ArrowFunctionExpression(_)))
+        {
+            return Ok(expr);
+        }
+        self.parse_expr_op(expr, start_pos, &start_loc, -1, for_init)
+    }
+
+    /// Parse binary operators with the operator precedence parsing
+    /// algorithm. `left` is the left-hand side of the operator.
+    /// `min_prec` provides context that allows the function to stop and
+    /// defer further parser to one of its callers when it encounters an
+    /// operator that has a lower precedence than the set it is parsing.
+    fn parse_expr_op(
+        &mut self,
+        left: Expression,
+        left_start_pos: i32,
+        left_start_loc: &Option<Position>,
+        min_prec: i32,
+        for_init: bool,
+    ) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let cur_token_type = self.cur_token_type.clone();
+        if let Some(mut prec) = cur_token_type.binop {
+            if (!for_init || cur_token_type.ne(&token_types._in)) && prec > min_prec {
+                let is_logical = cur_token_type.eq(&token_types.logical_or)
+                    || cur_token_type.eq(&token_types.logical_and);
+                let is_coalesce = cur_token_type.eq(&token_types.coalesce);
+                if is_coalesce {
+                    // Handle the precedence of `??` as equal to the range of
+                    // logical expressions. In other words, `node.right`
+                    // shouldn't contain logical expressions in order to check
+                    // the mixed error.
+                    prec = token_types.logical_and.binop.unwrap_or(prec);
+                }
+                let operator = self.cur_token_value.as_str().to_owned();
+                self.next(false)?;
+                let start_pos = self.cur_token_start;
+                let start_loc = self.cur_token_start_loc.clone();
+                let right = self.parse_maybe_unary(&mut None, false, false, for_init)?;
+                let right = self.parse_expr_op(right, start_pos, &start_loc, prec, for_init)?;
+                let node = self.build_binary(
+                    left_start_pos,
+                    left_start_loc,
+                    left,
+                    right,
+                    &operator,
+                    is_logical || is_coalesce,
+                )?;
+                let next_token_type = self.cur_token_type.clone();
+                if (is_logical && next_token_type.eq(&token_types.coalesce))
+                    || (is_coalesce
+                        && (next_token_type.eq(&token_types.logical_or)
+                            || next_token_type.eq(&token_types.logical_and)))
+                {
+                    self.raise_recoverable(
+                        self.cur_token_start,
+                        ErrorCode::MixedCoalesce,
+                        "Logical expressions and coalesce expressions cannot be mixed. Wrap \
+                         either by parentheses",
+                    )?;
+                }
+                return self.parse_expr_op(
+                    node,
+                    left_start_pos,
+                    left_start_loc,
+                    min_prec,
+                    for_init,
+                );
+            }
+        }
+        Ok(left)
+    }
+
+    fn build_binary(
         &self,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        left: Expression,
+        right: Expression,
+        operator: &str,
+        is_logical: bool,
+    ) -> Result<Expression, ParserError> {
+        if let Expression::PrivateIdentifier(right) = &right {
+            self.raise(
+                right.span.start,
+                ErrorCode::InvalidPrivateIn,
+                "Private identifier can only be left side of binary expression",
+            )?;
+        }
+        let span = self.finish_node(self.start_node_at(start_pos, start_loc));
+        Ok(if is_logical {
+            Expression::LogicalExpression(Box::new(LogicalExpression {
+                span,
+                left,
+                operator: operator_from_token(operator, LogicalOperator::from_token),
+                right,
+            }))
+        } else {
+            Expression::BinaryExpression(Box::new(BinaryExpression {
+                span,
+                left,
+                operator: operator_from_token(operator, BinaryOperator::from_token),
+                right,
+            }))
+        })
+    }
+
+    /// Parse unary operators, both prefix and postfix.
+    fn parse_maybe_unary(
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        saw_unary: bool,
+        is_inc_dec: bool,
+        for_init: bool,
+    ) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        let mut saw_unary = saw_unary;
+        let cur_token_type = self.cur_token_type.clone();
+        let mut expr;
+        if self.is_contextual("await") && self.can_await() {
+            expr = Expression::AwaitExpression(Box::new(self.parse_await(for_init)?));
+            saw_unary = true;
+        } else if cur_token_type.prefix {
+            let span = self.start_node();
+            let is_update = cur_token_type.eq(&token_types.inc_dec);
+            let operator = self.cur_token_value.as_str().to_owned();
+            self.next(false)?;
+            let argument = self.parse_maybe_unary(&mut None, true, is_update, for_init)?;
+            self.check_expression_errors(destructuring_errors, true)?;
+            if is_update {
+                self.check_lval_simple(&argument, BIND_NONE, &mut None)?;
+            } else if self.is_strict
+                && operator == "delete"
+                && matches!(argument, Expression::Identifier(_))
+            {
+                self.raise_recoverable(
+                    span.start,
+                    ErrorCode::StrictDelete,
+                    "Deleting local variable in strict mode",
+                )?;
+            } else if operator == "delete" && is_private_field_access(&argument) {
+                self.raise_recoverable(
+                    span.start,
+                    ErrorCode::DeletePrivateField,
+                    "Private fields can not be deleted",
+                )?;
+            } else {
+                saw_unary = true;
+            }
+            let span = self.finish_node(span);
+            expr = if is_update {
+                Expression::UpdateExpression(Box::new(UpdateExpression {
+                    span,
+                    operator: operator_from_token(&operator, UpdateOperator::from_token),
+                    prefix: true,
+                    argument,
+                }))
+            } else {
+                Expression::UnaryExpression(Box::new(UnaryExpression {
+                    span,
+                    operator: operator_from_token(&operator, UnaryOperator::from_token),
+                    argument,
+                }))
+            };
+        } else if !saw_unary && cur_token_type.eq(&token_types.private_id) {
+            if for_init || self.private_name_stack.is_empty() {
+                self.unexpected(None)?;
+            }
+            expr = Expression::PrivateIdentifier(Box::new(self.parse_private_ident()?));
+            // only could be private fields in 'in', such as #x in obj
+            if self.cur_token_type.ne(&token_types._in) {
+                self.unexpected(None)?;
+            }
+        } else {
+            expr = self.parse_expr_subscripts(destructuring_errors, for_init)?;
+            if self.check_expression_errors(destructuring_errors, false)? {
+                return Ok(expr);
+            }
+            while self.cur_token_type.postfix && !self.can_insert_semicolon() {
+                let span = self.start_node_at(start_pos, &start_loc);
+                let operator =
+                    operator_from_token(self.cur_token_value.as_str(), UpdateOperator::from_token);
+                self.check_lval_simple(&expr, BIND_NONE, &mut None)?;
+                self.next(false)?;
+                expr = Expression::UpdateExpression(Box::new(UpdateExpression {
+                    span: self.finish_node(span),
+                    operator,
+                    prefix: false,
+                    argument: expr,
+                }));
+            }
+        }
+
+        if !is_inc_dec && self.eat(&token_types.star_star)? {
+            if saw_unary {
+                self.unexpected(Some(self.last_token_start))?;
+            }
+            let right = self.parse_maybe_unary(&mut None, false, false, for_init)?;
+            return self.build_binary(start_pos, &start_loc, expr, right, "**", false);
+        }
+        Ok(expr)
+    }
+
+    /// Parse call, dot, and `[]`-subscript expressions.
+    fn parse_expr_subscripts(
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        for_init: bool,
+    ) -> Result<Expression, ParserError> {
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        let expr = self.parse_expr_atom(destructuring_errors, for_init)?;
+        if matches!(expr, Expression::ArrowFunctionExpression(_))
+            && &self.input[self.last_token_start as usize..self.last_token_end as usize] != ")"
+        {
+            return Ok(expr);
+        }
+        let result = self.parse_subscripts(expr, start_pos, &start_loc, false, for_init)?;
+        if let Some(errors) = destructuring_errors.as_mut() {
+            if let Expression::MemberExpression(member) = &result {
+                let start = member.span.start;
+                if errors.parenthesized_assign >= start {
+                    errors.parenthesized_assign = -1;
+                }
+                if errors.parenthesized_bind >= start {
+                    errors.parenthesized_bind = -1;
+                }
+                if errors.trailing_comma >= start {
+                    errors.trailing_comma = -1;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    fn parse_subscripts(
+        &mut self,
+        base: Expression,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        no_calls: bool,
+        for_init: bool,
+    ) -> Result<Expression, ParserError> {
+        let maybe_async_arrow = self.options.get_ecma_version_number() >= 8
+            && match &base {
+                Expression::Identifier(id) => {
+                    id.name == "async"
+                        && self.last_token_end == id.span.end
+                        && !self.can_insert_semicolon()
+                        && id.span.end - id.span.start == 5
+                        && self.potential_arrow_at == id.span.start
+                }
+                _ => false,
+            };
+        let mut base = base;
+        let mut optional_chained = false;
+        loop {
+            let (element, is_subscript) = self.parse_subscript(
+                base,
+                start_pos,
+                start_loc,
+                no_calls,
+                maybe_async_arrow,
+                optional_chained,
+                for_init,
+            )?;
+
+            let is_optional = match &element {
+                Expression::MemberExpression(member) => member.optional,
+                Expression::CallExpression(call) => call.optional,
+                _ => false,
+            };
+            if is_optional {
+                optional_chained = true;
+            }
+            if !is_subscript || matches!(element, Expression::ArrowFunctionExpression(_)) {
+                if optional_chained {
+                    let span = self.start_node_at(start_pos, start_loc);
+                    return Ok(Expression::ChainExpression(Box::new(ChainExpression {
+                        span: self.finish_node(span),
+                        expression: element,
+                    })));
+                }
+                return Ok(element);
+            }
+
+            base = element;
+        }
+    }
+
+    fn should_parse_async_arrow(&mut self) -> Result<bool, ParserError> {
+        Ok(!self.can_insert_semicolon() && self.eat(&get_token_types().arrow)?)
+    }
+
+    /// Parse a single subscript of `base`. The returned flag tells whether a
+    /// subscript was found, otherwise `base` is handed back as is.
+    fn parse_subscript(
+        &mut self,
+        base: Expression,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        no_calls: bool,
+        maybe_async_arrow: bool,
+        optional_chained: bool,
+        for_init: bool,
+    ) -> Result<(Expression, bool), ParserError> {
+        let token_types = get_token_types();
+        let ecma_version = self.options.get_ecma_version_number();
+        let optional_supported = ecma_version >= 11;
+        let optional = optional_supported && self.eat(&token_types.question_dot)?;
+        if no_calls && optional {
+            self.raise(
+                self.last_token_start,
+                ErrorCode::InvalidOptionalChain,
+                "Optional chaining cannot appear in the callee of new expressions",
+            )?;
+        }
+
+        let computed = self.eat(&token_types.bracket_l)?;
+        if computed
+            || (optional
+                && self.cur_token_type.ne(&token_types.paren_l)
+                && self.cur_token_type.ne(&token_types.back_quote))
+            || self.eat(&token_types.dot)?
+        {
+            let span = self.start_node_at(start_pos, start_loc);
+            let is_super = matches!(base, Expression::Super(_));
+            let property = if computed {
+                let property = self.parse_expression(false, &mut None)?;
+                self.expect(&token_types.bracket_r)?;
+                property
+            } else if self.cur_token_type.eq(&token_types.private_id) && !is_super {
+                Expression::PrivateIdentifier(Box::new(self.parse_private_ident()?))
+            } else {
+                Expression::Identifier(Box::new(self.parse_ident(true)?))
+            };
+            let member = MemberExpression {
+                span: self.finish_node(span),
+                object: base,
+                property,
+                computed,
+                optional,
+            };
+            Ok((Expression::MemberExpression(Box::new(member)), true))
+        } else if !no_calls && self.eat(&token_types.paren_l)? {
+            let mut destructuring_errors = Some(DestructuringErrors::default());
+            let old_yield_pos = self.yield_pos;
+            let old_await_pos = self.await_pos;
+            let old_await_ident_pos = self.await_ident_pos;
+            self.yield_pos = None;
+            self.await_pos = None;
+            self.await_ident_pos = 0;
+            let expr_list = self.parse_expr_list(
+                &token_types.paren_r,
+                ecma_version >= 8,
+                false,
+                &mut destructuring_errors,
+            )?;
+            if maybe_async_arrow && !optional && self.should_parse_async_arrow()? {
+                self.check_pattern_errors(&destructuring_errors, false)?;
+                self.check_yield_await_in_default_params()?;
+                if self.await_ident_pos > 0 {
+                    self.raise(
+                        self.await_ident_pos,
+                        ErrorCode::AwaitIdentifier,
+                        "Cannot use 'await' as identifier inside an async function",
+                    )?;
+                }
+                self.yield_pos = old_yield_pos;
+                self.await_pos = old_await_pos;
+                self.await_ident_pos = old_await_ident_pos;
+                let arrow = self.parse_arrow_expression(
+                    self.start_node_at(start_pos, start_loc),
+                    expr_list.into_iter().flatten().collect(),
+                    None,
+                    true,
+                    for_init,
+                )?;
+                return Ok((Expression::ArrowFunctionExpression(Box::new(arrow)), true));
+            }
+            self.check_expression_errors(&destructuring_errors, true)?;
+            self.yield_pos = old_yield_pos.or(self.yield_pos);
+            self.await_pos = old_await_pos.or(self.await_pos);
+            if old_await_ident_pos != 0 {
+                self.await_ident_pos = old_await_ident_pos;
+            }
+            let span = self.start_node_at(start_pos, start_loc);
+            let call = CallExpression {
+                span: self.finish_node(span),
+                callee: base,
+                arguments: expr_list.into_iter().flatten().collect(),
+                optional,
+            };
+            Ok((Expression::CallExpression(Box::new(call)), true))
+        } else if self.cur_token_type.eq(&token_types.back_quote) {
+            if optional || optional_chained {
+                self.raise(
+                    self.cur_token_start,
+                    ErrorCode::InvalidOptionalChain,
+                    "Optional chaining cannot appear in the tag of tagged template expressions",
+                )?;
+            }
+            let span = self.start_node_at(start_pos, start_loc);
+            let quasi = self.parse_template(true)?;
+            let tagged = TaggedTemplateExpression {
+                span: self.finish_node(span),
+                tag: base,
+                quasi,
+            };
+            Ok((Expression::TaggedTemplateExpression(Box::new(tagged)), true))
+        } else {
+            Ok((base, false))
+        }
+    }
+
+    /// Parse an atomic expression — either a single token that is an
+    /// expression, an expression started by a keyword like `function` or
+    /// `new`, or an expression wrapped in punctuation like `()`, `[]`,
+    /// or `{}`.
+    fn parse_expr_atom(
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        for_init: bool,
+    ) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        // If a division operator appears in an expression position, the
+        // tokenizer got confused, and we force it to read a regexp instead.
+        if self.cur_token_type.eq(&token_types.slash) {
+            self.read_regexp()?;
+        }
+
+        let can_be_arrow = self.potential_arrow_at == self.cur_token_start;
+
+        let cur_token_type = self.cur_token_type.clone();
+        if cur_token_type.eq(&token_types._super) {
+            if !self.allow_super() {
+                self.raise(
+                    self.cur_token_start,
+                    ErrorCode::SuperOutsideMethod,
+                    "'super' keyword outside a method",
+                )?;
+            }
+            let span = self.start_node();
+            self.next(false)?;
+            if self.cur_token_type.eq(&token_types.paren_l) && !self.allow_direct_super() {
+                self.raise(
+                    span.start,
+                    ErrorCode::SuperCallOutsideConstructor,
+                    "super() call outside constructor of a subclass",
+                )?;
+            }
+            // The `super` keyword can appear at below:
+            // SuperProperty:
+            //     super [ Expression ]
+            //     super . IdentifierName
+            // SuperCall:
+            //     super ( Arguments )
+            let next_token_type = self.cur_token_type.clone();
+            if next_token_type.ne(&token_types.dot)
+                && next_token_type.ne(&token_types.bracket_l)
+                && next_token_type.ne(&token_types.paren_l)
+            {
+                self.unexpected(None)?;
+            }
+            Ok(Expression::Super(Box::new(Super {
+                span: self.finish_node(span),
+            })))
+        } else if cur_token_type.eq(&token_types._this) {
+            let span = self.start_node();
+            self.next(false)?;
+            Ok(Expression::ThisExpression(Box::new(ThisExpression {
+                span: self.finish_node(span),
+            })))
+        } else if cur_token_type.eq(&token_types.name) {
+            let start_pos = self.cur_token_start;
+            let start_loc = self.cur_token_start_loc.clone();
+            let contains_esc = self.contains_esc;
+            let id = self.parse_ident(false)?;
+            let ecma_version = self.options.get_ecma_version_number();
+            if ecma_version >= 8
+                && !contains_esc
+                && id.name == "async"
+                && !self.can_insert_semicolon()
+                && self.eat(&token_types._function)?
+            {
+                self.override_context(&get_token_context_types().f_expr);
+                let function =
+                    self.parse_function(self.start_node_at(start_pos, &start_loc), 0, true)?;
+                return Ok(Expression::FunctionExpression(Box::new(function)));
+            }
+            if can_be_arrow && !self.can_insert_semicolon() {
+                if self.eat(&token_types.arrow)? {
+                    let arrow = self.parse_arrow_expression(
+                        self.start_node_at(start_pos, &start_loc),
+                        vec![ExpressionOrSpread::Expression(Expression::Identifier(
+                            Box::new(id),
+                        ))],
+                        None,
+                        false,
+                        for_init,
+                    )?;
+                    return Ok(Expression::ArrowFunctionExpression(Box::new(arrow)));
+                }
+                if ecma_version >= 8
+                    && id.name == "async"
+                    && self.cur_token_type.eq(&token_types.name)
+                    && !contains_esc
+                    && (!self.is_potential_arrow_in_for_await
+                        || self.cur_token_value.as_str() != "of"
+                        || self.contains_esc)
+                {
+                    let id = self.parse_ident(false)?;
+                    if self.can_insert_semicolon() || !self.eat(&token_types.arrow)? {
+                        self.unexpected(None)?;
+                    }
+                    let arrow = self.parse_arrow_expression(
+                        self.start_node_at(start_pos, &start_loc),
+                        vec![ExpressionOrSpread::Expression(Expression::Identifier(
+                            Box::new(id),
+                        ))],
+                        None,
+                        true,
+                        for_init,
+                    )?;
+                    return Ok(Expression::ArrowFunctionExpression(Box::new(arrow)));
+                }
+            }
+            Ok(Expression::Identifier(Box::new(id)))
+        } else if cur_token_type.eq(&token_types.regexp)
+            || cur_token_type.eq(&token_types.num)
+            || cur_token_type.eq(&token_types.string)
+        {
+            let value = match self.cur_token_value.clone() {
+                TokenValue::String(value) => LiteralValue::String(self.atoms.intern(&value)),
+                TokenValue::Number(value) => LiteralValue::Number(value),
+                TokenValue::BigInt(value) => LiteralValue::BigInt(self.atoms.intern(&value)),
+                TokenValue::Regex { pattern, flags } => LiteralValue::Regex {
+                    pattern: self.atoms.intern(&pattern),
+                    flags: self.atoms.intern(&flags),
+                },
+                TokenValue::Null => LiteralValue::Null,
+            };
+            Ok(Expression::Literal(Box::new(self.parse_literal(value)?)))
+        } else if cur_token_type.eq(&token_types._null)
+            || cur_token_type.eq(&token_types._true)
+            || cur_token_type.eq(&token_types._false)
+        {
+            let span = self.start_node();
+            let value = if cur_token_type.eq(&token_types._null) {
+                LiteralValue::Null
+            } else {
+                LiteralValue::Boolean(cur_token_type.eq(&token_types._true))
+            };
+            let raw = self
+                .atoms
+                .intern(cur_token_type.keyword.as_deref().unwrap_or_default());
+            self.next(false)?;
+            Ok(Expression::Literal(Box::new(Literal {
+                span: self.finish_node(span),
+                value,
+                raw,
+            })))
+        } else if cur_token_type.eq(&token_types.paren_l) {
+            let start = self.cur_token_start;
+            let expr = self.parse_paren_and_distinguish_expression(can_be_arrow, for_init)?;
+            if let Some(errors) = destructuring_errors.as_mut() {
+                if errors.parenthesized_assign < 0 && !self.is_simple_assign_target(&expr) {
+                    errors.parenthesized_assign = start;
+                }
+                if errors.parenthesized_bind < 0 {
+                    errors.parenthesized_bind = start;
+                }
+            }
+            Ok(expr)
+        } else if cur_token_type.eq(&token_types.bracket_l) {
+            let span = self.start_node();
+            self.next(false)?;
+            let elements =
+                self.parse_expr_list(&token_types.bracket_r, true, true, destructuring_errors)?;
+            Ok(Expression::ArrayExpression(Box::new(ArrayExpression {
+                span: self.finish_node(span),
+                elements,
+            })))
+        } else if cur_token_type.eq(&token_types.brace_l) {
+            self.override_context(&get_token_context_types().b_expr);
+            Ok(Expression::ObjectExpression(Box::new(
+                self.parse_obj(destructuring_errors)?,
+            )))
+        } else if cur_token_type.eq(&token_types._function) {
+            let span = self.start_node();
+            self.next(false)?;
+            Ok(Expression::FunctionExpression(Box::new(
+                self.parse_function(span, 0, false)?,
+            )))
+        } else if cur_token_type.eq(&token_types._class) {
+            Ok(Expression::ClassExpression(Box::new(self.parse_class(
+                self.start_node(),
+                false,
+                false,
+            )?)))
+        } else if cur_token_type.eq(&token_types._new) {
+            self.parse_new()
+        } else if cur_token_type.eq(&token_types.back_quote) {
+            Ok(Expression::TemplateLiteral(Box::new(
+                self.parse_template(false)?,
+            )))
+        } else if cur_token_type.eq(&token_types._import)
+            && self.options.get_ecma_version_number() >= 11
+        {
+            self.parse_expr_import()
+        } else {
+            self.unexpected(None)?;
+            unreachable!()
+        }
+    }
+
+    fn parse_expr_import(&mut self) -> Result<Expression, ParserError> {
+        let span = self.start_node();
+
+        // Consume `import` as an identifier for `import.meta`.
+        // Because `parse_ident(true)` doesn't check escape sequences, it needs
+        // the check of `contains_esc`.
+        if self.contains_esc {
+            self.raise_recoverable(
+                self.cur_token_start,
+                ErrorCode::EscapedKeyword,
+                "Escape sequence in keyword import",
+            )?;
+        }
+        let meta = self.parse_ident(true)?;
+
+        let token_types = get_token_types();
+        let cur_token_type = self.cur_token_type.clone();
+        if cur_token_type.eq(&token_types.paren_l) {
+            Ok(Expression::ImportExpression(Box::new(
+                self.parse_dynamic_import(span)?,
+            )))
+        } else if cur_token_type.eq(&token_types.dot) {
+            Ok(Expression::MetaProperty(Box::new(
+                self.parse_import_meta(span, meta)?,
+            )))
+        } else {
+            self.unexpected(None)?;
+            unreachable!()
+        }
+    }
+
+    fn parse_dynamic_import(&mut self, span: Span) -> Result<ImportExpression, ParserError> {
+        // skip `(`
+        self.next(false)?;
+
+        // Parse node.source.
+        let source = self.parse_maybe_assign(false, &mut None, None)?;
+
+        // Verify ending.
+        let token_types = get_token_types();
+        if !self.eat(&token_types.paren_r)? {
+            let error_pos = self.cur_token_start;
+            if self.eat(&token_types.comma)? && self.eat(&token_types.paren_r)? {
+                self.raise_recoverable(
+                    error_pos,
+                    ErrorCode::InvalidImportCall,
+                    "Trailing comma is not allowed in import()",
+                )?;
+            } else {
+                self.unexpected(Some(error_pos))?;
+            }
+        }
+
+        Ok(ImportExpression {
+            span: self.finish_node(span),
+            source,
+        })
+    }
+
+    fn parse_import_meta(
+        &mut self,
+        span: Span,
+        meta: Identifier,
+    ) -> Result<MetaProperty, ParserError> {
+        // skip `.`
+        self.next(false)?;
+
+        let contains_esc = self.contains_esc;
+        let property = self.parse_ident(true)?;
+
+        if property.name != "meta" {
+            self.raise_recoverable(
+                property.span.start,
+                ErrorCode::InvalidMetaProperty,
+                "The only valid meta property for import is 'import.meta'",
+            )?;
+        }
+        if contains_esc {
+            self.raise_recoverable(
+                span.start,
+                ErrorCode::EscapedKeyword,
+                "'import.meta' must not contain escaped characters",
+            )?;
+        }
+        if self.options.source_type != SourceType::Module
+            && !self.options.allow_import_export_everywhere
+        {
+            self.raise_recoverable(
+                span.start,
+                ErrorCode::ImportMetaOutsideModule,
+                "Cannot use 'import.meta' outside a module",
+            )?;
+        }
+
+        Ok(MetaProperty {
+            span: self.finish_node(span),
+            meta,
+            property,
+        })
+    }
+
+    fn parse_literal(&mut self, value: LiteralValue) -> Result<Literal, ParserError> {
+        let span = self.start_node();
+        let raw = self
+            .atoms
+            .intern(&self.input[self.cur_token_start as usize..self.cur_token_end as usize]);
+        self.next(false)?;
+        Ok(Literal {
+            span: self.finish_node(span),
+            value,
+            raw,
+        })
+    }
+
+    fn parse_paren_expression(&mut self) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        self.expect(&token_types.paren_l)?;
+        let value = self.parse_expression(false, &mut None)?;
+        self.expect(&token_types.paren_r)?;
+        Ok(value)
+    }
+
+    fn parse_paren_and_distinguish_expression(
+        &mut self,
+        can_be_arrow: bool,
         for_init: bool,
-        destructuring_errors: &Option<DestructuringErrors>,
-        after_left_parse: Option<Box<dyn Fn(&Node, &Option<i32>, &Option<Position>) -> Node>>,
-    ) -> Result<Node, ParserError> {
-        todo!()
+    ) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let ecma_version = self.options.get_ecma_version_number();
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        let allow_trailing_comma = ecma_version >= 8;
+        let value = if ecma_version >= 6 {
+            self.next(false)?;
+
+            let inner_start_pos = self.cur_token_start;
+            let inner_start_loc = self.cur_token_start_loc.clone();
+            let mut expr_list = vec![];
+            let mut rest = None;
+            let mut is_first = true;
+            let mut last_is_comma = false;
+            let mut destructuring_errors = Some(DestructuringErrors::default());
+            let old_yield_pos = self.yield_pos;
+            let old_await_pos = self.await_pos;
+            self.yield_pos = None;
+            self.await_pos = None;
+            // Do not save await_ident_pos to allow checking awaits nested in
+            // parameters
+            while self.cur_token_type.ne(&token_types.paren_r) {
+                if is_first {
+                    is_first = false;
+                } else {
+                    self.expect(&token_types.comma)?;
+                }
+                if allow_trailing_comma && self.after_trailing_comma(&token_types.paren_r, true)? {
+                    last_is_comma = true;
+                    break;
+                } else if self.cur_token_type.eq(&token_types.ellipsis) {
+                    rest = Some(self.parse_rest_binding()?);
+                    if self.cur_token_type.eq(&token_types.comma) {
+                        self.raise(
+                            self.cur_token_start,
+                            ErrorCode::RestTrailingComma,
+                            "Comma is not permitted after the rest element",
+                        )?;
+                    }
+                    break;
+                } else {
+                    expr_list.push(self.parse_maybe_assign(
+                        false,
+                        &mut destructuring_errors,
+                        None,
+                    )?);
+                }
+            }
+            let inner_end_pos = self.last_token_end;
+            let inner_end_loc = self.last_token_end_loc.clone();
+            self.expect(&token_types.paren_r)?;
+
+            if can_be_arrow && !self.can_insert_semicolon() && self.eat(&token_types.arrow)? {
+                self.check_pattern_errors(&destructuring_errors, false)?;
+                self.check_yield_await_in_default_params()?;
+                self.yield_pos = old_yield_pos;
+                self.await_pos = old_await_pos;
+                let arrow = self.parse_arrow_expression(
+                    self.start_node_at(start_pos, &start_loc),
+                    expr_list
+                        .into_iter()
+                        .map(ExpressionOrSpread::Expression)
+                        .collect(),
+                    rest,
+                    false,
+                    for_init,
+                )?;
+                return Ok(Expression::ArrowFunctionExpression(Box::new(arrow)));
+            }
+
+            if (expr_list.is_empty() && rest.is_none()) || last_is_comma {
+                self.unexpected(Some(self.last_token_start))?;
+            }
+            if let Some(rest) = rest {
+                self.unexpected(Some(rest.span.start))?;
+            }
+            self.check_expression_errors(&destructuring_errors, true)?;
+            self.yield_pos = old_yield_pos.or(self.yield_pos);
+            self.await_pos = old_await_pos.or(self.await_pos);
+
+            if expr_list.len() > 1 {
+                let span = self.start_node_at(inner_start_pos, &inner_start_loc);
+                Expression::SequenceExpression(Box::new(SequenceExpression {
+                    span: self.finish_node_at(span, inner_end_pos, &inner_end_loc),
+                    expressions: expr_list,
+                }))
+            } else {
+                expr_list.remove(0)
+            }
+        } else {
+            self.parse_paren_expression()?
+        };
+
+        if self.options.preserve_parens {
+            let span = self.start_node_at(start_pos, &start_loc);
+            Ok(Expression::ParenthesizedExpression(Box::new(
+                ParenthesizedExpression {
+                    span: self.finish_node(span),
+                    expression: value,
+                },
+            )))
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// New's precedence is slightly tricky. It must allow its argument to
+    /// be a `[]` or dot subscript expression, but not a call — at least,
+    /// not without wrapping it in parentheses. Thus, it uses the no_calls
+    /// argument to parse_subscripts to prevent it from consuming the
+    /// argument list.
+    fn parse_new(&mut self) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        if self.contains_esc {
+            self.raise_recoverable(
+                self.cur_token_start,
+                ErrorCode::EscapedKeyword,
+                "Escape sequence in keyword new",
+            )?;
+        }
+        let span = self.start_node();
+        let meta = self.parse_ident(true)?;
+        if self.options.get_ecma_version_number() >= 6 && self.eat(&token_types.dot)? {
+            let contains_esc = self.contains_esc;
+            let property = self.parse_ident(true)?;
+            if property.name != "target" {
+                self.raise_recoverable(
+                    property.span.start,
+                    ErrorCode::InvalidMetaProperty,
+                    "The only valid meta property for new is 'new.target'",
+                )?;
+            }
+            if contains_esc {
+                self.raise_recoverable(
+                    span.start,
+                    ErrorCode::EscapedKeyword,
+                    "'new.target' must not contain escaped characters",
+                )?;
+            }
+            if !self.allow_new_dot_target() {
+                self.raise_recoverable(
+                    span.start,
+                    ErrorCode::NewTargetOutsideFunction,
+                    "'new.target' can only be used in functions and class static block",
+                )?;
+            }
+            return Ok(Expression::MetaProperty(Box::new(MetaProperty {
+                span: self.finish_node(span),
+                meta,
+                property,
+            })));
+        }
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        let is_import = self.cur_token_type.eq(&token_types._import);
+        let callee = self.parse_expr_atom(&mut None, false)?;
+        let callee = self.parse_subscripts(callee, start_pos, &start_loc, true, false)?;
+        if is_import && matches!(callee, Expression::ImportExpression(_)) {
+            self.raise(
+                start_pos,
+                ErrorCode::InvalidImportCall,
+                "Cannot use new with import()",
+            )?;
+        }
+        let arguments = if self.eat(&token_types.paren_l)? {
+            let expr_list = self.parse_expr_list(
+                &token_types.paren_r,
+                self.options.get_ecma_version_number() >= 8,
+                false,
+                &mut None,
+            )?;
+            expr_list.into_iter().flatten().collect()
+        } else {
+            vec![]
+        };
+        Ok(Expression::NewExpression(Box::new(NewExpression {
+            span: self.finish_node(span),
+            callee,
+            arguments,
+        })))
+    }
+
+    /// Parse template expression.
+    fn parse_template_element(&mut self, is_tagged: bool) -> Result<TemplateElement, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        let value = if self.cur_token_type.eq(&token_types.invalid_template) {
+            if !is_tagged {
+                self.raise_recoverable(
+                    self.cur_token_start,
+                    ErrorCode::BadTemplateEscape,
+                    "Bad escape sequence in untagged template literal",
+                )?;
+            }
+            TemplateElementValue {
+                raw: self.atoms.intern(self.cur_token_value.as_str()),
+                cooked: None,
+            }
+        } else {
+            let raw = &self.input[self.cur_token_start as usize..self.cur_token_end as usize];
+            let raw = if raw.contains('\r') {
+                self.atoms
+                    .intern(&raw.replace("\r\n", "\n").replace('\r', "\n"))
+            } else {
+                self.atoms.intern(raw)
+            };
+            TemplateElementValue {
+                raw,
+                cooked: Some(self.atoms.intern(self.cur_token_value.as_str())),
+            }
+        };
+        self.next(false)?;
+        let tail = self.cur_token_type.eq(&token_types.back_quote);
+        Ok(TemplateElement {
+            span: self.finish_node(span),
+            value,
+            tail,
+        })
+    }
+
+    fn parse_template(&mut self, is_tagged: bool) -> Result<TemplateLiteral, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        self.next(false)?;
+        let mut expressions = vec![];
+        let element = self.parse_template_element(is_tagged)?;
+        let mut is_tail = element.tail;
+        let mut quasis = vec![element];
+        while !is_tail {
+            if self.cur_token_type.eq(&token_types.eof) {
+                self.raise(
+                    self.cur_token_pos,
+                    ErrorCode::UnterminatedTemplate,
+                    "Unterminated template literal",
+                )?;
+            }
+            self.expect(&token_types.dollar_brace_l)?;
+            expressions.push(self.parse_expression(false, &mut None)?);
+            self.expect(&token_types.brace_r)?;
+            let element = self.parse_template_element(is_tagged)?;
+            is_tail = element.tail;
+            quasis.push(element);
+        }
+        self.next(false)?;
+        Ok(TemplateLiteral {
+            span: self.finish_node(span),
+            expressions,
+            quasis,
+        })
     }
 
+    /// Parse an object literal.
     fn parse_obj(
-        &self,
-        is_pattern: bool,
-        destructuring_errors: &Option<DestructuringErrors>,
-    ) -> Result<Node, ParserError> {
-        todo!()
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<ObjectExpression, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        let mut is_first = true;
+        let mut prop_hash = PropHash::default();
+        let mut properties = vec![];
+        self.next(false)?;
+        while !self.eat(&token_types.brace_r)? {
+            if !is_first {
+                self.expect(&token_types.comma)?;
+                if self.options.get_ecma_version_number() >= 5
+                    && self.after_trailing_comma(&token_types.brace_r, false)?
+                {
+                    break;
+                }
+            } else {
+                is_first = false;
+            }
+
+            let prop = self.parse_property(destructuring_errors)?;
+            if let PropertyOrSpread::Property(prop) = &prop {
+                self.check_prop_clash(prop, &mut prop_hash, destructuring_errors)?;
+            }
+            properties.push(prop);
+        }
+        Ok(ObjectExpression {
+            span: self.finish_node(span),
+            properties,
+        })
     }
 
-    fn parse_ident(&self, is_liberal: bool) -> Result<Node, ParserError> {
-        todo!()
+    /// Parse an object binding pattern.
+    fn parse_obj_pattern(&mut self) -> Result<ObjectPattern, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        let mut is_first = true;
+        let mut properties = vec![];
+        self.next(false)?;
+        while !self.eat(&token_types.brace_r)? {
+            if !is_first {
+                self.expect(&token_types.comma)?;
+                if self.after_trailing_comma(&token_types.brace_r, false)? {
+                    break;
+                }
+            } else {
+                is_first = false;
+            }
+            properties.push(self.parse_property_pattern()?);
+        }
+        Ok(ObjectPattern {
+            span: self.finish_node(span),
+            properties,
+        })
+    }
+
+    fn parse_property(
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<PropertyOrSpread, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        let ecma_version = self.options.get_ecma_version_number();
+        if ecma_version >= 9 && self.eat(&token_types.ellipsis)? {
+            // Parse argument.
+            let argument = self.parse_maybe_assign(false, destructuring_errors, None)?;
+            // To disallow trailing comma via `to_assignable()`.
+            if self.cur_token_type.eq(&token_types.comma) {
+                if let Some(errors) = destructuring_errors.as_mut() {
+                    if errors.trailing_comma < 0 {
+                        errors.trailing_comma = self.cur_token_start;
+                    }
+                }
+            }
+            // Finish
+            return Ok(PropertyOrSpread::SpreadElement(Box::new(SpreadElement {
+                span: self.finish_node(span),
+                argument,
+            })));
+        }
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        let mut is_generator = ecma_version >= 6 && self.eat(&token_types.star)?;
+        let contains_esc = self.contains_esc;
+        let (mut computed, mut key) = self.parse_property_name()?;
+        let is_async = !contains_esc
+            && ecma_version >= 8
+            && !is_generator
+            && self.is_async_prop(computed, &key)?;
+        if is_async {
+            is_generator = ecma_version >= 9 && self.eat(&token_types.star)?;
+            (computed, key) = self.parse_property_name()?;
+        }
+        let prop = self.parse_property_value(
+            span,
+            computed,
+            key,
+            is_generator,
+            is_async,
+            start_pos,
+            &start_loc,
+            destructuring_errors,
+            contains_esc,
+        )?;
+        Ok(PropertyOrSpread::Property(Box::new(prop)))
+    }
+
+    /// Parse a property of an object binding pattern.
+    fn parse_property_pattern(&mut self) -> Result<PropertyOrRest, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        if self.options.get_ecma_version_number() >= 9 && self.eat(&token_types.ellipsis)? {
+            let argument = Pattern::Identifier(Box::new(self.parse_ident(false)?));
+            if self.cur_token_type.eq(&token_types.comma) {
+                self.raise(
+                    self.cur_token_start,
+                    ErrorCode::RestTrailingComma,
+                    "Comma is not permitted after the rest element",
+                )?;
+            }
+            return Ok(PropertyOrRest::RestElement(Box::new(RestElement {
+                span: self.finish_node(span),
+                argument,
+            })));
+        }
+        let start_pos = self.cur_token_start;
+        let start_loc = self.cur_token_start_loc.clone();
+        let (computed, key) = self.parse_property_name()?;
+        if self.eat(&token_types.colon)? {
+            let value_start_loc = self.cur_token_start_loc.clone();
+            let value = self.parse_maybe_default(self.cur_token_start, &value_start_loc, None)?;
+            return Ok(PropertyOrRest::Property(Box::new(AssignmentProperty {
+                span: self.finish_node(span),
+                shorthand: false,
+                computed,
+                key,
+                value,
+            })));
+        }
+        let id = match &key {
+            Expression::Identifier(id)
+                if !computed && self.cur_token_type.ne(&token_types.paren_l) =>
+            {
+                id.as_ref().clone()
+            }
+            _ => {
+                self.unexpected(None)?;
+                unreachable!()
+            }
+        };
+        self.check_unreserved(&id)?;
+        if id.name == "await" && self.await_ident_pos == 0 {
+            self.await_ident_pos = start_pos;
+        }
+        let value = self.parse_maybe_default(
+            start_pos,
+            &start_loc,
+            Some(Pattern::Identifier(Box::new(id))),
+        )?;
+        Ok(PropertyOrRest::Property(Box::new(AssignmentProperty {
+            span: self.finish_node(span),
+            shorthand: true,
+            computed: false,
+            key,
+            value,
+        })))
+    }
+
+    fn parse_getter_setter(
+        &mut self,
+        span: Span,
+        kind: PropertyKind,
+    ) -> Result<Property, ParserError> {
+        let (computed, key) = self.parse_property_name()?;
+        let value = self.parse_method(false, false, false)?;
+        let param_count = if kind == PropertyKind::Get { 0 } else { 1 };
+        if value.params.len() != param_count {
+            if kind == PropertyKind::Get {
+                self.raise_recoverable(
+                    value.span.start,
+                    ErrorCode::InvalidAccessorParams,
+                    "getter should have no params",
+                )?;
+            } else {
+                self.raise_recoverable(
+                    value.span.start,
+                    ErrorCode::InvalidAccessorParams,
+                    "setter should have exactly one param",
+                )?;
+            }
+        } else if kind == PropertyKind::Set && matches!(value.params[0], Pattern::RestElement(_)) {
+            self.raise_recoverable(
+                value.params[0].span().start,
+                ErrorCode::InvalidAccessorParams,
+                "Setter cannot use rest params",
+            )?;
+        }
+        Ok(Property {
+            span: self.finish_node(span),
+            method: false,
+            shorthand: false,
+            computed,
+            key,
+            kind,
+            value: Expression::FunctionExpression(Box::new(value)),
+        })
+    }
+
+    fn parse_property_value(
+        &mut self,
+        span: Span,
+        computed: bool,
+        key: Expression,
+        is_generator: bool,
+        is_async: bool,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+        contains_esc: bool,
+    ) -> Result<Property, ParserError> {
+        let token_types = get_token_types();
+        let ecma_version = self.options.get_ecma_version_number();
+        if (is_generator || is_async) && self.cur_token_type.eq(&token_types.colon) {
+            self.unexpected(None)?;
+        }
+
+        let cur_token_type = self.cur_token_type.clone();
+        if self.eat(&token_types.colon)? {
+            let value = self.parse_maybe_assign(false, destructuring_errors, None)?;
+            return Ok(Property {
+                span: self.finish_node(span),
+                method: false,
+                shorthand: false,
+                computed,
+                key,
+                kind: PropertyKind::Init,
+                value,
+            });
+        }
+        if ecma_version >= 6 && cur_token_type.eq(&token_types.paren_l) {
+            let value = self.parse_method(is_generator, is_async, false)?;
+            return Ok(Property {
+                span: self.finish_node(span),
+                method: true,
+                shorthand: false,
+                computed,
+                key,
+                kind: PropertyKind::Init,
+                value: Expression::FunctionExpression(Box::new(value)),
+            });
+        }
+        let id = match &key {
+            Expression::Identifier(id) if !computed => id.as_ref().clone(),
+            _ => {
+                self.unexpected(None)?;
+                unreachable!()
+            }
+        };
+        if !contains_esc
+            && ecma_version >= 5
+            && (id.name == "get" || id.name == "set")
+            && cur_token_type.ne(&token_types.comma)
+            && cur_token_type.ne(&token_types.brace_r)
+            && cur_token_type.ne(&token_types.eq)
+        {
+            if is_generator || is_async {
+                self.unexpected(None)?;
+            }
+            let kind = if id.name == "get" {
+                PropertyKind::Get
+            } else {
+                PropertyKind::Set
+            };
+            return self.parse_getter_setter(span, kind);
+        }
+        if ecma_version < 6 || is_generator || is_async {
+            self.unexpected(None)?;
+        }
+        self.check_unreserved(&id)?;
+        if id.name == "await" && self.await_ident_pos == 0 {
+            self.await_ident_pos = start_pos;
+        }
+        let value = if self.cur_token_type.eq(&token_types.eq) && destructuring_errors.is_some() {
+            if let Some(errors) = destructuring_errors.as_mut() {
+                if errors.shorthand_assign < 0 {
+                    errors.shorthand_assign = self.cur_token_start;
+                }
+            }
+            // The default is only valid once the object turns out to be a
+            // pattern, so until `to_assignable` it is kept as an assignment.
+            match self.parse_maybe_default(
+                start_pos,
+                start_loc,
+                Some(Pattern::Identifier(Box::new(id))),
+            )? {
+                Pattern::AssignmentPattern(assign) => {
+                    let assign = *assign;
+                    Expression::AssignmentExpression(Box::new(AssignmentExpression {
+                        span: assign.span,
+                        operator: AssignmentOperator::Assign,
+                        left: assign.left,
+                        right: assign.right,
+                    }))
+                }
+                _ => unreachable!(),
+            }
+        } else {
+            Expression::Identifier(Box::new(id))
+        };
+        Ok(Property {
+            span: self.finish_node(span),
+            method: false,
+            shorthand: true,
+            computed: false,
+            key,
+            kind: PropertyKind::Init,
+            value,
+        })
+    }
+
+    /// Parse a property name, returning whether it is computed along with
+    /// the key.
+    fn parse_property_name(&mut self) -> Result<(bool, Expression), ParserError> {
+        let token_types = get_token_types();
+        if self.options.get_ecma_version_number() >= 6 && self.eat(&token_types.bracket_l)? {
+            let key = self.parse_maybe_assign(false, &mut None, None)?;
+            self.expect(&token_types.bracket_r)?;
+            return Ok((true, key));
+        }
+        let cur_token_type = self.cur_token_type.clone();
+        let key = if cur_token_type.eq(&token_types.num) || cur_token_type.eq(&token_types.string) {
+            self.parse_expr_atom(&mut None, false)?
+        } else {
+            Expression::Identifier(Box::new(self.parse_ident(true)?))
+        };
+        Ok((false, key))
+    }
+
+    fn is_async_prop(&self, computed: bool, key: &Expression) -> Result<bool, ParserError> {
+        let token_types = get_token_types();
+        let cur_token_type = self.cur_token_type.clone();
+        Ok(!computed
+            && matches!(key, Expression::Identifier(id) if id.name == "async")
+            && (cur_token_type.eq(&token_types.name)
+                || cur_token_type.eq(&token_types.num)
+                || cur_token_type.eq(&token_types.string)
+                || cur_token_type.eq(&token_types.bracket_l)
+                || cur_token_type.keyword.is_some()
+                || (self.options.get_ecma_version_number() >= 9
+                    && cur_token_type.eq(&token_types.star)))
+            && !get_line_break_regex().is_match(
+                &self.input[self.last_token_end as usize..self.cur_token_start as usize],
+            )?)
+    }
+
+    /// Parse object or class method.
+    fn parse_method(
+        &mut self,
+        is_generator: bool,
+        is_async: bool,
+        allow_direct_super: bool,
+    ) -> Result<Function, ParserError> {
+        let span = self.start_node();
+        let old_yield_pos = self.yield_pos;
+        let old_await_pos = self.await_pos;
+        let old_await_ident_pos = self.await_ident_pos;
+
+        let ecma_version = self.options.get_ecma_version_number();
+        let generator = ecma_version >= 6 && is_generator;
+        let is_async = ecma_version >= 8 && is_async;
+
+        self.yield_pos = None;
+        self.await_pos = None;
+        self.await_ident_pos = 0;
+        self.enter_scope(
+            function_flags(is_async, generator)
+                | SCOPE_SUPER
+                | if allow_direct_super {
+                    SCOPE_DIRECT_SUPER
+                } else {
+                    0
+                },
+        );
+
+        let token_types = get_token_types();
+        self.expect(&token_types.paren_l)?;
+        let params: Vec<Pattern> = self
+            .parse_binding_list(&token_types.paren_r, false, ecma_version >= 8)?
+            .into_iter()
+            .flatten()
+            .collect();
+        self.check_yield_await_in_default_params()?;
+        let body = self.parse_function_body(span.start, None, &params, false, true)?;
+
+        self.yield_pos = old_yield_pos;
+        self.await_pos = old_await_pos;
+        self.await_ident_pos = old_await_ident_pos;
+        Ok(Function {
+            span: self.finish_node(span),
+            id: None,
+            generator,
+            is_async,
+            params,
+            body,
+        })
+    }
+
+    /// Parse arrow function expression with given parameters. A trailing
+    /// rest parameter is passed separately since it was already parsed as a
+    /// pattern.
+    fn parse_arrow_expression(
+        &mut self,
+        span: Span,
+        params: Vec<ExpressionOrSpread>,
+        rest: Option<RestElement>,
+        is_async: bool,
+        for_init: bool,
+    ) -> Result<ArrowFunctionExpression, ParserError> {
+        let old_yield_pos = self.yield_pos;
+        let old_await_pos = self.await_pos;
+        let old_await_ident_pos = self.await_ident_pos;
+
+        self.enter_scope(function_flags(is_async, false) | SCOPE_ARROW);
+        let is_async = self.options.get_ecma_version_number() >= 8 && is_async;
+
+        self.yield_pos = None;
+        self.await_pos = None;
+        self.await_ident_pos = 0;
+
+        let mut params: Vec<Pattern> = self
+            .to_assignable_list(params.into_iter().map(Some).collect(), true)?
+            .into_iter()
+            .flatten()
+            .collect();
+        if let Some(rest) = rest {
+            params.push(Pattern::RestElement(Box::new(rest)));
+        }
+        let body = if self.cur_token_type.ne(&get_token_types().brace_l) {
+            let body = self.parse_maybe_assign(for_init, &mut None, None)?;
+            self.check_params(&params, false)?;
+            self.exit_scope();
+            ArrowFunctionBody::Expression(body)
+        } else {
+            ArrowFunctionBody::BlockStatement(Box::new(
+                self.parse_function_body(span.start, None, &params, true, false)?,
+            ))
+        };
+
+        self.yield_pos = old_yield_pos;
+        self.await_pos = old_await_pos;
+        self.await_ident_pos = old_await_ident_pos;
+        Ok(ArrowFunctionExpression {
+            span: self.finish_node(span),
+            is_async,
+            params,
+            body,
+        })
+    }
+
+    /// Parse function body and check parameters. `start` is the start of
+    /// the function node, used to report an illegal 'use strict'.
+    fn parse_function_body(
+        &mut self,
+        start: i32,
+        id: Option<&Identifier>,
+        params: &[Pattern],
+        is_arrow_function: bool,
+        is_method: bool,
+    ) -> Result<BlockStatement, ParserError> {
+        let old_strict = self.is_strict;
+        let mut use_strict = false;
+
+        let non_simple =
+            self.options.get_ecma_version_number() >= 7 && !self.is_simple_param_list(params);
+        if !old_strict || non_simple {
+            use_strict = self.strict_directive(self.cur_token_end);
+            if use_strict && non_simple {
+                self.raise_recoverable(
+                    start,
+                    ErrorCode::UseStrictWithNonSimpleParams,
+                    "Illegal 'use strict' directive in function with non-simple parameter list",
+                )?;
+            }
+        }
+        // Labels are not visible across function boundaries.
+        let old_labels = std::mem::take(&mut self.labels);
+        if use_strict {
+            self.is_strict = true;
+        }
+
+        // Add the params to var_declared_names to ensure that an error is
+        // thrown if a let/const declaration in the function clashes with one
+        // of the params.
+        self.check_params(
+            params,
+            !old_strict
+                && !use_strict
+                && !is_arrow_function
+                && !is_method
+                && self.is_simple_param_list(params),
+        )?;
+        // Ensure the function name isn't a forbidden identifier in strict
+        // mode, e.g. 'eval'
+        if self.is_strict {
+            if let Some(id) = id {
+                self.check_lval_identifier(id, BIND_OUTSIDE, &mut None)?;
+            }
+        }
+        let mut body = self.parse_block(false, None, use_strict && !old_strict)?;
+        self.adapt_directive_prologue(&mut body.body);
+        self.labels = old_labels;
+        self.exit_scope();
+        Ok(body)
+    }
+
+    fn is_simple_param_list(&self, params: &[Pattern]) -> bool {
+        params
+            .iter()
+            .all(|param| matches!(param, Pattern::Identifier(_)))
+    }
+
+    /// Checks function params for various disallowed patterns such as using
+    /// "eval" or "arguments" and duplicate parameters.
+    fn check_params(
+        &mut self,
+        params: &[Pattern],
+        allow_duplicates: bool,
+    ) -> Result<(), ParserError> {
+        let mut name_hash = if allow_duplicates {
+            None
+        } else {
+            Some(HashSet::new())
+        };
+        for param in params {
+            self.check_lval_inner_pattern(param, BIND_VAR, &mut name_hash)?;
+        }
+        Ok(())
+    }
+
+    /// Parses a comma-separated list of expressions, and returns them as
+    /// an array. `close` is the token type that ends the list, and
+    /// `allow_empty` can be turned on to allow subsequent commas with
+    /// nothing in between them to be parsed as `None` (which is needed
+    /// for array literals).
+    fn parse_expr_list(
+        &mut self,
+        close: &TokenType,
+        allow_trailing_comma: bool,
+        allow_empty: bool,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<Vec<Option<ExpressionOrSpread>>, ParserError> {
+        let token_types = get_token_types();
+        let mut elements = vec![];
+        let mut is_first = true;
+        while !self.eat(close)? {
+            if !is_first {
+                self.expect(&token_types.comma)?;
+                if allow_trailing_comma && self.after_trailing_comma(close, false)? {
+                    break;
+                }
+            } else {
+                is_first = false;
+            }
+
+            let element = if allow_empty && self.cur_token_type.eq(&token_types.comma) {
+                None
+            } else if self.cur_token_type.eq(&token_types.ellipsis) {
+                let element = self.parse_spread(destructuring_errors)?;
+                if self.cur_token_type.eq(&token_types.comma) {
+                    if let Some(errors) = destructuring_errors.as_mut() {
+                        if errors.trailing_comma < 0 {
+                            errors.trailing_comma = self.cur_token_start;
+                        }
+                    }
+                }
+                Some(ExpressionOrSpread::SpreadElement(Box::new(element)))
+            } else {
+                Some(ExpressionOrSpread::Expression(self.parse_maybe_assign(
+                    false,
+                    destructuring_errors,
+                    None,
+                )?))
+            };
+            elements.push(element);
+        }
+        Ok(elements)
+    }
+
+    fn check_unreserved(&mut self, id: &Identifier) -> Result<(), ParserError> {
+        let name = id.name.as_str();
+        let start = id.span.start;
+        if self.in_generator() && name == "yield" {
+            self.raise_recoverable(
+                start,
+                ErrorCode::YieldIdentifier,
+                "Cannot use 'yield' as identifier inside a generator",
+            )?;
+        }
+        if self.is_async() && name == "await" {
+            self.raise_recoverable(
+                start,
+                ErrorCode::AwaitIdentifier,
+                "Cannot use 'await' as identifier inside an async function",
+            )?;
+        }
+        let in_class_field_init = self
+            .current_this_scope()
+            .map(|scope| scope.in_class_field_init)
+            .unwrap_or(false);
+        if in_class_field_init && name == "arguments" {
+            self.raise_recoverable(
+                start,
+                ErrorCode::ArgumentsInClassField,
+                "Cannot use 'arguments' in class field initializer",
+            )?;
+        }
+        if self.in_class_static_block() && (name == "arguments" || name == "await") {
+            self.raise(
+                start,
+                ErrorCode::InvalidInStaticBlock,
+                &format!("Cannot use {:} in class static initialization block", name),
+            )?;
+        }
+        if self.keywords.contains(name) {
+            self.raise(
+                start,
+                ErrorCode::ReservedWord,
+                &format!("Unexpected keyword '{:}'", name),
+            )?;
+        }
+        if self.options.get_ecma_version_number() < 6
+            && self.input[start as usize..id.span.end as usize].contains('\\')
+        {
+            return Ok(());
+        }
+        let reserved_words = if self.is_strict {
+            self.reserved_words_strict
+        } else {
+            self.reserved_words
+        };
+        if reserved_words.contains(name) {
+            if !self.is_async() && name == "await" {
+                self.raise_recoverable(
+                    start,
+                    ErrorCode::AwaitOutsideAsync,
+                    "Cannot use keyword 'await' outside an async function",
+                )?;
+            }
+            self.raise_recoverable(
+                start,
+                ErrorCode::ReservedWord,
+                &format!("The keyword '{:}' is reserved", name),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Parse the next token as an identifier. If `is_liberal` is true (used
+    /// when parsing properties), it will also convert keywords into
+    /// identifiers.
+    fn parse_ident(&mut self, is_liberal: bool) -> Result<Identifier, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        let cur_token_type = self.cur_token_type.clone();
+        let mut name = Atom::default();
+        if cur_token_type.eq(&token_types.name) {
+            name = self.atoms.intern(self.cur_token_value.as_str());
+        } else if let Some(keyword) = &cur_token_type.keyword {
+            name = self.atoms.intern(keyword);
+
+            // To fix https://github.com/acornjs/acorn/issues/575
+            // `class` and `function` keywords push new context into
+            // self.context. But there is no chance to pop the context if the
+            // keyword is consumed as an identifier such as a property name.
+            // If the previous token is a dot, this does not apply because the
+            // context-managing code already ignored the keyword
+            if (name == "class" || name == "function")
+                && (self.last_token_end != self.last_token_start + 1
+                    || self.char_code_at(self.last_token_start) != DOT)
+            {
+                self.context.pop();
+            }
+        } else {
+            self.unexpected(None)?;
+        }
+        self.next(is_liberal)?;
+        let id = Identifier {
+            span: self.finish_node(span),
+            name,
+        };
+        if !is_liberal {
+            self.check_unreserved(&id)?;
+            if id.name == "await" && self.await_ident_pos == 0 {
+                self.await_ident_pos = id.span.start;
+            }
+        }
+        Ok(id)
+    }
+
+    fn parse_private_ident(&mut self) -> Result<PrivateIdentifier, ParserError> {
+        let span = self.start_node();
+        let mut name = Atom::default();
+        if self.cur_token_type.eq(&get_token_types().private_id) {
+            name = self.atoms.intern(self.cur_token_value.as_str());
+        } else {
+            self.unexpected(None)?;
+        }
+        self.next(false)?;
+        let id = PrivateIdentifier {
+            span: self.finish_node(span),
+            name,
+        };
+
+        // For validating existence
+        match self.private_name_stack.last_mut() {
+            Some(scope) => scope.used.push(id.clone()),
+            None => {
+                self.raise(
+                    id.span.start,
+                    ErrorCode::UndeclaredPrivateName,
+                    &format!(
+                        "Private field '#{:}' must be declared in an enclosing class",
+                        id.name
+                    ),
+                )?;
+            }
+        }
+        Ok(id)
+    }
+
+    /// Parses yield expression inside generator.
+    fn parse_yield(&mut self, for_init: bool) -> Result<YieldExpression, ParserError> {
+        if self.yield_pos.is_none() {
+            self.yield_pos = Some(self.cur_token_start);
+        }
+
+        let token_types = get_token_types();
+        let span = self.start_node();
+        self.next(false)?;
+        let cur_token_type = self.cur_token_type.clone();
+        let (delegate, argument) = if cur_token_type.eq(&token_types.semi)
+            || self.can_insert_semicolon()
+            || (cur_token_type.ne(&token_types.star) && !cur_token_type.starts_expr)
+        {
+            (false, None)
+        } else {
+            let delegate = self.eat(&token_types.star)?;
+            (
+                delegate,
+                Some(self.parse_maybe_assign(for_init, &mut None, None)?),
+            )
+        };
+        Ok(YieldExpression {
+            span: self.finish_node(span),
+            delegate,
+            argument,
+        })
+    }
+
+    /// Parses await expression inside async function.
+    fn parse_await(&mut self, for_init: bool) -> Result<AwaitExpression, ParserError> {
+        if self.await_pos.is_none() {
+            self.await_pos = Some(self.cur_token_start);
+        }
+
+        let span = self.start_node();
+        self.next(false)?;
+        let argument = self.parse_maybe_unary(&mut None, true, false, for_init)?;
+        Ok(AwaitExpression {
+            span: self.finish_node(span),
+            argument,
+        })
     }
 }
@@ -0,0 +1,783 @@
+// ### Incremental reparsing
+//
+// After an edit to a program parsed before, only the part of the input
+// around the edit is parsed again: the body of the innermost function
+// enclosing it or, outside of functions, the top-level statement it falls
+// in. The parser is set up the way a full parse would reach that part,
+// with what the old tree tells about it: the scope of the function and its
+// parameters, the strictness, the private names of the enclosing classes
+// and the token context. The new nodes take the place of the old ones, and
+// the nodes after the edit are moved by its size.
+//
+// To make sure that the rest of the input parses the same, the old text of
+// the part is parsed again as well. It has to give back the old nodes, and
+// both parses have to leave the tokenizer in the same state, with the same
+// token read after the part. When they do not, when the new text does not
+// parse, or when the edit could change the names declared in the program
+// or its directive prologue, the whole input is parsed again, which also
+// gives the errors their place and wording of a full parse.
+
+use std::collections::HashMap;
+
+use crate::{
+    atom::Atom,
+    errors::ParserError,
+    expression::ExpressionParser,
+    location::{LocationParser, OffsetConverter, OffsetRewriter, Position},
+    node::*,
+    options::{OffsetUnit, Options, SourceType},
+    parser::Parser,
+    scope::{function_flags, ScopeParser, SCOPE_DIRECT_SUPER, SCOPE_SUPER},
+    statement::{is_private_name_conflicted, PrivateNameKind, PrivateNameScope, StatementParser},
+    token::{
+        context::{get_initial_context, get_token_context_types, TokenContext},
+        types::{get_token_types, TokenType},
+        TokenParser, TokenValue,
+    },
+    visit::{walk_expression, walk_function, NodeRef, Visit, VisitControl},
+    visit_mut::{self, NodeMut, VisitMut},
+};
+
+/// An edit to an input: the text between `start` and `end`, counted in the
+/// `offset_unit` of the options, is replaced by `text`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct TextEdit {
+    pub start: i32,
+    pub end: i32,
+    pub text: String,
+}
+
+pub fn reparse(
+    program: &mut Program,
+    input: &str,
+    edit: &TextEdit,
+    options: &Options,
+) -> Result<(), ParserError> {
+    let offsets = match options.offset_unit {
+        OffsetUnit::Utf8 => None,
+        _ => Some(OffsetConverter::new(input)),
+    };
+    let utf8 = OffsetConverter::default();
+    let converter = offsets.as_ref().unwrap_or(&utf8);
+    let start = converter.to_utf8_checked(input, edit.start, options.offset_unit)?;
+    let end = converter.to_utf8_checked(input, edit.end, options.offset_unit)?;
+    if start > end {
+        return Err(ParserError::InvalidOffset(edit.end));
+    }
+    let new_input = [
+        &input[..start as usize],
+        edit.text.as_str(),
+        &input[end as usize..],
+    ]
+    .concat();
+    let reparser = Reparser {
+        options,
+        old_input: input,
+        new_input: &new_input,
+        offsets,
+        edit,
+        start,
+        end,
+        delta: edit.text.len() as i32 - (end - start),
+    };
+    if !reparser.reparse(program) {
+        *program = crate::parse(&new_input, options)?;
+    }
+    Ok(())
+}
+
+struct Reparser<'a> {
+    options: &'a Options,
+    old_input: &'a str,
+    new_input: &'a str,
+    // Converts the offsets of the old tree to UTF-8, unless they are.
+    offsets: Option<OffsetConverter>,
+    edit: &'a TextEdit,
+    // The edit in UTF-8, and how much it moves the text after it.
+    start: i32,
+    end: i32,
+    delta: i32,
+}
+
+/// The body of a function around the edit, and what parsing it depends on.
+/// Offsets are those of the old tree.
+struct BodyRegion {
+    start: i32,
+    function_start: i32,
+    id: Option<Identifier>,
+    params: Vec<Pattern>,
+    scope_flags: i32,
+    is_method: bool,
+    is_strict: bool,
+    context: Vec<TokenContext>,
+    // The private names declared by each of the enclosing classes, from
+    // the outermost.
+    private_names: Vec<HashMap<Atom, PrivateNameKind>>,
+}
+
+/// What the tokenizer is left with after the part parsed again, with the
+/// offsets moved back by the size of the edit.
+#[derive(PartialEq)]
+struct EndState {
+    context: Vec<TokenContext>,
+    expr_allowed: bool,
+    contains_esc: bool,
+    last_token_end: i32,
+    token_type: TokenType,
+    token_value: TokenValue,
+    token_start: i32,
+    token_end: i32,
+}
+
+impl EndState {
+    fn new(parser: &Parser, delta: i32) -> Self {
+        EndState {
+            context: parser.context.clone(),
+            expr_allowed: parser.expr_allowed,
+            contains_esc: parser.contains_esc,
+            last_token_end: parser.last_token_end - delta,
+            token_type: parser.cur_token_type.clone(),
+            token_value: parser.cur_token_value.clone(),
+            token_start: parser.cur_token_start - delta,
+            token_end: parser.cur_token_end - delta,
+        }
+    }
+}
+
+impl Reparser<'_> {
+    /// Parses again the part of the input around the edit and updates
+    /// `program` with it. Returns `false` when the whole input has to be
+    /// parsed instead.
+    fn reparse(&self, program: &mut Program) -> bool {
+        let options = self.options;
+        if options.on_comment.is_some()
+            || options.on_token.is_some()
+            || options.on_insert_semicolon.is_some()
+            || options.on_trailing_comma.is_some()
+            || options.program.is_some()
+        {
+            return false;
+        }
+        let is_strict = options.source_type == SourceType::Module || has_use_strict(&program.body);
+        let mut finder = RegionFinder {
+            edit: self.edit,
+            is_strict,
+            frames: vec![],
+            method: None,
+            expression_start: -1,
+            bodies: vec![],
+        };
+        finder.visit_program(program);
+        for region in finder.bodies.iter().rev() {
+            if let Some((body, mut shift)) = self.reparse_body(program, region) {
+                shift.body = Some((region.start, body));
+                shift.visit_program(program);
+                return true;
+            }
+        }
+        if let Some(index) = self.statement_index(program) {
+            if let Some((statements, mut shift)) = self.reparse_statement(program, index, is_strict)
+            {
+                shift.shift_span(&mut program.span);
+                for statement in &mut program.body[index + 1..] {
+                    shift.visit_statement(statement);
+                }
+                program.body.splice(index..=index, statements);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn to_utf8(&self, offset: i32) -> i32 {
+        match &self.offsets {
+            Some(offsets) => offsets.convert(offset, self.options.offset_unit, OffsetUnit::Utf8),
+            None => offset,
+        }
+    }
+
+    fn reparse_body(
+        &self,
+        program: &Program,
+        region: &BodyRegion,
+    ) -> Option<(BlockStatement, Shift)> {
+        let mut id = region.id.clone();
+        let mut params = region.params.clone();
+        if let Some(offsets) = &self.offsets {
+            let mut converter = InputOffsets {
+                offsets,
+                unit: self.options.offset_unit,
+            };
+            if let Some(id) = &mut id {
+                converter.visit_identifier(id);
+            }
+            for param in &mut params {
+                converter.visit_pattern(param);
+            }
+        }
+        let utf8 = BodyRegion {
+            start: self.to_utf8(region.start),
+            function_start: self.to_utf8(region.function_start),
+            id,
+            params,
+            scope_flags: region.scope_flags,
+            is_method: region.is_method,
+            is_strict: region.is_strict,
+            context: region.context.clone(),
+            private_names: region.private_names.clone(),
+        };
+        let (old_body, old_parser) = self.parse_body(self.old_input, &utf8)?;
+        let mut check = BodyCheck {
+            start: region.start,
+            body: &old_body,
+            matches: false,
+        };
+        check.visit_program(program);
+        if !check.matches {
+            return None;
+        }
+        let (body, parser) = self.parse_body(self.new_input, &utf8)?;
+        if EndState::new(&old_parser, 0) != EndState::new(&parser, self.delta) {
+            return None;
+        }
+        Some((body, self.shift(&old_parser, &parser)))
+    }
+
+    /// Parses the function body of `region` in `input`, the way
+    /// `parse_function` and `parse_method` do once past the parameters.
+    fn parse_body(&self, input: &str, region: &BodyRegion) -> Option<(BlockStatement, Parser)> {
+        let mut parser = Parser::new(self.options, input, &Some(region.start));
+        parser.is_strict = region.is_strict;
+        parser.context = region.context.clone();
+        // The body follows the closing parenthesis of the parameters.
+        parser.cur_token_type = get_token_types().paren_r.clone();
+        parser.expr_allowed = false;
+        parser.private_name_stack = region
+            .private_names
+            .iter()
+            .map(|declared| PrivateNameScope {
+                declared: declared.clone(),
+                used: vec![],
+            })
+            .collect();
+        parser.enter_scope(region.scope_flags);
+        let result = parser
+            .next_token()
+            .and_then(|_| {
+                parser.parse_function_body(
+                    region.function_start,
+                    region.id.as_ref(),
+                    &region.params,
+                    false,
+                    region.is_method,
+                )
+            })
+            .and_then(|body| {
+                // The private names used have to be declared by the
+                // enclosing classes.
+                for _ in &region.private_names {
+                    parser.exit_class_body()?;
+                }
+                Ok(body)
+            });
+        let mut body = result.ok()?;
+        if !parser.take_errors().is_empty() {
+            return None;
+        }
+        if self.options.offset_unit != OffsetUnit::Utf8 {
+            OffsetRewriter { parser: &parser }.visit_block_statement(&mut body);
+        }
+        Some((body, parser))
+    }
+
+    /// Returns the index of the top-level statement that can be parsed
+    /// again on its own to take in the edit, if any.
+    fn statement_index(&self, program: &Program) -> Option<usize> {
+        let body = &program.body;
+        let index = body
+            .partition_point(|statement| statement.span().end < self.edit.start)
+            .min(body.len().checked_sub(1)?);
+        // The text between the statement and its neighbours is parsed with
+        // it, but not the neighbours themselves.
+        if index == 0
+            || matches!(body.get(index + 1), Some(next) if self.edit.end >= next.span().start)
+        {
+            return None;
+        }
+        // A string statement may join the directive prologue, which would
+        // make the program strict.
+        let in_prologue = body[..index].iter().all(|statement| {
+            matches!(statement, Statement::ExpressionStatement(statement) if statement.directive.is_some())
+        });
+        if in_prologue || declares_names(&body[index]) {
+            return None;
+        }
+        Some(index)
+    }
+
+    fn reparse_statement(
+        &self,
+        program: &Program,
+        index: usize,
+        is_strict: bool,
+    ) -> Option<(Vec<Statement>, Shift)> {
+        let start = self.to_utf8(program.body[index - 1].span().end);
+        // Only after a semicolon is the state of the tokenizer known
+        // without reading the statement before.
+        if !self.old_input[..start as usize].ends_with(';') {
+            return None;
+        }
+        let end = program
+            .body
+            .get(index + 1)
+            .map(|next| self.to_utf8(next.span().start));
+        let (old_statements, old_parser) =
+            self.parse_statements(self.old_input, start, end, is_strict)?;
+        if old_statements.len() != 1 || old_statements[0] != program.body[index] {
+            return None;
+        }
+        let (statements, parser) = self.parse_statements(
+            self.new_input,
+            start,
+            end.map(|end| end + self.delta),
+            is_strict,
+        )?;
+        if statements.iter().any(declares_names)
+            || EndState::new(&old_parser, 0) != EndState::new(&parser, self.delta)
+        {
+            return None;
+        }
+        Some((statements, self.shift(&old_parser, &parser)))
+    }
+
+    /// Parses the top-level statements of `input` between `start`, right
+    /// after a semicolon, and `end`, or the end of the input. Returns `None`
+    /// when they do not parse or do not end there, or when the first one
+    /// would instead continue the statement before.
+    fn parse_statements(
+        &self,
+        input: &str,
+        start: i32,
+        end: Option<i32>,
+        is_strict: bool,
+    ) -> Option<(Vec<Statement>, Parser)> {
+        let mut parser = Parser::new(self.options, input, &Some(start));
+        parser.is_strict = is_strict;
+        parser.cur_token_type = get_token_types().semi.clone();
+        parser.last_token_start = start - 1;
+        parser.last_token_end = start;
+        parser.last_token_start_loc = parser.cur_token_start_loc.clone();
+        parser.last_token_end_loc = parser.cur_token_start_loc.clone();
+        let mut statements = parse_statements_until(&mut parser, end).ok()??;
+        if !parser.take_errors().is_empty() {
+            return None;
+        }
+        if self.options.offset_unit != OffsetUnit::Utf8 {
+            let mut rewriter = OffsetRewriter { parser: &parser };
+            for statement in &mut statements {
+                rewriter.visit_statement(statement);
+            }
+        }
+        Some((statements, parser))
+    }
+
+    /// How the nodes after the edit move, from the parsers of the old and
+    /// the new text.
+    fn shift(&self, old_parser: &Parser, parser: &Parser) -> Shift {
+        let new_end = self.start + self.edit.text.len() as i32;
+        let position = |parser: &Parser, offset: i32| {
+            parser.output_position(&parser.line_index.position(offset))
+        };
+        Shift {
+            edit_start: self.edit.start,
+            old_end: self.edit.end,
+            delta: parser.output_offset(new_end) - self.edit.end,
+            positions: if self.options.locations {
+                Some((position(old_parser, self.end), position(parser, new_end)))
+            } else {
+                None
+            },
+            body: None,
+        }
+    }
+}
+
+fn parse_statements_until(
+    parser: &mut Parser,
+    end: Option<i32>,
+) -> Result<Option<Vec<Statement>>, ParserError> {
+    let token_types = get_token_types();
+    parser.next_token()?;
+    if parser.cur_token_type.eq(&token_types._else) {
+        return Ok(None);
+    }
+    let mut statements = vec![];
+    while parser.cur_token_type.ne(&token_types.eof)
+        && end.is_none_or(|end| parser.cur_token_start < end)
+    {
+        statements.push(parser.parse_statement(None, true, &mut None)?);
+    }
+    if matches!(end, Some(end) if parser.cur_token_start != end) {
+        return Ok(None);
+    }
+    Ok(Some(statements))
+}
+
+/// Whether a directive prologue makes its code strict.
+fn has_use_strict(statements: &[Statement]) -> bool {
+    statements
+        .iter()
+        .map_while(|statement| match statement {
+            Statement::ExpressionStatement(statement) => statement.directive.as_deref(),
+            _ => None,
+        })
+        .any(|directive| directive == "use strict")
+}
+
+/// Whether a top-level statement declares names in the scope of the
+/// program, or imports or exports some.
+fn declares_names(statement: &Statement) -> bool {
+    match statement {
+        Statement::LabeledStatement(statement) => declares_names(&statement.body),
+        Statement::FunctionDeclaration(_)
+        | Statement::VariableDeclaration(_)
+        | Statement::ClassDeclaration(_)
+        | Statement::ModuleDeclaration(_) => true,
+        _ => {
+            let mut finder = VarFinder { found: false };
+            finder.visit_statement(statement);
+            finder.found
+        }
+    }
+}
+
+/// Looks for `var` declarations outside of functions, which belong to the
+/// scope of the program, and for imports and exports.
+struct VarFinder {
+    found: bool,
+}
+
+impl Visit for VarFinder {
+    fn enter(&mut self, node: NodeRef<'_>) -> VisitControl {
+        match node {
+            _ if self.found => VisitControl::Skip,
+            NodeRef::VariableDeclaration(declaration)
+                if declaration.kind == VariableDeclarationKind::Var =>
+            {
+                self.found = true;
+                VisitControl::Skip
+            }
+            NodeRef::ImportDeclaration(_)
+            | NodeRef::ExportNamedDeclaration(_)
+            | NodeRef::ExportDefaultDeclaration(_)
+            | NodeRef::ExportAllDeclaration(_) => {
+                self.found = true;
+                VisitControl::Skip
+            }
+            NodeRef::Function(_)
+            | NodeRef::ArrowFunctionExpression(_)
+            | NodeRef::StaticBlock(_) => VisitControl::Skip,
+            _ => VisitControl::Continue,
+        }
+    }
+}
+
+/// A function, arrow function or class around the edit.
+struct Frame {
+    is_strict: bool,
+    // The context pushed by the `function` or `class` keyword.
+    context: Option<TokenContext>,
+    // The body and the private names of a class.
+    class: Option<(Span, HashMap<Atom, PrivateNameKind>)>,
+    has_super_class: bool,
+}
+
+/// Walks down to the edit, collecting the bodies of the functions around
+/// it.
+struct RegionFinder<'a> {
+    edit: &'a TextEdit,
+    is_strict: bool,
+    frames: Vec<Frame>,
+    // The start of the method about to be entered, and whether it may call
+    // `super()`.
+    method: Option<(i32, bool)>,
+    // The start of the function or class expression about to be entered.
+    expression_start: i32,
+    bodies: Vec<BodyRegion>,
+}
+
+impl RegionFinder<'_> {
+    fn is_strict(&self) -> bool {
+        self.frames
+            .last()
+            .map_or(self.is_strict, |frame| frame.is_strict)
+    }
+
+    fn enter_function(&mut self, function: &Function) {
+        let context_types = get_token_context_types();
+        let method = match self.method {
+            Some((start, allows_direct_super)) if start == function.span.start => {
+                Some(allows_direct_super)
+            }
+            _ => None,
+        };
+        // `async function` expressions lose the generator context in
+        // `parse_expr_atom`.
+        let is_expression = self.expression_start == function.span.start;
+        let context = match method {
+            Some(_) => None,
+            None => Some(
+                match (
+                    is_expression,
+                    function.generator && !(is_expression && function.is_async),
+                ) {
+                    (false, false) => context_types.f_stat.clone(),
+                    (false, true) => context_types.f_gen.clone(),
+                    (true, false) => context_types.f_expr.clone(),
+                    (true, true) => context_types.f_expr_gen.clone(),
+                },
+            ),
+        };
+        let is_strict = self.is_strict();
+        let body = &function.body.span;
+        if body.start < self.edit.start && self.edit.end < body.end {
+            let mut contexts = get_initial_context();
+            let function_context = context.clone().or_else(|| {
+                self.frames
+                    .iter()
+                    .rev()
+                    .find_map(|frame| frame.context.clone())
+            });
+            contexts.extend(function_context);
+            let mut scope_flags = function_flags(function.is_async, function.generator);
+            if let Some(allows_direct_super) = method {
+                // The brace of the class body or the object literal.
+                contexts.push(context_types.b_stat.clone());
+                scope_flags |= SCOPE_SUPER;
+                if allows_direct_super {
+                    scope_flags |= SCOPE_DIRECT_SUPER;
+                }
+            }
+            self.bodies.push(BodyRegion {
+                start: body.start,
+                function_start: function.span.start,
+                id: function.id.clone(),
+                params: function.params.clone(),
+                scope_flags,
+                is_method: method.is_some(),
+                is_strict,
+                context: contexts,
+                private_names: self
+                    .frames
+                    .iter()
+                    .filter_map(|frame| match &frame.class {
+                        Some((class_body, names))
+                            if class_body.start < body.start && body.end < class_body.end =>
+                        {
+                            Some(names.clone())
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+            });
+        }
+        self.frames.push(Frame {
+            is_strict: is_strict || has_use_strict(&function.body.body),
+            context,
+            class: None,
+            has_super_class: false,
+        });
+    }
+
+    fn enter_class(&mut self, class: &Class) {
+        let context_types = get_token_context_types();
+        let mut names = HashMap::new();
+        for element in &class.body.body {
+            is_private_name_conflicted(&mut names, element);
+        }
+        self.frames.push(Frame {
+            is_strict: true,
+            context: Some(if self.expression_start == class.span.start {
+                context_types.f_expr.clone()
+            } else {
+                context_types.f_stat.clone()
+            }),
+            class: Some((class.body.span.clone(), names)),
+            has_super_class: class.super_class.is_some(),
+        });
+    }
+}
+
+impl Visit for RegionFinder<'_> {
+    fn enter(&mut self, node: NodeRef<'_>) -> VisitControl {
+        let span = node.span();
+        if span.start > self.edit.start || span.end < self.edit.end {
+            return VisitControl::Skip;
+        }
+        match node {
+            NodeRef::Function(function) => self.enter_function(function),
+            NodeRef::ArrowFunctionExpression(arrow) => {
+                let is_strict = self.is_strict()
+                    || matches!(&arrow.body, ArrowFunctionBody::BlockStatement(body)
+                        if has_use_strict(&body.body));
+                self.frames.push(Frame {
+                    is_strict,
+                    context: None,
+                    class: None,
+                    has_super_class: false,
+                });
+            }
+            NodeRef::Class(class) => self.enter_class(class),
+            NodeRef::MethodDefinition(method) => {
+                let has_super_class = self
+                    .frames
+                    .last()
+                    .is_some_and(|frame| frame.has_super_class);
+                self.method = Some((
+                    method.value.span.start,
+                    method.kind == MethodDefinitionKind::Constructor && has_super_class,
+                ));
+            }
+            NodeRef::Property(property)
+                if property.method || property.kind != PropertyKind::Init =>
+            {
+                self.method = Some((property.value.span().start, false));
+            }
+            _ => {}
+        }
+        VisitControl::Continue
+    }
+
+    fn leave(&mut self, node: NodeRef<'_>) {
+        if let NodeRef::Function(_) | NodeRef::ArrowFunctionExpression(_) | NodeRef::Class(_) = node
+        {
+            self.frames.pop();
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let Expression::FunctionExpression(_) | Expression::ClassExpression(_) = expression {
+            self.expression_start = expression.span().start;
+        }
+        walk_expression(self, expression)
+    }
+}
+
+/// Checks that the body of the function starting at `start` is `body`.
+struct BodyCheck<'a> {
+    start: i32,
+    body: &'a BlockStatement,
+    matches: bool,
+}
+
+impl Visit for BodyCheck<'_> {
+    fn enter(&mut self, node: NodeRef<'_>) -> VisitControl {
+        let span = node.span();
+        if span.start > self.start || span.end <= self.start {
+            return VisitControl::Skip;
+        }
+        VisitControl::Continue
+    }
+
+    fn visit_function(&mut self, function: &Function) {
+        if function.body.span.start == self.start {
+            self.matches = function.body == *self.body;
+        } else if self.enter(NodeRef::Function(function)) == VisitControl::Continue {
+            walk_function(self, function);
+            self.leave(NodeRef::Function(function));
+        }
+    }
+}
+
+/// Converts the offsets of the nodes it walks from the unit of the options
+/// back to UTF-8. Only the offsets of the parameters are read by the
+/// parser, to check them.
+struct InputOffsets<'a> {
+    offsets: &'a OffsetConverter,
+    unit: OffsetUnit,
+}
+
+impl VisitMut for InputOffsets<'_> {
+    fn enter(&mut self, mut node: NodeMut<'_>) -> VisitControl {
+        let span = node.span_mut();
+        span.start = self
+            .offsets
+            .convert(span.start, self.unit, OffsetUnit::Utf8);
+        span.end = self.offsets.convert(span.end, self.unit, OffsetUnit::Utf8);
+        VisitControl::Continue
+    }
+}
+
+/// Moves the nodes after the edit by its size, and puts the body parsed
+/// again in place.
+struct Shift {
+    edit_start: i32,
+    old_end: i32,
+    delta: i32,
+    // The end of the edit in the old text and in the new one.
+    positions: Option<(Position, Position)>,
+    // The start of the body parsed again, and its new nodes.
+    body: Option<(i32, BlockStatement)>,
+}
+
+impl Shift {
+    fn shift_offset(&self, offset: &mut i32) -> bool {
+        let moved = *offset >= self.old_end;
+        if moved {
+            *offset += self.delta;
+        }
+        moved
+    }
+
+    fn shift_position(&self, position: &mut Position) {
+        if let Some((old_end, new_end)) = &self.positions {
+            if position.line == old_end.line {
+                position.column += new_end.column - old_end.column;
+            }
+            position.line += new_end.line - old_end.line;
+        }
+    }
+
+    fn shift_span(&self, span: &mut Span) {
+        if self.shift_offset(&mut span.start) {
+            if let Some(loc) = &mut span.loc {
+                self.shift_position(&mut loc.start);
+            }
+        }
+        if self.shift_offset(&mut span.end) {
+            if let Some(end) = span.loc.as_mut().and_then(|loc| loc.end.as_mut()) {
+                self.shift_position(end);
+            }
+        }
+        if let Some((start, end)) = &mut span.range {
+            self.shift_offset(start);
+            self.shift_offset(end);
+        }
+    }
+}
+
+impl VisitMut for Shift {
+    fn enter(&mut self, mut node: NodeMut<'_>) -> VisitControl {
+        let span = node.span_mut();
+        // Nothing before the edit moves.
+        if span.end <= self.edit_start {
+            return VisitControl::Skip;
+        }
+        self.shift_span(span);
+        VisitControl::Continue
+    }
+
+    fn visit_function(&mut self, function: &mut Function) {
+        if self.enter(NodeMut::Function(function)) == VisitControl::Skip {
+            return;
+        }
+        match self.body.take() {
+            Some((start, body)) if start == function.body.span.start => function.body = body,
+            body => {
+                self.body = body;
+                visit_mut::walk_function(self, function);
+            }
+        }
+        self.leave(NodeMut::Function(function));
+    }
+}
@@ -0,0 +1,237 @@
+// ## Keywords and reserved words
+//
+// Every word that is a keyword, or reserved, in some version or mode is
+// listed once in `word_lists`, along with the lists of the specification
+// it belongs to. A `WordSet` is the union of the lists in effect for a
+// parser, so that classifying an identifier is a match on its length and
+// text, without regular expressions or allocations.
+
+use crate::options::SourceType;
+
+/// Keywords of every version.
+const KEYWORD: u16 = 1;
+/// `class`, `const`, `export`, `extends`, `import` and `super`, keywords
+/// since ECMAScript 6.
+const KEYWORD_6: u16 = 1 << 1;
+/// `export` and `import`, keywords of modules before ECMAScript 6.
+const KEYWORD_MODULE: u16 = 1 << 2;
+const RESERVED_3: u16 = 1 << 3;
+const RESERVED_5: u16 = 1 << 4;
+const RESERVED_6: u16 = 1 << 5;
+/// `await`, reserved in modules.
+const RESERVED_MODULE: u16 = 1 << 6;
+const RESERVED_STRICT: u16 = 1 << 7;
+/// `eval` and `arguments`, which strict mode code cannot bind or assign.
+const RESERVED_STRICT_BIND: u16 = 1 << 8;
+
+/// The lists `word` belongs to.
+fn word_lists(word: &str) -> u16 {
+    match word.len() {
+        2 => match word {
+            "do" | "if" | "in" => KEYWORD,
+            _ => 0,
+        },
+        3 => match word {
+            "for" | "new" | "try" | "var" => KEYWORD,
+            "int" => RESERVED_3,
+            "let" => RESERVED_STRICT,
+            _ => 0,
+        },
+        4 => match word {
+            "case" | "else" | "null" | "this" | "true" | "void" | "with" => KEYWORD,
+            "enum" => RESERVED_3 | RESERVED_5 | RESERVED_6,
+            "byte" | "char" | "goto" | "long" => RESERVED_3,
+            "eval" => RESERVED_STRICT_BIND,
+            _ => 0,
+        },
+        5 => match word {
+            "break" | "catch" | "false" | "throw" | "while" => KEYWORD,
+            "class" | "super" => KEYWORD_6 | RESERVED_3 | RESERVED_5,
+            "const" => KEYWORD_6 | RESERVED_5,
+            "final" | "float" | "short" => RESERVED_3,
+            "await" => RESERVED_MODULE,
+            "yield" => RESERVED_STRICT,
+            _ => 0,
+        },
+        6 => match word {
+            "delete" | "return" | "switch" | "typeof" => KEYWORD,
+            "export" | "import" => KEYWORD_6 | KEYWORD_MODULE | RESERVED_3 | RESERVED_5,
+            "double" | "native" | "throws" => RESERVED_3,
+            "public" | "static" => RESERVED_3 | RESERVED_STRICT,
+            _ => 0,
+        },
+        7 => match word {
+            "default" | "finally" => KEYWORD,
+            "extends" => KEYWORD_6 | RESERVED_3 | RESERVED_5,
+            "boolean" => RESERVED_3,
+            "package" | "private" => RESERVED_3 | RESERVED_STRICT,
+            _ => 0,
+        },
+        8 => match word {
+            "continue" | "debugger" | "function" => KEYWORD,
+            "abstract" | "volatile" => RESERVED_3,
+            _ => 0,
+        },
+        9 => match word {
+            "transient" => RESERVED_3,
+            "interface" | "protected" => RESERVED_3 | RESERVED_STRICT,
+            "arguments" => RESERVED_STRICT_BIND,
+            _ => 0,
+        },
+        10 => match word {
+            "instanceof" => KEYWORD,
+            "implements" => RESERVED_3 | RESERVED_STRICT,
+            _ => 0,
+        },
+        12 => match word {
+            "synchronized" => RESERVED_3,
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+/// A set of keywords or reserved words.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct WordSet(u16);
+
+impl WordSet {
+    /// The keywords of `ecma_version`.
+    pub fn keywords(ecma_version: i32, source_type: &SourceType) -> Self {
+        WordSet(if ecma_version >= 6 {
+            KEYWORD | KEYWORD_6
+        } else if *source_type == SourceType::Module {
+            KEYWORD | KEYWORD_MODULE
+        } else {
+            KEYWORD
+        })
+    }
+
+    /// The words `ecma_version` reserves, outside of strict mode.
+    pub fn reserved_words(ecma_version: i32, source_type: &SourceType) -> Self {
+        let reserved = if ecma_version >= 6 {
+            RESERVED_6
+        } else if ecma_version == 5 {
+            RESERVED_5
+        } else {
+            RESERVED_3
+        };
+        WordSet(match source_type {
+            SourceType::Module => reserved | RESERVED_MODULE,
+            _ => reserved,
+        })
+    }
+
+    /// Adds the words reserved in strict mode.
+    pub fn strict(self) -> Self {
+        WordSet(self.0 | RESERVED_STRICT)
+    }
+
+    /// Adds `eval` and `arguments`, for the names strict mode code binds.
+    pub fn strict_bind(self) -> Self {
+        WordSet(self.0 | RESERVED_STRICT_BIND)
+    }
+
+    pub fn contains(self, word: &str) -> bool {
+        word_lists(word) & self.0 != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: &[&str] = &[
+        "let",
+        "yield",
+        "await",
+        "eval",
+        "arguments",
+        "enum",
+        "int",
+        "class",
+        "const",
+        "export",
+        "import",
+        "static",
+        "with",
+        "foo",
+    ];
+
+    fn words(set: WordSet) -> Vec<&'static str> {
+        WORDS
+            .iter()
+            .copied()
+            .filter(|word| set.contains(word))
+            .collect()
+    }
+
+    #[test]
+    fn word_sets() {
+        let script = SourceType::Script;
+        let module = SourceType::Module;
+        for (ecma_version, source_type, keywords, reserved, strict) in [
+            (
+                3,
+                &script,
+                &["with"][..],
+                &["enum", "int", "class", "export", "import", "static"][..],
+                &[
+                    "let", "yield", "enum", "int", "class", "export", "import", "static",
+                ][..],
+            ),
+            (
+                5,
+                &script,
+                &["with"],
+                &["enum", "class", "const", "export", "import"],
+                &[
+                    "let", "yield", "enum", "class", "const", "export", "import", "static",
+                ],
+            ),
+            (
+                5,
+                &module,
+                &["export", "import", "with"],
+                &["await", "enum", "class", "const", "export", "import"],
+                &[
+                    "let", "yield", "await", "enum", "class", "const", "export", "import", "static",
+                ],
+            ),
+            (
+                6,
+                &script,
+                &["class", "const", "export", "import", "with"],
+                &["enum"],
+                &["let", "yield", "enum", "static"],
+            ),
+            (
+                13,
+                &module,
+                &["class", "const", "export", "import", "with"],
+                &["await", "enum"],
+                &["let", "yield", "await", "enum", "static"],
+            ),
+        ] {
+            let context = (ecma_version, source_type);
+            assert_eq!(
+                words(WordSet::keywords(ecma_version, source_type)),
+                keywords,
+                "{:?}",
+                context
+            );
+            let reserved_words = WordSet::reserved_words(ecma_version, source_type);
+            assert_eq!(words(reserved_words), reserved, "{:?}", context);
+            assert_eq!(words(reserved_words.strict()), strict, "{:?}", context);
+            let mut strict_bind = strict.to_vec();
+            strict_bind.extend(["eval", "arguments"]);
+            strict_bind.sort_by_key(|word| WORDS.iter().position(|other| other == word));
+            assert_eq!(
+                words(reserved_words.strict().strict_bind()),
+                strict_bind,
+                "{:?}",
+                context
+            );
+        }
+    }
+}
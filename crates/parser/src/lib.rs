@@ -1,16 +1,243 @@
+//! A JavaScript parser following the [ESTree] spec, ported from [acorn].
+//!
+//! [ESTree]: https://github.com/estree/estree
+//! [acorn]: https://github.com/acornjs/acorn
+//!
+//! ```
+//! use muse_parser::{parse, to_json, Options, SourceType, Statement, VariableDeclarationKind};
+//!
+//! let options = Options {
+//!     source_type: SourceType::Module,
+//!     ..Options::default()
+//! };
+//! let program = parse("let answer = 42;", &options).unwrap();
+//! assert_eq!(program.body.len(), 1);
+//! assert!(matches!(
+//!     &program.body[0],
+//!     Statement::VariableDeclaration(declaration)
+//!         if declaration.kind == VariableDeclarationKind::Let
+//! ));
+//! assert!(to_json(&program)
+//!     .unwrap()
+//!     .starts_with(r#"{"type":"Program","start":0,"end":16,"body":[{"type":"VariableDeclaration""#));
+//! ```
+
+mod atom;
 mod char_codes;
+mod comment;
 mod errors;
+mod estree;
 mod expression;
 mod identifier;
+mod incremental;
+mod keywords;
 mod location;
+mod loose;
 mod lval;
 mod node;
 mod options;
 mod parser;
 mod regexp;
+mod render;
 mod scope;
+mod statement;
 mod token;
-mod types;
 mod unicode_properties;
 mod utils;
+pub mod visit;
+pub mod visit_mut;
 mod whitespace;
+
+use std::{
+    io::Read,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+pub use crate::{
+    atom::Atom,
+    comment::{attach_comments, Comment, CommentKind, OnComment},
+    errors::{ErrorCode, Label, ParserError},
+    estree::to_json,
+    incremental::TextEdit,
+    location::{
+        LineIndex, OffsetConverter, OnPosition, Position, ReportedPosition, SourceLocation,
+    },
+    loose::DUMMY_VALUE,
+    node::*,
+    options::{EcmaVersion, OffsetUnit, Options, Sink, SourceType},
+    render::RenderStyle,
+    token::{
+        stream::{ReadTokenizer, StreamTokenizer},
+        types::TokenType,
+        OnToken, Token, TokenValue, Tokenizer,
+    },
+};
+use crate::{
+    expression::ExpressionParser, location::LocationParser, parser::Parser, token::TokenParser,
+};
+
+/// Parses `input` as a complete program and returns its `Program` node.
+/// With the `collect_errors` option, fails with the first error collected.
+pub fn parse(input: &str, options: &Options) -> Result<Program, ParserError> {
+    match parse_with_errors(input, options) {
+        Ok((program, errors)) => match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(program),
+        },
+        // The list ends with the error that stopped the parse.
+        Err(mut errors) => Err(errors.remove(0)),
+    }
+}
+
+/// Parses `input` as a complete program and returns its `Program` node,
+/// along with the errors collected on the way when the `collect_errors`
+/// option is set. An error that the parser cannot go on after still fails
+/// the parse, with the errors collected before it followed by that error.
+pub fn parse_with_errors(
+    input: &str,
+    options: &Options,
+) -> Result<(Program, Vec<ParserError>), Vec<ParserError>> {
+    let mut parser = Parser::new(options, input, &None);
+    match parser.parse() {
+        Ok(mut program) => {
+            parser.output_program(&mut program);
+            Ok((program, parser.take_errors()))
+        }
+        Err(error) => {
+            let error = parser.output_error(error);
+            let mut errors = parser.take_errors();
+            errors.push(error);
+            Err(errors)
+        }
+    }
+}
+
+/// Parses each of `sources` as a complete program, like `parse`, spreading
+/// them over a pool of threads. The results are in the order of `sources`.
+///
+/// Callbacks of the options are shared by all the sources, and so are
+/// called from several threads in no particular order.
+pub fn parse_many<S: AsRef<str> + Sync>(
+    sources: &[S],
+    options: &Options,
+) -> Vec<Result<Program, ParserError>> {
+    let workers = thread::available_parallelism()
+        .map_or(1, |count| count.get())
+        .min(sources.len());
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, Result<Program, ParserError>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        match sources.get(index) {
+                            Some(source) => results.push((index, parse(source.as_ref(), options))),
+                            None => return results,
+                        }
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| match handle.join() {
+                Ok(results) => results,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    });
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+// `parse_many` shares the options between its threads and sends the
+// programs and errors back from them.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Options>();
+    assert_send_sync::<Program>();
+    assert_send_sync::<ParserError>();
+};
+
+/// Parses `input` as a complete program, recovering from syntax errors
+/// instead of stopping at the first one. Returns the program along with
+/// the errors recovered from, in source order; the list is empty when the
+/// input is valid, in which case the program is the one `parse` returns.
+///
+/// Missing identifiers and strings are filled in with nodes named
+/// `DUMMY_VALUE`, and the indentation of the input is used to guess where
+/// unclosed blocks and lists end.
+pub fn parse_loose(
+    input: &str,
+    options: &Options,
+) -> Result<(Program, Vec<ParserError>), ParserError> {
+    loose::parse(input, options)
+}
+
+/// Updates `program`, which `parse` returned for `input` with the same
+/// options, to the program of `input` after `edit`. Only the body of the
+/// innermost function around the edit, or the top-level statement it is
+/// in, is parsed again, and the nodes after it are moved; when the edit
+/// could change how the rest of the input parses, the whole new input is
+/// parsed. On error, `program` is left as it was.
+///
+/// With callbacks in the options, or a `program` to append to, the whole
+/// new input is always parsed. Comments attached by `attach_comments` are
+/// not moved.
+pub fn reparse(
+    program: &mut Program,
+    input: &str,
+    edit: &TextEdit,
+    options: &Options,
+) -> Result<(), ParserError> {
+    incremental::reparse(program, input, edit, options)
+}
+
+/// Parses a single expression starting at `offset` of `input`, counted in
+/// the `offset_unit` of the options. The rest of the input after the
+/// expression is ignored, its end can be read from the span of the
+/// returned node.
+pub fn parse_expression_at(
+    input: &str,
+    offset: i32,
+    options: &Options,
+) -> Result<Expression, ParserError> {
+    let offsets = match options.offset_unit {
+        OffsetUnit::Utf8 => OffsetConverter::default(),
+        _ => OffsetConverter::new(input),
+    };
+    let start = offsets.to_utf8_checked(input, offset, options.offset_unit)?;
+    let mut parser = Parser::new(options, input, &Some(start));
+    let mut expression = parser
+        .next_token()
+        .and_then(|_| parser.parse_expression(false, &mut None))
+        .map_err(|error| parser.output_error(error))?;
+    parser.output_expression(&mut expression);
+    match parser.take_errors().into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(expression),
+    }
+}
+
+/// Returns an iterator over the tokens of `input`.
+pub fn tokenizer(input: &str, options: &Options) -> Tokenizer {
+    Tokenizer::new(Parser::new(options, input, &None))
+}
+
+/// Returns a tokenizer over an input given a chunk at a time, which yields
+/// each token as soon as the input pushed so far shows where it ends.
+/// Offsets and locations are the same as with `tokenizer` over the whole
+/// input, which the tokenizer keeps in memory as it is pushed.
+pub fn stream_tokenizer(options: &Options) -> StreamTokenizer {
+    StreamTokenizer::new(options)
+}
+
+/// Returns an iterator over the tokens of the UTF-8 input read from
+/// `reader`, which yields each token as soon as enough of the input has
+/// been read.
+pub fn read_tokenizer<R: Read>(reader: R, options: &Options) -> ReadTokenizer<R> {
+    ReadTokenizer::new(reader, options)
+}
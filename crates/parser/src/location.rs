@@ -1,8 +1,19 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{errors::ParserError, parser::Parser, whitespace::next_line_break};
+use crate::{
+    atom::Atom,
+    errors::{ErrorCode, ParserError},
+    node::{Expression, Program},
+    options::{OffsetUnit, Sink},
+    parser::Parser,
+    visit::VisitControl,
+    visit_mut::{NodeMut, VisitMut},
+    whitespace::is_new_line,
+};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// A line, counted from 1, and a column, counted from 0 in the
+/// `offset_unit` of the options.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Position {
     pub line: i32,
     pub column: i32,
@@ -21,15 +32,16 @@ impl Position {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct SourceLocation {
     pub start: Position,
     pub end: Option<Position>,
-    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Atom>,
 }
 
 impl SourceLocation {
-    pub fn new(start: &Position, end: &Option<Position>, source: &Option<String>) -> Self {
+    pub fn new(start: &Position, end: &Option<Position>, source: &Option<Atom>) -> Self {
         SourceLocation {
             start: start.clone(),
             end: end.clone(),
@@ -38,49 +50,403 @@ impl SourceLocation {
     }
 }
 
-pub fn get_line_info(input: &str, offset: i32) -> Position {
-    let mut line: i32 = 1;
-    let mut cur: i32 = 0;
-    loop {
-        let next_break = next_line_break(input, cur, offset);
-        if next_break < 0 {
-            return Position::new(line, offset - cur);
+/// An offset, with its line and column when `locations` is set.
+pub type ReportedPosition = (i32, Option<Position>);
+
+/// Where the positions reported by `on_insert_semicolon` and
+/// `on_trailing_comma` go.
+pub type OnPosition = Sink<ReportedPosition>;
+
+/// The byte offsets at which the lines of an input start, built once so
+/// that offsets are turned into positions by binary search rather than by
+/// walking the input.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    line_starts: Vec<i32>,
+}
+
+impl LineIndex {
+    pub fn new(input: &str) -> Self {
+        let mut line_index = LineIndex {
+            line_starts: vec![0],
+        };
+        line_index.extend(input, 0);
+        line_index
+    }
+
+    /// Adds the lines of `input` from `from` on, for an input that has
+    /// grown since its first `from` bytes were indexed.
+    pub fn extend(&mut self, input: &str, mut from: usize) {
+        // A `\r\n` split across the old and the new text is one line break.
+        if input[..from].ends_with('\r') && input[from..].starts_with('\n') {
+            *self.line_starts.last_mut().unwrap() += 1;
+            from += 1;
+        }
+        let mut chars = input[from..].char_indices().peekable();
+        while let Some((index, ch)) = chars.next() {
+            if is_new_line(ch as i32) {
+                let mut next = from + index + ch.len_utf8();
+                if ch == '\r' && chars.next_if(|(_, next)| *next == '\n').is_some() {
+                    next += 1;
+                }
+                self.line_starts.push(next as i32);
+            }
+        }
+    }
+
+    /// The number of lines, which is at least one.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The offset at which `line`, counted from 1, starts.
+    pub fn line_start(&self, line: i32) -> i32 {
+        self.line_starts[(line - 1) as usize]
+    }
+
+    pub fn position(&self, offset: i32) -> Position {
+        let line = self
+            .line_starts
+            .partition_point(|start| *start <= offset)
+            .max(1);
+        Position::new(line as i32, offset - self.line_starts[line - 1])
+    }
+}
+
+/// A non-ASCII character, with where it starts in each unit. Offsets
+/// only differ between units after such characters.
+#[derive(Debug, Clone, Copy)]
+struct WideChar {
+    utf8: i32,
+    utf16: i32,
+    code_point: i32,
+    ch: char,
+}
+
+impl WideChar {
+    fn start(&self, unit: OffsetUnit) -> i32 {
+        match unit {
+            OffsetUnit::Utf8 => self.utf8,
+            OffsetUnit::Utf16 => self.utf16,
+            OffsetUnit::CodePoint => self.code_point,
+        }
+    }
+
+    fn len(&self, unit: OffsetUnit) -> i32 {
+        match unit {
+            OffsetUnit::Utf8 => self.ch.len_utf8() as i32,
+            OffsetUnit::Utf16 => self.ch.len_utf16() as i32,
+            OffsetUnit::CodePoint => 1,
+        }
+    }
+}
+
+/// Converts offsets into an input between units. Built once per input,
+/// each conversion is then a binary search over its non-ASCII characters.
+#[derive(Debug, Clone, Default)]
+pub struct OffsetConverter {
+    wide_chars: Vec<WideChar>,
+}
+
+impl OffsetConverter {
+    pub fn new(input: &str) -> Self {
+        let mut offsets = OffsetConverter::default();
+        offsets.extend(input, 0);
+        offsets
+    }
+
+    /// Adds the characters of `input` from `from` on, for an input that
+    /// has grown since its first `from` bytes were indexed.
+    pub fn extend(&mut self, input: &str, from: usize) {
+        let (mut utf16, mut code_point) = match self.wide_chars.last() {
+            Some(last) => {
+                let after = from as i32 - last.utf8 - last.len(OffsetUnit::Utf8);
+                (
+                    last.utf16 + last.len(OffsetUnit::Utf16) + after,
+                    last.code_point + 1 + after,
+                )
+            }
+            None => (from as i32, from as i32),
+        };
+        for (index, ch) in input[from..].char_indices() {
+            if !ch.is_ascii() {
+                self.wide_chars.push(WideChar {
+                    utf8: (from + index) as i32,
+                    utf16,
+                    code_point,
+                    ch,
+                });
+            }
+            utf16 += ch.len_utf16() as i32;
+            code_point += 1;
+        }
+    }
+
+    /// Converts `offset`, counted in `from`, to `to`. An offset inside a
+    /// character is moved to its start.
+    pub fn convert(&self, offset: i32, from: OffsetUnit, to: OffsetUnit) -> i32 {
+        if from == to {
+            return offset;
+        }
+        let index = self
+            .wide_chars
+            .partition_point(|wide_char| wide_char.start(from) <= offset);
+        match index.checked_sub(1).map(|index| self.wide_chars[index]) {
+            None => offset,
+            Some(wide_char) => {
+                let after = offset - wide_char.start(from) - wide_char.len(from);
+                if after < 0 {
+                    wide_char.start(to)
+                } else {
+                    // Only ASCII characters, one unit each, follow it up to
+                    // the offset.
+                    wide_char.start(to) + wide_char.len(to) + after
+                }
+            }
+        }
+    }
+
+    /// Converts `offset` into `input`, counted in `unit`, to UTF-8, or
+    /// fails with `InvalidOffset` if it is out of the input or inside a
+    /// character.
+    pub fn to_utf8_checked(
+        &self,
+        input: &str,
+        offset: i32,
+        unit: OffsetUnit,
+    ) -> Result<i32, ParserError> {
+        let utf8 = self.convert(offset, unit, OffsetUnit::Utf8);
+        // An offset inside a character is moved to its start, and so does
+        // not survive the round trip.
+        if self.convert(utf8, OffsetUnit::Utf8, unit) != offset
+            || utf8 < 0
+            || !input.is_char_boundary(utf8 as usize)
+        {
+            return Err(ParserError::InvalidOffset(offset));
         }
-        line += 1;
-        cur = next_break;
+        Ok(utf8)
+    }
+
+    /// Converts the column of `position`, counted in `from`, to `to`.
+    pub fn convert_position(
+        &self,
+        line_index: &LineIndex,
+        position: &Position,
+        from: OffsetUnit,
+        to: OffsetUnit,
+    ) -> Position {
+        if from == to || position.line < 1 || position.line as usize > line_index.line_count() {
+            return position.clone();
+        }
+        let line_start = line_index.line_start(position.line);
+        let offset = self.convert(line_start, OffsetUnit::Utf8, from) + position.column;
+        Position::new(
+            position.line,
+            self.convert(offset, from, to) - self.convert(line_start, OffsetUnit::Utf8, to),
+        )
     }
 }
 
 pub trait LocationParser {
     fn get_cur_position(&self) -> Option<Position>;
-    fn raise(&self, pos: i32, message: &str) -> Result<(), ParserError>;
-    fn raise_recoverable(&self, pos: i32, message: &str) -> Result<(), ParserError>;
+    fn syntax_error(&self, pos: i32, code: ErrorCode, message: &str) -> ParserError;
+    fn raise(&self, pos: i32, code: ErrorCode, message: &str) -> Result<(), ParserError>;
+    fn raise_recoverable(
+        &mut self,
+        pos: i32,
+        code: ErrorCode,
+        message: &str,
+    ) -> Result<(), ParserError>;
+    fn recover(&mut self, error: ParserError) -> Result<(), ParserError>;
+    fn output_offset(&self, offset: i32) -> i32;
+    fn output_position(&self, position: &Position) -> Position;
+    fn output_range(
+        &self,
+        start: &mut i32,
+        end: &mut i32,
+        loc: &mut Option<SourceLocation>,
+        range: &mut Option<(i32, i32)>,
+    );
+    fn output_error(&self, error: ParserError) -> ParserError;
+    fn output_program(&self, program: &mut Program);
+    fn output_expression(&self, expression: &mut Expression);
+}
+
+/// Converts the spans of the nodes it walks to the `offset_unit` of the
+/// options.
+pub(crate) struct OffsetRewriter<'a> {
+    pub(crate) parser: &'a Parser,
+}
+
+impl VisitMut for OffsetRewriter<'_> {
+    fn enter(&mut self, mut node: NodeMut<'_>) -> VisitControl {
+        let span = node.span_mut();
+        self.parser.output_range(
+            &mut span.start,
+            &mut span.end,
+            &mut span.loc,
+            &mut span.range,
+        );
+        VisitControl::Continue
+    }
 }
 
 impl LocationParser for Parser {
     fn get_cur_position(&self) -> Option<Position> {
         if self.options.locations {
             Some(Position::new(
-                self.cur_token_line.take(),
-                self.cur_token_pos.take() - self.cur_token_line_start.take(),
+                self.cur_token_line,
+                self.cur_token_pos - self.cur_token_line_start,
             ))
         } else {
             None
         }
     }
 
-    fn raise(&self, pos: i32, message: &str) -> Result<(), ParserError> {
-        let location = get_line_info(&self.input, pos);
-        let message = format!("{:} ({:}:{:})", message, location.line, location.column);
-        Err(ParserError::SyntaxError {
+    /// Builds the error for the text at `pos`. When a token starts there,
+    /// the error spans it.
+    fn syntax_error(&self, pos: i32, code: ErrorCode, message: &str) -> ParserError {
+        let location = self.line_index.position(pos);
+        let shown = self.output_position(&location);
+        let message = format!("{:} ({:}:{:})", message, shown.line, shown.column);
+        let end = if pos == self.cur_token_start && self.cur_token_end > pos {
+            self.cur_token_end
+        } else if pos == self.last_token_start && self.last_token_end > pos {
+            self.last_token_end
+        } else {
+            pos
+        };
+        ParserError::SyntaxError {
+            code,
             message,
             pos,
+            end,
             loc: location,
-            raised_at: pos,
-        })
+            raised_at: self.cur_token_pos,
+            labels: vec![],
+            notes: vec![],
+            unit: OffsetUnit::Utf8,
+        }
     }
 
-    fn raise_recoverable(&self, pos: i32, message: &str) -> Result<(), ParserError> {
-        self.raise(pos, message)
+    fn raise(&self, pos: i32, code: ErrorCode, message: &str) -> Result<(), ParserError> {
+        Err(self.syntax_error(pos, code, message))
+    }
+
+    /// Raises an error after which parsing can go on.
+    fn raise_recoverable(
+        &mut self,
+        pos: i32,
+        code: ErrorCode,
+        message: &str,
+    ) -> Result<(), ParserError> {
+        self.recover(self.syntax_error(pos, code, message))
+    }
+
+    /// Fails with an error after which parsing can go on or, with the
+    /// `collect_errors` option, records it.
+    fn recover(&mut self, error: ParserError) -> Result<(), ParserError> {
+        if self.options.collect_errors {
+            self.errors.push(error);
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+
+    // The parser works on byte offsets, which are only converted to the
+    // unit of the options in what it hands out.
+
+    fn output_offset(&self, offset: i32) -> i32 {
+        self.offsets
+            .convert(offset, OffsetUnit::Utf8, self.options.offset_unit)
+    }
+
+    fn output_position(&self, position: &Position) -> Position {
+        self.offsets.convert_position(
+            &self.line_index,
+            position,
+            OffsetUnit::Utf8,
+            self.options.offset_unit,
+        )
+    }
+
+    fn output_range(
+        &self,
+        start: &mut i32,
+        end: &mut i32,
+        loc: &mut Option<SourceLocation>,
+        range: &mut Option<(i32, i32)>,
+    ) {
+        if self.options.offset_unit == OffsetUnit::Utf8 {
+            return;
+        }
+        *start = self.output_offset(*start);
+        *end = self.output_offset(*end);
+        if let Some(loc) = loc {
+            loc.start = self.output_position(&loc.start);
+            loc.end = loc.end.as_ref().map(|end| self.output_position(end));
+        }
+        if let Some((range_start, range_end)) = range {
+            *range_start = self.output_offset(*range_start);
+            *range_end = self.output_offset(*range_end);
+        }
+    }
+
+    fn output_error(&self, mut error: ParserError) -> ParserError {
+        if let ParserError::SyntaxError {
+            pos,
+            end,
+            loc,
+            raised_at,
+            labels,
+            unit,
+            ..
+        } = &mut error
+        {
+            if *unit == OffsetUnit::Utf8 {
+                *pos = self.output_offset(*pos);
+                *end = self.output_offset(*end);
+                *loc = self.output_position(loc);
+                *raised_at = self.output_offset(*raised_at);
+                for label in labels {
+                    label.start = self.output_offset(label.start);
+                    label.end = self.output_offset(label.end);
+                }
+                *unit = self.options.offset_unit;
+            }
+        }
+        error
+    }
+
+    fn output_program(&self, program: &mut Program) {
+        if self.options.offset_unit == OffsetUnit::Utf8 {
+            return;
+        }
+        // The statements of a program passed in the options are already
+        // converted, and so is its start.
+        let mut rewriter = OffsetRewriter { parser: self };
+        match &self.options.program {
+            Some(original) => {
+                for statement in &mut program.body[original.body.len()..] {
+                    rewriter.visit_statement(statement);
+                }
+                let span = &mut program.span;
+                span.end = self.output_offset(span.end);
+                if let Some(loc) = &mut span.loc {
+                    loc.end = loc.end.as_ref().map(|end| self.output_position(end));
+                }
+                if let Some((_, range_end)) = &mut span.range {
+                    *range_end = self.output_offset(*range_end);
+                }
+            }
+            None => rewriter.visit_program(program),
+        }
+    }
+
+    fn output_expression(&self, expression: &mut Expression) {
+        if self.options.offset_unit != OffsetUnit::Utf8 {
+            OffsetRewriter { parser: self }.visit_expression(expression);
+        }
     }
 }
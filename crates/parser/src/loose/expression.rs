@@ -0,0 +1,1324 @@
+use crate::{
+    atom::Atom,
+    errors::{ErrorCode, ParserError},
+    expression::operator_from_token,
+    location::Position,
+    loose::{statement::LooseStatementParser, tokenize::LooseTokenizer, LooseParser, DUMMY_VALUE},
+    lval::simple_target_to_pattern,
+    node::{
+        ArrayExpression, ArrayPattern, ArrowFunctionBody, ArrowFunctionExpression,
+        AssignmentExpression, AssignmentOperator, AssignmentPattern, AssignmentProperty,
+        AwaitExpression, BinaryExpression, BinaryOperator, CallExpression, ChainExpression,
+        ConditionalExpression, Expression, ExpressionOrSpread, Function, Identifier,
+        ImportExpression, Literal, LiteralValue, LogicalExpression, LogicalOperator,
+        MemberExpression, MetaProperty, NewExpression, ObjectExpression, ObjectPattern,
+        ParenthesizedExpression, Pattern, PrivateIdentifier, Property, PropertyKind,
+        PropertyOrRest, PropertyOrSpread, RestElement, SequenceExpression, Span, Spanned,
+        SpreadElement, Super, TaggedTemplateExpression, TemplateElement, TemplateElementValue,
+        TemplateLiteral, ThisExpression, UnaryExpression, UnaryOperator, UpdateExpression,
+        UpdateOperator, YieldExpression,
+    },
+    statement::StatementParser,
+    token::{
+        context::{get_token_context_types, TokenContextParser},
+        types::{get_token_types, TokenType},
+        TokenValue,
+    },
+    whitespace::is_new_line,
+};
+
+/// Whether `expr` is an identifier inserted in place of missing syntax.
+pub(crate) fn is_dummy(expr: &Expression) -> bool {
+    matches!(expr, Expression::Identifier(id) if id.name == DUMMY_VALUE)
+}
+
+fn is_simple_target(expr: &Expression) -> bool {
+    match expr {
+        Expression::Identifier(_) | Expression::MemberExpression(_) => true,
+        Expression::ParenthesizedExpression(paren) => is_simple_target(&paren.expression),
+        _ => false,
+    }
+}
+
+pub(crate) trait LooseExpressionParser {
+    fn check_lval(&mut self, expr: Expression) -> Expression;
+    fn parse_expression(&mut self, no_in: bool) -> Result<Expression, ParserError>;
+    fn parse_paren_expression(&mut self) -> Result<Expression, ParserError>;
+    fn parse_maybe_assign(&mut self, no_in: bool) -> Result<Expression, ParserError>;
+    fn parse_maybe_conditional(&mut self, no_in: bool) -> Result<Expression, ParserError>;
+    fn parse_expr_ops(&mut self, no_in: bool) -> Result<Expression, ParserError>;
+    #[allow(clippy::too_many_arguments)]
+    fn parse_expr_op(
+        &mut self,
+        left: Expression,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        min_prec: i32,
+        no_in: bool,
+        indent: i32,
+        line: i32,
+    ) -> Result<Expression, ParserError>;
+    fn parse_maybe_unary(&mut self, saw_unary: bool) -> Result<Expression, ParserError>;
+    fn parse_expr_subscripts(&mut self) -> Result<Expression, ParserError>;
+    fn parse_subscripts(
+        &mut self,
+        base: Expression,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        no_calls: bool,
+        start_indent: i32,
+        line: i32,
+    ) -> Result<Expression, ParserError>;
+    fn parse_expr_atom(&mut self) -> Result<Expression, ParserError>;
+    fn parse_paren_and_distinguish_expression(&mut self) -> Result<Expression, ParserError>;
+    fn parse_literal(&mut self) -> Result<Literal, ParserError>;
+    fn parse_expr_import(&mut self) -> Result<Expression, ParserError>;
+    fn parse_new(&mut self) -> Result<Expression, ParserError>;
+    fn parse_template_element(&mut self) -> Result<TemplateElement, ParserError>;
+    fn parse_template(&mut self) -> Result<TemplateLiteral, ParserError>;
+    fn parse_obj(&mut self) -> Result<ObjectExpression, ParserError>;
+    fn is_async_prop(&self, computed: bool, key: &Expression) -> bool;
+    fn parse_property_name(&mut self) -> Result<(bool, Expression), ParserError>;
+    fn parse_property_accessor(&mut self) -> Result<Option<Expression>, ParserError>;
+    fn parse_ident(&mut self) -> Result<Identifier, ParserError>;
+    fn parse_private_ident(&mut self) -> Result<PrivateIdentifier, ParserError>;
+    fn to_assignable(&mut self, expr: Expression, is_binding: bool) -> Pattern;
+    fn to_assignable_list(
+        &mut self,
+        exprs: Vec<Option<ExpressionOrSpread>>,
+        is_binding: bool,
+    ) -> Vec<Option<Pattern>>;
+    fn parse_function_params(&mut self) -> Result<Vec<Pattern>, ParserError>;
+    fn parse_method(&mut self, is_generator: bool, is_async: bool)
+        -> Result<Function, ParserError>;
+    fn parse_arrow_expression(
+        &mut self,
+        span: Span,
+        params: Vec<ExpressionOrSpread>,
+        is_async: bool,
+    ) -> Result<ArrowFunctionExpression, ParserError>;
+    fn parse_expr_list(
+        &mut self,
+        close: &TokenType,
+        allow_empty: bool,
+    ) -> Result<Vec<Option<ExpressionOrSpread>>, ParserError>;
+    fn parse_await(&mut self) -> Result<AwaitExpression, ParserError>;
+}
+
+impl LooseExpressionParser for LooseParser {
+    /// Replaces an invalid assignment target with a dummy identifier.
+    fn check_lval(&mut self, expr: Expression) -> Expression {
+        match expr {
+            Expression::ParenthesizedExpression(mut paren) => {
+                paren.expression = self.check_lval(paren.expression);
+                Expression::ParenthesizedExpression(paren)
+            }
+            expr if is_simple_target(&expr) => expr,
+            expr => {
+                if !is_dummy(&expr) {
+                    self.report(
+                        expr.span().start,
+                        ErrorCode::InvalidAssignmentTarget,
+                        "Assigning to rvalue",
+                    );
+                }
+                Expression::Identifier(Box::new(self.dummy_ident()))
+            }
+        }
+    }
+
+    fn parse_expression(&mut self, no_in: bool) -> Result<Expression, ParserError> {
+        let (start_pos, start_loc) = self.store_current_pos();
+        let expr = self.parse_maybe_assign(no_in)?;
+        let token_types = get_token_types();
+        if self.tok_is(&token_types.comma) {
+            let span = self.start_node_at(start_pos, &start_loc);
+            let mut expressions = vec![expr];
+            while self.eat(&token_types.comma)? {
+                expressions.push(self.parse_maybe_assign(no_in)?);
+            }
+            return Ok(Expression::SequenceExpression(Box::new(
+                SequenceExpression {
+                    span: self.finish_node(span),
+                    expressions,
+                },
+            )));
+        }
+        Ok(expr)
+    }
+
+    fn parse_paren_expression(&mut self) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        self.push_cx();
+        self.expect(&token_types.paren_l)?;
+        let value = self.parse_expression(false)?;
+        self.pop_cx();
+        self.expect(&token_types.paren_r)?;
+        Ok(value)
+    }
+
+    fn parse_maybe_assign(&mut self, no_in: bool) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        // `yield` should be an identifier reference if it's not in
+        // generator functions.
+        if self.in_generator && self.is_contextual("yield") {
+            let span = self.start_node();
+            self.next()?;
+            let tok_type = self.tok.token_type.clone();
+            let (delegate, argument) = if tok_type.eq(&token_types.semi)
+                || self.can_insert_semicolon()
+                || (tok_type.ne(&token_types.star) && !tok_type.starts_expr)
+            {
+                (false, None)
+            } else {
+                let delegate = self.eat(&token_types.star)?;
+                (delegate, Some(self.parse_maybe_assign(false)?))
+            };
+            return Ok(Expression::YieldExpression(Box::new(YieldExpression {
+                span: self.finish_node(span),
+                delegate,
+                argument,
+            })));
+        }
+
+        let (start_pos, start_loc) = self.store_current_pos();
+        let left = self.parse_maybe_conditional(no_in)?;
+        let tok_type = self.tok.token_type.clone();
+        if tok_type.is_assign {
+            let span = self.start_node_at(start_pos, &start_loc);
+            let operator =
+                operator_from_token(self.tok.value.as_str(), AssignmentOperator::from_token);
+            let left = if tok_type.eq(&token_types.eq) {
+                self.to_assignable(left, false)
+            } else {
+                simple_target_to_pattern(self.check_lval(left))
+                    .unwrap_or_else(|expr| self.to_assignable(expr, false))
+            };
+            self.next()?;
+            let right = self.parse_maybe_assign(no_in)?;
+            return Ok(Expression::AssignmentExpression(Box::new(
+                AssignmentExpression {
+                    span: self.finish_node(span),
+                    operator,
+                    left,
+                    right,
+                },
+            )));
+        }
+        Ok(left)
+    }
+
+    fn parse_maybe_conditional(&mut self, no_in: bool) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let (start_pos, start_loc) = self.store_current_pos();
+        let expr = self.parse_expr_ops(no_in)?;
+        if self.eat(&token_types.question)? {
+            let span = self.start_node_at(start_pos, &start_loc);
+            let consequent = self.parse_maybe_assign(false)?;
+            let alternate = if self.expect(&token_types.colon)? {
+                self.parse_maybe_assign(no_in)?
+            } else {
+                Expression::Identifier(Box::new(self.dummy_ident()))
+            };
+            return Ok(Expression::ConditionalExpression(Box::new(
+                ConditionalExpression {
+                    span: self.finish_node(span),
+                    test: expr,
+                    consequent,
+                    alternate,
+                },
+            )));
+        }
+        Ok(expr)
+    }
+
+    fn parse_expr_ops(&mut self, no_in: bool) -> Result<Expression, ParserError> {
+        let (start_pos, start_loc) = self.store_current_pos();
+        let indent = self.cur_indent;
+        let line = self.cur_line_start;
+        let left = self.parse_maybe_unary(false)?;
+        self.parse_expr_op(left, start_pos, &start_loc, -1, no_in, indent, line)
+    }
+
+    /// Parses binary operators like the regular parser, except that an
+    /// operator is not continued on a line indented less than the start of
+    /// the expression.
+    fn parse_expr_op(
+        &mut self,
+        left: Expression,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        min_prec: i32,
+        no_in: bool,
+        indent: i32,
+        line: i32,
+    ) -> Result<Expression, ParserError> {
+        let dedented = |parser: &Self| {
+            parser.cur_line_start != line
+                && parser.cur_indent < indent
+                && parser.token_starts_line()
+        };
+        if dedented(self) {
+            return Ok(left);
+        }
+        let token_types = get_token_types();
+        let tok_type = self.tok.token_type.clone();
+        let prec = match tok_type.binop {
+            Some(prec) if (!no_in || tok_type.ne(&token_types._in)) && prec > min_prec => prec,
+            _ => return Ok(left),
+        };
+        let span = self.start_node_at(start_pos, start_loc);
+        let operator = self.tok.value.as_str().to_owned();
+        self.next()?;
+        let right = if dedented(self) {
+            Expression::Identifier(Box::new(self.dummy_ident()))
+        } else {
+            let (right_start_pos, right_start_loc) = self.store_current_pos();
+            let right = self.parse_maybe_unary(false)?;
+            self.parse_expr_op(
+                right,
+                right_start_pos,
+                &right_start_loc,
+                prec,
+                no_in,
+                indent,
+                line,
+            )?
+        };
+        let span = self.finish_node(span);
+        let node = match LogicalOperator::from_token(&operator) {
+            Some(operator) => Expression::LogicalExpression(Box::new(LogicalExpression {
+                span,
+                left,
+                operator,
+                right,
+            })),
+            None => Expression::BinaryExpression(Box::new(BinaryExpression {
+                span,
+                left,
+                operator: operator_from_token(&operator, BinaryOperator::from_token),
+                right,
+            })),
+        };
+        self.parse_expr_op(node, start_pos, start_loc, min_prec, no_in, indent, line)
+    }
+
+    fn parse_maybe_unary(&mut self, saw_unary: bool) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let ecma_version = self.ecma_version();
+        let (start_pos, start_loc) = self.store_current_pos();
+        let mut saw_unary = saw_unary;
+        let tok_type = self.tok.token_type.clone();
+        let mut expr;
+        if ecma_version >= 8
+            && self.is_contextual("await")
+            && (self.in_async
+                || (self.toks.is_in_module && ecma_version >= 13)
+                || (!self.in_function
+                    && self
+                        .toks
+                        .options
+                        .allow_await_outside_function
+                        .unwrap_or(false)))
+        {
+            expr = Expression::AwaitExpression(Box::new(self.parse_await()?));
+            saw_unary = true;
+        } else if tok_type.prefix {
+            let span = self.start_node();
+            let is_update = tok_type.eq(&token_types.inc_dec);
+            if !is_update {
+                saw_unary = true;
+            }
+            let operator = self.tok.value.as_str().to_owned();
+            self.next()?;
+            let argument = self.parse_maybe_unary(true)?;
+            expr = if is_update {
+                let argument = self.check_lval(argument);
+                Expression::UpdateExpression(Box::new(UpdateExpression {
+                    span: self.finish_node(span),
+                    operator: operator_from_token(&operator, UpdateOperator::from_token),
+                    prefix: true,
+                    argument,
+                }))
+            } else {
+                Expression::UnaryExpression(Box::new(UnaryExpression {
+                    span: self.finish_node(span),
+                    operator: operator_from_token(&operator, UnaryOperator::from_token),
+                    argument,
+                }))
+            };
+        } else if tok_type.eq(&token_types.ellipsis) {
+            // A spread where no spread is allowed is dropped, keeping its
+            // argument.
+            self.unexpected();
+            self.next()?;
+            expr = self.parse_maybe_unary(saw_unary)?;
+        } else if !saw_unary && tok_type.eq(&token_types.private_id) {
+            expr = Expression::PrivateIdentifier(Box::new(self.parse_private_ident()?));
+        } else {
+            expr = self.parse_expr_subscripts()?;
+            while self.tok.token_type.postfix && !self.can_insert_semicolon() {
+                let span = self.start_node_at(start_pos, &start_loc);
+                let operator =
+                    operator_from_token(self.tok.value.as_str(), UpdateOperator::from_token);
+                let argument = self.check_lval(expr);
+                self.next()?;
+                expr = Expression::UpdateExpression(Box::new(UpdateExpression {
+                    span: self.finish_node(span),
+                    operator,
+                    prefix: false,
+                    argument,
+                }));
+            }
+        }
+
+        if !saw_unary && self.eat(&token_types.star_star)? {
+            let span = self.start_node_at(start_pos, &start_loc);
+            let right = self.parse_maybe_unary(false)?;
+            expr = Expression::BinaryExpression(Box::new(BinaryExpression {
+                span: self.finish_node(span),
+                left: expr,
+                operator: BinaryOperator::Exponentiation,
+                right,
+            }));
+        }
+        Ok(expr)
+    }
+
+    fn parse_expr_subscripts(&mut self) -> Result<Expression, ParserError> {
+        let (start_pos, start_loc) = self.store_current_pos();
+        let indent = self.cur_indent;
+        let line = self.cur_line_start;
+        let base = self.parse_expr_atom()?;
+        self.parse_subscripts(base, start_pos, &start_loc, false, indent, line)
+    }
+
+    /// Parses subscripts like the regular parser. A subscript on a line
+    /// indented no more than the start of the expression is taken to
+    /// start a new statement, unless it is a member access continuing a
+    /// chain of calls.
+    fn parse_subscripts(
+        &mut self,
+        base: Expression,
+        start_pos: i32,
+        start_loc: &Option<Position>,
+        no_calls: bool,
+        start_indent: i32,
+        line: i32,
+    ) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let optional_supported = self.ecma_version() >= 11;
+        let mut start_indent = start_indent;
+        let mut base = base;
+        let mut optional_chained = false;
+        loop {
+            let dedented = |parser: &Self, start_indent: i32| {
+                parser.cur_line_start != line
+                    && parser.cur_indent <= start_indent
+                    && parser.token_starts_line()
+            };
+            if dedented(self, start_indent) {
+                if self.tok_is(&token_types.dot) && self.cur_indent == start_indent {
+                    start_indent -= 1;
+                } else {
+                    break;
+                }
+            }
+
+            let maybe_async_arrow = matches!(&base, Expression::Identifier(id) if id.name == "async")
+                && !self.can_insert_semicolon();
+            let optional = optional_supported && self.eat(&token_types.question_dot)?;
+            if optional {
+                optional_chained = true;
+            }
+
+            if (optional
+                && !self.tok_is(&token_types.paren_l)
+                && !self.tok_is(&token_types.bracket_l)
+                && !self.tok_is(&token_types.back_quote))
+                || self.eat(&token_types.dot)?
+            {
+                let span = self.start_node_at(start_pos, start_loc);
+                let property = if dedented(self, start_indent) {
+                    None
+                } else {
+                    self.parse_property_accessor()?
+                };
+                let property = property
+                    .unwrap_or_else(|| Expression::Identifier(Box::new(self.dummy_ident())));
+                base = Expression::MemberExpression(Box::new(MemberExpression {
+                    span: self.finish_node(span),
+                    object: base,
+                    property,
+                    computed: false,
+                    optional,
+                }));
+            } else if self.tok_is(&token_types.bracket_l) {
+                self.push_cx();
+                self.next()?;
+                let span = self.start_node_at(start_pos, start_loc);
+                let property = self.parse_expression(false)?;
+                self.pop_cx();
+                self.expect(&token_types.bracket_r)?;
+                base = Expression::MemberExpression(Box::new(MemberExpression {
+                    span: self.finish_node(span),
+                    object: base,
+                    property,
+                    computed: true,
+                    optional,
+                }));
+            } else if !no_calls && self.tok_is(&token_types.paren_l) {
+                let arguments: Vec<ExpressionOrSpread> = self
+                    .parse_expr_list(&token_types.paren_r, false)?
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                if maybe_async_arrow && self.eat(&token_types.arrow)? {
+                    let span = self.start_node_at(start_pos, start_loc);
+                    let arrow = self.parse_arrow_expression(span, arguments, true)?;
+                    return Ok(Expression::ArrowFunctionExpression(Box::new(arrow)));
+                }
+                let span = self.start_node_at(start_pos, start_loc);
+                base = Expression::CallExpression(Box::new(CallExpression {
+                    span: self.finish_node(span),
+                    callee: base,
+                    arguments,
+                    optional,
+                }));
+            } else if self.tok_is(&token_types.back_quote) {
+                let span = self.start_node_at(start_pos, start_loc);
+                let quasi = self.parse_template()?;
+                base = Expression::TaggedTemplateExpression(Box::new(TaggedTemplateExpression {
+                    span: self.finish_node(span),
+                    tag: base,
+                    quasi,
+                }));
+            } else {
+                break;
+            }
+        }
+
+        if optional_chained {
+            let span = self.start_node_at(start_pos, start_loc);
+            base = Expression::ChainExpression(Box::new(ChainExpression {
+                span: self.finish_node(span),
+                expression: base,
+            }));
+        }
+        Ok(base)
+    }
+
+    fn parse_expr_atom(&mut self) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let tok_type = self.tok.token_type.clone();
+        if tok_type.eq(&token_types._this) || tok_type.eq(&token_types._super) {
+            let span = self.start_node();
+            self.next()?;
+            let span = self.finish_node(span);
+            Ok(if tok_type.eq(&token_types._this) {
+                Expression::ThisExpression(Box::new(ThisExpression { span }))
+            } else {
+                Expression::Super(Box::new(Super { span }))
+            })
+        } else if tok_type.eq(&token_types.name) {
+            let (start_pos, start_loc) = self.store_current_pos();
+            let mut id = self.parse_ident()?;
+            let mut is_async = false;
+            if id.name == "async" && !self.can_insert_semicolon() {
+                if self.eat(&token_types._function)? {
+                    self.toks
+                        .override_context(&get_token_context_types().f_expr);
+                    let span = self.start_node_at(start_pos, &start_loc);
+                    return Ok(Expression::FunctionExpression(Box::new(
+                        self.parse_function(span, false, true)?,
+                    )));
+                }
+                if self.tok_is(&token_types.name)
+                    && self.look_ahead(1)?.token_type.eq(&token_types.arrow)
+                {
+                    id = self.parse_ident()?;
+                    is_async = true;
+                }
+            }
+            if self.eat(&token_types.arrow)? {
+                let span = self.start_node_at(start_pos, &start_loc);
+                let params = vec![ExpressionOrSpread::Expression(Expression::Identifier(
+                    Box::new(id),
+                ))];
+                return Ok(Expression::ArrowFunctionExpression(Box::new(
+                    self.parse_arrow_expression(span, params, is_async)?,
+                )));
+            }
+            Ok(Expression::Identifier(Box::new(id)))
+        } else if tok_type.eq(&token_types.regexp)
+            || tok_type.eq(&token_types.num)
+            || tok_type.eq(&token_types.string)
+        {
+            Ok(Expression::Literal(Box::new(self.parse_literal()?)))
+        } else if tok_type.eq(&token_types._null)
+            || tok_type.eq(&token_types._true)
+            || tok_type.eq(&token_types._false)
+        {
+            let span = self.start_node();
+            let value = if tok_type.eq(&token_types._null) {
+                LiteralValue::Null
+            } else {
+                LiteralValue::Boolean(tok_type.eq(&token_types._true))
+            };
+            let raw = self
+                .toks
+                .atoms
+                .intern(tok_type.keyword.as_deref().unwrap_or_default());
+            self.next()?;
+            Ok(Expression::Literal(Box::new(Literal {
+                span: self.finish_node(span),
+                value,
+                raw,
+            })))
+        } else if tok_type.eq(&token_types.paren_l) {
+            self.parse_paren_and_distinguish_expression()
+        } else if tok_type.eq(&token_types.bracket_l) {
+            let span = self.start_node();
+            let elements = self.parse_expr_list(&token_types.bracket_r, true)?;
+            Ok(Expression::ArrayExpression(Box::new(ArrayExpression {
+                span: self.finish_node(span),
+                elements,
+            })))
+        } else if tok_type.eq(&token_types.brace_l) {
+            self.toks
+                .override_context(&get_token_context_types().b_expr);
+            Ok(Expression::ObjectExpression(Box::new(self.parse_obj()?)))
+        } else if tok_type.eq(&token_types._class) {
+            Ok(Expression::ClassExpression(Box::new(
+                self.parse_class(false)?,
+            )))
+        } else if tok_type.eq(&token_types._function) {
+            let span = self.start_node();
+            self.next()?;
+            Ok(Expression::FunctionExpression(Box::new(
+                self.parse_function(span, false, false)?,
+            )))
+        } else if tok_type.eq(&token_types._new) {
+            self.parse_new()
+        } else if tok_type.eq(&token_types.back_quote) {
+            Ok(Expression::TemplateLiteral(Box::new(
+                self.parse_template()?,
+            )))
+        } else if tok_type.eq(&token_types._import) && self.ecma_version() >= 11 {
+            self.parse_expr_import()
+        } else {
+            self.unexpected();
+            Ok(Expression::Identifier(Box::new(self.dummy_ident())))
+        }
+    }
+
+    /// Parses a parenthesized expression or the parameters of an arrow
+    /// function. A spread or a trailing comma is only reported once it is
+    /// known not to be an arrow function.
+    fn parse_paren_and_distinguish_expression(&mut self) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let (start_pos, start_loc) = self.store_current_pos();
+        self.next()?;
+        let (inner_start_pos, inner_start_loc) = self.store_current_pos();
+        let mut items = vec![];
+        let mut trailing_comma = None;
+        // An empty pair of parentheses is only valid before an arrow, this
+        // is what the expression turns into otherwise.
+        let mut empty = None;
+        if self.tok_is(&token_types.paren_r) {
+            empty = Some(self.dummy_ident());
+        } else {
+            loop {
+                if self.tok_is(&token_types.ellipsis) {
+                    let span = self.start_node();
+                    self.next()?;
+                    let argument = self.parse_maybe_assign(false)?;
+                    items.push(ExpressionOrSpread::SpreadElement(Box::new(SpreadElement {
+                        span: self.finish_node(span),
+                        argument,
+                    })));
+                } else {
+                    items.push(ExpressionOrSpread::Expression(
+                        self.parse_maybe_assign(false)?,
+                    ));
+                }
+                if !self.eat(&token_types.comma)? {
+                    break;
+                }
+                if self.tok_is(&token_types.paren_r) {
+                    trailing_comma = Some(self.last.start);
+                    break;
+                }
+            }
+        }
+        let inner_span = self.finish_node(self.start_node_at(inner_start_pos, &inner_start_loc));
+        let empty_pos = self.tok.start;
+        self.expect(&token_types.paren_r)?;
+
+        if self.eat(&token_types.arrow)? {
+            // `(a,` followed by garbage leaves a dummy in the last slot.
+            if matches!(items.last(), Some(ExpressionOrSpread::Expression(expr)) if is_dummy(expr))
+            {
+                items.pop();
+            }
+            let span = self.start_node_at(start_pos, &start_loc);
+            return Ok(Expression::ArrowFunctionExpression(Box::new(
+                self.parse_arrow_expression(span, items, false)?,
+            )));
+        }
+
+        if let Some(pos) = trailing_comma {
+            self.report(pos, ErrorCode::UnexpectedToken, "Unexpected token");
+        }
+        let mut expressions: Vec<Expression> = items
+            .into_iter()
+            .map(|item| match item {
+                ExpressionOrSpread::Expression(expr) => expr,
+                ExpressionOrSpread::SpreadElement(spread) => {
+                    self.report(
+                        spread.span.start,
+                        ErrorCode::UnexpectedToken,
+                        "Unexpected token",
+                    );
+                    spread.argument
+                }
+            })
+            .collect();
+        let inner = match empty {
+            Some(dummy) => {
+                self.report(empty_pos, ErrorCode::UnexpectedToken, "Unexpected token");
+                Expression::Identifier(Box::new(dummy))
+            }
+            None if expressions.len() == 1 => expressions.remove(0),
+            None => Expression::SequenceExpression(Box::new(SequenceExpression {
+                span: inner_span,
+                expressions,
+            })),
+        };
+        if self.toks.options.preserve_parens {
+            let span = self.start_node_at(start_pos, &start_loc);
+            return Ok(Expression::ParenthesizedExpression(Box::new(
+                ParenthesizedExpression {
+                    span: self.finish_node(span),
+                    expression: inner,
+                },
+            )));
+        }
+        Ok(inner)
+    }
+
+    fn parse_literal(&mut self) -> Result<Literal, ParserError> {
+        let span = self.start_node();
+        let (value, raw) = {
+            let tok = &self.tok;
+            let atoms = &mut self.toks.atoms;
+            let value = match &tok.value {
+                TokenValue::String(value) => LiteralValue::String(atoms.intern(value)),
+                TokenValue::Number(value) => LiteralValue::Number(*value),
+                TokenValue::BigInt(value) => LiteralValue::BigInt(atoms.intern(value)),
+                TokenValue::Regex { pattern, flags } => LiteralValue::Regex {
+                    pattern: atoms.intern(pattern),
+                    flags: atoms.intern(flags),
+                },
+                TokenValue::Null => LiteralValue::Null,
+            };
+            let raw = atoms.intern(&self.toks.input[tok.start as usize..tok.end as usize]);
+            (value, raw)
+        };
+        self.next()?;
+        Ok(Literal {
+            span: self.finish_node(span),
+            value,
+            raw,
+        })
+    }
+
+    fn parse_expr_import(&mut self) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        let meta = self.parse_ident()?;
+        if self.tok_is(&token_types.paren_l) {
+            let mut arguments = self
+                .parse_expr_list(&token_types.paren_r, false)?
+                .into_iter()
+                .flatten();
+            let source = match arguments.next() {
+                Some(ExpressionOrSpread::Expression(source)) => source,
+                Some(ExpressionOrSpread::SpreadElement(spread)) => {
+                    self.report(
+                        spread.span.start,
+                        ErrorCode::UnexpectedToken,
+                        "Unexpected token",
+                    );
+                    spread.argument
+                }
+                None => Expression::Literal(Box::new(self.dummy_string())),
+            };
+            Ok(Expression::ImportExpression(Box::new(ImportExpression {
+                span: self.finish_node(span),
+                source,
+            })))
+        } else if self.tok_is(&token_types.dot) {
+            self.next()?;
+            let property = self.parse_ident()?;
+            Ok(Expression::MetaProperty(Box::new(MetaProperty {
+                span: self.finish_node(span),
+                meta,
+                property,
+            })))
+        } else {
+            self.unexpected();
+            Ok(Expression::Identifier(Box::new(meta)))
+        }
+    }
+
+    fn parse_new(&mut self) -> Result<Expression, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        let start_indent = self.cur_indent;
+        let line = self.cur_line_start;
+        let meta = self.parse_ident()?;
+        if self.ecma_version() >= 6 && self.eat(&token_types.dot)? {
+            let property = self.parse_ident()?;
+            return Ok(Expression::MetaProperty(Box::new(MetaProperty {
+                span: self.finish_node(span),
+                meta,
+                property,
+            })));
+        }
+        let (start_pos, start_loc) = self.store_current_pos();
+        let callee = self.parse_expr_atom()?;
+        let callee =
+            self.parse_subscripts(callee, start_pos, &start_loc, true, start_indent, line)?;
+        let arguments = if self.tok_is(&token_types.paren_l) {
+            self.parse_expr_list(&token_types.paren_r, false)?
+                .into_iter()
+                .flatten()
+                .collect()
+        } else {
+            vec![]
+        };
+        Ok(Expression::NewExpression(Box::new(NewExpression {
+            span: self.finish_node(span),
+            callee,
+            arguments,
+        })))
+    }
+
+    fn parse_template_element(&mut self) -> Result<TemplateElement, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        let value = {
+            let tok = &self.tok;
+            let atoms = &mut self.toks.atoms;
+            // The loose parser accepts invalid escapes even in untagged
+            // templates.
+            if tok.token_type.eq(&token_types.invalid_template) {
+                TemplateElementValue {
+                    raw: atoms.intern(tok.value.as_str()),
+                    cooked: None,
+                }
+            } else {
+                let raw = &self.toks.input[tok.start as usize..tok.end as usize];
+                let raw = if raw.contains('\r') {
+                    atoms.intern(&raw.replace("\r\n", "\n").replace('\r', "\n"))
+                } else {
+                    atoms.intern(raw)
+                };
+                TemplateElementValue {
+                    raw,
+                    cooked: Some(atoms.intern(tok.value.as_str())),
+                }
+            }
+        };
+        self.next()?;
+        let tail = self.tok_is(&token_types.back_quote);
+        Ok(TemplateElement {
+            span: self.finish_node(span),
+            value,
+            tail,
+        })
+    }
+
+    fn parse_template(&mut self) -> Result<TemplateLiteral, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        self.next()?;
+        let mut expressions = vec![];
+        let mut element = self.parse_template_element()?;
+        let mut quasis = vec![];
+        let mut unterminated = false;
+        while !element.tail {
+            quasis.push(element);
+            // The tokenizer has reported the unterminated template.
+            if self.tok_is(&token_types.eof) {
+                unterminated = true;
+                element = TemplateElement {
+                    span: self.dummy_node(),
+                    value: TemplateElementValue {
+                        raw: Atom::default(),
+                        cooked: Some(Atom::default()),
+                    },
+                    tail: true,
+                };
+                break;
+            }
+            self.next()?;
+            expressions.push(self.parse_expression(false)?);
+            element = if self.expect(&token_types.brace_r)? {
+                self.parse_template_element()?
+            } else {
+                TemplateElement {
+                    span: self.dummy_node(),
+                    value: TemplateElementValue {
+                        raw: Atom::default(),
+                        cooked: Some(Atom::default()),
+                    },
+                    tail: true,
+                }
+            };
+        }
+        quasis.push(element);
+        if !unterminated {
+            self.expect(&token_types.back_quote)?;
+        }
+        Ok(TemplateLiteral {
+            span: self.finish_node(span),
+            expressions,
+            quasis,
+        })
+    }
+
+    fn parse_obj(&mut self) -> Result<ObjectExpression, ParserError> {
+        let token_types = get_token_types();
+        let ecma_version = self.ecma_version();
+        let span = self.start_node();
+        let mut properties = vec![];
+        self.push_cx();
+        let mut indent = self.cur_indent + 1;
+        let mut line = self.cur_line_start;
+        self.eat(&token_types.brace_l)?;
+        if self.cur_indent + 1 < indent {
+            indent = self.cur_indent;
+            line = self.cur_line_start;
+        }
+        while !self.closes(&token_types.brace_r, indent, line, false) {
+            let prop_span = self.start_node();
+            if ecma_version >= 9 && self.eat(&token_types.ellipsis)? {
+                let argument = self.parse_maybe_assign(false)?;
+                properties.push(PropertyOrSpread::SpreadElement(Box::new(SpreadElement {
+                    span: self.finish_node(prop_span),
+                    argument,
+                })));
+                self.eat(&token_types.comma)?;
+                continue;
+            }
+            let (start_pos, start_loc) = self.store_current_pos();
+            let mut is_generator = ecma_version >= 6 && self.eat(&token_types.star)?;
+            let (mut computed, mut key) = self.parse_property_name()?;
+            let is_async = self.is_async_prop(computed, &key);
+            if is_async {
+                is_generator = ecma_version >= 9 && self.eat(&token_types.star)?;
+                (computed, key) = self.parse_property_name()?;
+            }
+            if is_dummy(&key) {
+                if is_dummy(&self.parse_maybe_assign(false)?) {
+                    self.next()?;
+                }
+                self.eat(&token_types.comma)?;
+                continue;
+            }
+
+            let mut method = false;
+            let mut shorthand = false;
+            let mut kind = PropertyKind::Init;
+            let value = if self.eat(&token_types.colon)? {
+                self.parse_maybe_assign(false)?
+            } else if ecma_version >= 6
+                && (self.tok_is(&token_types.paren_l) || self.tok_is(&token_types.brace_l))
+            {
+                method = true;
+                Expression::FunctionExpression(Box::new(self.parse_method(is_generator, is_async)?))
+            } else if ecma_version >= 5
+                && !computed
+                && matches!(&key, Expression::Identifier(id) if id.name == "get" || id.name == "set")
+                && !self.tok_is(&token_types.comma)
+                && !self.tok_is(&token_types.brace_r)
+                && !self.tok_is(&token_types.eq)
+            {
+                kind = match &key {
+                    Expression::Identifier(id) if id.name == "get" => PropertyKind::Get,
+                    _ => PropertyKind::Set,
+                };
+                (computed, key) = self.parse_property_name()?;
+                Expression::FunctionExpression(Box::new(self.parse_method(false, false)?))
+            } else {
+                shorthand = true;
+                if ecma_version < 6 {
+                    self.unexpected();
+                    Expression::Identifier(Box::new(self.dummy_ident()))
+                } else if self.eat(&token_types.eq)? {
+                    let assign_span = self.start_node_at(start_pos, &start_loc);
+                    let left = self.to_assignable(key.clone(), false);
+                    let right = self.parse_maybe_assign(false)?;
+                    Expression::AssignmentExpression(Box::new(AssignmentExpression {
+                        span: self.finish_node(assign_span),
+                        operator: AssignmentOperator::Assign,
+                        left,
+                        right,
+                    }))
+                } else {
+                    key.clone()
+                }
+            };
+            properties.push(PropertyOrSpread::Property(Box::new(Property {
+                span: self.finish_node(prop_span),
+                method,
+                shorthand,
+                computed,
+                key,
+                kind,
+                value,
+            })));
+            if !self.eat(&token_types.comma)? && !self.tok_is(&token_types.brace_r) {
+                self.unexpected();
+            }
+        }
+        self.pop_cx();
+        if !self.eat(&token_types.brace_r)? {
+            // If there is no closing brace, make the node span to the start
+            // of the next token.
+            self.extend_last_to_current();
+            self.unexpected();
+        }
+        Ok(ObjectExpression {
+            span: self.finish_node(span),
+            properties,
+        })
+    }
+
+    fn is_async_prop(&self, computed: bool, key: &Expression) -> bool {
+        let token_types = get_token_types();
+        let tok = &self.tok;
+        let last_end = self.last.end;
+        !computed
+            && matches!(key, Expression::Identifier(id) if id.name == "async")
+            && (tok.token_type.eq(&token_types.name)
+                || tok.token_type.eq(&token_types.num)
+                || tok.token_type.eq(&token_types.string)
+                || tok.token_type.eq(&token_types.bracket_l)
+                || tok.token_type.keyword.is_some()
+                || (self.ecma_version() >= 9 && tok.token_type.eq(&token_types.star)))
+            && !self.toks.input[last_end as usize..tok.start.max(last_end) as usize]
+                .chars()
+                .any(|ch| is_new_line(ch as i32))
+    }
+
+    fn parse_property_name(&mut self) -> Result<(bool, Expression), ParserError> {
+        let token_types = get_token_types();
+        if self.ecma_version() >= 6 && self.eat(&token_types.bracket_l)? {
+            let key = self.parse_expression(false)?;
+            self.expect(&token_types.bracket_r)?;
+            return Ok((true, key));
+        }
+        let key = if self.tok_is(&token_types.num) || self.tok_is(&token_types.string) {
+            self.parse_expr_atom()?
+        } else {
+            Expression::Identifier(Box::new(self.parse_ident()?))
+        };
+        Ok((false, key))
+    }
+
+    fn parse_property_accessor(&mut self) -> Result<Option<Expression>, ParserError> {
+        let token_types = get_token_types();
+        let tok_type = self.tok.token_type.clone();
+        if tok_type.eq(&token_types.name) || tok_type.keyword.is_some() {
+            return Ok(Some(Expression::Identifier(Box::new(self.parse_ident()?))));
+        }
+        if tok_type.eq(&token_types.private_id) {
+            return Ok(Some(Expression::PrivateIdentifier(Box::new(
+                self.parse_private_ident()?,
+            ))));
+        }
+        self.unexpected();
+        Ok(None)
+    }
+
+    fn parse_ident(&mut self) -> Result<Identifier, ParserError> {
+        let token_types = get_token_types();
+        let (name, is_keyword) = {
+            let tok = &self.tok;
+            if tok.token_type.eq(&token_types.name) {
+                (self.toks.atoms.intern(tok.value.as_str()), false)
+            } else if let Some(keyword) = &tok.token_type.keyword {
+                (self.toks.atoms.intern(keyword), true)
+            } else {
+                self.unexpected();
+                return Ok(self.dummy_ident());
+            }
+        };
+        // Keep the tokenizer from treating the keyword as one when it
+        // updates its context, when it has not read past it yet.
+        if is_keyword && self.ahead.is_empty() {
+            self.toks.cur_token_type = token_types.name.clone();
+        }
+        let span = self.start_node();
+        self.next()?;
+        Ok(Identifier {
+            span: self.finish_node(span),
+            name,
+        })
+    }
+
+    fn parse_private_ident(&mut self) -> Result<PrivateIdentifier, ParserError> {
+        let span = self.start_node();
+        let name = self.toks.atoms.intern(self.tok.value.as_str());
+        self.next()?;
+        Ok(PrivateIdentifier {
+            span: self.finish_node(span),
+            name,
+        })
+    }
+
+    /// Converts an expression to a pattern, replacing the parts that can
+    /// not be assigned to with dummy identifiers.
+    fn to_assignable(&mut self, expr: Expression, is_binding: bool) -> Pattern {
+        match expr {
+            Expression::Identifier(id) => Pattern::Identifier(id),
+            Expression::MemberExpression(member) if !is_binding => {
+                Pattern::MemberExpression(member)
+            }
+            Expression::ParenthesizedExpression(mut paren) => {
+                paren.expression = self.check_lval(paren.expression);
+                Pattern::ParenthesizedExpression(paren)
+            }
+            expr if self.ecma_version() < 6 => {
+                self.report(
+                    expr.span().start,
+                    ErrorCode::InvalidAssignmentTarget,
+                    "Assigning to rvalue",
+                );
+                Pattern::Identifier(Box::new(self.dummy_ident()))
+            }
+            Expression::ObjectExpression(object) => {
+                let object = *object;
+                let properties = object
+                    .properties
+                    .into_iter()
+                    .map(|prop| match prop {
+                        PropertyOrSpread::Property(prop) => {
+                            let prop = *prop;
+                            PropertyOrRest::Property(Box::new(AssignmentProperty {
+                                span: prop.span,
+                                shorthand: prop.shorthand,
+                                computed: prop.computed,
+                                key: prop.key,
+                                value: self.to_assignable(prop.value, is_binding),
+                            }))
+                        }
+                        PropertyOrSpread::SpreadElement(spread) => {
+                            let spread = *spread;
+                            PropertyOrRest::RestElement(Box::new(RestElement {
+                                span: spread.span,
+                                argument: self.to_assignable(spread.argument, is_binding),
+                            }))
+                        }
+                    })
+                    .collect();
+                Pattern::ObjectPattern(Box::new(ObjectPattern {
+                    span: object.span,
+                    properties,
+                }))
+            }
+            Expression::ArrayExpression(array) => {
+                let array = *array;
+                Pattern::ArrayPattern(Box::new(ArrayPattern {
+                    span: array.span,
+                    elements: self.to_assignable_list(array.elements, is_binding),
+                }))
+            }
+            Expression::AssignmentExpression(assign) => {
+                let assign = *assign;
+                Pattern::AssignmentPattern(Box::new(AssignmentPattern {
+                    span: assign.span,
+                    left: assign.left,
+                    right: assign.right,
+                }))
+            }
+            expr => {
+                if !is_dummy(&expr) {
+                    self.report(
+                        expr.span().start,
+                        ErrorCode::InvalidAssignmentTarget,
+                        "Assigning to rvalue",
+                    );
+                }
+                Pattern::Identifier(Box::new(self.dummy_ident()))
+            }
+        }
+    }
+
+    fn to_assignable_list(
+        &mut self,
+        exprs: Vec<Option<ExpressionOrSpread>>,
+        is_binding: bool,
+    ) -> Vec<Option<Pattern>> {
+        exprs
+            .into_iter()
+            .map(|expr| match expr? {
+                ExpressionOrSpread::Expression(expr) => Some(self.to_assignable(expr, is_binding)),
+                ExpressionOrSpread::SpreadElement(spread) => {
+                    let spread = *spread;
+                    Some(Pattern::RestElement(Box::new(RestElement {
+                        span: spread.span,
+                        argument: self.to_assignable(spread.argument, is_binding),
+                    })))
+                }
+            })
+            .collect()
+    }
+
+    fn parse_function_params(&mut self) -> Result<Vec<Pattern>, ParserError> {
+        if !self.tok_is(&get_token_types().paren_l) {
+            self.unexpected();
+            return Ok(vec![]);
+        }
+        let params = self.parse_expr_list(&get_token_types().paren_r, false)?;
+        Ok(self
+            .to_assignable_list(params, true)
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    fn parse_method(
+        &mut self,
+        is_generator: bool,
+        is_async: bool,
+    ) -> Result<Function, ParserError> {
+        let span = self.start_node();
+        let generator = self.ecma_version() >= 6 && is_generator;
+        let is_async = self.ecma_version() >= 8 && is_async;
+        let old_in_async = std::mem::replace(&mut self.in_async, is_async);
+        let old_in_generator = std::mem::replace(&mut self.in_generator, generator);
+        let old_in_function = std::mem::replace(&mut self.in_function, true);
+        let params = self.parse_function_params()?;
+        let mut body = self.parse_block()?;
+        self.toks.adapt_directive_prologue(&mut body.body);
+        self.in_async = old_in_async;
+        self.in_generator = old_in_generator;
+        self.in_function = old_in_function;
+        Ok(Function {
+            span: self.finish_node(span),
+            id: None,
+            generator,
+            is_async,
+            params,
+            body,
+        })
+    }
+
+    fn parse_arrow_expression(
+        &mut self,
+        span: Span,
+        params: Vec<ExpressionOrSpread>,
+        is_async: bool,
+    ) -> Result<ArrowFunctionExpression, ParserError> {
+        let is_async = self.ecma_version() >= 8 && is_async;
+        let old_in_async = std::mem::replace(&mut self.in_async, is_async);
+        let old_in_generator = std::mem::replace(&mut self.in_generator, false);
+        let old_in_function = std::mem::replace(&mut self.in_function, true);
+        let params = self
+            .to_assignable_list(params.into_iter().map(Some).collect(), true)
+            .into_iter()
+            .flatten()
+            .collect();
+        let body = if self.tok_is(&get_token_types().brace_l) {
+            let mut body = self.parse_block()?;
+            self.toks.adapt_directive_prologue(&mut body.body);
+            ArrowFunctionBody::BlockStatement(Box::new(body))
+        } else {
+            ArrowFunctionBody::Expression(self.parse_maybe_assign(false)?)
+        };
+        self.in_async = old_in_async;
+        self.in_generator = old_in_generator;
+        self.in_function = old_in_function;
+        Ok(ArrowFunctionExpression {
+            span: self.finish_node(span),
+            is_async,
+            params,
+            body,
+        })
+    }
+
+    /// Parses a list of expressions, starting at its opening token. The
+    /// list ends at the closing token, or at the first line indented no
+    /// more than the line it starts on.
+    fn parse_expr_list(
+        &mut self,
+        close: &TokenType,
+        allow_empty: bool,
+    ) -> Result<Vec<Option<ExpressionOrSpread>>, ParserError> {
+        let token_types = get_token_types();
+        self.push_cx();
+        let indent = self.cur_indent;
+        let line = self.cur_line_start;
+        let mut elements = vec![];
+        // Opening bracket
+        self.next()?;
+        while !self.closes(close, indent + 1, line, false) {
+            if self.eat(&token_types.comma)? {
+                elements.push(if allow_empty {
+                    None
+                } else {
+                    let start = self.last.start;
+                    self.report(start, ErrorCode::UnexpectedToken, "Unexpected token");
+                    Some(ExpressionOrSpread::Expression(Expression::Identifier(
+                        Box::new(self.dummy_ident()),
+                    )))
+                });
+                continue;
+            }
+            let element = if self.tok_is(&token_types.ellipsis) {
+                let span = self.start_node();
+                self.next()?;
+                let argument = self.parse_maybe_assign(false)?;
+                ExpressionOrSpread::SpreadElement(Box::new(SpreadElement {
+                    span: self.finish_node(span),
+                    argument,
+                }))
+            } else {
+                ExpressionOrSpread::Expression(self.parse_maybe_assign(false)?)
+            };
+            if matches!(&element, ExpressionOrSpread::Expression(expr) if is_dummy(expr)) {
+                if self.closes(close, indent, line, false) {
+                    break;
+                }
+                self.next()?;
+            } else {
+                elements.push(Some(element));
+            }
+            if !self.eat(&token_types.comma)? && !self.tok_is(close) {
+                self.unexpected();
+            }
+        }
+        self.pop_cx();
+        if !self.eat(close)? {
+            // If there is no closing token, make the node span to the start
+            // of the next token.
+            self.extend_last_to_current();
+            self.unexpected();
+        }
+        Ok(elements)
+    }
+
+    fn parse_await(&mut self) -> Result<AwaitExpression, ParserError> {
+        let span = self.start_node();
+        self.next()?;
+        let argument = self.parse_maybe_unary(true)?;
+        Ok(AwaitExpression {
+            span: self.finish_node(span),
+            argument,
+        })
+    }
+}
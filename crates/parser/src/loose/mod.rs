@@ -0,0 +1,414 @@
+// ## Loose parsing
+//
+// An error-tolerant parser, ported from [acorn-loose]. It reads its
+// tokens from the regular tokenizer but never gives up: tokenizer errors
+// are turned into replacement tokens, missing pieces of syntax are
+// filled in with dummy nodes, and the indentation of the code is used
+// to guess where an unclosed block or list ends. Every error it
+// recovers from is kept as a diagnostic.
+//
+// [acorn-loose]: https://github.com/acornjs/acorn/tree/master/acorn-loose
+
+use std::collections::VecDeque;
+
+use self::{statement::LooseStatementParser, tokenize::LooseTokenizer};
+use crate::{
+    errors::{ErrorCode, ParserError},
+    location::{LocationParser, Position, SourceLocation},
+    node::{Identifier, Literal, LiteralValue, NodeParser, Program, Span},
+    options::Options,
+    parser::Parser,
+    token::{
+        types::{get_token_types, TokenType},
+        Token, TokenValue,
+    },
+    whitespace::is_new_line,
+};
+
+mod expression;
+mod statement;
+mod tokenize;
+
+/// The name of the identifiers, and the value of the string literals,
+/// that the loose parser inserts where a piece of syntax is missing.
+pub const DUMMY_VALUE: &str = "✖";
+
+/// The width of a tab when measuring indentation.
+const TAB_SIZE: i32 = 4;
+
+pub(crate) struct LooseParser {
+    toks: Parser,
+    /// Whether the tokenizer has read its first token.
+    started: bool,
+    tok: Token,
+    last: Token,
+    /// Tokens read ahead of `tok`.
+    ahead: VecDeque<Token>,
+    /// The indentation of the enclosing constructs.
+    context: Vec<i32>,
+    cur_indent: i32,
+    cur_line_start: i32,
+    next_line_start: i32,
+    in_async: bool,
+    in_generator: bool,
+    in_function: bool,
+    diagnostics: Vec<ParserError>,
+}
+
+/// Parses `input`, recovering from syntax errors. Input the regular
+/// parser accepts gets exactly the tree it returns, the loose parser is
+/// only used when it fails.
+pub(crate) fn parse(
+    input: &str,
+    options: &Options,
+) -> Result<(Program, Vec<ParserError>), ParserError> {
+    // The regular parser already goes on after some errors, the loose one
+    // is only needed for the others.
+    let options = &Options {
+        collect_errors: true,
+        ..options.clone()
+    };
+    // The first attempt runs without the callbacks, so that they do not
+    // hear about the same tokens twice when it fails.
+    let quiet_options = Options {
+        on_comment: None,
+        on_token: None,
+        on_insert_semicolon: None,
+        on_trailing_comma: None,
+        ..options.clone()
+    };
+    let has_callbacks = options.on_comment.is_some()
+        || options.on_token.is_some()
+        || options.on_insert_semicolon.is_some()
+        || options.on_trailing_comma.is_some();
+    let mut parser = Parser::new(&quiet_options, input, &None);
+    let error = match parser.parse() {
+        Ok(program) => {
+            let (mut parser, mut program) = if has_callbacks {
+                let mut parser = Parser::new(options, input, &None);
+                let program = parser.parse().map_err(|error| parser.output_error(error))?;
+                (parser, program)
+            } else {
+                (parser, program)
+            };
+            parser.output_program(&mut program);
+            let mut errors = parser.take_errors();
+            errors.sort_by_key(error_pos);
+            return Ok((program, errors));
+        }
+        Err(error @ ParserError::SyntaxError { .. }) => error,
+        Err(error) => return Err(error),
+    };
+    let mut loose = LooseParser::new(options, input);
+    let mut program = loose
+        .parse()
+        .map_err(|error| loose.toks.output_error(error))?;
+    // The loose parser does not check everything the regular one does,
+    // make sure the errors found before it took over are reported.
+    for error in std::mem::take(&mut parser.errors) {
+        loose.push_diagnostic(error);
+    }
+    loose.push_diagnostic(error);
+    loose.toks.output_program(&mut program);
+    let diagnostics = loose
+        .take_diagnostics()
+        .into_iter()
+        .map(|error| loose.toks.output_error(error))
+        .collect();
+    Ok((program, diagnostics))
+}
+
+fn error_pos(error: &ParserError) -> Option<i32> {
+    match error {
+        ParserError::SyntaxError { pos, .. } => Some(*pos),
+        _ => None,
+    }
+}
+
+impl LooseParser {
+    pub(crate) fn new(options: &Options, input: &str) -> Self {
+        let toks = Parser::new(options, input, &None);
+        let tok = Token {
+            token_type: get_token_types().eof.clone(),
+            value: TokenValue::Null,
+            start: 0,
+            end: 0,
+            loc: toks
+                .get_cur_position()
+                .map(|here| SourceLocation::new(&here, &Some(here.clone()), &toks.source_file)),
+            range: None,
+        };
+        let mut parser = LooseParser {
+            toks,
+            started: false,
+            tok: tok.clone(),
+            last: tok,
+            ahead: VecDeque::new(),
+            context: vec![],
+            cur_indent: 0,
+            cur_line_start: 0,
+            next_line_start: 0,
+            in_async: false,
+            in_generator: false,
+            in_function: false,
+            diagnostics: vec![],
+        };
+        parser.next_line_start = parser.next_line_after(parser.cur_line_start);
+        parser
+    }
+
+    pub(crate) fn parse(&mut self) -> Result<Program, ParserError> {
+        self.next()?;
+        self.parse_top_level()
+    }
+
+    /// Returns the errors recovered from so far, in source order.
+    pub(crate) fn take_diagnostics(&mut self) -> Vec<ParserError> {
+        for error in std::mem::take(&mut self.toks.errors) {
+            self.push_diagnostic(error);
+        }
+        let mut diagnostics = std::mem::take(&mut self.diagnostics);
+        diagnostics.sort_by_key(error_pos);
+        diagnostics
+    }
+
+    /// Records an error the parser recovered from. Only the first error
+    /// at a position is kept, as the dummy nodes and skipped tokens that
+    /// fix one problem often trip over it again.
+    fn push_diagnostic(&mut self, error: ParserError) {
+        let pos = error_pos(&error);
+        if self.diagnostics.iter().all(|other| error_pos(other) != pos) {
+            self.diagnostics.push(error);
+        }
+    }
+
+    fn report(&mut self, pos: i32, code: ErrorCode, message: &str) {
+        // The tokenizer is ahead of the parser, find the token at `pos`
+        // among the ones the parser has seen.
+        let end = [&self.tok, &self.last]
+            .into_iter()
+            .find(|token| token.start == pos && token.end > pos)
+            .map_or(pos, |token| token.end);
+        self.push_diagnostic(self.toks.syntax_error(pos, code, message).with_end(end));
+    }
+
+    fn unexpected(&mut self) {
+        let start = self.tok.start;
+        self.report(start, ErrorCode::UnexpectedToken, "Unexpected token");
+    }
+
+    fn ecma_version(&self) -> i32 {
+        self.toks.options.get_ecma_version_number()
+    }
+
+    fn tok_is(&self, token_type: &TokenType) -> bool {
+        self.tok.token_type.eq(token_type)
+    }
+
+    fn start_node(&self) -> Span {
+        let (pos, loc) = self.store_current_pos();
+        self.start_node_at(pos, &loc)
+    }
+
+    fn store_current_pos(&self) -> (i32, Option<Position>) {
+        let tok = &self.tok;
+        (tok.start, tok.loc.as_ref().map(|loc| loc.start.clone()))
+    }
+
+    fn start_node_at(&self, pos: i32, loc: &Option<Position>) -> Span {
+        Span::new(&self.toks, pos, loc)
+    }
+
+    fn finish_node(&self, span: Span) -> Span {
+        let last = &self.last;
+        let loc = last.loc.as_ref().and_then(|loc| loc.end.clone());
+        self.toks.finish_node_at(span, last.end, &loc)
+    }
+
+    /// Starts and finishes an empty node at the current token. The parent
+    /// node then ends where the dummy node is.
+    fn dummy_node(&mut self) -> Span {
+        let span = self.start_node();
+        let loc = span.loc.as_ref().map(|loc| loc.start.clone());
+        let mut last = self.tok.clone();
+        last.token_type = get_token_types().name.clone();
+        last.end = last.start;
+        if let Some(last_loc) = &mut last.loc {
+            last_loc.end = Some(last_loc.start.clone());
+        }
+        self.last = last;
+        let start = span.start;
+        self.toks.finish_node_at(span, start, &loc)
+    }
+
+    fn dummy_ident(&mut self) -> Identifier {
+        Identifier {
+            span: self.dummy_node(),
+            name: self.toks.atoms.intern(DUMMY_VALUE),
+        }
+    }
+
+    fn dummy_string(&mut self) -> Literal {
+        Literal {
+            span: self.dummy_node(),
+            value: LiteralValue::String(self.toks.atoms.intern(DUMMY_VALUE)),
+            raw: self.toks.atoms.intern(DUMMY_VALUE),
+        }
+    }
+
+    /// Makes the last token end where the current one starts, so that a
+    /// node missing its closing token spans up to the next token.
+    fn extend_last_to_current(&mut self) {
+        self.last.end = self.tok.start;
+        if let (Some(last_loc), Some(tok_loc)) = (&mut self.last.loc, &self.tok.loc) {
+            last_loc.end = Some(tok_loc.start.clone());
+        }
+    }
+
+    fn eat(&mut self, token_type: &TokenType) -> Result<bool, ParserError> {
+        if self.tok_is(token_type) {
+            self.next()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn is_contextual(&self, name: &str) -> bool {
+        let tok = &self.tok;
+        tok.token_type.eq(&get_token_types().name) && tok.value.as_str() == name
+    }
+
+    fn eat_contextual(&mut self, name: &str) -> Result<bool, ParserError> {
+        if self.is_contextual(name) {
+            self.next()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn can_insert_semicolon(&self) -> bool {
+        let token_types = get_token_types();
+        let tok = &self.tok;
+        let last_end = self.last.end;
+        tok.token_type.eq(&token_types.eof)
+            || tok.token_type.eq(&token_types.brace_r)
+            || (last_end < tok.start
+                && self.toks.input[last_end as usize..tok.start as usize]
+                    .chars()
+                    .any(|ch| is_new_line(ch as i32)))
+    }
+
+    fn semicolon(&mut self) -> Result<bool, ParserError> {
+        self.eat(&get_token_types().semi)
+    }
+
+    /// Consumes a semicolon, reporting it as missing when none can be
+    /// inserted either.
+    fn expect_semicolon(&mut self) -> Result<(), ParserError> {
+        if !self.semicolon()? && !self.can_insert_semicolon() {
+            self.unexpected();
+        }
+        Ok(())
+    }
+
+    /// Consumes a token of the given type. When it is one or two tokens
+    /// further, the tokens in between are skipped.
+    fn expect(&mut self, token_type: &TokenType) -> Result<bool, ParserError> {
+        if self.eat(token_type)? {
+            return Ok(true);
+        }
+        self.unexpected();
+        for i in 1..=2 {
+            if self.look_ahead(i)?.token_type.eq(token_type) {
+                for _ in 0..i {
+                    self.next()?;
+                }
+                self.next()?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn push_cx(&mut self) {
+        self.context.push(self.cur_indent);
+    }
+
+    fn pop_cx(&mut self) {
+        if let Some(indent) = self.context.pop() {
+            self.cur_indent = indent;
+        }
+    }
+
+    /// Returns the offset of the line break ending the line `pos` is on.
+    fn line_end(&self, pos: i32) -> i32 {
+        let rest = &self.toks.input[pos as usize..];
+        let len = rest
+            .find(|ch: char| is_new_line(ch as i32))
+            .unwrap_or(rest.len());
+        pos + len as i32
+    }
+
+    /// Returns the start of the line following the one `pos` is on, or
+    /// just past the end of the input on the last line.
+    fn next_line_after(&self, pos: i32) -> i32 {
+        let line_index = &self.toks.line_index;
+        let line = line_index.position(pos).line;
+        if (line as usize) < line_index.line_count() {
+            line_index.line_start(line + 1)
+        } else {
+            self.toks.input.len() as i32 + 1
+        }
+    }
+
+    fn indentation_after(&self, pos: i32) -> i32 {
+        let mut count = 0;
+        for byte in self
+            .toks
+            .input
+            .as_bytes()
+            .get(pos as usize..)
+            .unwrap_or_default()
+        {
+            match byte {
+                b' ' => count += 1,
+                b'\t' => count += TAB_SIZE,
+                _ => break,
+            }
+        }
+        count
+    }
+
+    /// Whether the list or block being parsed ends at the current token:
+    /// either the closing token is found, or the current token starts a
+    /// line indented less than the construct.
+    fn closes(&self, close: &TokenType, indent: i32, line: i32, block_heuristic: bool) -> bool {
+        if self.tok_is(close) || self.tok_is(&get_token_types().eof) {
+            return true;
+        }
+        let next_line_start = self.next_line_start;
+        line != self.cur_line_start
+            && self.cur_indent < indent
+            && self.token_starts_line()
+            && (!block_heuristic
+                || next_line_start >= self.toks.input.len() as i32
+                || self.indentation_after(next_line_start) < indent)
+    }
+
+    fn token_starts_line(&self) -> bool {
+        let start = self.tok.start as usize;
+        match self
+            .toks
+            .input
+            .as_bytes()
+            .get(self.cur_line_start as usize..start)
+        {
+            Some(indentation) => indentation
+                .iter()
+                .all(|byte| *byte == b' ' || *byte == b'\t'),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,1066 @@
+use crate::{
+    errors::{ErrorCode, ParserError},
+    loose::{
+        expression::{is_dummy, LooseExpressionParser},
+        tokenize::LooseTokenizer,
+        LooseParser, DUMMY_VALUE,
+    },
+    node::{
+        BlockStatement, BreakStatement, CatchClause, Class, ClassBody, ClassElement,
+        ContinueStatement, DebuggerStatement, Declaration, DoWhileStatement, EmptyStatement,
+        ExportAllDeclaration, ExportDefaultDeclaration, ExportDefaultDeclarationKind,
+        ExportNamedDeclaration, ExportSpecifier, Expression, ExpressionStatement, ForInStatement,
+        ForInit, ForLeft, ForOfStatement, ForStatement, Function, Identifier, IfStatement,
+        ImportDeclaration, ImportDeclarationSpecifier, ImportDefaultSpecifier,
+        ImportNamespaceSpecifier, ImportSpecifier, LabeledStatement, Literal, LiteralValue,
+        MethodDefinition, MethodDefinitionKind, ModuleDeclaration, ModuleExportName, Pattern,
+        Program, PropertyDefinition, ReturnStatement, Span, Spanned, Statement, StaticBlock,
+        SwitchCase, SwitchStatement, ThrowStatement, TryStatement, VariableDeclaration,
+        VariableDeclarationKind, VariableDeclarator, WhileStatement, WithStatement,
+    },
+    statement::StatementParser,
+    token::types::get_token_types,
+};
+
+fn is_dummy_name(name: &ModuleExportName) -> bool {
+    matches!(name, ModuleExportName::Identifier(id) if id.name == DUMMY_VALUE)
+}
+
+pub(crate) trait LooseStatementParser {
+    fn parse_top_level(&mut self) -> Result<Program, ParserError>;
+    fn parse_statement(&mut self) -> Result<Statement, ParserError>;
+    fn parse_block(&mut self) -> Result<BlockStatement, ParserError>;
+    fn parse_for(&mut self, span: Span, init: Option<ForInit>) -> Result<Statement, ParserError>;
+    fn parse_for_in(
+        &mut self,
+        span: Span,
+        left: ForLeft,
+        is_await: bool,
+    ) -> Result<Statement, ParserError>;
+    fn parse_var(
+        &mut self,
+        span: Span,
+        no_in: bool,
+        kind: VariableDeclarationKind,
+    ) -> Result<VariableDeclaration, ParserError>;
+    fn parse_class(&mut self, id_required: bool) -> Result<Class, ParserError>;
+    fn parse_class_element(&mut self) -> Result<Option<ClassElement>, ParserError>;
+    fn parse_class_static_block(&mut self, span: Span) -> Result<StaticBlock, ParserError>;
+    fn is_class_element_name_start(&self) -> bool;
+    fn parse_class_element_name(&mut self) -> Result<(bool, Expression), ParserError>;
+    fn parse_function(
+        &mut self,
+        span: Span,
+        id_required: bool,
+        is_async: bool,
+    ) -> Result<Function, ParserError>;
+    fn parse_export(&mut self) -> Result<Statement, ParserError>;
+    fn parse_import(&mut self) -> Result<ImportDeclaration, ParserError>;
+    fn parse_import_specifiers(&mut self) -> Result<Vec<ImportDeclarationSpecifier>, ParserError>;
+    fn parse_export_specifier_list(&mut self) -> Result<Vec<ExportSpecifier>, ParserError>;
+    fn parse_module_export_name(&mut self) -> Result<ModuleExportName, ParserError>;
+    fn parse_module_source(&mut self) -> Result<Literal, ParserError>;
+}
+
+impl LooseStatementParser for LooseParser {
+    fn parse_top_level(&mut self) -> Result<Program, ParserError> {
+        let mut program = match &self.toks.options.program {
+            Some(program) => program.clone(),
+            None => Program {
+                span: self.start_node_at(0, &Some(self.toks.line_index.position(0))),
+                body: vec![],
+                source_type: self.toks.options.source_type.clone(),
+            },
+        };
+        while !self.tok_is(&get_token_types().eof) {
+            program.body.push(self.parse_statement()?);
+        }
+        self.toks.adapt_directive_prologue(&mut program.body);
+        self.last = self.tok.clone();
+        program.source_type = self.toks.options.source_type.clone();
+        program.span = self.finish_node(program.span);
+        Ok(program)
+    }
+
+    fn parse_statement(&mut self) -> Result<Statement, ParserError> {
+        let token_types = get_token_types();
+        // Tokens that can not start a statement are skipped one at a time.
+        loop {
+            let mut start_type = self.tok.token_type.clone();
+            let span = self.start_node();
+            let mut kind = None;
+            if self.ahead.is_empty() && self.toks.is_let(None)? {
+                start_type = token_types._var.clone();
+                kind = Some(VariableDeclarationKind::Let);
+            }
+
+            if start_type.eq(&token_types._break) || start_type.eq(&token_types._continue) {
+                self.next()?;
+                let label = if self.semicolon()? || self.can_insert_semicolon() {
+                    None
+                } else {
+                    let label = if self.tok_is(&token_types.name) {
+                        Some(self.parse_ident()?)
+                    } else {
+                        None
+                    };
+                    self.expect_semicolon()?;
+                    label
+                };
+                let span = self.finish_node(span);
+                return Ok(if start_type.eq(&token_types._break) {
+                    Statement::BreakStatement(Box::new(BreakStatement { span, label }))
+                } else {
+                    Statement::ContinueStatement(Box::new(ContinueStatement { span, label }))
+                });
+            } else if start_type.eq(&token_types._debugger) {
+                self.next()?;
+                self.expect_semicolon()?;
+                return Ok(Statement::DebuggerStatement(Box::new(DebuggerStatement {
+                    span: self.finish_node(span),
+                })));
+            } else if start_type.eq(&token_types._do) {
+                self.next()?;
+                let body = self.parse_statement()?;
+                let test = if self.eat(&token_types._while)? {
+                    self.parse_paren_expression()?
+                } else {
+                    self.unexpected();
+                    Expression::Identifier(Box::new(self.dummy_ident()))
+                };
+                self.semicolon()?;
+                return Ok(Statement::DoWhileStatement(Box::new(DoWhileStatement {
+                    span: self.finish_node(span),
+                    body,
+                    test,
+                })));
+            } else if start_type.eq(&token_types._for) {
+                self.next()?;
+                let is_await = self.ecma_version() >= 9 && self.eat_contextual("await")?;
+                self.push_cx();
+                self.expect(&token_types.paren_l)?;
+                if self.tok_is(&token_types.semi) {
+                    return self.parse_for(span, None);
+                }
+                let is_let = self.ahead.is_empty() && self.toks.is_let(None)?;
+                if is_let || self.tok_is(&token_types._var) || self.tok_is(&token_types._const) {
+                    let kind = if is_let {
+                        VariableDeclarationKind::Let
+                    } else if self.tok_is(&token_types._var) {
+                        VariableDeclarationKind::Var
+                    } else {
+                        VariableDeclarationKind::Const
+                    };
+                    let init = self.parse_var(self.start_node(), true, kind)?;
+                    if init.declarations.len() == 1
+                        && (self.tok_is(&token_types._in) || self.is_contextual("of"))
+                    {
+                        return self.parse_for_in(
+                            span,
+                            ForLeft::VariableDeclaration(Box::new(init)),
+                            is_await,
+                        );
+                    }
+                    return self
+                        .parse_for(span, Some(ForInit::VariableDeclaration(Box::new(init))));
+                }
+                let init = self.parse_expression(true)?;
+                if self.tok_is(&token_types._in) || self.is_contextual("of") {
+                    let left = self.to_assignable(init, false);
+                    return self.parse_for_in(span, ForLeft::Pattern(left), is_await);
+                }
+                return self.parse_for(span, Some(ForInit::Expression(init)));
+            } else if start_type.eq(&token_types._function) {
+                self.next()?;
+                return Ok(Statement::FunctionDeclaration(Box::new(
+                    self.parse_function(span, true, false)?,
+                )));
+            } else if start_type.eq(&token_types._if) {
+                self.next()?;
+                let test = self.parse_paren_expression()?;
+                let consequent = self.parse_statement()?;
+                let alternate = if self.eat(&token_types._else)? {
+                    Some(self.parse_statement()?)
+                } else {
+                    None
+                };
+                return Ok(Statement::IfStatement(Box::new(IfStatement {
+                    span: self.finish_node(span),
+                    test,
+                    consequent,
+                    alternate,
+                })));
+            } else if start_type.eq(&token_types._return) {
+                self.next()?;
+                let argument = if self.eat(&token_types.semi)? || self.can_insert_semicolon() {
+                    None
+                } else {
+                    let argument = self.parse_expression(false)?;
+                    self.expect_semicolon()?;
+                    Some(argument)
+                };
+                return Ok(Statement::ReturnStatement(Box::new(ReturnStatement {
+                    span: self.finish_node(span),
+                    argument,
+                })));
+            } else if start_type.eq(&token_types._switch) {
+                let block_indent = self.cur_indent;
+                let line = self.cur_line_start;
+                self.next()?;
+                let discriminant = self.parse_paren_expression()?;
+                let mut cases = vec![];
+                self.push_cx();
+                self.expect(&token_types.brace_l)?;
+
+                let mut cur: Option<SwitchCase> = None;
+                while !self.closes(&token_types.brace_r, block_indent, line, true) {
+                    if self.tok_is(&token_types._case) || self.tok_is(&token_types._default) {
+                        let is_case = self.tok_is(&token_types._case);
+                        if let Some(mut case) = cur.take() {
+                            case.span = self.finish_node(case.span);
+                            cases.push(case);
+                        }
+                        let case_span = self.start_node();
+                        self.next()?;
+                        let test = if is_case {
+                            Some(self.parse_expression(false)?)
+                        } else {
+                            None
+                        };
+                        self.expect(&token_types.colon)?;
+                        cur = Some(SwitchCase {
+                            span: case_span,
+                            consequent: vec![],
+                            test,
+                        });
+                    } else {
+                        if cur.is_none() {
+                            self.unexpected();
+                            cur = Some(SwitchCase {
+                                span: self.start_node(),
+                                consequent: vec![],
+                                test: None,
+                            });
+                        }
+                        let statement = self.parse_statement()?;
+                        if let Some(case) = cur.as_mut() {
+                            case.consequent.push(statement);
+                        }
+                    }
+                }
+                if let Some(mut case) = cur {
+                    case.span = self.finish_node(case.span);
+                    cases.push(case);
+                }
+                self.pop_cx();
+                if !self.eat(&token_types.brace_r)? {
+                    self.unexpected();
+                }
+                return Ok(Statement::SwitchStatement(Box::new(SwitchStatement {
+                    span: self.finish_node(span),
+                    discriminant,
+                    cases,
+                })));
+            } else if start_type.eq(&token_types._throw) {
+                self.next()?;
+                let argument = self.parse_expression(false)?;
+                self.expect_semicolon()?;
+                return Ok(Statement::ThrowStatement(Box::new(ThrowStatement {
+                    span: self.finish_node(span),
+                    argument,
+                })));
+            } else if start_type.eq(&token_types._try) {
+                self.next()?;
+                let block = self.parse_block()?;
+                let mut handler = None;
+                if self.tok_is(&token_types._catch) {
+                    let clause_span = self.start_node();
+                    self.next()?;
+                    let param = if self.eat(&token_types.paren_l)? {
+                        let param = self.parse_expr_atom()?;
+                        let param = self.to_assignable(param, true);
+                        self.expect(&token_types.paren_r)?;
+                        Some(param)
+                    } else {
+                        None
+                    };
+                    let body = self.parse_block()?;
+                    handler = Some(CatchClause {
+                        span: self.finish_node(clause_span),
+                        param,
+                        body,
+                    });
+                }
+                let finalizer = if self.eat(&token_types._finally)? {
+                    Some(self.parse_block()?)
+                } else {
+                    None
+                };
+                if handler.is_none() && finalizer.is_none() {
+                    self.report(
+                        span.start,
+                        ErrorCode::MissingCatchOrFinally,
+                        "Missing catch or finally clause",
+                    );
+                    return Ok(Statement::BlockStatement(Box::new(block)));
+                }
+                return Ok(Statement::TryStatement(Box::new(TryStatement {
+                    span: self.finish_node(span),
+                    block,
+                    handler,
+                    finalizer,
+                })));
+            } else if start_type.eq(&token_types._var) || start_type.eq(&token_types._const) {
+                let kind = kind.unwrap_or(if start_type.eq(&token_types._var) {
+                    VariableDeclarationKind::Var
+                } else {
+                    VariableDeclarationKind::Const
+                });
+                return Ok(Statement::VariableDeclaration(Box::new(
+                    self.parse_var(span, false, kind)?,
+                )));
+            } else if start_type.eq(&token_types._while) {
+                self.next()?;
+                let test = self.parse_paren_expression()?;
+                let body = self.parse_statement()?;
+                return Ok(Statement::WhileStatement(Box::new(WhileStatement {
+                    span: self.finish_node(span),
+                    test,
+                    body,
+                })));
+            } else if start_type.eq(&token_types._with) {
+                self.next()?;
+                let object = self.parse_paren_expression()?;
+                let body = self.parse_statement()?;
+                return Ok(Statement::WithStatement(Box::new(WithStatement {
+                    span: self.finish_node(span),
+                    object,
+                    body,
+                })));
+            } else if start_type.eq(&token_types.brace_l) {
+                return Ok(Statement::BlockStatement(Box::new(self.parse_block()?)));
+            } else if start_type.eq(&token_types.semi) {
+                self.next()?;
+                return Ok(Statement::EmptyStatement(Box::new(EmptyStatement {
+                    span: self.finish_node(span),
+                })));
+            } else if start_type.eq(&token_types._class) {
+                return Ok(Statement::ClassDeclaration(Box::new(
+                    self.parse_class(true)?,
+                )));
+            } else if start_type.eq(&token_types._import)
+                && !(self.ecma_version() > 10 && {
+                    let next_type = self.look_ahead(1)?.token_type;
+                    next_type.eq(&token_types.paren_l) || next_type.eq(&token_types.dot)
+                })
+            {
+                return Ok(Statement::ModuleDeclaration(
+                    ModuleDeclaration::ImportDeclaration(Box::new(self.parse_import()?)),
+                ));
+            } else if start_type.eq(&token_types._export) {
+                return self.parse_export();
+            }
+
+            if self.ahead.is_empty() && self.toks.is_async_function()? {
+                self.next()?;
+                self.next()?;
+                return Ok(Statement::FunctionDeclaration(Box::new(
+                    self.parse_function(span, true, true)?,
+                )));
+            }
+            let expr = self.parse_expression(false)?;
+            if is_dummy(&expr) {
+                self.next()?;
+                if self.tok_is(&token_types.eof) {
+                    return Ok(Statement::EmptyStatement(Box::new(EmptyStatement {
+                        span: self.finish_node(span),
+                    })));
+                }
+                continue;
+            }
+            if start_type.eq(&token_types.name) {
+                if let Expression::Identifier(label) = &expr {
+                    if self.eat(&token_types.colon)? {
+                        let label = label.as_ref().clone();
+                        let body = self.parse_statement()?;
+                        return Ok(Statement::LabeledStatement(Box::new(LabeledStatement {
+                            span: self.finish_node(span),
+                            body,
+                            label,
+                        })));
+                    }
+                }
+            }
+            self.expect_semicolon()?;
+            return Ok(Statement::ExpressionStatement(Box::new(
+                ExpressionStatement {
+                    span: self.finish_node(span),
+                    expression: expr,
+                    directive: None,
+                },
+            )));
+        }
+    }
+
+    /// Parses a block. A block missing its closing brace ends at the first
+    /// line indented no more than the line it starts on.
+    fn parse_block(&mut self) -> Result<BlockStatement, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        self.push_cx();
+        self.expect(&token_types.brace_l)?;
+        let block_indent = self.cur_indent;
+        let line = self.cur_line_start;
+        let mut body = vec![];
+        while !self.closes(&token_types.brace_r, block_indent, line, true) {
+            body.push(self.parse_statement()?);
+        }
+        self.pop_cx();
+        if !self.eat(&token_types.brace_r)? {
+            self.unexpected();
+        }
+        Ok(BlockStatement {
+            span: self.finish_node(span),
+            body,
+        })
+    }
+
+    fn parse_for(&mut self, span: Span, init: Option<ForInit>) -> Result<Statement, ParserError> {
+        let token_types = get_token_types();
+        let mut test = None;
+        let mut update = None;
+        if self.eat(&token_types.semi)? && !self.tok_is(&token_types.semi) {
+            test = Some(self.parse_expression(false)?);
+        }
+        if self.eat(&token_types.semi)? && !self.tok_is(&token_types.paren_r) {
+            update = Some(self.parse_expression(false)?);
+        }
+        self.pop_cx();
+        self.expect(&token_types.paren_r)?;
+        let body = self.parse_statement()?;
+        Ok(Statement::ForStatement(Box::new(ForStatement {
+            span: self.finish_node(span),
+            init,
+            test,
+            update,
+            body,
+        })))
+    }
+
+    fn parse_for_in(
+        &mut self,
+        span: Span,
+        left: ForLeft,
+        is_await: bool,
+    ) -> Result<Statement, ParserError> {
+        let token_types = get_token_types();
+        let is_for_in = self.tok_is(&token_types._in);
+        self.next()?;
+        let right = self.parse_expression(false)?;
+        self.pop_cx();
+        self.expect(&token_types.paren_r)?;
+        let body = self.parse_statement()?;
+        let span = self.finish_node(span);
+        Ok(if is_for_in {
+            Statement::ForInStatement(Box::new(ForInStatement {
+                span,
+                left,
+                right,
+                body,
+            }))
+        } else {
+            Statement::ForOfStatement(Box::new(ForOfStatement {
+                span,
+                is_await,
+                left,
+                right,
+                body,
+            }))
+        })
+    }
+
+    fn parse_var(
+        &mut self,
+        span: Span,
+        no_in: bool,
+        kind: VariableDeclarationKind,
+    ) -> Result<VariableDeclaration, ParserError> {
+        let token_types = get_token_types();
+        self.next()?;
+        let mut declarations = vec![];
+        loop {
+            let decl_span = self.start_node();
+            let id = if self.ecma_version() >= 6 {
+                let id = self.parse_expr_atom()?;
+                self.to_assignable(id, true)
+            } else {
+                Pattern::Identifier(Box::new(self.parse_ident()?))
+            };
+            let init = if self.eat(&token_types.eq)? {
+                Some(self.parse_maybe_assign(no_in)?)
+            } else {
+                None
+            };
+            declarations.push(VariableDeclarator {
+                span: self.finish_node(decl_span),
+                id,
+                init,
+            });
+            if !self.eat(&token_types.comma)? {
+                break;
+            }
+        }
+        if !no_in {
+            self.expect_semicolon()?;
+        }
+        Ok(VariableDeclaration {
+            span: self.finish_node(span),
+            declarations,
+            kind,
+        })
+    }
+
+    /// Parses a class. Unlike in acorn-loose, a semicolon after the class
+    /// is left to the enclosing statement, as class expressions can be
+    /// followed by one.
+    fn parse_class(&mut self, id_required: bool) -> Result<Class, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        self.next()?;
+        let id = if self.tok_is(&token_types.name) {
+            Some(self.parse_ident()?)
+        } else if id_required {
+            self.unexpected();
+            Some(self.dummy_ident())
+        } else {
+            None
+        };
+        let super_class = if self.eat(&token_types._extends)? {
+            Some(self.parse_expression(false)?)
+        } else {
+            None
+        };
+        let body_span = self.start_node();
+        let mut body = vec![];
+        self.push_cx();
+        let mut indent = self.cur_indent + 1;
+        let mut line = self.cur_line_start;
+        if !self.eat(&token_types.brace_l)? {
+            self.unexpected();
+        }
+        if self.cur_indent + 1 < indent {
+            indent = self.cur_indent;
+            line = self.cur_line_start;
+        }
+        while !self.closes(&token_types.brace_r, indent, line, false) {
+            if let Some(element) = self.parse_class_element()? {
+                body.push(element);
+            }
+        }
+        self.pop_cx();
+        if !self.eat(&token_types.brace_r)? {
+            // If there is no closing brace, make the node span to the start
+            // of the next token.
+            self.extend_last_to_current();
+            self.unexpected();
+        }
+        let body = ClassBody {
+            span: self.finish_node(body_span),
+            body,
+        };
+        Ok(Class {
+            span: self.finish_node(span),
+            id,
+            super_class,
+            body,
+        })
+    }
+
+    fn parse_class_element(&mut self) -> Result<Option<ClassElement>, ParserError> {
+        let token_types = get_token_types();
+        if self.eat(&token_types.semi)? {
+            return Ok(None);
+        }
+
+        let ecma_version = self.ecma_version();
+        let span = self.start_node();
+        let mut key_name = None;
+        let mut is_generator = false;
+        let mut is_async = false;
+        let mut kind = MethodDefinitionKind::Method;
+
+        // Parse modifiers
+        let mut is_static = false;
+        if self.eat_contextual("static")? {
+            // Parse static init block
+            if ecma_version >= 13 && self.eat(&token_types.brace_l)? {
+                return Ok(Some(ClassElement::StaticBlock(Box::new(
+                    self.parse_class_static_block(span)?,
+                ))));
+            }
+            if self.is_class_element_name_start() || self.tok_is(&token_types.star) {
+                is_static = true;
+            } else {
+                key_name = Some("static");
+            }
+        }
+        if key_name.is_none() && ecma_version >= 8 && self.eat_contextual("async")? {
+            if (self.is_class_element_name_start() || self.tok_is(&token_types.star))
+                && !self.can_insert_semicolon()
+            {
+                is_async = true;
+            } else {
+                key_name = Some("async");
+            }
+        }
+        if key_name.is_none() {
+            is_generator = self.eat(&token_types.star)?;
+            let accessor = if self.eat_contextual("get")? {
+                Some(("get", MethodDefinitionKind::Get))
+            } else if self.eat_contextual("set")? {
+                Some(("set", MethodDefinitionKind::Set))
+            } else {
+                None
+            };
+            if let Some((name, accessor_kind)) = accessor {
+                if self.is_class_element_name_start() {
+                    kind = accessor_kind;
+                } else {
+                    key_name = Some(name);
+                }
+            }
+        }
+
+        // Parse element name
+        let (computed, key) = match key_name {
+            // `async`, `get`, `set`, or `static` were not a keyword
+            // contextually. The last token is any of those, make it the
+            // element name.
+            Some(name) => {
+                let (start, loc) = {
+                    let last = &self.last;
+                    (last.start, last.loc.as_ref().map(|loc| loc.start.clone()))
+                };
+                let key_span = self.start_node_at(start, &loc);
+                let key = Identifier {
+                    span: self.finish_node(key_span),
+                    name: self.toks.atoms.intern(name),
+                };
+                (false, Expression::Identifier(Box::new(key)))
+            }
+            None => {
+                let (computed, key) = self.parse_class_element_name()?;
+                // Skip broken stuff.
+                if is_dummy(&key) {
+                    if is_dummy(&self.parse_maybe_assign(false)?) {
+                        self.next()?;
+                    }
+                    self.eat(&token_types.comma)?;
+                    return Ok(None);
+                }
+                (computed, key)
+            }
+        };
+
+        // Parse element value
+        if ecma_version < 13
+            || self.tok_is(&token_types.paren_l)
+            || kind != MethodDefinitionKind::Method
+            || is_generator
+            || is_async
+        {
+            let is_constructor = !computed
+                && !is_static
+                && !is_generator
+                && !is_async
+                && kind == MethodDefinitionKind::Method
+                && match &key {
+                    Expression::Identifier(id) => id.name == "constructor",
+                    Expression::Literal(literal) => {
+                        matches!(&literal.value, LiteralValue::String(value) if value == "constructor")
+                    }
+                    _ => false,
+                };
+            let value = self.parse_method(is_generator, is_async)?;
+            return Ok(Some(ClassElement::MethodDefinition(Box::new(
+                MethodDefinition {
+                    span: self.finish_node(span),
+                    is_static,
+                    computed,
+                    key,
+                    kind: if is_constructor {
+                        MethodDefinitionKind::Constructor
+                    } else {
+                        kind
+                    },
+                    value,
+                },
+            ))));
+        }
+
+        let value = if self.eat(&token_types.eq)? {
+            let old_in_function = std::mem::replace(&mut self.in_function, true);
+            let value = self.parse_maybe_assign(false)?;
+            self.in_function = old_in_function;
+            Some(value)
+        } else {
+            None
+        };
+        self.expect_semicolon()?;
+        Ok(Some(ClassElement::PropertyDefinition(Box::new(
+            PropertyDefinition {
+                span: self.finish_node(span),
+                is_static,
+                computed,
+                key,
+                value,
+            },
+        ))))
+    }
+
+    fn parse_class_static_block(&mut self, span: Span) -> Result<StaticBlock, ParserError> {
+        let token_types = get_token_types();
+        let block_indent = self.cur_indent;
+        let line = self.cur_line_start;
+        let mut body = vec![];
+        self.push_cx();
+        while !self.closes(&token_types.brace_r, block_indent, line, true) {
+            body.push(self.parse_statement()?);
+        }
+        self.pop_cx();
+        if !self.eat(&token_types.brace_r)? {
+            self.unexpected();
+        }
+        Ok(StaticBlock {
+            span: self.finish_node(span),
+            body,
+        })
+    }
+
+    fn is_class_element_name_start(&self) -> bool {
+        let token_types = get_token_types();
+        let tok_type = &self.tok.token_type;
+        tok_type.eq(&token_types.name)
+            || tok_type.eq(&token_types.private_id)
+            || tok_type.eq(&token_types.num)
+            || tok_type.eq(&token_types.string)
+            || tok_type.eq(&token_types.bracket_l)
+            || tok_type.keyword.is_some()
+    }
+
+    fn parse_class_element_name(&mut self) -> Result<(bool, Expression), ParserError> {
+        if self.tok_is(&get_token_types().private_id) {
+            Ok((
+                false,
+                Expression::PrivateIdentifier(Box::new(self.parse_private_ident()?)),
+            ))
+        } else {
+            self.parse_property_name()
+        }
+    }
+
+    fn parse_function(
+        &mut self,
+        span: Span,
+        id_required: bool,
+        is_async: bool,
+    ) -> Result<Function, ParserError> {
+        let token_types = get_token_types();
+        let generator = self.ecma_version() >= 6 && self.eat(&token_types.star)?;
+        let is_async = self.ecma_version() >= 8 && is_async;
+        let id = if self.tok_is(&token_types.name) {
+            Some(self.parse_ident()?)
+        } else if id_required {
+            self.unexpected();
+            Some(self.dummy_ident())
+        } else {
+            None
+        };
+        let old_in_async = std::mem::replace(&mut self.in_async, is_async);
+        let old_in_generator = std::mem::replace(&mut self.in_generator, generator);
+        let old_in_function = std::mem::replace(&mut self.in_function, true);
+        let params = self.parse_function_params()?;
+        let mut body = self.parse_block()?;
+        self.toks.adapt_directive_prologue(&mut body.body);
+        self.in_async = old_in_async;
+        self.in_generator = old_in_generator;
+        self.in_function = old_in_function;
+        Ok(Function {
+            span: self.finish_node(span),
+            id,
+            generator,
+            is_async,
+            params,
+            body,
+        })
+    }
+
+    fn parse_export(&mut self) -> Result<Statement, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        self.next()?;
+        if self.eat(&token_types.star)? {
+            let exported = if self.ecma_version() >= 11 && self.eat_contextual("as")? {
+                Some(self.parse_module_export_name()?)
+            } else {
+                None
+            };
+            let source = if self.eat_contextual("from")? {
+                self.parse_module_source()?
+            } else {
+                self.unexpected();
+                self.dummy_string()
+            };
+            self.expect_semicolon()?;
+            return Ok(Statement::ModuleDeclaration(
+                ModuleDeclaration::ExportAllDeclaration(Box::new(ExportAllDeclaration {
+                    span: self.finish_node(span),
+                    exported,
+                    source,
+                })),
+            ));
+        }
+        if self.eat(&token_types._default)? {
+            // export default (function foo() {}) // This is FunctionExpression.
+            let is_async = !self.tok_is(&token_types._function)
+                && self.ahead.is_empty()
+                && self.toks.is_async_function()?;
+            let declaration = if self.tok_is(&token_types._function) || is_async {
+                let function_span = self.start_node();
+                self.next()?;
+                if is_async {
+                    self.next()?;
+                }
+                ExportDefaultDeclarationKind::FunctionDeclaration(Box::new(self.parse_function(
+                    function_span,
+                    false,
+                    is_async,
+                )?))
+            } else if self.tok_is(&token_types._class) {
+                ExportDefaultDeclarationKind::ClassDeclaration(Box::new(self.parse_class(false)?))
+            } else {
+                let declaration = self.parse_maybe_assign(false)?;
+                self.expect_semicolon()?;
+                ExportDefaultDeclarationKind::Expression(declaration)
+            };
+            return Ok(Statement::ModuleDeclaration(
+                ModuleDeclaration::ExportDefaultDeclaration(Box::new(ExportDefaultDeclaration {
+                    span: self.finish_node(span),
+                    declaration,
+                })),
+            ));
+        }
+        let is_declaration = self.tok.token_type.keyword.is_some()
+            || (self.ahead.is_empty()
+                && (self.toks.is_let(None)? || self.toks.is_async_function()?));
+        let (declaration, specifiers, source) = if is_declaration {
+            let declaration = match self.parse_statement()? {
+                Statement::FunctionDeclaration(function) => {
+                    Declaration::FunctionDeclaration(function)
+                }
+                Statement::VariableDeclaration(declaration) => {
+                    Declaration::VariableDeclaration(declaration)
+                }
+                Statement::ClassDeclaration(class) => Declaration::ClassDeclaration(class),
+                statement => {
+                    self.report(
+                        statement.span().start,
+                        ErrorCode::UnexpectedToken,
+                        "Unexpected token",
+                    );
+                    return Ok(statement);
+                }
+            };
+            (Some(declaration), vec![], None)
+        } else {
+            let specifiers = self.parse_export_specifier_list()?;
+            let source = if self.eat_contextual("from")? {
+                Some(self.parse_module_source()?)
+            } else {
+                None
+            };
+            self.expect_semicolon()?;
+            (None, specifiers, source)
+        };
+        Ok(Statement::ModuleDeclaration(
+            ModuleDeclaration::ExportNamedDeclaration(Box::new(ExportNamedDeclaration {
+                span: self.finish_node(span),
+                declaration,
+                specifiers,
+                source,
+            })),
+        ))
+    }
+
+    fn parse_import(&mut self) -> Result<ImportDeclaration, ParserError> {
+        let token_types = get_token_types();
+        let span = self.start_node();
+        self.next()?;
+        let mut specifiers = vec![];
+        let source = if self.tok_is(&token_types.string) {
+            self.parse_module_source()?
+        } else {
+            if self.tok_is(&token_types.name) && !self.is_contextual("from") {
+                let default_span = self.start_node();
+                let local = self.parse_ident()?;
+                specifiers.push(ImportDeclarationSpecifier::ImportDefaultSpecifier(
+                    Box::new(ImportDefaultSpecifier {
+                        span: self.finish_node(default_span),
+                        local,
+                    }),
+                ));
+                self.eat(&token_types.comma)?;
+            }
+            specifiers.append(&mut self.parse_import_specifiers()?);
+            if self.eat_contextual("from")? {
+                self.parse_module_source()?
+            } else {
+                self.unexpected();
+                self.dummy_string()
+            }
+        };
+        self.expect_semicolon()?;
+        Ok(ImportDeclaration {
+            span: self.finish_node(span),
+            specifiers,
+            source,
+        })
+    }
+
+    fn parse_import_specifiers(&mut self) -> Result<Vec<ImportDeclarationSpecifier>, ParserError> {
+        let token_types = get_token_types();
+        let mut specifiers = vec![];
+        if self.tok_is(&token_types.star) {
+            let span = self.start_node();
+            self.next()?;
+            let local = if self.eat_contextual("as")? {
+                self.parse_ident()?
+            } else {
+                self.unexpected();
+                self.dummy_ident()
+            };
+            specifiers.push(ImportDeclarationSpecifier::ImportNamespaceSpecifier(
+                Box::new(ImportNamespaceSpecifier {
+                    span: self.finish_node(span),
+                    local,
+                }),
+            ));
+            return Ok(specifiers);
+        }
+        if !self.tok_is(&token_types.brace_l) {
+            return Ok(specifiers);
+        }
+        let indent = self.cur_indent;
+        let line = self.cur_line_start;
+        let mut continued_line = self.next_line_start;
+        self.push_cx();
+        self.next()?;
+        if self.cur_line_start > continued_line {
+            continued_line = self.cur_line_start;
+        }
+        loop {
+            let extra_indent = i32::from(self.cur_line_start <= continued_line);
+            if self.closes(&token_types.brace_r, indent + extra_indent, line, false)
+                || self.is_contextual("from")
+            {
+                break;
+            }
+            let span = self.start_node();
+            let imported = self.parse_module_export_name()?;
+            if is_dummy_name(&imported) {
+                break;
+            }
+            let local = if self.eat_contextual("as")? {
+                self.parse_ident()?
+            } else {
+                match &imported {
+                    ModuleExportName::Identifier(id) => id.as_ref().clone(),
+                    ModuleExportName::Literal(literal) => {
+                        self.report(
+                            literal.span.start,
+                            ErrorCode::InvalidAssignmentTarget,
+                            "A string literal cannot be used as an imported binding.",
+                        );
+                        self.dummy_ident()
+                    }
+                }
+            };
+            specifiers.push(ImportDeclarationSpecifier::ImportSpecifier(Box::new(
+                ImportSpecifier {
+                    span: self.finish_node(span),
+                    imported,
+                    local,
+                },
+            )));
+            self.eat(&token_types.comma)?;
+        }
+        if !self.eat(&token_types.brace_r)? {
+            self.unexpected();
+        }
+        self.pop_cx();
+        Ok(specifiers)
+    }
+
+    fn parse_export_specifier_list(&mut self) -> Result<Vec<ExportSpecifier>, ParserError> {
+        let token_types = get_token_types();
+        let mut specifiers = vec![];
+        let indent = self.cur_indent;
+        let line = self.cur_line_start;
+        let mut continued_line = self.next_line_start;
+        self.push_cx();
+        if !self.eat(&token_types.brace_l)? {
+            self.unexpected();
+        }
+        if self.cur_line_start > continued_line {
+            continued_line = self.cur_line_start;
+        }
+        loop {
+            let extra_indent = i32::from(self.cur_line_start <= continued_line);
+            if self.closes(&token_types.brace_r, indent + extra_indent, line, false)
+                || self.is_contextual("from")
+            {
+                break;
+            }
+            let span = self.start_node();
+            let local = self.parse_module_export_name()?;
+            if is_dummy_name(&local) {
+                break;
+            }
+            let exported = if self.eat_contextual("as")? {
+                self.parse_module_export_name()?
+            } else {
+                local.clone()
+            };
+            specifiers.push(ExportSpecifier {
+                span: self.finish_node(span),
+                local,
+                exported,
+            });
+            self.eat(&token_types.comma)?;
+        }
+        if !self.eat(&token_types.brace_r)? {
+            self.unexpected();
+        }
+        self.pop_cx();
+        Ok(specifiers)
+    }
+
+    fn parse_module_export_name(&mut self) -> Result<ModuleExportName, ParserError> {
+        Ok(
+            if self.ecma_version() >= 13 && self.tok_is(&get_token_types().string) {
+                ModuleExportName::Literal(Box::new(self.parse_literal()?))
+            } else {
+                ModuleExportName::Identifier(Box::new(self.parse_ident()?))
+            },
+        )
+    }
+
+    fn parse_module_source(&mut self) -> Result<Literal, ParserError> {
+        if self.tok_is(&get_token_types().string) {
+            self.parse_literal()
+        } else {
+            self.unexpected();
+            Ok(self.dummy_string())
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{
+    errors::{ErrorCode, ParserError},
+    location::SourceLocation,
+    loose::{LooseParser, DUMMY_VALUE},
+    token::{
+        types::{get_token_types, TokenType},
+        Token, TokenParser, TokenValue,
+    },
+    whitespace::is_new_line,
+};
+
+fn is_space(ch: char) -> bool {
+    matches!(ch, '\t'..='\r' | ' ' | '\u{a0}') || is_new_line(ch as i32)
+}
+
+/// Whether `text` ends with a keyword after which an expression is
+/// expected, like `return` or `typeof`.
+fn ends_with_expression_keyword(text: &str) -> bool {
+    let word_start = text
+        .char_indices()
+        .rev()
+        .find(|(_, ch)| !(ch.is_ascii_alphanumeric() || *ch == '_'))
+        .map_or(0, |(index, ch)| index + ch.len_utf8());
+    matches!(
+        &text[word_start..],
+        "case"
+            | "else"
+            | "return"
+            | "throw"
+            | "new"
+            | "in"
+            | "of"
+            | "instanceof"
+            | "typeof"
+            | "delete"
+            | "void"
+    )
+}
+
+pub(crate) trait LooseTokenizer {
+    fn next(&mut self) -> Result<(), ParserError>;
+    fn read_token(&mut self) -> Result<Token, ParserError>;
+    fn recover(
+        &self,
+        message: &str,
+        pos: i32,
+        raised_at: i32,
+    ) -> (i32, Option<(TokenType, TokenValue, i32, i32)>);
+    fn make_token(&self, token_type: TokenType, value: TokenValue, start: i32, end: i32) -> Token;
+    fn reset_to(&mut self, pos: i32);
+    fn look_ahead(&mut self, n: usize) -> Result<Token, ParserError>;
+}
+
+impl LooseTokenizer for LooseParser {
+    fn next(&mut self) -> Result<(), ParserError> {
+        let next = match self.ahead.pop_front() {
+            Some(token) => token,
+            None => self.read_token()?,
+        };
+        self.last = std::mem::replace(&mut self.tok, next);
+
+        let start = self.tok.start;
+        if start >= self.next_line_start {
+            while start >= self.next_line_start {
+                self.cur_line_start = self.next_line_start;
+                self.next_line_start = self.next_line_after(self.cur_line_start);
+            }
+            self.cur_indent = self.indentation_after(self.cur_line_start);
+        }
+        Ok(())
+    }
+
+    /// Reads the next token from the tokenizer. When the tokenizer fails,
+    /// the error is recorded and it resumes after the offending text,
+    /// often returning a token standing in for it.
+    fn read_token(&mut self) -> Result<Token, ParserError> {
+        let token_types = get_token_types();
+        let mut result = if std::mem::replace(&mut self.started, true) {
+            if self.toks.contains_esc {
+                if let Some(keyword) = self.toks.cur_token_type.keyword.clone() {
+                    self.report(
+                        self.toks.cur_token_start,
+                        ErrorCode::EscapedKeyword,
+                        &format!("Escape sequence in keyword {:}", keyword),
+                    );
+                }
+            }
+            self.toks.next(true)
+        } else {
+            self.toks.next_token()
+        };
+        loop {
+            let error = match result {
+                Ok(()) => break,
+                Err(error) => error,
+            };
+            let (message, pos, raised_at) = match &error {
+                ParserError::SyntaxError {
+                    message,
+                    pos,
+                    raised_at,
+                    ..
+                } => (message.clone(), *pos, *raised_at),
+                _ => return Err(error),
+            };
+            self.push_diagnostic(error);
+            let (resume_at, replacement) = self.recover(&message, pos, raised_at);
+            self.reset_to(resume_at);
+            if let Some((token_type, value, start, end)) = replacement {
+                return Ok(self.make_token(token_type, value, start, end));
+            }
+            result = self.toks.next_token();
+        }
+
+        // A `..` typo is read as a spread.
+        let end = self.toks.cur_token_end;
+        if self.toks.cur_token_type.eq(&token_types.dot)
+            && self.toks.input[end as usize..].starts_with('.')
+            && self.ecma_version() >= 6
+        {
+            self.toks.cur_token_end = end + 1;
+            self.toks.cur_token_pos = end + 1;
+            if let Some(loc) = self.toks.cur_token_end_loc.as_mut() {
+                loc.column += 1;
+            }
+            self.toks.cur_token_type = token_types.ellipsis.clone();
+        }
+        Ok(Token::new(&self.toks))
+    }
+
+    /// Decides, based on the message of a tokenizer error, where to resume
+    /// tokenizing and which token, if any, stands in for the skipped text.
+    fn recover(
+        &self,
+        message: &str,
+        pos: i32,
+        raised_at: i32,
+    ) -> (i32, Option<(TokenType, TokenValue, i32, i32)>) {
+        let token_types = get_token_types();
+        let input = self.toks.input.as_str();
+        let lowercase = message.to_lowercase();
+        // Errors at the end of the input can point past it.
+        let pos = pos.min(input.len() as i32);
+        let raised_at = raised_at.min(input.len() as i32);
+        let dummy = |at: i32| {
+            Some((
+                token_types.name.clone(),
+                TokenValue::String(DUMMY_VALUE.to_owned()),
+                at,
+                at,
+            ))
+        };
+        // Skips to the first character matching `stop`, or past it.
+        let skip = |from: i32, stop: fn(char) -> bool, past: bool| {
+            let rest = &input[from as usize..];
+            match rest.char_indices().find(|(_, ch)| stop(*ch)) {
+                Some((index, ch)) if past => from + (index + ch.len_utf8()) as i32,
+                Some((index, _)) => from + index as i32,
+                None => input.len() as i32,
+            }
+        };
+
+        if lowercase.contains("unterminated") {
+            let after_start = pos
+                + input[pos as usize..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8) as i32;
+            let end = self.line_end(after_start);
+            let text = &input[pos as usize..end as usize];
+            let content = &input[after_start as usize..end as usize];
+            let replacement = if message.contains("string") {
+                Some((
+                    token_types.string.clone(),
+                    TokenValue::String(content.to_owned()),
+                    pos,
+                    end,
+                ))
+            } else if lowercase.contains("regular expr") {
+                Some((
+                    token_types.regexp.clone(),
+                    TokenValue::Regex {
+                        pattern: content.to_owned(),
+                        flags: String::new(),
+                    },
+                    pos,
+                    end,
+                ))
+            } else if message.contains("template") {
+                Some((
+                    token_types.template.clone(),
+                    TokenValue::String(text.to_owned()),
+                    pos,
+                    end,
+                ))
+            } else {
+                None
+            };
+            (end, replacement)
+        } else if lowercase.contains("character escape")
+            || lowercase.contains("expected hexadecimal")
+        {
+            let resume_at = skip(
+                raised_at,
+                |ch| ch == '"' || ch == '\'' || is_new_line(ch as i32),
+                true,
+            );
+            (resume_at, dummy(resume_at))
+        } else if lowercase.contains("unexpected character") {
+            let len = input[raised_at as usize..]
+                .chars()
+                .next()
+                .map_or(0, char::len_utf8);
+            (raised_at + len as i32, None)
+        } else if lowercase.contains("regular expression") {
+            (raised_at, dummy(raised_at))
+        } else {
+            // Invalid numbers, escapes and the like: skip the rest of the
+            // word.
+            let resume_at = skip(raised_at, is_space, false);
+            (resume_at, dummy(resume_at))
+        }
+    }
+
+    fn make_token(&self, token_type: TokenType, value: TokenValue, start: i32, end: i32) -> Token {
+        let options = &self.toks.options;
+        Token {
+            token_type,
+            value,
+            start,
+            end,
+            loc: if options.locations {
+                Some(SourceLocation::new(
+                    &self.toks.line_index.position(start),
+                    &Some(self.toks.line_index.position(end)),
+                    &self.toks.source_file,
+                ))
+            } else {
+                None
+            },
+            range: if options.ranges {
+                Some((start, end))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Moves the tokenizer to `pos`, guessing from the text before it
+    /// whether an expression is allowed there.
+    fn reset_to(&mut self, pos: i32) {
+        let toks = &mut self.toks;
+        toks.cur_token_pos = pos;
+        toks.contains_esc = false;
+        let before = &toks.input[..pos as usize];
+        toks.expr_allowed = match before.chars().next_back() {
+            None => true,
+            Some(ch) if "[{(,;:?/*=+-~!|&%^<".contains(ch) => true,
+            Some('e' | 'n' | 'w' | 'f' | 'd') => ends_with_expression_keyword(before),
+            Some(_) => false,
+        };
+        if toks.options.locations {
+            let position = toks.line_index.position(pos);
+            toks.cur_token_line = position.line;
+            toks.cur_token_line_start = pos - position.column;
+        }
+    }
+
+    fn look_ahead(&mut self, n: usize) -> Result<Token, ParserError> {
+        while n > self.ahead.len() {
+            let token = self.read_token()?;
+            self.ahead.push_back(token);
+        }
+        Ok(self.ahead[n - 1].clone())
+    }
+}
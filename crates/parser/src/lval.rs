@@ -1,14 +1,16 @@
-use std::{
-    any::Any,
-    borrow::{Borrow, BorrowMut},
-    collections::{HashMap, HashSet},
-};
+use std::collections::HashSet;
 
 use crate::{
-    errors::ParserError,
+    atom::Atom,
+    errors::{ErrorCode, ParserError},
     expression::ExpressionParser,
     location::{LocationParser, Position},
-    node::{Node, NodeParser, NodeType},
+    node::{
+        ArrayPattern, AssignmentOperator, AssignmentPattern, AssignmentProperty, Expression,
+        ExpressionOrSpread, Identifier, NodeParser, ObjectPattern, ParenthesizedExpression,
+        Pattern, PropertyKind, PropertyOrRest, PropertyOrSpread, RestElement, Spanned,
+        SpreadElement,
+    },
     parser::Parser,
     scope::{ScopeParser, BIND_LEXICAL, BIND_NONE, BIND_OUTSIDE},
     token::{
@@ -18,249 +20,398 @@ use crate::{
     utils::{DestructuringErrors, UtilsParser},
 };
 
+/// Reinterprets a simple assignment target as a pattern, handing the
+/// expression back when it is not one.
+pub(crate) fn simple_target_to_pattern(expr: Expression) -> Result<Pattern, Expression> {
+    match expr {
+        Expression::Identifier(id) => Ok(Pattern::Identifier(id)),
+        Expression::MemberExpression(member) => Ok(Pattern::MemberExpression(member)),
+        Expression::ParenthesizedExpression(paren) => Ok(Pattern::ParenthesizedExpression(paren)),
+        expr => Err(expr),
+    }
+}
+
+/// The reverse of `simple_target_to_pattern`, used to put a converted target
+/// back inside a `ParenthesizedExpression`.
+fn simple_pattern_to_target(pattern: Pattern) -> Result<Expression, Pattern> {
+    match pattern {
+        Pattern::Identifier(id) => Ok(Expression::Identifier(id)),
+        Pattern::MemberExpression(member) => Ok(Expression::MemberExpression(member)),
+        Pattern::ParenthesizedExpression(paren) => Ok(Expression::ParenthesizedExpression(paren)),
+        pattern => Err(pattern),
+    }
+}
+
 pub trait LvalParser {
     fn to_assignable(
-        &self,
-        node: &mut Node,
+        &mut self,
+        expr: Expression,
         is_binding: bool,
         destructuring_errors: &Option<DestructuringErrors>,
-    ) -> Result<(), ParserError>;
-    fn to_assignable_list(&self, node: &mut Vec<Node>, is_binding: bool)
-        -> Result<(), ParserError>;
+    ) -> Result<Pattern, ParserError>;
+    fn to_assignable_pattern(
+        &mut self,
+        pattern: Pattern,
+        is_binding: bool,
+    ) -> Result<Pattern, ParserError>;
+    fn to_assignable_rest(
+        &mut self,
+        spread: SpreadElement,
+        is_binding: bool,
+    ) -> Result<RestElement, ParserError>;
+    fn to_assignable_list(
+        &mut self,
+        exprs: Vec<Option<ExpressionOrSpread>>,
+        is_binding: bool,
+    ) -> Result<Vec<Option<Pattern>>, ParserError>;
+    fn to_simple_assignable(&self, expr: Expression) -> Result<Pattern, ParserError>;
     fn parse_spread(
-        &self,
-        destructuring_errors: &Option<DestructuringErrors>,
-    ) -> Result<Node, ParserError>;
-    fn parse_rest_binding(&self) -> Result<Node, ParserError>;
-    fn parse_binding_atom(&self) -> Result<Node, ParserError>;
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<SpreadElement, ParserError>;
+    fn parse_rest_binding(&mut self) -> Result<RestElement, ParserError>;
+    fn parse_binding_atom(&mut self) -> Result<Pattern, ParserError>;
     fn parse_binding_list(
-        &self,
+        &mut self,
         close: &TokenType,
         allow_empty: bool,
         allow_trailing_comma: bool,
-    ) -> Result<Vec<Node>, ParserError>;
-    fn parse_binding_list_item(&self, node: &Node) -> Result<Node, ParserError>;
+    ) -> Result<Vec<Option<Pattern>>, ParserError>;
+    fn parse_binding_list_item(&self, param: Pattern) -> Result<Pattern, ParserError>;
     fn parse_maybe_default(
-        &self,
+        &mut self,
         start_pos: i32,
         start_loc: &Option<Position>,
-        left: &Option<Node>,
-    ) -> Result<Node, ParserError>;
+        left: Option<Pattern>,
+    ) -> Result<Pattern, ParserError>;
     fn check_lval_simple(
-        &self,
-        node: &Node,
+        &mut self,
+        expr: &Expression,
+        binding_type: i32,
+        check_clashes: &mut Option<HashSet<Atom>>,
+    ) -> Result<(), ParserError>;
+    fn check_lval_identifier(
+        &mut self,
+        id: &Identifier,
         binding_type: i32,
-        check_clashes: &mut Option<HashSet<String>>,
+        check_clashes: &mut Option<HashSet<Atom>>,
     ) -> Result<(), ParserError>;
     fn check_lval_pattern(
-        &self,
-        node: &Node,
+        &mut self,
+        pattern: &Pattern,
         binding_type: i32,
-        check_clashes: &mut Option<HashSet<String>>,
+        check_clashes: &mut Option<HashSet<Atom>>,
     ) -> Result<(), ParserError>;
     fn check_lval_inner_pattern(
-        &self,
-        node: &Node,
+        &mut self,
+        pattern: &Pattern,
         binding_type: i32,
-        check_clashes: &mut Option<HashSet<String>>,
+        check_clashes: &mut Option<HashSet<Atom>>,
     ) -> Result<(), ParserError>;
 }
 
 impl LvalParser for Parser {
     /// Convert existing expression atom to assignable pattern if possible.
     fn to_assignable(
-        &self,
-        node: &mut Node,
+        &mut self,
+        expr: Expression,
         is_binding: bool,
         destructuring_errors: &Option<DestructuringErrors>,
-    ) -> Result<(), ParserError> {
-        if self.options.get_ecma_version_number() >= 6 {
-            match node.node_type {
-                NodeType::Identifier => {
-                    if self.is_async() && node.name.eq("await") {
-                        self.raise(
-                            node.start,
-                            "Cannot use 'await' as identifier inside an async function",
-                        )?;
-                    }
+    ) -> Result<Pattern, ParserError> {
+        if self.options.get_ecma_version_number() < 6 {
+            if destructuring_errors.is_some() {
+                self.check_pattern_errors(destructuring_errors, true)?;
+            }
+            return self.to_simple_assignable(expr);
+        }
+        match expr {
+            Expression::Identifier(id) => {
+                if self.is_async() && id.name == "await" {
+                    self.raise(
+                        id.span.start,
+                        ErrorCode::AwaitIdentifier,
+                        "Cannot use 'await' as identifier inside an async function",
+                    )?;
                 }
-                NodeType::ObjectExpression => {
-                    node.node_type = NodeType::ObjectPattern;
-                    if destructuring_errors.is_some() {
-                        self.check_expression_errors(destructuring_errors, true)?;
-                    }
-                    let properties: &mut Vec<Node> = node.properties.borrow_mut();
-                    for prop in properties {
-                        self.to_assignable(prop, is_binding, &None)?;
-                        if let Some(argument) = prop.argument.borrow() {
-                            if prop.node_type == NodeType::RestElement
-                                && (argument.node_type == NodeType::ArrayPattern
-                                    || argument.node_type == NodeType::ObjectPattern)
+                Ok(Pattern::Identifier(id))
+            }
+            Expression::ObjectExpression(object) => {
+                if destructuring_errors.is_some() {
+                    self.check_pattern_errors(destructuring_errors, true)?;
+                }
+                let object = *object;
+                let mut properties = vec![];
+                for prop in object.properties {
+                    properties.push(match prop {
+                        PropertyOrSpread::Property(prop) => {
+                            let prop = *prop;
+                            if prop.kind != PropertyKind::Init {
+                                self.raise(
+                                    prop.key.span().start,
+                                    ErrorCode::InvalidDestructuring,
+                                    "Object pattern can't contain getter or setter",
+                                )?;
+                            }
+                            PropertyOrRest::Property(Box::new(AssignmentProperty {
+                                span: prop.span,
+                                shorthand: prop.shorthand,
+                                computed: prop.computed,
+                                key: prop.key,
+                                value: self.to_assignable(prop.value, is_binding, &None)?,
+                            }))
+                        }
+                        PropertyOrSpread::SpreadElement(spread) => {
+                            let rest = self.to_assignable_rest(*spread, is_binding)?;
+                            if let Pattern::ArrayPattern(_) | Pattern::ObjectPattern(_) =
+                                &rest.argument
                             {
-                                self.raise(argument.start, "Unexpected token")?;
+                                self.raise(
+                                    rest.argument.span().start,
+                                    ErrorCode::UnexpectedToken,
+                                    "Unexpected token",
+                                )?;
                             }
+                            PropertyOrRest::RestElement(Box::new(rest))
                         }
-                    }
+                    });
                 }
-                NodeType::Property => {
-                    if node.kind.ne("init") {
-                        if let Some(key) = node.key.borrow() {
-                            self.raise(key.start, "Object pattern can't contain getter or setter")?;
-                        }
-                    }
-                    if let Some(value) = node.value.borrow_mut() {
-                        self.to_assignable(value, is_binding, &None)?;
-                    }
+                Ok(Pattern::ObjectPattern(Box::new(ObjectPattern {
+                    span: object.span,
+                    properties,
+                })))
+            }
+            Expression::ArrayExpression(array) => {
+                if destructuring_errors.is_some() {
+                    self.check_pattern_errors(destructuring_errors, true)?;
                 }
-                NodeType::ArrayExpression => {
-                    node.node_type = NodeType::ArrayPattern;
-                    if destructuring_errors.is_some() {
-                        self.check_pattern_errors(destructuring_errors, true)?;
-                    }
-                    self.to_assignable_list(node.elements.borrow_mut(), is_binding)?;
+                let array = *array;
+                Ok(Pattern::ArrayPattern(Box::new(ArrayPattern {
+                    span: array.span,
+                    elements: self.to_assignable_list(array.elements, is_binding)?,
+                })))
+            }
+            Expression::AssignmentExpression(assign) => {
+                let assign = *assign;
+                if assign.operator != AssignmentOperator::Assign {
+                    self.raise(
+                        assign.left.span().end,
+                        ErrorCode::InvalidDestructuring,
+                        "Only '=' operator can be used for specifying default value.",
+                    )?;
                 }
-                NodeType::SpreadElement => {
-                    node.node_type = NodeType::RestElement;
-                    if let Some(argument) = node.argument.borrow_mut() {
-                        self.to_assignable(argument, is_binding, &None)?;
-                        if argument.node_type == NodeType::AssignmentPattern {
+                Ok(Pattern::AssignmentPattern(Box::new(AssignmentPattern {
+                    span: assign.span,
+                    left: self.to_assignable_pattern(assign.left, is_binding)?,
+                    right: assign.right,
+                })))
+            }
+            Expression::ParenthesizedExpression(paren) => {
+                let paren = *paren;
+                let inner_start = paren.expression.span().start;
+                let inner =
+                    self.to_assignable(paren.expression, is_binding, destructuring_errors)?;
+                match simple_pattern_to_target(inner) {
+                    Ok(expression) if !is_binding => Ok(Pattern::ParenthesizedExpression(
+                        Box::new(ParenthesizedExpression {
+                            span: paren.span,
+                            expression,
+                        }),
+                    )),
+                    // Recoverable in acorn, but there is no pattern to return
+                    // here.
+                    _ => {
+                        if is_binding {
                             self.raise(
-                                argument.start,
-                                "Rest elements cannot have a default value",
+                                paren.span.start,
+                                ErrorCode::InvalidAssignmentTarget,
+                                "Binding parenthesized expression",
                             )?;
-                        }
-                    }
-                }
-                NodeType::AssignmentExpression => {
-                    if let Some(left) = node.left.borrow_mut() {
-                        if node.operator.ne("=") {
+                        } else {
                             self.raise(
-                                left.end,
-                                "Only '=' operator can be used for specifying default value.",
+                                inner_start,
+                                ErrorCode::InvalidAssignmentTarget,
+                                "Assigning to rvalue",
                             )?;
                         }
-                        node.node_type = NodeType::AssignmentPattern;
-                        node.operator = "".to_string();
-                        self.to_assignable(left, is_binding, &None)?;
+                        unreachable!()
                     }
                 }
-                NodeType::ParenthesizedExpression => {
-                    if let Some(expression) = node.expression.borrow_mut() {
-                        self.to_assignable(expression, is_binding, destructuring_errors)?;
-                    }
-                }
-                NodeType::ChainExpression => {
-                    self.raise_recoverable(
-                        node.start,
-                        "Optional chaining cannot appear in left-hand side",
-                    )?;
-                }
-                _ => {
-                    if node.node_type != NodeType::ObjectPattern
-                        && node.node_type != NodeType::ArrayPattern
-                        && node.node_type != NodeType::AssignmentPattern
-                        && node.node_type != NodeType::RestElement
-                        && (node.node_type != NodeType::MemberExpression || is_binding)
-                    {
-                        self.raise(node.start, "Assigning to rvalue")?;
-                    }
-                }
-            };
-        } else if destructuring_errors.is_some() {
-            self.check_expression_errors(destructuring_errors, true)?;
+            }
+            Expression::ChainExpression(chain) => {
+                self.raise(
+                    chain.span.start,
+                    ErrorCode::InvalidOptionalChain,
+                    "Optional chaining cannot appear in left-hand side",
+                )?;
+                unreachable!()
+            }
+            Expression::MemberExpression(member) if !is_binding => {
+                Ok(Pattern::MemberExpression(member))
+            }
+            expr => {
+                self.raise(
+                    expr.span().start,
+                    ErrorCode::InvalidAssignmentTarget,
+                    "Assigning to rvalue",
+                )?;
+                unreachable!()
+            }
+        }
+    }
+
+    /// Check a target that has already been converted to a pattern, like
+    /// the left side of a nested default value.
+    fn to_assignable_pattern(
+        &mut self,
+        pattern: Pattern,
+        is_binding: bool,
+    ) -> Result<Pattern, ParserError> {
+        match pattern {
+            Pattern::Identifier(id) => {
+                self.to_assignable(Expression::Identifier(id), is_binding, &None)
+            }
+            Pattern::MemberExpression(member) if is_binding => {
+                self.raise(
+                    member.span.start,
+                    ErrorCode::InvalidAssignmentTarget,
+                    "Assigning to rvalue",
+                )?;
+                unreachable!()
+            }
+            Pattern::ParenthesizedExpression(paren) => self.to_assignable(
+                Expression::ParenthesizedExpression(paren),
+                is_binding,
+                &None,
+            ),
+            pattern => Ok(pattern),
         }
-        Ok(())
+    }
+
+    /// Convert a spread element of an array or object literal to a rest
+    /// element.
+    fn to_assignable_rest(
+        &mut self,
+        spread: SpreadElement,
+        is_binding: bool,
+    ) -> Result<RestElement, ParserError> {
+        let argument = self.to_assignable(spread.argument, is_binding, &None)?;
+        if let Pattern::AssignmentPattern(assign) = &argument {
+            self.raise(
+                assign.span.start,
+                ErrorCode::InvalidDestructuring,
+                "Rest elements cannot have a default value",
+            )?;
+        }
+        Ok(RestElement {
+            span: spread.span,
+            argument,
+        })
     }
 
     /// Convert list of expression atoms to binding list.
     fn to_assignable_list(
-        &self,
-        nodes: &mut Vec<Node>,
+        &mut self,
+        exprs: Vec<Option<ExpressionOrSpread>>,
         is_binding: bool,
-    ) -> Result<(), ParserError> {
-        let node_length = nodes.len();
-        for index in 0..node_length {
-            self.to_assignable(&mut nodes[index], is_binding, &None)?;
+    ) -> Result<Vec<Option<Pattern>>, ParserError> {
+        let mut patterns = vec![];
+        for expr in exprs {
+            patterns.push(match expr {
+                Some(ExpressionOrSpread::Expression(expr)) => {
+                    Some(self.to_assignable(expr, is_binding, &None)?)
+                }
+                Some(ExpressionOrSpread::SpreadElement(spread)) => Some(Pattern::RestElement(
+                    Box::new(self.to_assignable_rest(*spread, is_binding)?),
+                )),
+                None => None,
+            });
         }
-        if node_length > 0 {
-            let last = &nodes[node_length - 1];
+        if let Some(Some(Pattern::RestElement(last))) = patterns.last() {
             if self.options.get_ecma_version_number() == 6
                 && is_binding
-                && last.node_type == NodeType::RestElement
+                && !matches!(last.argument, Pattern::Identifier(_))
             {
-                let (is_throw_error, pos) = match last.argument.borrow() {
-                    Some(argument) => (
-                        argument.node_type != NodeType::Identifier,
-                        Some(argument.start),
-                    ),
-                    None => (true, None),
-                };
-                if is_throw_error {
-                    self.unexpected(pos)?;
-                }
+                self.unexpected(Some(last.argument.span().start))?;
+            }
+        }
+        Ok(patterns)
+    }
+
+    /// Convert the target of a compound assignment, which has already been
+    /// verified by `check_lval_simple`.
+    fn to_simple_assignable(&self, expr: Expression) -> Result<Pattern, ParserError> {
+        match simple_target_to_pattern(expr) {
+            Ok(pattern) => Ok(pattern),
+            Err(expr) => {
+                self.raise(
+                    expr.span().start,
+                    ErrorCode::InvalidAssignmentTarget,
+                    "Assigning to rvalue",
+                )?;
+                unreachable!()
             }
         }
-        Ok(())
     }
 
     /// Parses spread element.
     fn parse_spread(
-        &self,
-        destructuring_errors: &Option<DestructuringErrors>,
-    ) -> Result<Node, ParserError> {
-        let mut node = self.start_node();
+        &mut self,
+        destructuring_errors: &mut Option<DestructuringErrors>,
+    ) -> Result<SpreadElement, ParserError> {
+        let span = self.start_node();
         self.next(false)?;
-        node.argument = Box::new(Some(self.parse_maybe_assign(
-            false,
-            destructuring_errors,
-            None,
-        )?));
-        self.finish_node(&mut node, NodeType::SpreadElement);
-        Ok(node)
+        let argument = self.parse_maybe_assign(false, destructuring_errors, None)?;
+        Ok(SpreadElement {
+            span: self.finish_node(span),
+            argument,
+        })
     }
 
-    fn parse_rest_binding(&self) -> Result<Node, ParserError> {
-        let mut node = self.start_node();
+    fn parse_rest_binding(&mut self) -> Result<RestElement, ParserError> {
+        let span = self.start_node();
         self.next(false)?;
         // RestElement inside of a function parameter must be an identifier
         if self.options.get_ecma_version_number() == 6
-            && self.cur_token_type.borrow().ne(&get_token_types().name)
+            && self.cur_token_type.ne(&get_token_types().name)
         {
             self.unexpected(None)?;
         }
-        node.argument = Box::new(Some(self.parse_binding_atom()?));
-        self.finish_node(&mut node, NodeType::RestElement);
-        Ok(node)
+        let argument = self.parse_binding_atom()?;
+        Ok(RestElement {
+            span: self.finish_node(span),
+            argument,
+        })
     }
 
     /// Parses lvalue (assignable) atom.
-    fn parse_binding_atom(&self) -> Result<Node, ParserError> {
+    fn parse_binding_atom(&mut self) -> Result<Pattern, ParserError> {
         if self.options.get_ecma_version_number() >= 6 {
             let token_types = get_token_types();
-            let cur_token_type = self.cur_token_type.borrow();
+            let cur_token_type = self.cur_token_type.clone();
             if cur_token_type.eq(&token_types.bracket_l) {
-                let mut node = self.start_node();
+                let span = self.start_node();
                 self.next(false)?;
-                node.elements =
-                    Box::new(self.parse_binding_list(&token_types.brace_r, true, true)?);
-                self.finish_node(&mut node, NodeType::ArrayPattern);
-                return Ok(node);
+                let elements = self.parse_binding_list(&token_types.bracket_r, true, true)?;
+                return Ok(Pattern::ArrayPattern(Box::new(ArrayPattern {
+                    span: self.finish_node(span),
+                    elements,
+                })));
             }
             if cur_token_type.eq(&token_types.brace_l) {
-                return self.parse_obj(true, &None);
+                return Ok(Pattern::ObjectPattern(Box::new(self.parse_obj_pattern()?)));
             }
         }
-        self.parse_ident(false)
+        Ok(Pattern::Identifier(Box::new(self.parse_ident(false)?)))
     }
 
     fn parse_binding_list(
-        &self,
+        &mut self,
         close: &TokenType,
         allow_empty: bool,
         allow_trailing_comma: bool,
-    ) -> Result<Vec<Node>, ParserError> {
+    ) -> Result<Vec<Option<Pattern>>, ParserError> {
         let mut is_first = true;
-        let mut elements: Vec<Node> = vec![];
+        let mut elements = vec![];
         let token_types = get_token_types();
         while !self.eat(close)? {
             if is_first {
@@ -268,62 +419,60 @@ impl LvalParser for Parser {
             } else {
                 self.expect(&token_types.comma)?;
             }
-            if allow_empty && self.cur_token_type.borrow().eq(&token_types.comma) {
-                continue;
-            }
-            if allow_trailing_comma && self.after_trailing_comma(close, false)? {
+            if allow_empty && self.cur_token_type.eq(&token_types.comma) {
+                elements.push(None);
+            } else if allow_trailing_comma && self.after_trailing_comma(close, false)? {
                 break;
-            }
-            if self.cur_token_type.borrow().eq(&token_types.ellipsis) {
-                let rest = self.parse_rest_binding()?;
-                elements.push(self.parse_binding_list_item(&rest)?);
-                if self.cur_token_type.borrow().eq(&token_types.comma) {
+            } else if self.cur_token_type.eq(&token_types.ellipsis) {
+                let rest = Pattern::RestElement(Box::new(self.parse_rest_binding()?));
+                elements.push(Some(self.parse_binding_list_item(rest)?));
+                if self.cur_token_type.eq(&token_types.comma) {
                     self.raise(
-                        self.cur_token_start.get(),
+                        self.cur_token_start,
+                        ErrorCode::RestTrailingComma,
                         "Comma is not permitted after the rest element",
                     )?;
                 }
                 self.expect(close)?;
                 break;
             } else {
-                let element = self.parse_maybe_default(
-                    self.cur_token_start.get(),
-                    &*self.cur_token_start_loc.borrow(),
-                    &None,
-                )?;
-                elements.push(self.parse_binding_list_item(&element)?);
+                let start_loc = self.cur_token_start_loc.clone();
+                let element = self.parse_maybe_default(self.cur_token_start, &start_loc, None)?;
+                elements.push(Some(self.parse_binding_list_item(element)?));
             }
         }
         Ok(elements)
     }
 
-    fn parse_binding_list_item(&self, node: &Node) -> Result<Node, ParserError> {
-        Ok(node.clone())
+    fn parse_binding_list_item(&self, param: Pattern) -> Result<Pattern, ParserError> {
+        Ok(param)
     }
 
     /// Parses assignment pattern around given atom if possible.
     fn parse_maybe_default(
-        &self,
+        &mut self,
         start_pos: i32,
         start_loc: &Option<Position>,
-        left: &Option<Node>,
-    ) -> Result<Node, ParserError> {
+        left: Option<Pattern>,
+    ) -> Result<Pattern, ParserError> {
         let left = match left {
-            Some(left) => left.clone(),
+            Some(left) => left,
             None => self.parse_binding_atom()?,
         };
         if self.options.get_ecma_version_number() < 6 || !self.eat(&get_token_types().eq)? {
             return Ok(left);
         }
 
-        let mut node = self.start_node_at(start_pos, start_loc);
-        node.left = Box::new(Some(left));
-        node.right = Box::new(Some(self.parse_maybe_assign(false, &None, None)?));
-        self.finish_node(&mut node, NodeType::AssignmentPattern);
-        Ok(node)
+        let span = self.start_node_at(start_pos, start_loc);
+        let right = self.parse_maybe_assign(false, &mut None, None)?;
+        Ok(Pattern::AssignmentPattern(Box::new(AssignmentPattern {
+            span: self.finish_node(span),
+            left,
+            right,
+        })))
     }
 
-    /// The following three functions all verify that a node is an lvalue —
+    /// The following functions all verify that a node is an lvalue —
     /// something that can be bound, or assigned to. In order to do so, they
     /// perform a variety of checks:
     ///
@@ -344,16 +493,14 @@ impl LvalParser for Parser {
     ///   for constructs for which the spec says
     ///
     ///   > It is a Syntax Error if AssignmentTargetType of [the production] is
-    /// not   > simple.
+    ///   > not simple.
     ///
-    ///   It is also appropriate for checking if an identifier is valid and not
-    ///   defined elsewhere, like import declarations or function/class
-    /// identifiers.
+    ///   Checking if an identifier is valid and not defined elsewhere, like
+    ///   import declarations or function/class identifiers, is done by its
+    ///   check_lval_identifier() part.
     ///
-    ///   Examples where this is used include:
-    ///     a += …;
-    ///     import a from '…';
-    ///   where a is the node to be checked.
+    ///   Examples where this is used include `a += …;` and
+    ///   `import a from '…';`, where a is the node to be checked.
     ///
     /// - check_lval_pattern() shall be used if the syntactic construct supports
     ///   anything check_lval_simple() supports, as well as object and array
@@ -361,14 +508,11 @@ impl LvalParser for Parser {
     ///   for which the spec says
     ///
     ///   > It is a Syntax Error if [the production] is neither an ObjectLiteral
-    /// nor   > an ArrayLiteral and AssignmentTargetType of [the production]
-    /// is not   > simple.
+    ///   > nor an ArrayLiteral and AssignmentTargetType of [the production]
+    ///   > is not simple.
     ///
-    ///   Examples where this is used include:
-    ///     (a = …);
-    ///     const a = …;
-    ///     try { … } catch (a) { … }
-    ///   where a is the node to be checked.
+    ///   Examples where this is used include `(a = …);`, `const a = …;` and
+    ///   `try { … } catch (a) { … }`, where a is the node to be checked.
     ///
     /// - check_lval_inner_pattern() shall be used if the syntactic construct
     ///   supports anything check_lval_pattern() supports, as well as default
@@ -376,8 +520,8 @@ impl LvalParser for Parser {
     ///   appear within an object or array destructuring pattern.
     ///
     ///   As a special case, function parameters also use
-    /// check_lval_inner_pattern(),   as they also support defaults and rest
-    /// constructs.
+    ///   check_lval_inner_pattern(), as they also support defaults and rest
+    ///   constructs.
     ///
     /// These functions deliberately support both assignment and binding
     /// constructs, as the logic for both is exceedingly similar. If the
@@ -391,133 +535,172 @@ impl LvalParser for Parser {
     /// provided construct is an assignment (i.e., binding_type is
     /// BIND_NONE).
     fn check_lval_simple(
-        &self,
-        node: &Node,
+        &mut self,
+        expr: &Expression,
         binding_type: i32,
-        check_clashes: &mut Option<HashSet<String>>,
+        check_clashes: &mut Option<HashSet<Atom>>,
     ) -> Result<(), ParserError> {
         let is_bind = binding_type != BIND_NONE;
-        match node.node_type {
-            NodeType::Identifier => {
-                if self.is_strict.get()
-                    && self.reserved_words_strict_bind_regex.is_match(&node.name)?
-                {
+        match expr {
+            Expression::Identifier(id) => {
+                self.check_lval_identifier(id, binding_type, check_clashes)
+            }
+            Expression::ChainExpression(chain) => self.raise_recoverable(
+                chain.span.start,
+                ErrorCode::InvalidOptionalChain,
+                "Optional chaining cannot appear in left-hand side",
+            ),
+            Expression::MemberExpression(member) => {
+                if is_bind {
                     return self.raise_recoverable(
-                        node.start,
-                        &*if is_bind {
-                            format!("Binding {:} in strict mode", node.name)
-                        } else {
-                            format!("Assigning to {:} in strict mode", node.name)
-                        },
+                        member.span.start,
+                        ErrorCode::InvalidAssignmentTarget,
+                        "Binding member expression",
                     );
                 }
+                Ok(())
+            }
+            Expression::ParenthesizedExpression(paren) => {
                 if is_bind {
-                    if binding_type == BIND_LEXICAL && node.name.eq("let") {
-                        return self.raise_recoverable(
-                            node.start,
-                            "let is disallowed as a lexically bound name",
-                        );
-                    }
-                    if let Some(check_clashes) = check_clashes {
-                        if check_clashes.contains(&node.name) {
-                            return self.raise_recoverable(node.start, "Argument name clash");
-                        }
-                        check_clashes.insert(node.name.clone());
-                    }
-                    if binding_type != BIND_OUTSIDE {
-                        self.declare_name(&node.name, binding_type, node.start)?;
-                    }
+                    return self.raise_recoverable(
+                        paren.span.start,
+                        ErrorCode::InvalidAssignmentTarget,
+                        "Binding parenthesized expression",
+                    );
                 }
+                self.check_lval_simple(&paren.expression, binding_type, check_clashes)
             }
-            NodeType::ChainExpression => {
+            _ => self.raise_recoverable(
+                expr.span().start,
+                ErrorCode::InvalidAssignmentTarget,
+                if is_bind {
+                    "Binding rvalue"
+                } else {
+                    "Assigning to rvalue"
+                },
+            ),
+        }
+    }
+
+    fn check_lval_identifier(
+        &mut self,
+        id: &Identifier,
+        binding_type: i32,
+        check_clashes: &mut Option<HashSet<Atom>>,
+    ) -> Result<(), ParserError> {
+        let is_bind = binding_type != BIND_NONE;
+        if self.is_strict && self.reserved_words_strict_bind.contains(&id.name) {
+            return self.raise_recoverable(
+                id.span.start,
+                ErrorCode::StrictModeBinding,
+                &if is_bind {
+                    format!("Binding {:} in strict mode", id.name)
+                } else {
+                    format!("Assigning to {:} in strict mode", id.name)
+                },
+            );
+        }
+        if is_bind {
+            if binding_type == BIND_LEXICAL && id.name == "let" {
                 return self.raise_recoverable(
-                    node.start,
-                    "Optional chaining cannot appear in left-hand side",
+                    id.span.start,
+                    ErrorCode::LetInLexicalBinding,
+                    "let is disallowed as a lexically bound name",
                 );
             }
-            NodeType::MemberExpression => {
-                if is_bind {
-                    return self.raise_recoverable(node.start, "Binding member expression");
-                }
-            }
-            NodeType::ParenthesizedExpression => {
-                if is_bind {
-                    return self.raise_recoverable(node.start, "Binding parenthesized expression");
-                }
-                if let Some(expression) = node.expression.borrow() {
-                    return self.check_lval_simple(expression, binding_type, check_clashes);
+            if let Some(check_clashes) = check_clashes {
+                if check_clashes.contains(&id.name) {
+                    return self.raise_recoverable(
+                        id.span.start,
+                        ErrorCode::DuplicateParameter,
+                        "Argument name clash",
+                    );
                 }
+                check_clashes.insert(id.name.clone());
             }
-            _ => {
-                return self.raise_recoverable(
-                    node.start,
-                    if is_bind {
-                        "Binding rvalue"
-                    } else {
-                        "Assigning to rvalue"
-                    },
-                )
+            if binding_type != BIND_OUTSIDE {
+                self.declare_name(id, binding_type)?;
             }
         }
         Ok(())
     }
 
     fn check_lval_pattern(
-        &self,
-        node: &Node,
+        &mut self,
+        pattern: &Pattern,
         binding_type: i32,
-        check_clashes: &mut Option<HashSet<String>>,
+        check_clashes: &mut Option<HashSet<Atom>>,
     ) -> Result<(), ParserError> {
-        match node.node_type {
-            NodeType::ObjectPattern => {
-                let properties: &Vec<Node> = node.properties.borrow();
-                for property in properties {
-                    self.check_lval_inner_pattern(property, binding_type, check_clashes)?;
+        let is_bind = binding_type != BIND_NONE;
+        match pattern {
+            Pattern::ObjectPattern(object) => {
+                for property in object.properties.iter() {
+                    match property {
+                        PropertyOrRest::Property(property) => self.check_lval_inner_pattern(
+                            &property.value,
+                            binding_type,
+                            check_clashes,
+                        )?,
+                        PropertyOrRest::RestElement(rest) => {
+                            self.check_lval_pattern(&rest.argument, binding_type, check_clashes)?
+                        }
+                    }
                 }
+                Ok(())
             }
-            NodeType::ArrayPattern => {
-                let elements: &Vec<Node> = node.elements.borrow();
-                for element in elements {
+            Pattern::ArrayPattern(array) => {
+                for element in array.elements.iter().flatten() {
                     self.check_lval_inner_pattern(element, binding_type, check_clashes)?;
                 }
+                Ok(())
+            }
+            Pattern::Identifier(id) => self.check_lval_identifier(id, binding_type, check_clashes),
+            Pattern::MemberExpression(member) => {
+                if is_bind {
+                    return self.raise_recoverable(
+                        member.span.start,
+                        ErrorCode::InvalidAssignmentTarget,
+                        "Binding member expression",
+                    );
+                }
+                Ok(())
             }
-            _ => {
-                self.check_lval_simple(node, binding_type, check_clashes)?;
+            Pattern::ParenthesizedExpression(paren) => {
+                if is_bind {
+                    return self.raise_recoverable(
+                        paren.span.start,
+                        ErrorCode::InvalidAssignmentTarget,
+                        "Binding parenthesized expression",
+                    );
+                }
+                self.check_lval_simple(&paren.expression, binding_type, check_clashes)
             }
+            Pattern::AssignmentPattern(_) | Pattern::RestElement(_) => self.raise_recoverable(
+                pattern.span().start,
+                ErrorCode::InvalidAssignmentTarget,
+                if is_bind {
+                    "Binding rvalue"
+                } else {
+                    "Assigning to rvalue"
+                },
+            ),
         }
-        Ok(())
     }
 
     fn check_lval_inner_pattern(
-        &self,
-        node: &Node,
+        &mut self,
+        pattern: &Pattern,
         binding_type: i32,
-        check_clashes: &mut Option<HashSet<String>>,
+        check_clashes: &mut Option<HashSet<Atom>>,
     ) -> Result<(), ParserError> {
-        match node.node_type {
-            NodeType::Property => {
-                // AssignmentProperty has type === "Property"
-                if let Some(value) = node.value.borrow() {
-                    self.check_lval_inner_pattern(value, binding_type, check_clashes)
-                } else {
-                    Ok(())
-                }
-            }
-            NodeType::AssignmentPattern => {
-                if let Some(left) = node.left.borrow() {
-                    self.check_lval_pattern(left, binding_type, check_clashes)
-                } else {
-                    Ok(())
-                }
+        match pattern {
+            Pattern::AssignmentPattern(assign) => {
+                self.check_lval_pattern(&assign.left, binding_type, check_clashes)
             }
-            NodeType::RestElement => {
-                if let Some(argument) = node.argument.borrow() {
-                    self.check_lval_pattern(argument, binding_type, check_clashes)
-                } else {
-                    Ok(())
-                }
+            Pattern::RestElement(rest) => {
+                self.check_lval_pattern(&rest.argument, binding_type, check_clashes)
             }
-            _ => self.check_lval_pattern(node, binding_type, check_clashes),
+            _ => self.check_lval_pattern(pattern, binding_type, check_clashes),
         }
     }
 }
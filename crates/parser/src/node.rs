@@ -1,143 +1,1253 @@
-use serde::{Deserialize, Serialize};
+// ### The AST
+//
+// One struct per [ESTree] node type, grouped into the `Statement`,
+// `Expression`, `Pattern` and `ModuleDeclaration` enums. Every node starts
+// with a `Span` header that holds what acorn's `Node` constructor records:
+// the offsets and, depending on the options, `loc`, `range` and the
+// source file.
+//
+// [ESTree]: https://github.com/estree/estree
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
 
 use crate::{
+    atom::Atom,
+    comment::Comment,
+    estree::{node_type, EstreeVariant, SpanFields},
     location::{Position, SourceLocation},
+    options::SourceType,
     parser::Parser,
 };
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-pub enum NodeType {
-    ArrayExpression,
-    ArrayPattern,
-    AssignmentExpression,
-    AssignmentPattern,
-    ChainExpression,
-    Identifier,
-    MemberExpression,
-    Null,
-    ObjectExpression,
-    ObjectPattern,
-    ParenthesizedExpression,
-    Property,
-    RestElement,
-    SpreadElement,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Node {
-    pub name: String,
-    pub node_type: NodeType,
-    pub operator: String,
-    pub kind: String,
+/// The location information shared by every node.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "SpanFields")]
+pub struct Span {
     pub start: i32,
     pub end: i32,
     pub loc: Option<SourceLocation>,
-    pub source_file: Option<String>,
     pub range: Option<(i32, i32)>,
-    pub left: Box<Option<Node>>,
-    pub right: Box<Option<Node>>,
-    pub key: Box<Option<Node>>,
-    pub value: Box<Option<Node>>,
-    pub argument: Box<Option<Node>>,
-    pub expression: Box<Option<Node>>,
-    pub elements: Box<Vec<Node>>,
-    pub properties: Box<Vec<Node>>,
-}
-
-impl Node {
-    pub fn new(parser: &Parser, pos: i32, loc: &Option<Position>) -> Self {
-        Node {
-            name: "".to_string(),
-            node_type: NodeType::Null,
-            operator: "".to_string(),
-            kind: "".to_string(),
+    pub source_file: Option<Atom>,
+    /// The comments attached to the node by `attach_comments`.
+    pub comments: Option<Box<NodeComments>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeComments {
+    pub leading: Vec<Comment>,
+    pub trailing: Vec<Comment>,
+}
+
+impl Span {
+    pub(crate) fn new(parser: &Parser, pos: i32, loc: &Option<Position>) -> Self {
+        Span {
             start: pos,
             end: 0,
             loc: if parser.options.locations {
-                if let Some(loc) = loc {
-                    Some(SourceLocation::new(loc, &None, &parser.source_file))
-                } else {
-                    None
-                }
+                let loc = loc
+                    .clone()
+                    .unwrap_or_else(|| parser.line_index.position(pos));
+                Some(SourceLocation::new(&loc, &None, &parser.source_file))
             } else {
                 None
             },
-            source_file: parser.options.direct_source_file.clone(),
             range: if parser.options.ranges {
                 Some((pos, 0))
             } else {
                 None
             },
-            left: Box::new(None),
-            right: Box::new(None),
-            key: Box::new(None),
-            value: Box::new(None),
-            argument: Box::new(None),
-            expression: Box::new(None),
-            elements: Box::new(vec![]),
-            properties: Box::new(vec![]),
+            source_file: parser.direct_source_file.clone(),
+            comments: None,
+        }
+    }
+}
+
+/// Implemented by every node to give access to its `Span` header.
+pub trait Spanned {
+    fn span(&self) -> &Span;
+}
+
+macro_rules! impl_spanned {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl Spanned for $name {
+                fn span(&self) -> &Span {
+                    &self.span
+                }
+            }
+        )*
+    };
+}
+
+/// Declares an enum whose variants each wrap a node, and implements
+/// `Spanned` for it by forwarding to the wrapped node. The ESTree `type` of
+/// a node is the name of the variant that holds it, so the enum also takes
+/// care of (de)serializing its nodes; see `estree`.
+macro_rules! node_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident($node:ty),)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant($node),)*
+        }
+
+        impl Spanned for $name {
+            fn span(&self) -> &Span {
+                match self {
+                    $($name::$variant(node) => node.span(),)*
+                }
+            }
+        }
+
+        impl EstreeVariant for $name {
+            fn accepts(_variant: &str, node_type: &str) -> bool {
+                $(<$node as EstreeVariant>::accepts(stringify!($variant), node_type))||*
+            }
+
+            fn serialize_variant<S: Serializer>(
+                &self,
+                _variant: &'static str,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                self.serialize(serializer)
+            }
+
+            fn from_value(value: Value) -> serde_json::Result<Self> {
+                serde_json::from_value(value)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    $($name::$variant(node) => {
+                        node.serialize_variant(stringify!($variant), serializer)
+                    })*
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = Value::deserialize(deserializer)?;
+                let node_type = node_type(&value).map_err(de::Error::custom)?;
+                $(
+                    if <$node as EstreeVariant>::accepts(stringify!($variant), &node_type) {
+                        return <$node as EstreeVariant>::from_value(value)
+                            .map($name::$variant)
+                            .map_err(de::Error::custom);
+                    }
+                )*
+                Err(de::Error::custom(format!(
+                    "unexpected node type `{}` for {}",
+                    node_type,
+                    stringify!($name)
+                )))
+            }
+        }
+    };
+}
+
+/// Declares an operator enum along with the conversions from and to the
+/// source text of the operator.
+macro_rules! operator_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident => $operator:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+        pub enum $name {
+            $(#[serde(rename = $operator)] $variant,)*
+        }
+
+        impl $name {
+            pub fn from_token(operator: &str) -> Option<Self> {
+                match operator {
+                    $($operator => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $operator,)*
+                }
+            }
+        }
+    };
+}
+
+// ## Programs and statements
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Program {
+    #[serde(flatten)]
+    pub span: Span,
+    pub body: Vec<Statement>,
+    #[serde(rename = "sourceType")]
+    pub source_type: SourceType,
+}
+
+node_enum! {
+    /// Any statement. Import and export declarations are statements as
+    /// well, since `allow_import_export_everywhere` lets them appear in
+    /// nested blocks.
+    pub enum Statement {
+        ExpressionStatement(Box<ExpressionStatement>),
+        BlockStatement(Box<BlockStatement>),
+        EmptyStatement(Box<EmptyStatement>),
+        DebuggerStatement(Box<DebuggerStatement>),
+        WithStatement(Box<WithStatement>),
+        ReturnStatement(Box<ReturnStatement>),
+        LabeledStatement(Box<LabeledStatement>),
+        BreakStatement(Box<BreakStatement>),
+        ContinueStatement(Box<ContinueStatement>),
+        IfStatement(Box<IfStatement>),
+        SwitchStatement(Box<SwitchStatement>),
+        ThrowStatement(Box<ThrowStatement>),
+        TryStatement(Box<TryStatement>),
+        WhileStatement(Box<WhileStatement>),
+        DoWhileStatement(Box<DoWhileStatement>),
+        ForStatement(Box<ForStatement>),
+        ForInStatement(Box<ForInStatement>),
+        ForOfStatement(Box<ForOfStatement>),
+        FunctionDeclaration(Box<Function>),
+        VariableDeclaration(Box<VariableDeclaration>),
+        ClassDeclaration(Box<Class>),
+        ModuleDeclaration(ModuleDeclaration),
+    }
+}
+
+/// An expression statement. Statements of a directive prologue, such as
+/// `"use strict";`, carry the raw string of the directive.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExpressionStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub expression: Expression,
+    pub directive: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BlockStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EmptyStatement {
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DebuggerStatement {
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WithStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub object: Expression,
+    pub body: Statement,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ReturnStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub argument: Option<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LabeledStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub body: Statement,
+    pub label: Identifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BreakStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub label: Option<Identifier>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ContinueStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub label: Option<Identifier>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IfStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub test: Expression,
+    pub consequent: Statement,
+    pub alternate: Option<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SwitchStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub discriminant: Expression,
+    pub cases: Vec<SwitchCase>,
+}
+
+/// A `case` clause, or the `default` clause when `test` is `None`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SwitchCase {
+    #[serde(flatten)]
+    pub span: Span,
+    pub consequent: Vec<Statement>,
+    pub test: Option<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ThrowStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub argument: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TryStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub block: BlockStatement,
+    pub handler: Option<CatchClause>,
+    pub finalizer: Option<BlockStatement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CatchClause {
+    #[serde(flatten)]
+    pub span: Span,
+    pub param: Option<Pattern>,
+    pub body: BlockStatement,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WhileStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub test: Expression,
+    pub body: Statement,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DoWhileStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub body: Statement,
+    pub test: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ForStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub init: Option<ForInit>,
+    pub test: Option<Expression>,
+    pub update: Option<Expression>,
+    pub body: Statement,
+}
+
+node_enum! {
+    /// The `init` of a `for` loop.
+    pub enum ForInit {
+        VariableDeclaration(Box<VariableDeclaration>),
+        Expression(Expression),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ForInStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub left: ForLeft,
+    pub right: Expression,
+    pub body: Statement,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ForOfStatement {
+    #[serde(flatten)]
+    pub span: Span,
+    #[serde(rename = "await", default)]
+    pub is_await: bool,
+    pub left: ForLeft,
+    pub right: Expression,
+    pub body: Statement,
+}
+
+node_enum! {
+    /// The `left` of a `for`/`in` or `for`/`of` loop.
+    pub enum ForLeft {
+        VariableDeclaration(Box<VariableDeclaration>),
+        Pattern(Pattern),
+    }
+}
+
+// ## Declarations
+
+node_enum! {
+    /// The declarations that may follow `export`.
+    pub enum Declaration {
+        FunctionDeclaration(Box<Function>),
+        VariableDeclaration(Box<VariableDeclaration>),
+        ClassDeclaration(Box<Class>),
+    }
+}
+
+impl From<Declaration> for Statement {
+    fn from(declaration: Declaration) -> Self {
+        match declaration {
+            Declaration::FunctionDeclaration(function) => Statement::FunctionDeclaration(function),
+            Declaration::VariableDeclaration(declaration) => {
+                Statement::VariableDeclaration(declaration)
+            }
+            Declaration::ClassDeclaration(class) => Statement::ClassDeclaration(class),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct VariableDeclaration {
+    #[serde(flatten)]
+    pub span: Span,
+    pub declarations: Vec<VariableDeclarator>,
+    pub kind: VariableDeclarationKind,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableDeclarationKind {
+    Var,
+    Let,
+    Const,
+}
+
+impl VariableDeclarationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VariableDeclarationKind::Var => "var",
+            VariableDeclarationKind::Let => "let",
+            VariableDeclarationKind::Const => "const",
         }
     }
 }
 
-pub trait NodeParser {
-    fn start_node(&self) -> Node;
-    fn start_node_at(&self, pos: i32, loc: &Option<Position>) -> Node;
-    fn finish_node(&self, node: &mut Node, node_type: NodeType);
-    fn finish_node_at(
-        &self,
-        node: &mut Node,
-        node_type: NodeType,
-        pos: i32,
-        loc: &Option<Position>,
-    );
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct VariableDeclarator {
+    #[serde(flatten)]
+    pub span: Span,
+    pub id: Pattern,
+    pub init: Option<Expression>,
+}
+
+// ## Functions and classes
+
+/// A function declaration, function expression or method. The `id` is
+/// only absent for function expressions, methods and
+/// `export default function () {}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Function {
+    #[serde(flatten)]
+    pub span: Span,
+    pub id: Option<Identifier>,
+    #[serde(default)]
+    pub generator: bool,
+    #[serde(rename = "async", default)]
+    pub is_async: bool,
+    pub params: Vec<Pattern>,
+    pub body: BlockStatement,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ArrowFunctionExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    #[serde(rename = "async", default)]
+    pub is_async: bool,
+    pub params: Vec<Pattern>,
+    pub body: ArrowFunctionBody,
+}
+
+node_enum! {
+    /// The body of an arrow function, which is an expression unless it
+    /// starts with a brace.
+    pub enum ArrowFunctionBody {
+        BlockStatement(Box<BlockStatement>),
+        Expression(Expression),
+    }
+}
+
+/// A class declaration or class expression. The `id` is only absent for
+/// class expressions and `export default class {}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Class {
+    #[serde(flatten)]
+    pub span: Span,
+    pub id: Option<Identifier>,
+    #[serde(rename = "superClass")]
+    pub super_class: Option<Expression>,
+    pub body: ClassBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ClassBody {
+    #[serde(flatten)]
+    pub span: Span,
+    pub body: Vec<ClassElement>,
+}
+
+node_enum! {
+    pub enum ClassElement {
+        MethodDefinition(Box<MethodDefinition>),
+        PropertyDefinition(Box<PropertyDefinition>),
+        StaticBlock(Box<StaticBlock>),
+    }
+}
+
+/// A class method. The `key` is an `Expression::PrivateIdentifier` for
+/// private methods.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MethodDefinition {
+    #[serde(flatten)]
+    pub span: Span,
+    #[serde(rename = "static")]
+    pub is_static: bool,
+    pub computed: bool,
+    pub key: Expression,
+    pub kind: MethodDefinitionKind,
+    pub value: Function,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MethodDefinitionKind {
+    Constructor,
+    Method,
+    Get,
+    Set,
+}
+
+/// A class field. The `key` is an `Expression::PrivateIdentifier` for
+/// private fields.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PropertyDefinition {
+    #[serde(flatten)]
+    pub span: Span,
+    #[serde(rename = "static")]
+    pub is_static: bool,
+    pub computed: bool,
+    pub key: Expression,
+    pub value: Option<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StaticBlock {
+    #[serde(flatten)]
+    pub span: Span,
+    pub body: Vec<Statement>,
+}
+
+// ## Expressions
+
+node_enum! {
+    pub enum Expression {
+        Identifier(Box<Identifier>),
+        Literal(Box<Literal>),
+        ThisExpression(Box<ThisExpression>),
+        Super(Box<Super>),
+        ArrayExpression(Box<ArrayExpression>),
+        ObjectExpression(Box<ObjectExpression>),
+        FunctionExpression(Box<Function>),
+        ArrowFunctionExpression(Box<ArrowFunctionExpression>),
+        ClassExpression(Box<Class>),
+        TemplateLiteral(Box<TemplateLiteral>),
+        TaggedTemplateExpression(Box<TaggedTemplateExpression>),
+        MemberExpression(Box<MemberExpression>),
+        CallExpression(Box<CallExpression>),
+        NewExpression(Box<NewExpression>),
+        ChainExpression(Box<ChainExpression>),
+        SequenceExpression(Box<SequenceExpression>),
+        UnaryExpression(Box<UnaryExpression>),
+        UpdateExpression(Box<UpdateExpression>),
+        BinaryExpression(Box<BinaryExpression>),
+        LogicalExpression(Box<LogicalExpression>),
+        AssignmentExpression(Box<AssignmentExpression>),
+        ConditionalExpression(Box<ConditionalExpression>),
+        YieldExpression(Box<YieldExpression>),
+        AwaitExpression(Box<AwaitExpression>),
+        MetaProperty(Box<MetaProperty>),
+        ImportExpression(Box<ImportExpression>),
+        ParenthesizedExpression(Box<ParenthesizedExpression>),
+        /// Only valid as the property of a member expression, the left
+        /// side of `in` or the key of a class element.
+        PrivateIdentifier(Box<PrivateIdentifier>),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Identifier {
+    #[serde(flatten)]
+    pub span: Span,
+    pub name: Atom,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PrivateIdentifier {
+    #[serde(flatten)]
+    pub span: Span,
+    pub name: Atom,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Literal {
+    pub span: Span,
+    pub value: LiteralValue,
+    pub raw: Atom,
+}
+
+/// The value of a `Literal` node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiteralValue {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(Atom),
+    BigInt(Atom),
+    Regex { pattern: Atom, flags: Atom },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ThisExpression {
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Super {
+    #[serde(flatten)]
+    pub span: Span,
+}
+
+/// An array literal. Holes are `None`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ArrayExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub elements: Vec<Option<ExpressionOrSpread>>,
+}
+
+node_enum! {
+    /// An element of an array literal or an argument of a call.
+    pub enum ExpressionOrSpread {
+        Expression(Expression),
+        SpreadElement(Box<SpreadElement>),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpreadElement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub argument: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ObjectExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub properties: Vec<PropertyOrSpread>,
+}
+
+node_enum! {
+    pub enum PropertyOrSpread {
+        Property(Box<Property>),
+        SpreadElement(Box<SpreadElement>),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Property {
+    #[serde(flatten)]
+    pub span: Span,
+    #[serde(default)]
+    pub method: bool,
+    #[serde(default)]
+    pub shorthand: bool,
+    #[serde(default)]
+    pub computed: bool,
+    pub key: Expression,
+    pub kind: PropertyKind,
+    pub value: Expression,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PropertyKind {
+    Init,
+    Get,
+    Set,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TemplateLiteral {
+    #[serde(flatten)]
+    pub span: Span,
+    pub expressions: Vec<Expression>,
+    pub quasis: Vec<TemplateElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TemplateElement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub value: TemplateElementValue,
+    pub tail: bool,
+}
+
+/// The text of a template element. `cooked` is `None` when a tagged
+/// template contains an invalid escape sequence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateElementValue {
+    pub raw: Atom,
+    pub cooked: Option<Atom>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TaggedTemplateExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub tag: Expression,
+    pub quasi: TemplateLiteral,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MemberExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub object: Expression,
+    pub property: Expression,
+    pub computed: bool,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CallExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub callee: Expression,
+    pub arguments: Vec<ExpressionOrSpread>,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NewExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub callee: Expression,
+    pub arguments: Vec<ExpressionOrSpread>,
+}
+
+/// Wraps an optional chain such as `a?.b.c`, whose `expression` is a
+/// member or call expression.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ChainExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub expression: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SequenceExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub expressions: Vec<Expression>,
+}
+
+operator_enum! {
+    pub enum UnaryOperator {
+        Minus => "-",
+        Plus => "+",
+        LogicalNot => "!",
+        BitwiseNot => "~",
+        Typeof => "typeof",
+        Void => "void",
+        Delete => "delete",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UnaryExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub operator: UnaryOperator,
+    pub argument: Expression,
+}
+
+operator_enum! {
+    pub enum UpdateOperator {
+        Increment => "++",
+        Decrement => "--",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UpdateExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub operator: UpdateOperator,
+    pub prefix: bool,
+    pub argument: Expression,
+}
+
+operator_enum! {
+    pub enum BinaryOperator {
+        Equality => "==",
+        Inequality => "!=",
+        StrictEquality => "===",
+        StrictInequality => "!==",
+        LessThan => "<",
+        LessThanOrEqual => "<=",
+        GreaterThan => ">",
+        GreaterThanOrEqual => ">=",
+        ShiftLeft => "<<",
+        ShiftRight => ">>",
+        ShiftRightZeroFill => ">>>",
+        Addition => "+",
+        Subtraction => "-",
+        Multiplication => "*",
+        Division => "/",
+        Remainder => "%",
+        Exponentiation => "**",
+        BitwiseOr => "|",
+        BitwiseXor => "^",
+        BitwiseAnd => "&",
+        In => "in",
+        Instanceof => "instanceof",
+    }
+}
+
+/// A binary operation. The `left` is an `Expression::PrivateIdentifier`
+/// for `#x in obj`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BinaryExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub left: Expression,
+    pub operator: BinaryOperator,
+    pub right: Expression,
+}
+
+operator_enum! {
+    pub enum LogicalOperator {
+        Or => "||",
+        And => "&&",
+        Coalesce => "??",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LogicalExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub left: Expression,
+    pub operator: LogicalOperator,
+    pub right: Expression,
+}
+
+operator_enum! {
+    pub enum AssignmentOperator {
+        Assign => "=",
+        Addition => "+=",
+        Subtraction => "-=",
+        Multiplication => "*=",
+        Division => "/=",
+        Remainder => "%=",
+        Exponentiation => "**=",
+        ShiftLeft => "<<=",
+        ShiftRight => ">>=",
+        ShiftRightZeroFill => ">>>=",
+        BitwiseOr => "|=",
+        BitwiseXor => "^=",
+        BitwiseAnd => "&=",
+        LogicalOr => "||=",
+        LogicalAnd => "&&=",
+        Coalesce => "??=",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AssignmentExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub operator: AssignmentOperator,
+    pub left: Pattern,
+    pub right: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConditionalExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub test: Expression,
+    pub consequent: Expression,
+    pub alternate: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct YieldExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub delegate: bool,
+    pub argument: Option<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AwaitExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub argument: Expression,
+}
+
+/// `new.target` or `import.meta`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MetaProperty {
+    #[serde(flatten)]
+    pub span: Span,
+    pub meta: Identifier,
+    pub property: Identifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub source: Expression,
+}
+
+/// Only produced when `preserve_parens` is set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ParenthesizedExpression {
+    #[serde(flatten)]
+    pub span: Span,
+    pub expression: Expression,
+}
+
+// ## Patterns
+
+node_enum! {
+    /// A binding or assignment target.
+    pub enum Pattern {
+        Identifier(Box<Identifier>),
+        ObjectPattern(Box<ObjectPattern>),
+        ArrayPattern(Box<ArrayPattern>),
+        RestElement(Box<RestElement>),
+        AssignmentPattern(Box<AssignmentPattern>),
+        /// Only valid as an assignment target.
+        MemberExpression(Box<MemberExpression>),
+        /// A parenthesized assignment target, kept when `preserve_parens`
+        /// is set.
+        ParenthesizedExpression(Box<ParenthesizedExpression>),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ObjectPattern {
+    #[serde(flatten)]
+    pub span: Span,
+    pub properties: Vec<PropertyOrRest>,
+}
+
+node_enum! {
+    pub enum PropertyOrRest {
+        Property(Box<AssignmentProperty>),
+        RestElement(Box<RestElement>),
+    }
+}
+
+/// A property of an object pattern, which is a `Property` node in ESTree
+/// that is always of the `init` kind and never a method.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AssignmentProperty {
+    #[serde(flatten)]
+    pub span: Span,
+    #[serde(default)]
+    pub shorthand: bool,
+    #[serde(default)]
+    pub computed: bool,
+    pub key: Expression,
+    pub value: Pattern,
+}
+
+/// An array pattern. Holes are `None`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ArrayPattern {
+    #[serde(flatten)]
+    pub span: Span,
+    pub elements: Vec<Option<Pattern>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RestElement {
+    #[serde(flatten)]
+    pub span: Span,
+    pub argument: Pattern,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AssignmentPattern {
+    #[serde(flatten)]
+    pub span: Span,
+    pub left: Pattern,
+    pub right: Expression,
+}
+
+// ## Modules
+
+node_enum! {
+    pub enum ModuleDeclaration {
+        ImportDeclaration(Box<ImportDeclaration>),
+        ExportNamedDeclaration(Box<ExportNamedDeclaration>),
+        ExportDefaultDeclaration(Box<ExportDefaultDeclaration>),
+        ExportAllDeclaration(Box<ExportAllDeclaration>),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportDeclaration {
+    #[serde(flatten)]
+    pub span: Span,
+    pub specifiers: Vec<ImportDeclarationSpecifier>,
+    pub source: Literal,
+}
+
+node_enum! {
+    pub enum ImportDeclarationSpecifier {
+        ImportSpecifier(Box<ImportSpecifier>),
+        ImportDefaultSpecifier(Box<ImportDefaultSpecifier>),
+        ImportNamespaceSpecifier(Box<ImportNamespaceSpecifier>),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportSpecifier {
+    #[serde(flatten)]
+    pub span: Span,
+    pub imported: ModuleExportName,
+    pub local: Identifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportDefaultSpecifier {
+    #[serde(flatten)]
+    pub span: Span,
+    pub local: Identifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportNamespaceSpecifier {
+    #[serde(flatten)]
+    pub span: Span,
+    pub local: Identifier,
+}
+
+node_enum! {
+    /// The name of an export or import specifier, which may be a string
+    /// literal since ES2022.
+    pub enum ModuleExportName {
+        Identifier(Box<Identifier>),
+        Literal(Box<Literal>),
+    }
+}
+
+impl ModuleExportName {
+    /// The name this specifier exports or imports.
+    pub fn name(&self) -> &str {
+        match self {
+            ModuleExportName::Identifier(id) => &id.name,
+            ModuleExportName::Literal(literal) => match &literal.value {
+                LiteralValue::String(value) => value,
+                _ => &literal.raw,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExportNamedDeclaration {
+    #[serde(flatten)]
+    pub span: Span,
+    pub declaration: Option<Declaration>,
+    pub specifiers: Vec<ExportSpecifier>,
+    pub source: Option<Literal>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExportSpecifier {
+    #[serde(flatten)]
+    pub span: Span,
+    pub local: ModuleExportName,
+    pub exported: ModuleExportName,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExportDefaultDeclaration {
+    #[serde(flatten)]
+    pub span: Span,
+    pub declaration: ExportDefaultDeclarationKind,
+}
+
+node_enum! {
+    pub enum ExportDefaultDeclarationKind {
+        FunctionDeclaration(Box<Function>),
+        ClassDeclaration(Box<Class>),
+        Expression(Expression),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExportAllDeclaration {
+    #[serde(flatten)]
+    pub span: Span,
+    pub exported: Option<ModuleExportName>,
+    pub source: Literal,
+}
+
+impl_spanned!(
+    Program,
+    ExpressionStatement,
+    BlockStatement,
+    EmptyStatement,
+    DebuggerStatement,
+    WithStatement,
+    ReturnStatement,
+    LabeledStatement,
+    BreakStatement,
+    ContinueStatement,
+    IfStatement,
+    SwitchStatement,
+    SwitchCase,
+    ThrowStatement,
+    TryStatement,
+    CatchClause,
+    WhileStatement,
+    DoWhileStatement,
+    ForStatement,
+    ForInStatement,
+    ForOfStatement,
+    VariableDeclaration,
+    VariableDeclarator,
+    Function,
+    ArrowFunctionExpression,
+    Class,
+    ClassBody,
+    MethodDefinition,
+    PropertyDefinition,
+    StaticBlock,
+    Identifier,
+    PrivateIdentifier,
+    Literal,
+    ThisExpression,
+    Super,
+    ArrayExpression,
+    SpreadElement,
+    ObjectExpression,
+    Property,
+    TemplateLiteral,
+    TemplateElement,
+    TaggedTemplateExpression,
+    MemberExpression,
+    CallExpression,
+    NewExpression,
+    ChainExpression,
+    SequenceExpression,
+    UnaryExpression,
+    UpdateExpression,
+    BinaryExpression,
+    LogicalExpression,
+    AssignmentExpression,
+    ConditionalExpression,
+    YieldExpression,
+    AwaitExpression,
+    MetaProperty,
+    ImportExpression,
+    ParenthesizedExpression,
+    ObjectPattern,
+    AssignmentProperty,
+    ArrayPattern,
+    RestElement,
+    AssignmentPattern,
+    ImportDeclaration,
+    ImportSpecifier,
+    ImportDefaultSpecifier,
+    ImportNamespaceSpecifier,
+    ExportNamedDeclaration,
+    ExportSpecifier,
+    ExportDefaultDeclaration,
+    ExportAllDeclaration,
+);
+
+pub(crate) trait NodeParser {
+    fn start_node(&self) -> Span;
+    fn start_node_at(&self, pos: i32, loc: &Option<Position>) -> Span;
+    fn finish_node(&self, span: Span) -> Span;
+    fn finish_node_at(&self, span: Span, pos: i32, loc: &Option<Position>) -> Span;
 }
 
 impl NodeParser for Parser {
-    fn start_node(&self) -> Node {
-        self.start_node_at(
-            self.cur_token_start.get(),
-            &self.cur_token_start_loc.borrow().clone(),
-        )
-    }
-
-    fn start_node_at(&self, pos: i32, loc: &Option<Position>) -> Node {
-        Node::new(self, pos, loc)
-    }
-
-    fn finish_node(&self, node: &mut Node, node_type: NodeType) {
-        self.finish_node_at(
-            node,
-            node_type,
-            self.last_token_end.get(),
-            &self.last_token_end_loc.borrow().clone(),
-        );
-    }
-
-    fn finish_node_at(
-        &self,
-        node: &mut Node,
-        node_type: NodeType,
-        pos: i32,
-        loc: &Option<Position>,
-    ) {
-        node.node_type = node_type.clone();
-        node.end = pos;
+    fn start_node(&self) -> Span {
+        self.start_node_at(self.cur_token_start, &self.cur_token_start_loc)
+    }
+
+    fn start_node_at(&self, pos: i32, loc: &Option<Position>) -> Span {
+        Span::new(self, pos, loc)
+    }
+
+    fn finish_node(&self, span: Span) -> Span {
+        self.finish_node_at(span, self.last_token_end, &self.last_token_end_loc)
+    }
+
+    /// Finish a node whose end is not the end of the last token.
+    fn finish_node_at(&self, mut span: Span, pos: i32, loc: &Option<Position>) -> Span {
+        span.end = pos;
         if self.options.locations {
-            if let Some(loc) = loc {
-                if let Some(ref current_loc) = node.loc {
-                    node.loc = Some(SourceLocation::new(
-                        &current_loc.start,
-                        &Some(loc.clone()),
-                        &self.source_file,
-                    ));
-                }
+            if let Some(ref current_loc) = span.loc {
+                let loc = loc.clone().unwrap_or_else(|| self.line_index.position(pos));
+                span.loc = Some(SourceLocation::new(
+                    &current_loc.start,
+                    &Some(loc),
+                    &self.source_file,
+                ));
             }
         }
         if self.options.ranges {
-            if let Some((start_range, _)) = node.range {
-                node.range = Some((start_range, pos));
+            if let Some((start_range, _)) = span.range {
+                span.range = Some((start_range, pos));
             }
         }
+        span
     }
 }
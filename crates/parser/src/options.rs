@@ -1,20 +1,52 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
 use serde::{Deserialize, Serialize};
 
-use crate::node::Node;
+use crate::{comment::OnComment, location::OnPosition, node::Program, token::OnToken};
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-pub enum SourceType {
-    Script,
-    Module,
+/// Where the items a parse reports, like comments or tokens, go.
+#[derive(Clone)]
+pub enum Sink<T> {
+    /// Called with each item, in source order.
+    Callback(Arc<dyn Fn(&T) + Send + Sync>),
+    /// Collects the items, in source order.
+    Collect(Arc<Mutex<Vec<T>>>),
+}
+
+impl<T> Sink<T> {
+    pub fn callback(callback: impl Fn(&T) + Send + Sync + 'static) -> Self {
+        Sink::Callback(Arc::new(callback))
+    }
+
+    pub(crate) fn push(&self, item: T) {
+        match self {
+            Sink::Callback(callback) => callback(&item),
+            Sink::Collect(items) => items.lock().unwrap().push(item),
+        }
+    }
 }
 
-impl Default for SourceType {
-    fn default() -> Self {
-        SourceType::Script
+impl<T: fmt::Debug> fmt::Debug for Sink<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sink::Callback(_) => f.write_str("Callback(..)"),
+            Sink::Collect(items) => f.debug_tuple("Collect").field(items).finish(),
+        }
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceType {
+    #[default]
+    Script,
+    Module,
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub enum EcmaVersion {
     Ecma3 = 3,
     Ecma5 = 5,
@@ -26,10 +58,24 @@ pub enum EcmaVersion {
     Ecma2020 = 11,
     Ecma2021 = 12,
     Ecma2022 = 13,
+    #[default]
     Latest = 100000000,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What offsets and columns count.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum OffsetUnit {
+    /// Bytes of the UTF-8 input, which index Rust strings.
+    #[default]
+    Utf8,
+    /// UTF-16 code units, which index JavaScript strings. Other ESTree
+    /// parsers count in them.
+    Utf16,
+    /// Unicode code points, i.e. `char`s.
+    CodePoint,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Options {
     pub ecma_version: EcmaVersion,
     #[serde(default)]
@@ -47,11 +93,35 @@ pub struct Options {
     pub locations: bool,
     #[serde(default)]
     pub ranges: bool,
-    pub program: Option<Node>,
+    pub program: Option<Program>,
     pub source_file: Option<String>,
     pub direct_source_file: Option<String>,
     #[serde(default)]
     pub preserve_parens: bool,
+    /// Records the errors the parser can go on after, like redeclared
+    /// bindings or invalid regular expressions, instead of stopping at
+    /// the first one. They are returned by `parse_with_errors`.
+    #[serde(default)]
+    pub collect_errors: bool,
+    /// The unit of the offsets and columns of nodes, tokens, comments and
+    /// errors, and of the offset given to `parse_expression_at`.
+    #[serde(default)]
+    pub offset_unit: OffsetUnit,
+    /// Receives every comment the tokenizer skips.
+    #[serde(skip)]
+    pub on_comment: Option<OnComment>,
+    /// Receives every token consumed by the parser, the final `eof` token
+    /// included.
+    #[serde(skip)]
+    pub on_token: Option<OnToken>,
+    /// Receives the end of the previous token whenever a semicolon is
+    /// automatically inserted.
+    #[serde(skip)]
+    pub on_insert_semicolon: Option<OnPosition>,
+    /// Receives the start of the comma whenever a trailing comma is
+    /// accepted.
+    #[serde(skip)]
+    pub on_trailing_comma: Option<OnPosition>,
 }
 
 impl Options {
@@ -1,193 +1,194 @@
-use std::{
-    cell::{Cell, RefCell},
-    collections::HashMap,
-    rc::Rc,
-};
-
-use fancy_regex::Regex;
+use std::collections::HashMap;
 
 use crate::{
+    atom::{Atom, Interner},
     errors::ParserError,
-    location::{LocationParser, Position},
-    node::Node,
-    options::{EcmaVersion, Options, SourceType},
+    keywords::WordSet,
+    location::{LineIndex, LocationParser, OffsetConverter, Position},
+    node::{Identifier, NodeParser, Program},
+    options::{OffsetUnit, Options, SourceType},
     regexp::RegExpValidationState,
-    scope::{Scope, ScopeParser, SCOPE_ASYNC, SCOPE_TOP},
+    scope::{
+        Scope, ScopeParser, SCOPE_ASYNC, SCOPE_CLASS_STATIC_BLOCK, SCOPE_DIRECT_SUPER,
+        SCOPE_FUNCTION, SCOPE_GENERATOR, SCOPE_SUPER, SCOPE_TOP,
+    },
+    statement::{Label, PrivateNameScope, StatementParser},
     token::{
         context::{get_initial_context, TokenContext},
         types::{get_token_types, TokenType},
-        TokenValue,
+        TokenParser, TokenValue,
     },
-    utils::{get_regex_from_words, UtilsParser},
-    whitespace::get_line_break_regex,
+    utils::UtilsParser,
 };
 
-const BASE_KEYWORDS: &str = "break|case|catch|continue|debugger|default|do|else|finally|for|function|if|return|switch|throw|try|var|while|with|null|true|false|instanceof|typeof|void|delete|new|in|this";
-
-fn get_keywords(ecma_version: i32, source_type: &SourceType) -> String {
-    if ecma_version >= 6 {
-        format!("{:}|const|class|extends|export|import|super", BASE_KEYWORDS)
-    } else if *source_type == SourceType::Module {
-        format!("{:}|export|import", BASE_KEYWORDS)
-    } else {
-        BASE_KEYWORDS.to_owned()
-    }
-}
-
-fn get_reserved_words(ecma_version: i32, source_type: &SourceType) -> String {
-    let reserved_words = if ecma_version >= 6 {
-        "enum"
-    } else if ecma_version == 5 {
-        "class|enum|extends|super|const|export|import"
-    } else {
-        "abstract|boolean|byte|char|class|double|enum|export|extends|final|float|goto|implements|import|int|interface|long|native|package|private|protected|public|short|static|super|synchronized|throws|transient|volatile"
-    };
-    match source_type {
-        SourceType::Module => format!("{:}|await", reserved_words),
-        _ => reserved_words.to_owned(),
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Parser {
     pub options: Options,
-    pub source_file: Option<String>,
-    pub keywords_regex: Regex,
-    pub reserved_words_regex: Regex,
-    pub reserved_words_strict_regex: Regex,
-    pub reserved_words_strict_bind_regex: Regex,
+    pub source_file: Option<Atom>,
+    pub direct_source_file: Option<Atom>,
+    pub keywords: WordSet,
+    pub reserved_words: WordSet,
+    pub reserved_words_strict: WordSet,
+    pub reserved_words_strict_bind: WordSet,
     pub input: String,
+    pub line_index: LineIndex,
+    pub offsets: OffsetConverter,
     pub contains_esc: bool,
-    pub cur_token_pos: Cell<i32>,
-    pub cur_token_line_start: Cell<i32>,
-    pub cur_token_line: Cell<i32>,
-    pub cur_token_start: Cell<i32>,
-    pub cur_token_end: Cell<i32>,
-    pub cur_token_start_loc: RefCell<Option<Position>>,
-    pub cur_token_end_loc: RefCell<Option<Position>>,
-    pub cur_token_type: RefCell<TokenType>,
-    pub cur_token_value: RefCell<TokenValue>,
-    pub last_token_start: Cell<i32>,
-    pub last_token_end: Cell<i32>,
-    pub last_token_start_loc: RefCell<Option<Position>>,
-    pub last_token_end_loc: RefCell<Option<Position>>,
-    pub context: RefCell<Vec<TokenContext>>,
-    pub expr_allowed: Cell<bool>,
+    pub in_template_element: bool,
+    pub cur_token_pos: i32,
+    pub cur_token_line_start: i32,
+    pub cur_token_line: i32,
+    pub cur_token_start: i32,
+    pub cur_token_end: i32,
+    pub cur_token_start_loc: Option<Position>,
+    pub cur_token_end_loc: Option<Position>,
+    pub cur_token_type: TokenType,
+    pub cur_token_value: TokenValue,
+    pub last_token_start: i32,
+    pub last_token_end: i32,
+    pub last_token_start_loc: Option<Position>,
+    pub last_token_end_loc: Option<Position>,
+    pub context: Vec<TokenContext>,
+    pub expr_allowed: bool,
     pub is_in_module: bool,
-    pub is_strict: Cell<bool>,
-    pub potential_arrow_at: Cell<i32>,
-    pub is_potential_arrow_in_for_await: Cell<bool>,
-    pub yield_pos: Cell<Option<i32>>,
-    pub await_pos: Cell<Option<i32>>,
-    pub await_ident_pos: Cell<i32>,
-    pub labels: RefCell<Vec<String>>,
-    pub undefined_exports: RefCell<HashMap<String, Position>>,
-    pub scope_stack: RefCell<Vec<Scope>>,
-    pub regexp_state: RefCell<Option<RegExpValidationState>>,
-    pub private_name_stack: RefCell<Vec<Node>>,
+    pub is_strict: bool,
+    pub potential_arrow_at: i32,
+    pub is_potential_arrow_in_for_await: bool,
+    pub yield_pos: Option<i32>,
+    pub await_pos: Option<i32>,
+    pub await_ident_pos: i32,
+    pub labels: Vec<Label>,
+    pub undefined_exports: HashMap<Atom, Identifier>,
+    pub scope_stack: Vec<Scope>,
+    pub regexp_state: Option<RegExpValidationState>,
+    pub private_name_stack: Vec<PrivateNameScope>,
+    pub errors: Vec<ParserError>,
+    pub atoms: Interner,
 }
 
 impl Parser {
-    pub fn new(
-        options: &Options,
-        input: &str,
-        start_pos: &Option<i32>,
-    ) -> Result<Rc<Parser>, ParserError> {
+    pub fn new(options: &Options, input: &str, start_pos: &Option<i32>) -> Parser {
+        let ecma_version = options.get_ecma_version_number();
         let allow_reserved = match options.allow_reserved {
             Some(v) => v,
-            None => options.get_ecma_version_number() < 5,
-        };
-        let reserved_words = if allow_reserved {
-            get_reserved_words(options.get_ecma_version_number(), &options.source_type)
-        } else {
-            "".to_string()
+            None => ecma_version < 5,
         };
-        let reserved_strict_words = if reserved_words.is_empty() {
-            "implements|interface|let|package|private|protected|public|static|yield".to_string()
+        let reserved_words = if !allow_reserved {
+            WordSet::reserved_words(ecma_version, &options.source_type)
         } else {
-            format!(
-                "{:}|implements|interface|let|package|private|protected|public|static|yield",
-                reserved_words
-            )
+            WordSet::default()
         };
+        let line_index = LineIndex::new(input);
         let (cur_token_pos, cur_token_line_start, cur_token_line) = match start_pos {
             Some(start_pos) => {
-                let cur_token_pos = *start_pos;
-                let cur_token_line_start = (&input[0..cur_token_pos as usize])
-                    .rfind("\n")
-                    .map(|v| v + 1)
-                    .unwrap_or(0);
-                let cur_token_line = get_line_break_regex()
-                    .find_iter(&input[0..cur_token_line_start])
-                    .count()
-                    + 1;
-                (
-                    cur_token_pos,
-                    cur_token_line_start as i32,
-                    cur_token_line as i32,
-                )
+                let position = line_index.position(*start_pos);
+                (*start_pos, *start_pos - position.column, position.line)
             }
             None => (0, 0, 1),
         };
 
-        let parser = Rc::new(Parser {
+        let mut parser = Parser {
             options: Options {
                 allow_reserved: Some(allow_reserved),
                 ..options.clone()
             },
-            source_file: options.source_file.clone(),
-            keywords_regex: get_regex_from_words(&get_keywords(
-                options.get_ecma_version_number(),
-                &options.source_type,
-            )),
-            reserved_words_regex: get_regex_from_words(&reserved_words),
-            reserved_words_strict_regex: get_regex_from_words(&reserved_strict_words),
-            reserved_words_strict_bind_regex: get_regex_from_words(&format!(
-                "{:}|eval|arguments",
-                reserved_strict_words
-            )),
+            source_file: options.source_file.as_deref().map(Atom::from),
+            direct_source_file: options.direct_source_file.as_deref().map(Atom::from),
+            keywords: WordSet::keywords(ecma_version, &options.source_type),
+            reserved_words,
+            reserved_words_strict: reserved_words.strict(),
+            reserved_words_strict_bind: reserved_words.strict().strict_bind(),
             input: input.to_owned(),
+            line_index,
+            offsets: if options.offset_unit == OffsetUnit::Utf8 {
+                OffsetConverter::default()
+            } else {
+                OffsetConverter::new(input)
+            },
             contains_esc: false,
-            cur_token_pos: Cell::from(cur_token_pos),
-            cur_token_line_start: Cell::from(cur_token_line_start),
-            cur_token_line: Cell::from(cur_token_line),
-            cur_token_start: Cell::from(cur_token_pos),
-            cur_token_end: Cell::from(cur_token_pos),
-            cur_token_start_loc: RefCell::from(None),
-            cur_token_end_loc: RefCell::from(None),
-            cur_token_type: RefCell::from(get_token_types().eof.clone()),
-            cur_token_value: RefCell::from(TokenValue::Null),
-            last_token_start: Cell::from(cur_token_pos),
-            last_token_end: Cell::from(cur_token_pos),
-            last_token_start_loc: RefCell::from(None),
-            last_token_end_loc: RefCell::from(None),
-            context: RefCell::from(get_initial_context()),
-            expr_allowed: Cell::from(true),
+            in_template_element: false,
+            cur_token_pos,
+            cur_token_line_start,
+            cur_token_line,
+            cur_token_start: cur_token_pos,
+            cur_token_end: cur_token_pos,
+            cur_token_start_loc: None,
+            cur_token_end_loc: None,
+            cur_token_type: get_token_types().eof.clone(),
+            cur_token_value: TokenValue::Null,
+            last_token_start: cur_token_pos,
+            last_token_end: cur_token_pos,
+            last_token_start_loc: None,
+            last_token_end_loc: None,
+            context: get_initial_context(),
+            expr_allowed: true,
             is_in_module: options.source_type == SourceType::Module,
-            is_strict: Cell::from(false),
-            potential_arrow_at: Cell::from(-1),
-            is_potential_arrow_in_for_await: Cell::from(false),
-            yield_pos: Cell::from(None),
-            await_pos: Cell::from(None),
-            await_ident_pos: Cell::from(0),
-            labels: RefCell::from(vec![]),
-            undefined_exports: RefCell::from(HashMap::new()),
-            scope_stack: RefCell::from(vec![]),
-            regexp_state: RefCell::from(None),
-            private_name_stack: RefCell::from(vec![]),
-        });
+            is_strict: false,
+            potential_arrow_at: -1,
+            is_potential_arrow_in_for_await: false,
+            yield_pos: None,
+            await_pos: None,
+            await_ident_pos: 0,
+            labels: vec![],
+            undefined_exports: HashMap::new(),
+            scope_stack: vec![],
+            regexp_state: None,
+            private_name_stack: vec![],
+            errors: vec![],
+            atoms: Interner::default(),
+        };
         let cur_position = parser.get_cur_position();
-        *parser.cur_token_start_loc.borrow_mut() = cur_position.clone();
-        *parser.cur_token_end_loc.borrow_mut() = cur_position.clone();
-        *parser.regexp_state.borrow_mut() =
-            Some(RegExpValidationState::new(Rc::downgrade(&parser)));
+        parser.cur_token_start_loc = cur_position.clone();
+        parser.cur_token_end_loc = cur_position;
+        if parser.cur_token_pos == 0
+            && parser.options.allow_hash_bang
+            && parser.input.starts_with("#!")
+        {
+            parser.skip_line_comment(2);
+        }
         parser.enter_scope(SCOPE_TOP);
-        parser.is_strict.set(if parser.is_in_module {
+        parser.is_strict = if parser.is_in_module {
             true
         } else {
-            parser.strict_directive(parser.cur_token_pos.get())?
-        });
-        Ok(parser)
+            parser.strict_directive(parser.cur_token_pos)
+        };
+        parser
+    }
+
+    /// Parses the whole input as a program, appending the statements to
+    /// `options.program` when one is given.
+    pub fn parse(&mut self) -> Result<Program, ParserError> {
+        let program = match &self.options.program {
+            Some(program) => program.clone(),
+            None => Program {
+                span: self.start_node(),
+                body: vec![],
+                source_type: self.options.source_type.clone(),
+            },
+        };
+        self.next_token()?;
+        self.parse_top_level(program)
+    }
+
+    /// Appends `chunk` to the input, for tokenizing an input that is
+    /// read a chunk at a time. The input is kept whole, since offsets,
+    /// positions and checkpoints all index into it from its start.
+    pub fn push_input(&mut self, chunk: &str) {
+        let from = self.input.len();
+        self.input.push_str(chunk);
+        self.line_index.extend(&self.input, from);
+        if self.options.offset_unit != OffsetUnit::Utf8 {
+            self.offsets.extend(&self.input, from);
+        }
+    }
+
+    /// Takes the errors collected so far, with their offsets in the unit
+    /// of the options.
+    pub fn take_errors(&mut self) -> Vec<ParserError> {
+        std::mem::take(&mut self.errors)
+            .into_iter()
+            .map(|error| self.output_error(error))
+            .collect()
     }
 
     pub fn is_async(&self) -> bool {
@@ -196,4 +197,65 @@ impl Parser {
             None => false,
         }
     }
+
+    pub fn in_function(&self) -> bool {
+        match self.current_var_scope() {
+            Some(scope) => scope.flags & SCOPE_FUNCTION > 0,
+            None => false,
+        }
+    }
+
+    pub fn in_generator(&self) -> bool {
+        match self.current_var_scope() {
+            Some(scope) => (scope.flags & SCOPE_GENERATOR > 0) && !scope.in_class_field_init,
+            None => false,
+        }
+    }
+
+    pub fn in_class_static_block(&self) -> bool {
+        match self.current_var_scope() {
+            Some(scope) => scope.flags & SCOPE_CLASS_STATIC_BLOCK > 0,
+            None => false,
+        }
+    }
+
+    /// Whether `await` starts an await expression at the current position,
+    /// which also covers top-level await in modules.
+    pub fn can_await(&self) -> bool {
+        for scope in self.scope_stack.iter().rev() {
+            if scope.in_class_field_init || scope.flags & SCOPE_CLASS_STATIC_BLOCK > 0 {
+                return false;
+            }
+            if scope.flags & SCOPE_FUNCTION > 0 {
+                return scope.flags & SCOPE_ASYNC > 0;
+            }
+        }
+        (self.is_in_module && self.options.get_ecma_version_number() >= 13)
+            || self.options.allow_await_outside_function.unwrap_or(false)
+    }
+
+    pub fn allow_super(&self) -> bool {
+        let in_super_scope = match self.current_this_scope() {
+            Some(scope) => scope.flags & SCOPE_SUPER > 0 || scope.in_class_field_init,
+            None => false,
+        };
+        in_super_scope || self.options.allow_super_outside_method.unwrap_or(false)
+    }
+
+    pub fn allow_direct_super(&self) -> bool {
+        match self.current_this_scope() {
+            Some(scope) => scope.flags & SCOPE_DIRECT_SUPER > 0,
+            None => false,
+        }
+    }
+
+    pub fn allow_new_dot_target(&self) -> bool {
+        match self.current_this_scope() {
+            Some(scope) => {
+                scope.flags & (SCOPE_FUNCTION | SCOPE_CLASS_STATIC_BLOCK) > 0
+                    || scope.in_class_field_init
+            }
+            None => false,
+        }
+    }
 }
@@ -1,21 +1,21 @@
-use std::rc::Weak;
-
 use crate::{
     char_codes::*,
-    errors::ParserError,
+    errors::{ErrorCode, ParserError},
     identifier::is_identifier_start,
     location::LocationParser,
     parser::Parser,
     unicode_properties::{get_unicode_properties, UnicodeProperties},
-    utils::{get_codes_from_string, get_string_from_code, get_string_from_codes},
+    utils::{get_string_from_code, get_string_from_codes},
 };
 
 #[derive(Debug, Clone)]
 pub struct RegExpValidationState {
-    parser: Weak<Parser>,
+    ecma_version: i32,
     valid_flags: String,
     unicode_properties: Option<&'static UnicodeProperties>,
     source: String,
+    // The UTF-16 code units of the source, which the pattern is indexed by.
+    codes: Vec<i32>,
     flags: String,
     start: i32,
     switch_u: bool,
@@ -31,11 +31,9 @@ pub struct RegExpValidationState {
 }
 
 impl RegExpValidationState {
-    pub fn new(parser: Weak<Parser>) -> Self {
-        let options = &parser.upgrade().unwrap().options;
-        let ecma_version = options.get_ecma_version_number();
+    pub fn new(ecma_version: i32) -> Self {
         RegExpValidationState {
-            parser,
+            ecma_version,
             valid_flags: format!(
                 "gim{:}{:}{:}",
                 if ecma_version >= 6 { "uy" } else { "" },
@@ -48,6 +46,7 @@ impl RegExpValidationState {
                 ecma_version
             }),
             source: "".to_string(),
+            codes: vec![],
             flags: "".to_string(),
             start: 0,
             switch_u: false,
@@ -66,36 +65,30 @@ impl RegExpValidationState {
     pub fn reset(&mut self, start: i32, pattern: &str, flags: &str) {
         self.start = start;
         self.source = pattern.to_owned();
+        self.codes = pattern.encode_utf16().map(i32::from).collect();
         self.flags = flags.to_owned();
-        if let Some(parser) = self.parser.upgrade() {
-            let unicode = flags.contains("u");
-            let ecma_version = parser.options.get_ecma_version_number();
-            self.switch_u = unicode && ecma_version >= 6;
-            self.switch_n = unicode && ecma_version >= 9;
-        } else {
-            self.switch_u = false;
-            self.switch_n = false;
-        }
-    }
-
-    pub fn raise(&self, message: &str) -> Result<(), ParserError> {
-        match self.parser.upgrade() {
-            Some(parser) => parser.raise_recoverable(
-                self.start,
-                &format!(
-                    "Invalid regular expression: /{:}/: {:}",
-                    self.source, message
-                ),
+        let unicode = flags.contains("u");
+        self.switch_u = unicode && self.ecma_version >= 6;
+        self.switch_n = unicode && self.ecma_version >= 9;
+    }
+
+    /// Reports an error in the pattern being validated through `parser`.
+    pub fn raise(&self, parser: &mut Parser, message: &str) -> Result<(), ParserError> {
+        parser.raise_recoverable(
+            self.start,
+            ErrorCode::InvalidRegex,
+            &format!(
+                "Invalid regular expression: /{:}/: {:}",
+                self.source, message
             ),
-            None => Err(ParserError::UnKnown),
-        }
+        )
     }
 
     /// If u flag is given, this returns the code point at the index (it
     /// combines a surrogate pair). Otherwise, this returns the code unit of
     /// the index (can be a part of a surrogate pair).
     pub fn at(&self, index: i32, force_u: bool) -> i32 {
-        let source_codes = get_codes_from_string(&self.source);
+        let source_codes = &self.codes;
         let source_codes_len = source_codes.len() as i32;
         if index >= source_codes_len {
             return -1;
@@ -110,7 +103,7 @@ impl RegExpValidationState {
         }
 
         let next_code = source_codes[index as usize + 1];
-        if next_code >= 0xdc00 && next_code <= 0xdfff {
+        if (0xdc00..=0xdfff).contains(&next_code) {
             (current_code << 10) + next_code - 0x35fdc00
         } else {
             current_code
@@ -118,7 +111,7 @@ impl RegExpValidationState {
     }
 
     pub fn next_index(&self, index: i32, force_u: bool) -> i32 {
-        let source_codes = get_codes_from_string(&self.source);
+        let source_codes = &self.codes;
         let source_codes_len = source_codes.len() as i32;
         if index >= source_codes_len {
             return 1;
@@ -134,7 +127,7 @@ impl RegExpValidationState {
         }
 
         let next_code = source_codes[index as usize + 1];
-        if next_code < 0xdc00 || next_code > 0xdfff {
+        if !(0xdc00..=0xdfff).contains(&next_code) {
             index + 1
         } else {
             index + 2
@@ -173,24 +166,24 @@ fn code_point_to_string(code: i32) -> String {
 }
 
 fn is_octal_digit(code: i32) -> bool {
-    code >= DIGIT_0 && code <= DIGIT_7
+    (DIGIT_0..=DIGIT_7).contains(&code)
 }
 
 fn is_decimal_digit(code: i32) -> bool {
-    code >= DIGIT_0 && code <= DIGIT_9
+    (DIGIT_0..=DIGIT_9).contains(&code)
 }
 
 fn is_hex_digit(code: i32) -> bool {
-    (code >= DIGIT_0 && code <= DIGIT_9)
-        || (code >= UPPERCASE_A && code <= UPPERCASE_F)
-        || (code >= LOWERCASE_A && code <= LOWERCASE_F)
+    (DIGIT_0..=DIGIT_9).contains(&code)
+        || (UPPERCASE_A..=UPPERCASE_F).contains(&code)
+        || (LOWERCASE_A..=LOWERCASE_F).contains(&code)
 }
 
 fn hex_to_int(code: i32) -> i32 {
-    if code >= UPPERCASE_A && code <= UPPERCASE_F {
+    if (UPPERCASE_A..=UPPERCASE_F).contains(&code) {
         return 10 + (code - UPPERCASE_A);
     }
-    if code >= LOWERCASE_A && code <= LOWERCASE_F {
+    if (LOWERCASE_A..=LOWERCASE_F).contains(&code) {
         return 10 + (code - LOWERCASE_A);
     }
     code - DIGIT_0
@@ -198,11 +191,11 @@ fn hex_to_int(code: i32) -> i32 {
 
 fn is_syntax_character(code: i32) -> bool {
     code == DOLLAR_SIGN
-        || code >= LEFT_PARENTHESIS && code <= PLUS_SIGN
+        || (LEFT_PARENTHESIS..=PLUS_SIGN).contains(&code)
         || code == DOT
         || code == QUESTION_MARK
-        || code >= LEFT_SQUARE_BRACKET && code <= CARET
-        || code >= LEFT_CURLY_BRACE && code <= RIGHT_CURLY_BRACE
+        || (LEFT_SQUARE_BRACKET..=CARET).contains(&code)
+        || (LEFT_CURLY_BRACE..=RIGHT_CURLY_BRACE).contains(&code)
 }
 
 fn is_character_class_escape(code: i32) -> bool {
@@ -215,7 +208,7 @@ fn is_character_class_escape(code: i32) -> bool {
 }
 
 fn is_control_letter(code: i32) -> bool {
-    (code >= UPPERCASE_A && code <= UPPERCASE_Z) || (code >= LOWERCASE_A && code <= LOWERCASE_Z)
+    (UPPERCASE_A..=UPPERCASE_Z).contains(&code) || (LOWERCASE_A..=LOWERCASE_Z).contains(&code)
 }
 
 fn is_unicode_property_name_character(code: i32) -> bool {
@@ -236,25 +229,28 @@ fn is_reg_exp_identifier_part(code: i32) -> bool {
 }
 
 fn is_valid_unicode(code: i32) -> bool {
-    code >= 0 && code <= 0x10ffff
+    (0..=0x10ffff).contains(&code)
 }
 
 pub trait RegexpParser {
-    fn validate_reg_exp_flags(&self, state: &RegExpValidationState) -> Result<(), ParserError>;
+    fn validate_reg_exp_flags(&mut self, state: &RegExpValidationState) -> Result<(), ParserError>;
     fn validate_reg_exp_pattern(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<(), ParserError>;
-    fn regexp_eat_assertion(&self, state: &mut RegExpValidationState) -> Result<bool, ParserError>;
+    fn regexp_eat_assertion(
+        &mut self,
+        state: &mut RegExpValidationState,
+    ) -> Result<bool, ParserError>;
     fn regexp_eat_decimal_digits(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_eat_decimal_escape(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_validate_unicode_property_name_or_value(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         name_or_value: &str,
     ) -> Result<(), ParserError>;
     fn regexp_validate_unicode_property_name_and_value(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         name: &str,
         value: &str,
@@ -266,37 +262,39 @@ pub trait RegexpParser {
     ) -> bool;
     fn regexp_eat_unicode_property_name(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_eat_unicode_property_value_expression(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_character_class_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_back_reference(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_eat_hex_digits(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_eat_fixed_hex_digits(&self, state: &mut RegExpValidationState, length: i32) -> bool;
     fn regexp_eat_reg_exp_unicode_escape_sequence(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         force_u: bool,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_reg_exp_identifier_part(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_reg_exp_identifier_start(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_reg_exp_identifier_name(
-        &self,
+        &mut self,
+        state: &mut RegExpValidationState,
+    ) -> Result<bool, ParserError>;
+    fn regexp_eat_group_name(
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
-    fn regexp_eat_group_name(&self, state: &mut RegExpValidationState)
-        -> Result<bool, ParserError>;
     fn regexp_eat_k_group_name(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_syntax_character(&self, state: &mut RegExpValidationState) -> bool;
@@ -304,7 +302,7 @@ pub trait RegexpParser {
     fn regexp_eat_octal_digit(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_eat_legacy_octal_escape_sequence(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_eat_hex_escape_sequence(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_zero(&self, state: &mut RegExpValidationState) -> bool;
@@ -312,82 +310,96 @@ pub trait RegexpParser {
     fn regexp_eat_c_control_letter(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_eat_control_escape(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_eat_character_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_atom_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_reverse_solidus_atom_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_class_control_letter(&self, state: &mut RegExpValidationState) -> bool;
     fn regexp_eat_class_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
-    fn regexp_eat_class_atom(&self, state: &mut RegExpValidationState)
-        -> Result<bool, ParserError>;
-    fn regexp_class_ranges(&self, state: &mut RegExpValidationState) -> Result<(), ParserError>;
+    fn regexp_eat_class_atom(
+        &mut self,
+        state: &mut RegExpValidationState,
+    ) -> Result<bool, ParserError>;
+    fn regexp_class_ranges(&mut self, state: &mut RegExpValidationState)
+        -> Result<(), ParserError>;
     fn regexp_eat_character_class(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_uncapturing_group(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
-    fn regexp_group_specifier(&self, state: &mut RegExpValidationState) -> Result<(), ParserError>;
+    fn regexp_group_specifier(
+        &mut self,
+        state: &mut RegExpValidationState,
+    ) -> Result<(), ParserError>;
     fn regexp_eat_capturing_group(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_braced_quantifier(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         no_error: bool,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_invalid_braced_quantifier(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_extended_pattern_character(&self, state: &mut RegExpValidationState) -> bool;
-    fn regexp_eat_atom(&self, state: &mut RegExpValidationState) -> Result<bool, ParserError>;
+    fn regexp_eat_atom(&mut self, state: &mut RegExpValidationState) -> Result<bool, ParserError>;
     fn regexp_eat_extended_atom(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_pattern_characters(&self, state: &mut RegExpValidationState) -> bool;
-    fn regexp_eat_term(&self, state: &mut RegExpValidationState) -> Result<bool, ParserError>;
-    fn regexp_alternative(&self, state: &mut RegExpValidationState) -> Result<(), ParserError>;
+    fn regexp_eat_term(&mut self, state: &mut RegExpValidationState) -> Result<bool, ParserError>;
+    fn regexp_alternative(&mut self, state: &mut RegExpValidationState) -> Result<(), ParserError>;
     fn regexp_eat_quantifier_prefix(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         no_error: bool,
     ) -> Result<bool, ParserError>;
     fn regexp_eat_quantifier(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         no_error: bool,
     ) -> Result<bool, ParserError>;
-    fn regexp_disjunction(&self, state: &mut RegExpValidationState) -> Result<(), ParserError>;
-    fn regexp_pattern(&self, state: &mut RegExpValidationState) -> Result<(), ParserError>;
+    fn regexp_disjunction(&mut self, state: &mut RegExpValidationState) -> Result<(), ParserError>;
+    fn regexp_pattern(&mut self, state: &mut RegExpValidationState) -> Result<(), ParserError>;
 }
 
 impl RegexpParser for Parser {
     /// Validate the flags part of a given RegExpLiteral.
-    fn validate_reg_exp_flags(&self, state: &RegExpValidationState) -> Result<(), ParserError> {
+    fn validate_reg_exp_flags(&mut self, state: &RegExpValidationState) -> Result<(), ParserError> {
         let valid_flags = &state.valid_flags;
         let flags = &state.flags;
         for (index, flag) in flags.chars().enumerate() {
             if !valid_flags.contains(flag) {
-                return self.raise_recoverable(state.start, "Invalid regular expression flag");
+                return self.raise_recoverable(
+                    state.start,
+                    ErrorCode::InvalidRegexFlag,
+                    "Invalid regular expression flag",
+                );
             }
             let flags = &flags[index + 1..];
             if flags.contains(flag) {
-                return self.raise_recoverable(state.start, "Duplicate regular expression flag");
+                return self.raise_recoverable(
+                    state.start,
+                    ErrorCode::DuplicateRegexFlag,
+                    "Duplicate regular expression flag",
+                );
             }
         }
         Ok(())
@@ -395,7 +407,7 @@ impl RegexpParser for Parser {
 
     /// Validate the pattern part of a given RegExpLiteral.
     fn validate_reg_exp_pattern(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<(), ParserError> {
         self.regexp_pattern(state)?;
@@ -406,7 +418,7 @@ impl RegexpParser for Parser {
         // were not matched by the parse, or if any Early Error conditions exist.
         if !state.switch_n
             && self.options.get_ecma_version_number() >= 9
-            && state.group_names.len() > 0
+            && !state.group_names.is_empty()
         {
             state.switch_n = true;
             self.regexp_pattern(state)?;
@@ -415,7 +427,10 @@ impl RegexpParser for Parser {
     }
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-annexB-Assertion
-    fn regexp_eat_assertion(&self, state: &mut RegExpValidationState) -> Result<bool, ParserError> {
+    fn regexp_eat_assertion(
+        &mut self,
+        state: &mut RegExpValidationState,
+    ) -> Result<bool, ParserError> {
         let start = state.pos;
         state.last_assertion_is_quantifiable = false;
         if state.eat(CARET, false) || state.eat(DOLLAR_SIGN, false) {
@@ -437,7 +452,7 @@ impl RegexpParser for Parser {
             if state.eat(EQUALS_TO, false) || state.eat(EXCLAMATION_MARK, false) {
                 self.regexp_disjunction(state)?;
                 if !state.eat(RIGHT_PARENTHESIS, false) {
-                    state.raise("Unterminated group")?;
+                    state.raise(self, "Unterminated group")?;
                 }
                 state.last_assertion_is_quantifiable = !lookbehind;
                 return Ok(true);
@@ -455,6 +470,7 @@ impl RegexpParser for Parser {
             let code = state.current(false);
             if is_decimal_digit(code) {
                 state.last_int_value = 10 * state.last_int_value + (code - DIGIT_0);
+                state.advance(false);
             } else {
                 break;
             }
@@ -465,13 +481,13 @@ impl RegexpParser for Parser {
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-DecimalEscape
     fn regexp_eat_decimal_escape(&self, state: &mut RegExpValidationState) -> bool {
         state.last_int_value = 0;
-        let code = state.current(false);
-        if code >= DIGIT_1 && code <= DIGIT_9 {
+        let mut code = state.current(false);
+        if (DIGIT_1..=DIGIT_9).contains(&code) {
             loop {
                 state.last_int_value = 10 * state.last_int_value + (code - DIGIT_0);
                 state.advance(false);
-                let code = state.current(false);
-                if code < DIGIT_0 || code > DIGIT_9 {
+                code = state.current(false);
+                if !(DIGIT_0..=DIGIT_9).contains(&code) {
                     break;
                 }
             }
@@ -482,21 +498,21 @@ impl RegexpParser for Parser {
     }
 
     fn regexp_validate_unicode_property_name_or_value(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         name_or_value: &str,
     ) -> Result<(), ParserError> {
         match state.unicode_properties {
             Some(unicode_properties) => match unicode_properties.binary.is_match(name_or_value) {
-                Ok(_) => Ok(()),
-                _ => state.raise("Invalid property name or value"),
+                Ok(true) => Ok(()),
+                _ => state.raise(self, "Invalid property name or value"),
             },
-            None => state.raise("State unicode_properties is undefined"),
+            None => state.raise(self, "State unicode_properties is undefined"),
         }
     }
 
     fn regexp_validate_unicode_property_name_and_value(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         name: &str,
         value: &str,
@@ -504,12 +520,12 @@ impl RegexpParser for Parser {
         match state.unicode_properties {
             Some(unicode_properties) => match unicode_properties.get_non_binary_regex(name) {
                 Some(regex) => match regex.is_match(value) {
-                    Ok(_) => Ok(()),
-                    _ => state.raise("Invalid property value"),
+                    Ok(true) => Ok(()),
+                    _ => state.raise(self, "Invalid property value"),
                 },
-                None => state.raise("Invalid property name"),
+                None => state.raise(self, "Invalid property name"),
             },
-            None => state.raise("State unicode_properties is undefined"),
+            None => state.raise(self, "State unicode_properties is undefined"),
         }
     }
 
@@ -530,7 +546,7 @@ impl RegexpParser for Parser {
                 break;
             }
         }
-        state.last_string_value.len() > 0
+        !state.last_string_value.is_empty()
     }
 
     /// LoneUnicodePropertyNameOrValue ::
@@ -559,17 +575,17 @@ impl RegexpParser for Parser {
                 break;
             }
         }
-        state.last_string_value.len() > 0
+        !state.last_string_value.is_empty()
     }
 
     /// UnicodePropertyValueExpression ::
     ///   UnicodePropertyName `=` UnicodePropertyValue
     ///   LoneUnicodePropertyNameOrValue
     fn regexp_eat_unicode_property_value_expression(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
-        let start = state.start;
+        let start = state.pos;
         // UnicodePropertyName `=` UnicodePropertyValue
         if self.regexp_eat_unicode_property_name(state) && state.eat(EQUALS_TO, false) {
             let name = state.last_string_value.clone();
@@ -593,7 +609,7 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-CharacterClassEscape
     fn regexp_eat_character_class_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         let code = state.current(false);
@@ -614,7 +630,7 @@ impl RegexpParser for Parser {
             {
                 return Ok(true);
             }
-            state.raise("Invalid property name")?;
+            state.raise(self, "Invalid property name")?;
         }
         Ok(false)
     }
@@ -674,7 +690,7 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-RegExpUnicodeEscapeSequence
     fn regexp_eat_reg_exp_unicode_escape_sequence(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         force_u: bool,
     ) -> Result<bool, ParserError> {
@@ -683,14 +699,14 @@ impl RegexpParser for Parser {
         if state.eat(LOWERCASE_U, false) {
             if self.regexp_eat_fixed_hex_digits(state, 4) {
                 let lead = state.last_int_value;
-                if switch_u && lead >= 0xd800 && lead <= 0xdbff {
+                if switch_u && (0xd800..=0xdbff).contains(&lead) {
                     let lead_surrogate_end = state.pos;
                     if state.eat(BACKSLASH, false)
                         && state.eat(LOWERCASE_U, false)
                         && self.regexp_eat_fixed_hex_digits(state, 4)
                     {
                         let trail = state.last_int_value;
-                        if trail >= 0xdc00 && trail <= 0xdfff {
+                        if (0xdc00..=0xdfff).contains(&trail) {
                             state.last_int_value =
                                 (lead - 0xd800) * 0x400 + (trail - 0xdc00) + 0x10000;
                             return Ok(true);
@@ -710,7 +726,7 @@ impl RegexpParser for Parser {
                 return Ok(true);
             }
             if switch_u {
-                state.raise("Invalid unicode escape")?;
+                state.raise(self, "Invalid unicode escape")?;
             }
             state.pos = start;
         }
@@ -725,7 +741,7 @@ impl RegexpParser for Parser {
     ///   <ZWNJ>
     ///   <ZWJ>
     fn regexp_eat_reg_exp_identifier_part(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         let start = state.pos;
@@ -750,7 +766,7 @@ impl RegexpParser for Parser {
     ///   `_`
     ///  `\` RegExpUnicodeEscapeSequence[+U]
     fn regexp_eat_reg_exp_identifier_start(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         let start = state.pos;
@@ -775,7 +791,7 @@ impl RegexpParser for Parser {
     /// Note: this updates `state.last_string_value` property with the eaten
     /// name.
     fn regexp_eat_reg_exp_identifier_name(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         state.last_string_value = "".to_string();
@@ -803,7 +819,7 @@ impl RegexpParser for Parser {
     /// Note: this updates `state.last_string_value` property with the eaten
     /// name.
     fn regexp_eat_group_name(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         state.last_string_value = "".to_string();
@@ -811,13 +827,13 @@ impl RegexpParser for Parser {
             if self.regexp_eat_reg_exp_identifier_name(state)? && state.eat(GREATER_THAN, false) {
                 return Ok(true);
             }
-            state.raise("Invalid capture group name")?;
+            state.raise(self, "Invalid capture group name")?;
         }
         Ok(false)
     }
 
     fn regexp_eat_k_group_name(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         if state.eat(LOWERCASE_K, false) {
@@ -827,7 +843,7 @@ impl RegexpParser for Parser {
                     .push(state.last_string_value.clone());
                 return Ok(true);
             }
-            state.raise("Invalid named reference")?;
+            state.raise(self, "Invalid named reference")?;
         }
         Ok(false)
     }
@@ -887,7 +903,7 @@ impl RegexpParser for Parser {
             if self.regexp_eat_octal_digit(state) {
                 let n2 = state.last_int_value;
                 if n1 <= 3 && self.regexp_eat_octal_digit(state) {
-                    state.last_int_value = n1 * 64 + n2 * 8 + state.last_int_value;
+                    state.last_int_value += n1 * 64 + n2 * 8;
                 } else {
                     state.last_int_value = n1 * 8 + n2;
                 }
@@ -902,7 +918,7 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-HexEscapeSequence
     fn regexp_eat_hex_escape_sequence(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         let start = state.pos;
@@ -911,7 +927,7 @@ impl RegexpParser for Parser {
                 return Ok(true);
             }
             if state.switch_u {
-                state.raise("Invalid escape")?;
+                state.raise(self, "Invalid escape")?;
             }
             state.pos = start;
         }
@@ -948,7 +964,7 @@ impl RegexpParser for Parser {
             }
             state.pos = start;
         }
-        return false;
+        false
     }
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-ControlEscape
@@ -986,7 +1002,7 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-annexB-CharacterEscape
     fn regexp_eat_character_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         Ok(self.regexp_eat_control_escape(state)
@@ -1000,7 +1016,7 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-annexB-AtomEscape
     fn regexp_eat_atom_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         if self.regexp_eat_back_reference(state)
@@ -1012,15 +1028,15 @@ impl RegexpParser for Parser {
         }
         if state.switch_u {
             if state.current(false) == LOWERCASE_C {
-                state.raise("Invalid unicode escape")?;
+                state.raise(self, "Invalid unicode escape")?;
             }
-            state.raise("Invalid escape")?;
+            state.raise(self, "Invalid escape")?;
         }
         Ok(false)
     }
 
     fn regexp_eat_reverse_solidus_atom_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         let start = state.pos;
@@ -1047,7 +1063,7 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-annexB-ClassEscape
     fn regexp_eat_class_escape(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         let start = state.pos;
@@ -1074,7 +1090,7 @@ impl RegexpParser for Parser {
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-ClassAtom
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-ClassAtomNoDash
     fn regexp_eat_class_atom(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         let start = state.pos;
@@ -1085,9 +1101,9 @@ impl RegexpParser for Parser {
             if state.switch_u {
                 let code = state.current(false);
                 if code == LOWERCASE_C || is_octal_digit(code) {
-                    state.raise("Invalid class escape")?;
+                    state.raise(self, "Invalid class escape")?;
                 }
-                state.raise("Invalid escape")?;
+                state.raise(self, "Invalid escape")?;
             }
             state.pos = start;
         }
@@ -1103,16 +1119,19 @@ impl RegexpParser for Parser {
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-ClassRanges
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-NonemptyClassRanges
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-NonemptyClassRangesNoDash
-    fn regexp_class_ranges(&self, state: &mut RegExpValidationState) -> Result<(), ParserError> {
+    fn regexp_class_ranges(
+        &mut self,
+        state: &mut RegExpValidationState,
+    ) -> Result<(), ParserError> {
         while self.regexp_eat_class_atom(state)? {
             let left = state.last_int_value;
             if state.eat(DASH, false) && self.regexp_eat_class_atom(state)? {
                 let right = state.last_int_value;
                 if state.switch_u && (left == -1 || right == -1) {
-                    state.raise("Invalid character class")?;
+                    state.raise(self, "Invalid character class")?;
                 }
                 if left != -1 && right != -1 && left > right {
-                    state.raise("Range out of order in character class")?;
+                    state.raise(self, "Range out of order in character class")?;
                 }
             }
         }
@@ -1121,7 +1140,7 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-CharacterClass
     fn regexp_eat_character_class(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         if state.eat(LEFT_SQUARE_BRACKET, false) {
@@ -1130,13 +1149,13 @@ impl RegexpParser for Parser {
             if state.eat(RIGHT_SQUARE_BRACKET, false) {
                 return Ok(true);
             }
-            state.raise("Unterminated character class")?;
+            state.raise(self, "Unterminated character class")?;
         }
         Ok(false)
     }
 
     fn regexp_eat_uncapturing_group(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         let start = state.pos;
@@ -1146,7 +1165,7 @@ impl RegexpParser for Parser {
                 if state.eat(RIGHT_PARENTHESIS, false) {
                     return Ok(true);
                 }
-                state.raise("Unterminated group")?;
+                state.raise(self, "Unterminated group")?;
             }
             state.pos = start;
         }
@@ -1156,64 +1175,66 @@ impl RegexpParser for Parser {
     /// GroupSpecifier ::
     ///   [empty]
     ///   `?` GroupName
-    fn regexp_group_specifier(&self, state: &mut RegExpValidationState) -> Result<(), ParserError> {
+    fn regexp_group_specifier(
+        &mut self,
+        state: &mut RegExpValidationState,
+    ) -> Result<(), ParserError> {
         if state.eat(QUESTION_MARK, false) {
             if self.regexp_eat_group_name(state)? {
                 if state.group_names.contains(&state.last_string_value) {
-                    state.raise("Duplicate capture group name")?;
+                    state.raise(self, "Duplicate capture group name")?;
                 }
                 state.group_names.push(state.last_string_value.clone());
                 return Ok(());
             }
-            state.raise("Invalid group")?;
+            state.raise(self, "Invalid group")?;
         }
         Ok(())
     }
 
     fn regexp_eat_capturing_group(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         if state.eat(LEFT_PARENTHESIS, false) {
             if self.options.get_ecma_version_number() >= 9 {
                 self.regexp_group_specifier(state)?;
             } else if state.current(false) == QUESTION_MARK {
-                state.raise("Invalid group")?;
+                state.raise(self, "Invalid group")?;
             }
             self.regexp_disjunction(state)?;
             if state.eat(RIGHT_PARENTHESIS, false) {
                 state.num_capturing_parens += 1;
                 return Ok(true);
             }
-            state.raise("Unterminated group")?;
+            state.raise(self, "Unterminated group")?;
         }
         Ok(false)
     }
 
     fn regexp_eat_braced_quantifier(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         no_error: bool,
     ) -> Result<bool, ParserError> {
         let start = state.pos;
         if state.eat(LEFT_CURLY_BRACE, false) {
-            let mut min = 0;
             let mut max = -1;
             if self.regexp_eat_decimal_digits(state) {
-                min = state.last_int_value;
+                let min = state.last_int_value;
                 if state.eat(COMMA, false) && self.regexp_eat_decimal_digits(state) {
                     max = state.last_int_value;
                 }
                 if state.eat(RIGHT_CURLY_BRACE, false) {
                     // SyntaxError in https://www.ecma-international.org/ecma-262/8.0/#sec-term
                     if max != -1 && max < min && !no_error {
-                        state.raise("numbers out of order in {} quantifier")?;
+                        state.raise(self, "numbers out of order in {} quantifier")?;
                     }
                     return Ok(true);
                 }
             }
             if state.switch_u && !no_error {
-                state.raise("Incomplete quantifier")?;
+                state.raise(self, "Incomplete quantifier")?;
             }
             state.pos = start
         }
@@ -1222,11 +1243,11 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-annexB-InvalidBracedQuantifier
     fn regexp_eat_invalid_braced_quantifier(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         if self.regexp_eat_braced_quantifier(state, true)? {
-            state.raise("Nothing to repeat")?;
+            state.raise(self, "Nothing to repeat")?;
         }
         Ok(false)
     }
@@ -1236,7 +1257,7 @@ impl RegexpParser for Parser {
         let code = state.current(false);
         if code != -1
             && code != DOLLAR_SIGN
-            && !(code >= LEFT_PARENTHESIS && code <= PLUS_SIGN)
+            && !(LEFT_PARENTHESIS..=PLUS_SIGN).contains(&code)
             && code != DOT
             && code != QUESTION_MARK
             && code != LEFT_SQUARE_BRACKET
@@ -1251,7 +1272,7 @@ impl RegexpParser for Parser {
     }
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-Atom
-    fn regexp_eat_atom(&self, state: &mut RegExpValidationState) -> Result<bool, ParserError> {
+    fn regexp_eat_atom(&mut self, state: &mut RegExpValidationState) -> Result<bool, ParserError> {
         Ok(self.regexp_eat_pattern_characters(state)
             || state.eat(DOT, false)
             || self.regexp_eat_reverse_solidus_atom_escape(state)?
@@ -1262,7 +1283,7 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-annexB-ExtendedAtom
     fn regexp_eat_extended_atom(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
     ) -> Result<bool, ParserError> {
         Ok(state.eat(DOT, false)
@@ -1289,15 +1310,16 @@ impl RegexpParser for Parser {
     }
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-annexB-Term
-    fn regexp_eat_term(&self, state: &mut RegExpValidationState) -> Result<bool, ParserError> {
+    fn regexp_eat_term(&mut self, state: &mut RegExpValidationState) -> Result<bool, ParserError> {
         if self.regexp_eat_assertion(state)? {
             // Handle `QuantifiableAssertion Quantifier` alternative.
             // `state.last_assertion_is_quantifiable` is true if the last eaten Assertion
             // is a QuantifiableAssertion.
-            if state.last_assertion_is_quantifiable && self.regexp_eat_quantifier(state, false)? {
-                if state.switch_u {
-                    state.raise("Invalid quantifier")?;
-                }
+            if state.last_assertion_is_quantifiable
+                && self.regexp_eat_quantifier(state, false)?
+                && state.switch_u
+            {
+                state.raise(self, "Invalid quantifier")?;
             }
             return Ok(true);
         }
@@ -1313,15 +1335,15 @@ impl RegexpParser for Parser {
     }
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-Alternative
-    fn regexp_alternative(&self, state: &mut RegExpValidationState) -> Result<(), ParserError> {
-        let source_len = state.source.len() as i32;
+    fn regexp_alternative(&mut self, state: &mut RegExpValidationState) -> Result<(), ParserError> {
+        let source_len = state.codes.len() as i32;
         while state.pos < source_len && self.regexp_eat_term(state)? {}
         Ok(())
     }
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-QuantifierPrefix
     fn regexp_eat_quantifier_prefix(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         no_error: bool,
     ) -> Result<bool, ParserError> {
@@ -1333,7 +1355,7 @@ impl RegexpParser for Parser {
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-Quantifier
     fn regexp_eat_quantifier(
-        &self,
+        &mut self,
         state: &mut RegExpValidationState,
         no_error: bool,
     ) -> Result<bool, ParserError> {
@@ -1346,22 +1368,22 @@ impl RegexpParser for Parser {
     }
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-Disjunction
-    fn regexp_disjunction(&self, state: &mut RegExpValidationState) -> Result<(), ParserError> {
+    fn regexp_disjunction(&mut self, state: &mut RegExpValidationState) -> Result<(), ParserError> {
         self.regexp_alternative(state)?;
         while state.eat(VERTICAL_BAR, false) {
             self.regexp_alternative(state)?;
         }
         if self.regexp_eat_quantifier(state, true)? {
-            state.raise("Nothing to repeat")?;
+            state.raise(self, "Nothing to repeat")?;
         }
         if state.eat(LEFT_CURLY_BRACE, false) {
-            state.raise("Lone quantifier brackets")?;
+            state.raise(self, "Lone quantifier brackets")?;
         }
         Ok(())
     }
 
     /// https://www.ecma-international.org/ecma-262/8.0/#prod-Pattern
-    fn regexp_pattern(&self, state: &mut RegExpValidationState) -> Result<(), ParserError> {
+    fn regexp_pattern(&mut self, state: &mut RegExpValidationState) -> Result<(), ParserError> {
         state.pos = 0;
         state.last_int_value = 0;
         state.last_string_value = "".to_string();
@@ -1371,21 +1393,21 @@ impl RegexpParser for Parser {
         state.group_names = vec![];
         state.back_reference_names = vec![];
         self.regexp_disjunction(state)?;
-        let source_len = state.source.len() as i32;
+        let source_len = state.codes.len() as i32;
         if state.pos != source_len {
             if state.eat(RIGHT_PARENTHESIS, false) {
-                state.raise("Unmatched ')'")?;
+                state.raise(self, "Unmatched ')'")?;
             }
             if state.eat(RIGHT_SQUARE_BRACKET, false) || state.eat(RIGHT_CURLY_BRACE, false) {
-                state.raise("Lone quantifier brackets")?;
+                state.raise(self, "Lone quantifier brackets")?;
             }
         }
         if state.max_back_reference > state.num_capturing_parens {
-            state.raise("Invalid escape")?;
+            state.raise(self, "Invalid escape")?;
         }
         for name in &state.back_reference_names {
             if !state.group_names.contains(name) {
-                state.raise("Invalid named capture referenced")?;
+                state.raise(self, "Invalid named capture referenced")?;
             }
         }
         Ok(())
@@ -0,0 +1,238 @@
+// ### Error rendering
+//
+// Prints a syntax error the way compilers do: a header with its code and
+// message, then the source lines it points at, with carets under the
+// offending text and dashes under the spans of its labels.
+//
+//     error[DuplicateBinding]: Identifier 'a' has already been declared
+//      --> main.js:2:5
+//       |
+//     1 | let a = 1;
+//       |     - first declared here
+//     2 | let a = 2;
+//       |     ^
+
+use std::fmt::Write;
+
+use crate::{
+    errors::ParserError,
+    location::{LineIndex, OffsetConverter},
+    options::OffsetUnit,
+};
+
+/// How `ParserError::render` formats its output.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RenderStyle {
+    Plain,
+    /// Colored with ANSI escape sequences, for terminals.
+    Ansi,
+}
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+struct Painter(RenderStyle);
+
+impl Painter {
+    fn paint(&self, color: &str, text: &str) -> String {
+        match self.0 {
+            RenderStyle::Plain => text.to_owned(),
+            RenderStyle::Ansi => format!("{:}{:}{:}", color, text, RESET),
+        }
+    }
+}
+
+/// A span to underline, with the text printed after it.
+struct Annotation<'a> {
+    start: usize,
+    end: usize,
+    message: Option<&'a str>,
+    is_primary: bool,
+}
+
+/// The `(start, end)` byte offsets of `line`, without its line terminator.
+fn line_bounds(input: &str, line_index: &LineIndex, line: i32) -> (usize, usize) {
+    let start = line_index.line_start(line) as usize;
+    if line as usize == line_index.line_count() {
+        return (start, input.len());
+    }
+    let text = &input[start..line_index.line_start(line + 1) as usize];
+    let terminator = if text.ends_with("\r\n") {
+        2
+    } else {
+        text.chars().next_back().map_or(0, char::len_utf8)
+    };
+    (start, start + text.len() - terminator)
+}
+
+/// Clamps an offset of an error to the input, on a character boundary.
+fn clamp_offset(input: &str, offset: i32) -> usize {
+    let mut offset = (offset.max(0) as usize).min(input.len());
+    while !input.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// Replaces the characters of `text` by spaces, keeping tabs so that what
+/// follows lines up with the source line above.
+fn blank(text: &str) -> String {
+    text.chars()
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect()
+}
+
+impl ParserError {
+    /// Renders the error with the lines of `input` it points at, for
+    /// printing to users. `source_file` is shown next to the position.
+    pub fn render(&self, input: &str, source_file: Option<&str>, style: RenderStyle) -> String {
+        let painter = Painter(style);
+        let mut out = String::new();
+        let (message, pos, end, labels, notes, unit) = match self {
+            ParserError::SyntaxError {
+                message,
+                pos,
+                end,
+                loc,
+                labels,
+                notes,
+                unit,
+                ..
+            } => {
+                let suffix = format!(" ({:}:{:})", loc.line, loc.column);
+                let message = message.strip_suffix(&suffix).unwrap_or(message);
+                (
+                    message.to_owned(),
+                    *pos,
+                    *end,
+                    labels.as_slice(),
+                    notes.as_slice(),
+                    *unit,
+                )
+            }
+            error => (
+                error.to_string(),
+                0,
+                0,
+                [].as_slice(),
+                [].as_slice(),
+                OffsetUnit::Utf8,
+            ),
+        };
+        let _ = writeln!(
+            out,
+            "{:}{:}",
+            painter.paint(RED, &format!("error[{:}]", self.code())),
+            painter.paint(BOLD, &format!(": {:}", message))
+        );
+        if !matches!(self, ParserError::SyntaxError { .. }) {
+            return out;
+        }
+
+        let line_index = LineIndex::new(input);
+        let line_of = |offset: usize| line_index.position(offset as i32).line;
+        let offsets = match unit {
+            OffsetUnit::Utf8 => OffsetConverter::default(),
+            _ => OffsetConverter::new(input),
+        };
+        let to_byte =
+            |offset: i32| clamp_offset(input, offsets.convert(offset, unit, OffsetUnit::Utf8));
+        let mut annotations = vec![Annotation {
+            start: to_byte(pos),
+            end: to_byte(end),
+            message: None,
+            is_primary: true,
+        }];
+        annotations.extend(labels.iter().map(|label| Annotation {
+            start: to_byte(label.start),
+            end: to_byte(label.end),
+            message: Some(label.message.as_str()),
+            is_primary: false,
+        }));
+        let mut shown_lines: Vec<i32> = annotations
+            .iter()
+            .map(|annotation| line_of(annotation.start))
+            .collect();
+        shown_lines.sort_unstable();
+        shown_lines.dedup();
+        let width = shown_lines[shown_lines.len() - 1].to_string().len();
+        let gutter = |number: &str| painter.paint(BLUE, &format!("{:>width$} |", number));
+        let source_line = |line: i32| {
+            let (start, end) = line_bounds(input, &line_index, line);
+            format!("{:} {:}", gutter(&line.to_string()), &input[start..end])
+        };
+
+        let primary_start = annotations[0].start;
+        let primary_line = line_of(primary_start);
+        let line_start = line_index.line_start(primary_line) as usize;
+        let column = input[line_start..primary_start].chars().count() + 1;
+        let _ = writeln!(
+            out,
+            "{:}{:} {:}:{:}",
+            " ".repeat(width),
+            painter.paint(BLUE, "-->"),
+            match source_file {
+                Some(source_file) => format!("{:}:{:}", source_file, primary_line),
+                None => primary_line.to_string(),
+            },
+            column
+        );
+        let _ = writeln!(out, "{:}", gutter(""));
+
+        let mut previous: Option<i32> = None;
+        for line in shown_lines {
+            match previous {
+                Some(previous) if line == previous + 2 => {
+                    let _ = writeln!(out, "{:}", source_line(previous + 1));
+                }
+                Some(previous) if line > previous + 2 => {
+                    let _ = writeln!(out, "{:}", painter.paint(BLUE, "..."));
+                }
+                _ => {}
+            }
+            previous = Some(line);
+
+            let _ = writeln!(out, "{:}", source_line(line));
+            let (line_start, line_end) = line_bounds(input, &line_index, line);
+            let mut on_line: Vec<&Annotation> = annotations
+                .iter()
+                .filter(|annotation| line_of(annotation.start) == line)
+                .collect();
+            on_line.sort_by_key(|annotation| annotation.start);
+            for annotation in on_line {
+                // Spans running over several lines are underlined up to the
+                // end of their first one.
+                let end = annotation.end.clamp(annotation.start, line_end);
+                let len = input[annotation.start..end].chars().count().max(1);
+                let (mark, color) = if annotation.is_primary {
+                    ("^", RED)
+                } else {
+                    ("-", BLUE)
+                };
+                let mut underline = mark.repeat(len);
+                if let Some(message) = annotation.message {
+                    underline = format!("{:} {:}", underline, message);
+                }
+                let _ = writeln!(
+                    out,
+                    "{:} {:}{:}",
+                    gutter(""),
+                    blank(&input[line_start..annotation.start]),
+                    painter.paint(color, &underline)
+                );
+            }
+        }
+        for note in notes {
+            let _ = writeln!(
+                out,
+                "{:} {:} {:}",
+                " ".repeat(width),
+                painter.paint(BLUE, "="),
+                format_args!("{:} {:}", painter.paint(BOLD, "note:"), note)
+            );
+        }
+        out
+    }
+}
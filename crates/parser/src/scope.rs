@@ -1,4 +1,12 @@
-use crate::{errors::ParserError, location::LocationParser, parser::Parser, types::Identifier};
+use std::collections::HashMap;
+
+use crate::{
+    atom::Atom,
+    errors::{ErrorCode, ParserError},
+    location::LocationParser,
+    node::Identifier,
+    parser::Parser,
+};
 
 /// Each scope gets a bitset that may contain these flags
 pub const SCOPE_TOP: i32 = 1;
@@ -30,13 +38,15 @@ pub const BIND_OUTSIDE: i32 = 5; // Special case for function names as bound ins
 pub struct Scope {
     pub flags: i32,
     // A list of var-declared names in the current lexical scope
-    pub var: Vec<String>,
+    pub var: Vec<Atom>,
     // A list of lexically-declared names in the current lexical scope
-    pub lexical: Vec<String>,
+    pub lexical: Vec<Atom>,
     // A list of lexically-declared FunctionDeclaration names in the current lexical scope
-    pub functions: Vec<String>,
+    pub functions: Vec<Atom>,
     // A switch to disallow the identifier reference 'arguments'
     pub in_class_field_init: bool,
+    // The span of the first declaration of each name in the current scope
+    pub declared_at: HashMap<Atom, (i32, i32)>,
 }
 
 impl Scope {
@@ -47,69 +57,87 @@ impl Scope {
             lexical: vec![],
             functions: vec![],
             in_class_field_init: false,
+            declared_at: HashMap::new(),
         }
     }
 }
 
 pub trait ScopeParser {
-    fn replace_current_scope(&self, key: &str, scope: &Scope);
-    fn remove_undefined_exports(&self, key: &str, scope: &Scope);
-
-    fn enter_scope(&self, flags: i32);
-    fn exit_scope(&self);
-    fn current_scope(&self) -> Option<Scope>;
-    fn current_var_scope(&self) -> Option<Scope>;
-    fn current_this_scope(&self) -> Option<Scope>;
+    fn push_current_scope_name(&mut self, key: &Atom, binding_type: i32, span: (i32, i32));
+    fn remove_undefined_exports(&mut self, key: &str, scope_flags: i32);
+
+    fn enter_scope(&mut self, flags: i32);
+    fn exit_scope(&mut self);
+    fn current_scope(&self) -> Option<&Scope>;
+    fn current_var_scope(&self) -> Option<&Scope>;
+    fn current_this_scope(&self) -> Option<&Scope>;
+    fn set_in_class_field_init(&mut self, in_class_field_init: bool) -> bool;
     fn treat_functions_as_var_in_scope(&self, scope: &Scope) -> bool;
-    fn declare_name(&self, name: &str, binding_type: i32, pos: i32) -> Result<(), ParserError>;
-    fn check_local_export(&self, identifier: &Identifier);
+    fn declare_name(&mut self, id: &Identifier, binding_type: i32) -> Result<(), ParserError>;
+    fn check_local_export(&mut self, id: &Identifier);
 }
 
 impl ScopeParser for Parser {
-    fn replace_current_scope(&self, key: &str, scope: &Scope) {
-        let mut scope = scope.clone();
-        scope.lexical.push(key.to_owned());
-        let mut scope_stack = self.scope_stack.borrow_mut();
-        scope_stack.pop();
-        scope_stack.push(scope);
+    /// Records a name in the list of the innermost scope that matches the
+    /// binding type, i.e. `functions` for function declarations and
+    /// `lexical` otherwise.
+    fn push_current_scope_name(&mut self, key: &Atom, binding_type: i32, span: (i32, i32)) {
+        if let Some(scope) = self.scope_stack.last_mut() {
+            scope.declared_at.entry(key.clone()).or_insert(span);
+            if binding_type == BIND_FUNCTION {
+                scope.functions.push(key.clone());
+            } else {
+                scope.lexical.push(key.clone());
+            }
+        }
     }
 
-    fn remove_undefined_exports(&self, key: &str, scope: &Scope) {
-        if self.is_in_module && (scope.flags & SCOPE_TOP) > 0 {
-            self.undefined_exports.borrow_mut().remove(key);
+    fn remove_undefined_exports(&mut self, key: &str, scope_flags: i32) {
+        if self.is_in_module && (scope_flags & SCOPE_TOP) > 0 {
+            self.undefined_exports.remove(key);
         }
     }
 
-    fn enter_scope(&self, flags: i32) {
-        self.scope_stack.borrow_mut().push(Scope::new(flags))
+    fn enter_scope(&mut self, flags: i32) {
+        self.scope_stack.push(Scope::new(flags))
     }
 
-    fn exit_scope(&self) {
-        self.scope_stack.borrow_mut().pop();
+    fn exit_scope(&mut self) {
+        self.scope_stack.pop();
     }
 
-    fn current_scope(&self) -> Option<Scope> {
-        self.scope_stack.borrow().last().map(|v| v.clone())
+    fn current_scope(&self) -> Option<&Scope> {
+        self.scope_stack.last()
     }
 
-    fn current_var_scope(&self) -> Option<Scope> {
-        let mut scope_stack = self.scope_stack.borrow().clone();
-        scope_stack.reverse();
-        scope_stack
+    fn current_var_scope(&self) -> Option<&Scope> {
+        self.scope_stack
             .iter()
+            .rev()
             .find(|scope| (scope.flags & SCOPE_VAR) > 0)
-            .map(|v| v.clone())
     }
 
     /// Could be useful for `this`, `new.target`, `super()`, `super.property`,
     /// and `super[property]`.
-    fn current_this_scope(&self) -> Option<Scope> {
-        let mut scope_stack = self.scope_stack.borrow().clone();
-        scope_stack.reverse();
-        scope_stack
+    fn current_this_scope(&self) -> Option<&Scope> {
+        self.scope_stack
             .iter()
+            .rev()
+            .find(|scope| (scope.flags & SCOPE_VAR) > 0 && (scope.flags & SCOPE_ARROW) <= 0)
+    }
+
+    /// Updates `in_class_field_init` of the current `this` scope in place,
+    /// returning the previous value so that it can be restored.
+    fn set_in_class_field_init(&mut self, in_class_field_init: bool) -> bool {
+        match self
+            .scope_stack
+            .iter_mut()
+            .rev()
             .find(|scope| (scope.flags & SCOPE_VAR) > 0 && (scope.flags & SCOPE_ARROW) <= 0)
-            .map(|v| v.clone())
+        {
+            Some(scope) => std::mem::replace(&mut scope.in_class_field_init, in_class_field_init),
+            None => false,
+        }
     }
 
     /// The spec says:
@@ -119,54 +147,55 @@ impl ScopeParser for Parser {
         (scope.flags & SCOPE_FUNCTION) > 0 || !self.is_in_module && (scope.flags & SCOPE_TOP) > 0
     }
 
-    fn declare_name(&self, name: &str, binding_type: i32, pos: i32) -> Result<(), ParserError> {
+    fn declare_name(&mut self, id: &Identifier, binding_type: i32) -> Result<(), ParserError> {
+        let name = &id.name;
+        let span = (id.span.start, id.span.end);
         let mut redeclared = false;
+        // The span of the declaration `name` clashes with.
+        let mut first_declared = None;
         match binding_type {
             BIND_LEXICAL => {
                 if let Some(scope) = self.current_scope() {
-                    let name = name.to_owned();
-                    redeclared = scope.lexical.contains(&name)
-                        || scope.functions.contains(&name)
-                        || scope.var.contains(&name);
-                    self.replace_current_scope(&name, &scope);
-                    self.remove_undefined_exports(&name, &scope);
-                }
-            }
-            BIND_SIMPLE_CATCH => {
-                if let Some(scope) = self.current_scope() {
-                    self.replace_current_scope(name, &scope);
+                    redeclared = scope.lexical.contains(name)
+                        || scope.functions.contains(name)
+                        || scope.var.contains(name);
+                    first_declared = scope.declared_at.get(name).copied();
+                    let scope_flags = scope.flags;
+                    self.push_current_scope_name(name, BIND_LEXICAL, span);
+                    self.remove_undefined_exports(name, scope_flags);
                 }
             }
+            BIND_SIMPLE_CATCH => self.push_current_scope_name(name, BIND_SIMPLE_CATCH, span),
             BIND_FUNCTION => {
                 if let Some(scope) = self.current_scope() {
-                    let name = name.to_owned();
-                    if self.treat_functions_as_var_in_scope(&scope) {
-                        redeclared = scope.lexical.contains(&name);
+                    redeclared = if self.treat_functions_as_var_in_scope(scope) {
+                        scope.lexical.contains(name)
                     } else {
-                        redeclared = scope.lexical.contains(&name) || scope.var.contains(&name);
-                    }
-                    self.replace_current_scope(&name, &scope);
+                        scope.lexical.contains(name) || scope.var.contains(name)
+                    };
+                    first_declared = scope.declared_at.get(name).copied();
+                    self.push_current_scope_name(name, BIND_FUNCTION, span);
                 }
             }
             _ => {
-                let mut scope_stack = self.scope_stack.borrow_mut();
-                let mut index: i32 = scope_stack.len() as i32 - 1;
+                let mut index: i32 = self.scope_stack.len() as i32 - 1;
                 while index >= 0 {
-                    let key = name.to_owned();
-                    let mut scope = scope_stack[index as usize].clone();
+                    let scope = &self.scope_stack[index as usize];
                     let scope_flags = scope.flags;
-                    if scope.lexical.contains(&key)
-                        && !((scope_flags & SCOPE_SIMPLE_CATCH) > 0 && scope.lexical[0].eq(&key))
-                        || !self.treat_functions_as_var_in_scope(&scope)
-                            && scope.functions.contains(&key)
+                    if scope.lexical.contains(name)
+                        && !((scope_flags & SCOPE_SIMPLE_CATCH) > 0 && scope.lexical[0].eq(name))
+                        || !self.treat_functions_as_var_in_scope(scope)
+                            && scope.functions.contains(name)
                     {
                         redeclared = true;
+                        first_declared = scope.declared_at.get(name).copied();
                         break;
                     }
 
-                    self.remove_undefined_exports(&key, &scope);
-                    scope.lexical.push(key);
-                    scope_stack[index as usize] = scope;
+                    self.remove_undefined_exports(name, scope_flags);
+                    let scope = &mut self.scope_stack[index as usize];
+                    scope.declared_at.entry(name.clone()).or_insert(span);
+                    scope.var.push(name.clone());
                     if (scope_flags & SCOPE_VAR) > 0 {
                         break;
                     }
@@ -174,24 +203,31 @@ impl ScopeParser for Parser {
                 }
             }
         };
-        if redeclared {
-            self.raise_recoverable(
-                pos,
+        if !redeclared {
+            return Ok(());
+        }
+        let mut error = self
+            .syntax_error(
+                id.span.start,
+                ErrorCode::DuplicateBinding,
                 &format!("Identifier '{:}' has already been declared", name),
             )
-        } else {
-            Ok(())
+            .with_end(id.span.end);
+        if let Some((start, end)) = first_declared {
+            error = error.with_label(start, end, "first declared here");
         }
+        self.recover(error)
     }
 
-    fn check_local_export(&self, identifier: &Identifier) {
-        if let Some(scope) = self.scope_stack.borrow().first() {
-            let name = identifier.name.clone();
-            if !scope.lexical.contains(&name) && !scope.var.contains(&name) {
-                self.undefined_exports
-                    .borrow_mut()
-                    .insert(name, identifier.loc.start.clone());
-            }
+    /// Remembers an exported local name that is not declared (yet) at the top
+    /// level, so that it can be reported at the end of the program.
+    fn check_local_export(&mut self, id: &Identifier) {
+        let is_declared = match self.scope_stack.first() {
+            Some(scope) => scope.lexical.contains(&id.name) || scope.var.contains(&id.name),
+            None => false,
+        };
+        if !is_declared {
+            self.undefined_exports.insert(id.name.clone(), id.clone());
         }
     }
 }
@@ -0,0 +1,2350 @@
+// ### Statement parsing
+//
+// Parse a program. Initializes the parser, reads any number of
+// statements, and wraps them in a Program node. Optionally takes a
+// `program` argument. If present, the statements will be appended
+// to its body instead of creating a new node.
+
+use std::collections::HashMap;
+
+use crate::{
+    atom::Atom,
+    errors::{ErrorCode, ParserError},
+    expression::ExpressionParser,
+    identifier::{is_identifier_char, is_identifier_start},
+    location::LocationParser,
+    lval::LvalParser,
+    node::{
+        BlockStatement, BreakStatement, CatchClause, Class, ClassBody, ClassElement,
+        ContinueStatement, DebuggerStatement, Declaration, DoWhileStatement, EmptyStatement,
+        ExportAllDeclaration, ExportDefaultDeclaration, ExportDefaultDeclarationKind,
+        ExportNamedDeclaration, ExportSpecifier, Expression, ExpressionStatement, ForInStatement,
+        ForInit, ForLeft, ForOfStatement, ForStatement, Function, Identifier, IfStatement,
+        ImportDeclaration, ImportDeclarationSpecifier, ImportDefaultSpecifier,
+        ImportNamespaceSpecifier, ImportSpecifier, LabeledStatement, Literal, LiteralValue,
+        MethodDefinition, MethodDefinitionKind, ModuleDeclaration, ModuleExportName, NodeParser,
+        Pattern, PrivateIdentifier, Program, PropertyDefinition, PropertyOrRest, ReturnStatement,
+        Span, Spanned, Statement, StaticBlock, SwitchCase, SwitchStatement, ThrowStatement,
+        TryStatement, VariableDeclaration, VariableDeclarationKind, VariableDeclarator,
+        WhileStatement, WithStatement,
+    },
+    parser::Parser,
+    scope::{
+        function_flags, ScopeParser, BIND_FUNCTION, BIND_LEXICAL, BIND_NONE, BIND_SIMPLE_CATCH,
+        BIND_VAR, SCOPE_CLASS_STATIC_BLOCK, SCOPE_SIMPLE_CATCH, SCOPE_SUPER,
+    },
+    token::{types::get_token_types, TokenParser},
+    utils::{DestructuringErrors, UtilsParser},
+    whitespace::{get_line_break_regex, skip_white_space},
+};
+
+/// The kind of statement a label is attached to, which decides whether
+/// `break` and `continue` may target it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LabelKind {
+    Loop,
+    Switch,
+}
+
+/// An entry on the label stack. Loops and `switch` statements push an
+/// anonymous label so that unlabeled `break`/`continue` can be verified.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Label {
+    pub name: Option<Atom>,
+    pub kind: Option<LabelKind>,
+    pub statement_start: i32,
+}
+
+impl Label {
+    fn anonymous(kind: LabelKind) -> Self {
+        Label {
+            name: None,
+            kind: Some(kind),
+            statement_start: -1,
+        }
+    }
+}
+
+/// How a private name has been declared so far in a class body. A getter
+/// and a setter with the same placement may share a name.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PrivateNameKind {
+    Complete,
+    InstanceGetter,
+    InstanceSetter,
+    StaticGetter,
+    StaticSetter,
+}
+
+/// An entry on the private name stack, one per class body being parsed.
+#[derive(Debug, Clone, Default)]
+pub struct PrivateNameScope {
+    pub declared: HashMap<Atom, PrivateNameKind>,
+    pub used: Vec<PrivateIdentifier>,
+}
+
+/// Records the private name declared by a class element, returning whether
+/// it clashes with an earlier declaration.
+pub(crate) fn is_private_name_conflicted(
+    private_name_map: &mut HashMap<Atom, PrivateNameKind>,
+    element: &ClassElement,
+) -> bool {
+    let (key, next) = match element {
+        ClassElement::MethodDefinition(method) => (
+            &method.key,
+            match (method.is_static, method.kind) {
+                (false, MethodDefinitionKind::Get) => PrivateNameKind::InstanceGetter,
+                (false, MethodDefinitionKind::Set) => PrivateNameKind::InstanceSetter,
+                (true, MethodDefinitionKind::Get) => PrivateNameKind::StaticGetter,
+                (true, MethodDefinitionKind::Set) => PrivateNameKind::StaticSetter,
+                _ => PrivateNameKind::Complete,
+            },
+        ),
+        ClassElement::PropertyDefinition(field) => (&field.key, PrivateNameKind::Complete),
+        ClassElement::StaticBlock(_) => return false,
+    };
+    let name = match key {
+        Expression::PrivateIdentifier(key) => key.name.clone(),
+        _ => return false,
+    };
+
+    match private_name_map.get(&name) {
+        // `class { get #a(){}; static set #a(_){} }` is also conflict.
+        Some(PrivateNameKind::InstanceGetter) if next == PrivateNameKind::InstanceSetter => {}
+        Some(PrivateNameKind::InstanceSetter) if next == PrivateNameKind::InstanceGetter => {}
+        Some(PrivateNameKind::StaticGetter) if next == PrivateNameKind::StaticSetter => {}
+        Some(PrivateNameKind::StaticSetter) if next == PrivateNameKind::StaticGetter => {}
+        Some(_) => return true,
+        None => {
+            private_name_map.insert(name, next);
+            return false;
+        }
+    }
+    private_name_map.insert(name, PrivateNameKind::Complete);
+    false
+}
+
+/// Whether the key of a class element is the non-computed `name`.
+fn check_key_name(computed: bool, key: &Expression, name: &str) -> bool {
+    if computed {
+        return false;
+    }
+    match key {
+        Expression::Identifier(key) => key.name == name,
+        Expression::Literal(key) => {
+            matches!(&key.value, LiteralValue::String(value) if value == name)
+        }
+        _ => false,
+    }
+}
+
+/// Whether the raw source of a string literal encodes a lone surrogate.
+/// Only `\u` escapes can produce one, and the decoded string value can not
+/// tell them apart since they are replaced when converted to UTF-8.
+fn has_lone_surrogate(raw: &str) -> bool {
+    let mut units = vec![];
+    let mut chars = raw.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut buffer = [0u16; 2];
+            units.extend_from_slice(ch.encode_utf16(&mut buffer));
+            continue;
+        }
+        if chars.clone().next() != Some('u') {
+            // Other escapes never produce surrogates; skip the escaped char.
+            chars.next();
+            continue;
+        }
+        chars.next();
+        let rest = chars.as_str();
+        let (hex, len) = match rest.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => return false,
+            },
+            None => (rest.get(..4).unwrap_or(""), 4),
+        };
+        let code = match u32::from_str_radix(hex, 16) {
+            Ok(code) => code,
+            Err(_) => return false,
+        };
+        match char::from_u32(code) {
+            Some(ch) => {
+                let mut buffer = [0u16; 2];
+                units.extend_from_slice(ch.encode_utf16(&mut buffer));
+            }
+            None => units.push(code as u16),
+        }
+        chars = rest[len.min(rest.len())..].chars();
+    }
+    char::decode_utf16(units).any(|unit| unit.is_err())
+}
+
+// Flags for `parse_function`.
+pub const FUNC_STATEMENT: i32 = 1;
+pub const FUNC_HANGING_STATEMENT: i32 = 2;
+pub const FUNC_NULLABLE_ID: i32 = 4;
+
+pub trait StatementParser {
+    fn parse_top_level(&mut self, program: Program) -> Result<Program, ParserError>;
+    fn is_let(&self, context: Option<&str>) -> Result<bool, ParserError>;
+    fn is_async_function(&self) -> Result<bool, ParserError>;
+    fn parse_statement(
+        &mut self,
+        context: Option<&str>,
+        top_level: bool,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<Statement, ParserError>;
+    fn parse_break_continue_statement(
+        &mut self,
+        span: Span,
+        keyword: &str,
+    ) -> Result<Statement, ParserError>;
+    fn parse_debugger_statement(&mut self, span: Span) -> Result<DebuggerStatement, ParserError>;
+    fn parse_do_statement(&mut self, span: Span) -> Result<DoWhileStatement, ParserError>;
+    fn parse_for_statement(&mut self, span: Span) -> Result<Statement, ParserError>;
+    fn parse_function_statement(
+        &mut self,
+        span: Span,
+        is_async: bool,
+        declaration_position: bool,
+    ) -> Result<Function, ParserError>;
+    fn parse_if_statement(&mut self, span: Span) -> Result<IfStatement, ParserError>;
+    fn parse_return_statement(&mut self, span: Span) -> Result<ReturnStatement, ParserError>;
+    fn parse_switch_statement(&mut self, span: Span) -> Result<SwitchStatement, ParserError>;
+    fn parse_throw_statement(&mut self, span: Span) -> Result<ThrowStatement, ParserError>;
+    fn parse_catch_clause_param(&mut self) -> Result<Pattern, ParserError>;
+    fn parse_try_statement(&mut self, span: Span) -> Result<TryStatement, ParserError>;
+    fn parse_var_statement(
+        &mut self,
+        span: Span,
+        kind: VariableDeclarationKind,
+    ) -> Result<VariableDeclaration, ParserError>;
+    fn parse_while_statement(&mut self, span: Span) -> Result<WhileStatement, ParserError>;
+    fn parse_with_statement(&mut self, span: Span) -> Result<WithStatement, ParserError>;
+    fn parse_empty_statement(&mut self, span: Span) -> Result<EmptyStatement, ParserError>;
+    fn parse_labeled_statement(
+        &mut self,
+        span: Span,
+        maybe_name: &str,
+        label: Identifier,
+        context: Option<&str>,
+    ) -> Result<LabeledStatement, ParserError>;
+    fn parse_expression_statement(
+        &mut self,
+        span: Span,
+        expr: Expression,
+    ) -> Result<ExpressionStatement, ParserError>;
+    fn parse_block(
+        &mut self,
+        create_new_lexical_scope: bool,
+        span: Option<Span>,
+        exit_strict: bool,
+    ) -> Result<BlockStatement, ParserError>;
+    fn parse_for(&mut self, span: Span, init: Option<ForInit>)
+        -> Result<ForStatement, ParserError>;
+    fn parse_for_in(
+        &mut self,
+        span: Span,
+        left: ForLeft,
+        is_await: bool,
+    ) -> Result<Statement, ParserError>;
+    fn parse_var(
+        &mut self,
+        is_for: bool,
+        kind: VariableDeclarationKind,
+    ) -> Result<Vec<VariableDeclarator>, ParserError>;
+    fn parse_var_id(&mut self, kind: VariableDeclarationKind) -> Result<Pattern, ParserError>;
+    fn parse_function(
+        &mut self,
+        span: Span,
+        statement: i32,
+        is_async: bool,
+    ) -> Result<Function, ParserError>;
+    fn parse_function_params(&mut self) -> Result<Vec<Pattern>, ParserError>;
+    fn parse_class(
+        &mut self,
+        span: Span,
+        is_statement: bool,
+        nullable_id: bool,
+    ) -> Result<Class, ParserError>;
+    fn parse_class_element(
+        &mut self,
+        constructor_allows_super: bool,
+    ) -> Result<Option<ClassElement>, ParserError>;
+    fn is_class_element_name_start(&self) -> bool;
+    fn parse_class_element_name(&mut self) -> Result<(bool, Expression), ParserError>;
+    #[allow(clippy::too_many_arguments)]
+    fn parse_class_method(
+        &mut self,
+        span: Span,
+        is_static: bool,
+        computed: bool,
+        key: Expression,
+        kind: MethodDefinitionKind,
+        is_generator: bool,
+        is_async: bool,
+        allows_direct_super: bool,
+    ) -> Result<MethodDefinition, ParserError>;
+    fn parse_class_field(
+        &mut self,
+        span: Span,
+        is_static: bool,
+        computed: bool,
+        key: Expression,
+    ) -> Result<PropertyDefinition, ParserError>;
+    fn parse_class_static_block(&mut self, span: Span) -> Result<StaticBlock, ParserError>;
+    fn parse_class_id(
+        &mut self,
+        is_statement: bool,
+        nullable_id: bool,
+    ) -> Result<Option<Identifier>, ParserError>;
+    fn parse_class_super(&mut self) -> Result<Option<Expression>, ParserError>;
+    fn enter_class_body(&mut self);
+    fn exit_class_body(&mut self) -> Result<(), ParserError>;
+    fn parse_export(
+        &mut self,
+        span: Span,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<ModuleDeclaration, ParserError>;
+    fn parse_export_declaration(&mut self) -> Result<Declaration, ParserError>;
+    fn parse_export_all_declaration(
+        &mut self,
+        span: Span,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<ExportAllDeclaration, ParserError>;
+    fn parse_export_default_declaration(
+        &mut self,
+    ) -> Result<ExportDefaultDeclarationKind, ParserError>;
+    fn check_export(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<(), ParserError>;
+    fn check_pattern_export(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+        pattern: &Pattern,
+    ) -> Result<(), ParserError>;
+    fn check_variable_export(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+        declarations: &[VariableDeclarator],
+    ) -> Result<(), ParserError>;
+    fn should_parse_export_statement(&self) -> Result<bool, ParserError>;
+    fn parse_export_specifier(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<ExportSpecifier, ParserError>;
+    fn parse_export_specifiers(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<Vec<ExportSpecifier>, ParserError>;
+    fn parse_import(&mut self, span: Span) -> Result<ImportDeclaration, ParserError>;
+    fn parse_import_specifier(&mut self) -> Result<ImportSpecifier, ParserError>;
+    fn parse_import_default_specifier(&mut self) -> Result<ImportDefaultSpecifier, ParserError>;
+    fn parse_import_namespace_specifier(&mut self)
+        -> Result<ImportNamespaceSpecifier, ParserError>;
+    fn parse_import_specifiers(&mut self) -> Result<Vec<ImportDeclarationSpecifier>, ParserError>;
+    fn parse_module_export_name(&mut self) -> Result<ModuleExportName, ParserError>;
+    fn parse_module_source(&mut self) -> Result<Literal, ParserError>;
+    fn adapt_directive_prologue(&self, statements: &mut [Statement]);
+    fn is_directive_candidate(&self, statement: &Statement) -> bool;
+}
+
+impl StatementParser for Parser {
+    fn parse_top_level(&mut self, mut program: Program) -> Result<Program, ParserError> {
+        let token_types = get_token_types();
+        let mut exports = Some(HashMap::new());
+        while self.cur_token_type.ne(&token_types.eof) {
+            let statement = self.parse_statement(None, true, &mut exports)?;
+            program.body.push(statement);
+        }
+        if self.is_in_module {
+            // Report the names in source order.
+            let mut undefined_exports: Vec<_> = self
+                .undefined_exports
+                .iter()
+                .map(|(name, id)| (id.span.start, name.clone()))
+                .collect();
+            undefined_exports.sort();
+            for (start, name) in undefined_exports {
+                self.raise_recoverable(
+                    start,
+                    ErrorCode::UndefinedExport,
+                    &format!("Export '{:}' is not defined", name),
+                )?;
+            }
+        }
+        self.adapt_directive_prologue(&mut program.body);
+        self.next(false)?;
+        program.source_type = self.options.source_type.clone();
+        program.span = self.finish_node(program.span);
+        Ok(program)
+    }
+
+    /// Whether the current `let` starts a lexical declaration. When
+    /// `context` is set only a Statement is allowed, so the ambiguous
+    /// cases are treated as an identifier named `let`.
+    fn is_let(&self, context: Option<&str>) -> Result<bool, ParserError> {
+        if self.options.get_ecma_version_number() < 6 || !self.is_contextual("let") {
+            return Ok(false);
+        }
+        let input = self.input.as_str();
+        let next = self.cur_token_pos as usize;
+        let next = next + skip_white_space(input, next);
+        let mut chars = input[next..].chars();
+        let next_char = match chars.next() {
+            Some(next_char) => next_char,
+            None => return Ok(false),
+        };
+        // For ambiguous cases, determine if a LexicalDeclaration (or only a
+        // Statement) is allowed here. If context is not empty then only a
+        // Statement is allowed. However, `let [` is an explicit negative
+        // lookahead for ExpressionStatement, so special-case it first.
+        if next_char == '[' || next_char == '\\' || next_char as u32 > 0xffff {
+            return Ok(true);
+        }
+        if context.is_some() {
+            return Ok(false);
+        }
+
+        if next_char == '{' {
+            return Ok(true);
+        }
+        if is_identifier_start(next_char as i32, true) {
+            let mut end = next + next_char.len_utf8();
+            for ch in chars {
+                if ch == '\\' || ch as u32 > 0xffff {
+                    return Ok(true);
+                }
+                if !is_identifier_char(ch as i32, true) {
+                    break;
+                }
+                end += ch.len_utf8();
+            }
+            let ident = &input[next..end];
+            if !matches!(ident, "in" | "instanceof") {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Check if the current token starts an `async function` declaration,
+    /// without a line break between the two keywords.
+    fn is_async_function(&self) -> Result<bool, ParserError> {
+        if self.options.get_ecma_version_number() < 8 || !self.is_contextual("async") {
+            return Ok(false);
+        }
+        let input = self.input.as_str();
+        let pos = self.cur_token_pos as usize;
+        let next = pos + skip_white_space(input, pos);
+        if get_line_break_regex().is_match(&input[pos..next])?
+            || !input[next..].starts_with("function")
+        {
+            return Ok(false);
+        }
+        Ok(match input[next + 8..].chars().next() {
+            Some(after) => !is_identifier_char(after as i32, true),
+            None => true,
+        })
+    }
+
+    /// Parse a single statement.
+    ///
+    /// If expecting a statement and finding a slash operator, parse a
+    /// regular expression literal. This is to handle cases like
+    /// `if (foo) /blah/.exec(foo)`, where looking at the previous token
+    /// does not help.
+    fn parse_statement(
+        &mut self,
+        context: Option<&str>,
+        top_level: bool,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<Statement, ParserError> {
+        let token_types = get_token_types();
+        let mut start_type = self.cur_token_type.clone();
+        let span = self.start_node();
+        let mut kind = None;
+
+        if self.is_let(context)? {
+            start_type = token_types._var.clone();
+            kind = Some(VariableDeclarationKind::Let);
+        }
+
+        // Most types of statements are recognized by the keyword they
+        // start with. Many are trivial to parse, some require a bit of
+        // complexity.
+        if start_type.eq(&token_types._break) || start_type.eq(&token_types._continue) {
+            let keyword = start_type.keyword.clone().unwrap_or_default();
+            self.parse_break_continue_statement(span, &keyword)
+        } else if start_type.eq(&token_types._debugger) {
+            Ok(Statement::DebuggerStatement(Box::new(
+                self.parse_debugger_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types._do) {
+            Ok(Statement::DoWhileStatement(Box::new(
+                self.parse_do_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types._for) {
+            self.parse_for_statement(span)
+        } else if start_type.eq(&token_types._function) {
+            // Function as sole body of either an if statement or a labeled
+            // statement works, but not when it is part of a labeled statement
+            // that is the sole body of an if statement.
+            if let Some(context) = context {
+                if (self.is_strict || context != "if" && context != "label")
+                    && self.options.get_ecma_version_number() >= 6
+                {
+                    self.unexpected(None)?;
+                }
+            }
+            Ok(Statement::FunctionDeclaration(Box::new(
+                self.parse_function_statement(span, false, context.is_none())?,
+            )))
+        } else if start_type.eq(&token_types._class) {
+            if context.is_some() {
+                self.unexpected(None)?;
+            }
+            Ok(Statement::ClassDeclaration(Box::new(
+                self.parse_class(span, true, false)?,
+            )))
+        } else if start_type.eq(&token_types._if) {
+            Ok(Statement::IfStatement(Box::new(
+                self.parse_if_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types._return) {
+            Ok(Statement::ReturnStatement(Box::new(
+                self.parse_return_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types._switch) {
+            Ok(Statement::SwitchStatement(Box::new(
+                self.parse_switch_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types._throw) {
+            Ok(Statement::ThrowStatement(Box::new(
+                self.parse_throw_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types._try) {
+            Ok(Statement::TryStatement(Box::new(
+                self.parse_try_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types._const) || start_type.eq(&token_types._var) {
+            let kind = kind.unwrap_or(if start_type.eq(&token_types._const) {
+                VariableDeclarationKind::Const
+            } else {
+                VariableDeclarationKind::Var
+            });
+            if context.is_some() && kind != VariableDeclarationKind::Var {
+                self.unexpected(None)?;
+            }
+            Ok(Statement::VariableDeclaration(Box::new(
+                self.parse_var_statement(span, kind)?,
+            )))
+        } else if start_type.eq(&token_types._while) {
+            Ok(Statement::WhileStatement(Box::new(
+                self.parse_while_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types._with) {
+            Ok(Statement::WithStatement(Box::new(
+                self.parse_with_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types.brace_l) {
+            Ok(Statement::BlockStatement(Box::new(self.parse_block(
+                true,
+                Some(span),
+                false,
+            )?)))
+        } else if start_type.eq(&token_types.semi) {
+            Ok(Statement::EmptyStatement(Box::new(
+                self.parse_empty_statement(span)?,
+            )))
+        } else if start_type.eq(&token_types._export) || start_type.eq(&token_types._import) {
+            if self.options.get_ecma_version_number() > 10 && start_type.eq(&token_types._import) {
+                let next = self.cur_token_pos as usize;
+                let next = next + skip_white_space(&self.input, next);
+                // `import(...)` and `import.meta` start an expression statement.
+                if let Some('(' | '.') = self.input[next..].chars().next() {
+                    let expr = self.parse_expression(false, &mut None)?;
+                    return Ok(Statement::ExpressionStatement(Box::new(
+                        self.parse_expression_statement(span, expr)?,
+                    )));
+                }
+            }
+
+            if !self.options.allow_import_export_everywhere {
+                if !top_level {
+                    return Err(self
+                        .syntax_error(
+                            self.cur_token_start,
+                            ErrorCode::ImportExportNotTopLevel,
+                            "'import' and 'export' may only appear at the top level",
+                        )
+                        .with_note("the `allow_import_export_everywhere` option allows them"));
+                }
+                if !self.is_in_module {
+                    return Err(self
+                        .syntax_error(
+                            self.cur_token_start,
+                            ErrorCode::ImportExportOutsideModule,
+                            "'import' and 'export' may appear only with 'sourceType: module'",
+                        )
+                        .with_note("set the `source_type` option to `SourceType::Module`"));
+                }
+            }
+            Ok(Statement::ModuleDeclaration(
+                if start_type.eq(&token_types._import) {
+                    ModuleDeclaration::ImportDeclaration(Box::new(self.parse_import(span)?))
+                } else {
+                    self.parse_export(span, exports)?
+                },
+            ))
+        } else {
+            if self.is_async_function()? {
+                if context.is_some() {
+                    self.unexpected(None)?;
+                }
+                self.next(false)?;
+                return Ok(Statement::FunctionDeclaration(Box::new(
+                    self.parse_function_statement(span, true, context.is_none())?,
+                )));
+            }
+
+            // If the statement does not start with a statement keyword or a
+            // brace, it's an ExpressionStatement or LabeledStatement. We
+            // simply start parsing an expression, and afterwards, if the
+            // next token is a colon and the expression was a simple
+            // Identifier node, we switch to interpreting it as a label.
+            let maybe_name = self.cur_token_value.as_str().to_owned();
+            let expr = self.parse_expression(false, &mut None)?;
+            match expr {
+                Expression::Identifier(label)
+                    if start_type.eq(&token_types.name) && self.eat(&token_types.colon)? =>
+                {
+                    Ok(Statement::LabeledStatement(Box::new(
+                        self.parse_labeled_statement(span, &maybe_name, *label, context)?,
+                    )))
+                }
+                expr => Ok(Statement::ExpressionStatement(Box::new(
+                    self.parse_expression_statement(span, expr)?,
+                ))),
+            }
+        }
+    }
+
+    fn parse_break_continue_statement(
+        &mut self,
+        span: Span,
+        keyword: &str,
+    ) -> Result<Statement, ParserError> {
+        let token_types = get_token_types();
+        let is_break = keyword == "break";
+        self.next(false)?;
+        let label = if self.eat(&token_types.semi)? || self.insert_semicolon() {
+            None
+        } else if self.cur_token_type.ne(&token_types.name) {
+            self.unexpected(None)?;
+            None
+        } else {
+            let label = self.parse_ident(false)?;
+            self.semicolon()?;
+            Some(label)
+        };
+
+        // Verify that there is an actual destination to break or
+        // continue to.
+        let label_name = label.as_ref().map(|label| label.name.clone());
+        let has_destination = self.labels.iter().any(|label| {
+            if label_name.is_none() || label.name == label_name {
+                if label.kind.is_some() && (is_break || label.kind == Some(LabelKind::Loop)) {
+                    return true;
+                }
+                if label_name.is_some() && is_break {
+                    return true;
+                }
+            }
+            false
+        });
+        if !has_destination {
+            self.raise(
+                span.start,
+                ErrorCode::UnsyntacticBreakContinue,
+                &format!("Unsyntactic {:}", keyword),
+            )?;
+        }
+        let span = self.finish_node(span);
+        Ok(if is_break {
+            Statement::BreakStatement(Box::new(BreakStatement { span, label }))
+        } else {
+            Statement::ContinueStatement(Box::new(ContinueStatement { span, label }))
+        })
+    }
+
+    fn parse_debugger_statement(&mut self, span: Span) -> Result<DebuggerStatement, ParserError> {
+        self.next(false)?;
+        self.semicolon()?;
+        Ok(DebuggerStatement {
+            span: self.finish_node(span),
+        })
+    }
+
+    fn parse_do_statement(&mut self, span: Span) -> Result<DoWhileStatement, ParserError> {
+        let token_types = get_token_types();
+        self.next(false)?;
+        self.labels.push(Label::anonymous(LabelKind::Loop));
+        let body = self.parse_statement(Some("do"), false, &mut None)?;
+        self.labels.pop();
+        self.expect(&token_types._while)?;
+        let test = self.parse_paren_expression()?;
+        if self.options.get_ecma_version_number() >= 6 {
+            self.eat(&token_types.semi)?;
+        } else {
+            self.semicolon()?;
+        }
+        Ok(DoWhileStatement {
+            span: self.finish_node(span),
+            body,
+            test,
+        })
+    }
+
+    /// Disambiguating between a `for` and a `for`/`in` or `for`/`of`
+    /// loop is non-trivial. Basically, we have to parse the init `var`
+    /// statement or expression, disallowing the `in` operator (see
+    /// the second parameter to `parse_expression`), and then check
+    /// whether the next token is `in` or `of`. When there is no init
+    /// part (semicolon immediately after the opening parenthesis), it
+    /// is a regular `for` loop.
+    fn parse_for_statement(&mut self, span: Span) -> Result<Statement, ParserError> {
+        let token_types = get_token_types();
+        let ecma_version = self.options.get_ecma_version_number();
+        self.next(false)?;
+        let await_at = if ecma_version >= 9 && self.can_await() && self.eat_contextual("await")? {
+            self.last_token_start
+        } else {
+            -1
+        };
+        self.labels.push(Label::anonymous(LabelKind::Loop));
+        self.enter_scope(0);
+        self.expect(&token_types.paren_l)?;
+        if self.cur_token_type.eq(&token_types.semi) {
+            if await_at > -1 {
+                self.unexpected(Some(await_at))?;
+            }
+            return Ok(Statement::ForStatement(Box::new(
+                self.parse_for(span, None)?,
+            )));
+        }
+        let is_let = self.is_let(None)?;
+        let start_type = self.cur_token_type.clone();
+        if start_type.eq(&token_types._var) || start_type.eq(&token_types._const) || is_let {
+            let init_span = self.start_node();
+            let kind = if is_let {
+                VariableDeclarationKind::Let
+            } else if start_type.eq(&token_types._const) {
+                VariableDeclarationKind::Const
+            } else {
+                VariableDeclarationKind::Var
+            };
+            self.next(false)?;
+            let declarations = self.parse_var(true, kind)?;
+            let init = VariableDeclaration {
+                span: self.finish_node(init_span),
+                declarations,
+                kind,
+            };
+            if (self.cur_token_type.eq(&token_types._in)
+                || (ecma_version >= 6 && self.is_contextual("of")))
+                && init.declarations.len() == 1
+            {
+                let mut is_await = false;
+                if ecma_version >= 9 {
+                    if self.cur_token_type.eq(&token_types._in) {
+                        if await_at > -1 {
+                            self.unexpected(Some(await_at))?;
+                        }
+                    } else {
+                        is_await = await_at > -1;
+                    }
+                }
+                return self.parse_for_in(
+                    span,
+                    ForLeft::VariableDeclaration(Box::new(init)),
+                    is_await,
+                );
+            }
+            if await_at > -1 {
+                self.unexpected(Some(await_at))?;
+            }
+            return Ok(Statement::ForStatement(Box::new(self.parse_for(
+                span,
+                Some(ForInit::VariableDeclaration(Box::new(init))),
+            )?)));
+        }
+        let starts_with_let = self.is_contextual("let");
+        let contains_esc = self.contains_esc;
+        let init_pos = self.cur_token_start;
+        let mut destructuring_errors = Some(DestructuringErrors::default());
+        let init = if await_at > -1 {
+            // `for await (async of x)` must not start an async arrow function.
+            self.is_potential_arrow_in_for_await = true;
+            let init = self.parse_expr_subscripts(&mut destructuring_errors, true);
+            self.is_potential_arrow_in_for_await = false;
+            init?
+        } else {
+            self.parse_expression(true, &mut destructuring_errors)?
+        };
+        let is_for_in = self.cur_token_type.eq(&token_types._in);
+        let is_for_of = !is_for_in && ecma_version >= 6 && self.is_contextual("of");
+        if is_for_in || is_for_of {
+            let mut is_await = false;
+            if await_at > -1 {
+                // implies `ecma_version >= 9` (see the declaration of `await_at`)
+                if is_for_in {
+                    self.unexpected(Some(await_at))?;
+                }
+                is_await = true;
+            } else if is_for_of && ecma_version >= 8 {
+                // `for (async of x)` is ambiguous with an async arrow function.
+                if init.span().start == init_pos
+                    && !contains_esc
+                    && matches!(&init, Expression::Identifier(id) if id.name == "async")
+                {
+                    self.unexpected(None)?;
+                }
+            }
+            if starts_with_let && is_for_of {
+                self.raise(
+                    init.span().start,
+                    ErrorCode::LetInForOf,
+                    "The left-hand side of a for-of loop may not start with 'let'.",
+                )?;
+            }
+            let init = self.to_assignable(init, false, &destructuring_errors)?;
+            self.check_lval_pattern(&init, BIND_NONE, &mut None)?;
+            return self.parse_for_in(span, ForLeft::Pattern(init), is_await);
+        } else {
+            self.check_expression_errors(&destructuring_errors, true)?;
+        }
+        if await_at > -1 {
+            self.unexpected(Some(await_at))?;
+        }
+        Ok(Statement::ForStatement(Box::new(
+            self.parse_for(span, Some(ForInit::Expression(init)))?,
+        )))
+    }
+
+    fn parse_function_statement(
+        &mut self,
+        span: Span,
+        is_async: bool,
+        declaration_position: bool,
+    ) -> Result<Function, ParserError> {
+        self.next(false)?;
+        let hanging = if declaration_position {
+            0
+        } else {
+            FUNC_HANGING_STATEMENT
+        };
+        self.parse_function(span, FUNC_STATEMENT | hanging, is_async)
+    }
+
+    fn parse_if_statement(&mut self, span: Span) -> Result<IfStatement, ParserError> {
+        let token_types = get_token_types();
+        self.next(false)?;
+        let test = self.parse_paren_expression()?;
+        // allow function declarations in branches, but only in non-strict mode
+        let consequent = self.parse_statement(Some("if"), false, &mut None)?;
+        let alternate = if self.eat(&token_types._else)? {
+            Some(self.parse_statement(Some("if"), false, &mut None)?)
+        } else {
+            None
+        };
+        Ok(IfStatement {
+            span: self.finish_node(span),
+            test,
+            consequent,
+            alternate,
+        })
+    }
+
+    fn parse_return_statement(&mut self, span: Span) -> Result<ReturnStatement, ParserError> {
+        let token_types = get_token_types();
+        if !self.in_function() && !self.options.allow_return_outside_function {
+            return Err(self
+                .syntax_error(
+                    self.cur_token_start,
+                    ErrorCode::ReturnOutsideFunction,
+                    "'return' outside of function",
+                )
+                .with_note("the `allow_return_outside_function` option allows it"));
+        }
+        self.next(false)?;
+
+        // In `return` (and `break`/`continue`), the keywords with
+        // optional arguments, we eagerly look for a semicolon or the
+        // possibility to insert one.
+        let argument = if self.eat(&token_types.semi)? || self.insert_semicolon() {
+            None
+        } else {
+            let argument = self.parse_expression(false, &mut None)?;
+            self.semicolon()?;
+            Some(argument)
+        };
+        Ok(ReturnStatement {
+            span: self.finish_node(span),
+            argument,
+        })
+    }
+
+    fn parse_switch_statement(&mut self, span: Span) -> Result<SwitchStatement, ParserError> {
+        let token_types = get_token_types();
+        self.next(false)?;
+        let discriminant = self.parse_paren_expression()?;
+        let mut cases = vec![];
+        self.expect(&token_types.brace_l)?;
+        self.labels.push(Label::anonymous(LabelKind::Switch));
+        self.enter_scope(0);
+
+        // Statements under must be grouped (by label) in SwitchCase
+        // nodes. `cur` is used to keep the node that we are currently
+        // adding statements to.
+        let mut cur: Option<SwitchCase> = None;
+        let mut saw_default = false;
+        while self.cur_token_type.ne(&token_types.brace_r) {
+            let cur_token_type = self.cur_token_type.clone();
+            if cur_token_type.eq(&token_types._case) || cur_token_type.eq(&token_types._default) {
+                let is_case = cur_token_type.eq(&token_types._case);
+                if let Some(case) = cur.take() {
+                    cases.push(SwitchCase {
+                        span: self.finish_node(case.span),
+                        ..case
+                    });
+                }
+                let case_span = self.start_node();
+                self.next(false)?;
+                let test = if is_case {
+                    Some(self.parse_expression(false, &mut None)?)
+                } else {
+                    if saw_default {
+                        self.raise_recoverable(
+                            self.last_token_start,
+                            ErrorCode::DuplicateDefaultClause,
+                            "Multiple default clauses",
+                        )?;
+                    }
+                    saw_default = true;
+                    None
+                };
+                self.expect(&token_types.colon)?;
+                cur = Some(SwitchCase {
+                    span: case_span,
+                    consequent: vec![],
+                    test,
+                });
+            } else {
+                match cur.as_mut() {
+                    Some(case) => case
+                        .consequent
+                        .push(self.parse_statement(None, false, &mut None)?),
+                    None => self.unexpected(None)?,
+                }
+            }
+        }
+        self.exit_scope();
+        if let Some(case) = cur {
+            cases.push(SwitchCase {
+                span: self.finish_node(case.span),
+                ..case
+            });
+        }
+        // Closing brace
+        self.next(false)?;
+        self.labels.pop();
+        Ok(SwitchStatement {
+            span: self.finish_node(span),
+            discriminant,
+            cases,
+        })
+    }
+
+    fn parse_throw_statement(&mut self, span: Span) -> Result<ThrowStatement, ParserError> {
+        self.next(false)?;
+        let between = &self.input[self.last_token_end as usize..self.cur_token_start as usize];
+        if get_line_break_regex().is_match(between)? {
+            self.raise(
+                self.last_token_end,
+                ErrorCode::NewlineAfterThrow,
+                "Illegal newline after throw",
+            )?;
+        }
+        let argument = self.parse_expression(false, &mut None)?;
+        self.semicolon()?;
+        Ok(ThrowStatement {
+            span: self.finish_node(span),
+            argument,
+        })
+    }
+
+    fn parse_catch_clause_param(&mut self) -> Result<Pattern, ParserError> {
+        let param = self.parse_binding_atom()?;
+        let simple = matches!(param, Pattern::Identifier(_));
+        self.enter_scope(if simple { SCOPE_SIMPLE_CATCH } else { 0 });
+        self.check_lval_pattern(
+            &param,
+            if simple {
+                BIND_SIMPLE_CATCH
+            } else {
+                BIND_LEXICAL
+            },
+            &mut None,
+        )?;
+        self.expect(&get_token_types().paren_r)?;
+        Ok(param)
+    }
+
+    fn parse_try_statement(&mut self, span: Span) -> Result<TryStatement, ParserError> {
+        let token_types = get_token_types();
+        self.next(false)?;
+        let block = self.parse_block(true, None, false)?;
+        let mut handler = None;
+        if self.cur_token_type.eq(&token_types._catch) {
+            let clause_span = self.start_node();
+            self.next(false)?;
+            let param = if self.eat(&token_types.paren_l)? {
+                Some(self.parse_catch_clause_param()?)
+            } else {
+                if self.options.get_ecma_version_number() < 10 {
+                    self.unexpected(None)?;
+                }
+                self.enter_scope(0);
+                None
+            };
+            let body = self.parse_block(false, None, false)?;
+            self.exit_scope();
+            handler = Some(CatchClause {
+                span: self.finish_node(clause_span),
+                param,
+                body,
+            });
+        }
+        let finalizer = if self.eat(&token_types._finally)? {
+            Some(self.parse_block(true, None, false)?)
+        } else {
+            None
+        };
+        if handler.is_none() && finalizer.is_none() {
+            self.raise(
+                span.start,
+                ErrorCode::MissingCatchOrFinally,
+                "Missing catch or finally clause",
+            )?;
+        }
+        Ok(TryStatement {
+            span: self.finish_node(span),
+            block,
+            handler,
+            finalizer,
+        })
+    }
+
+    fn parse_var_statement(
+        &mut self,
+        span: Span,
+        kind: VariableDeclarationKind,
+    ) -> Result<VariableDeclaration, ParserError> {
+        self.next(false)?;
+        let declarations = self.parse_var(false, kind)?;
+        self.semicolon()?;
+        Ok(VariableDeclaration {
+            span: self.finish_node(span),
+            declarations,
+            kind,
+        })
+    }
+
+    fn parse_while_statement(&mut self, span: Span) -> Result<WhileStatement, ParserError> {
+        self.next(false)?;
+        let test = self.parse_paren_expression()?;
+        self.labels.push(Label::anonymous(LabelKind::Loop));
+        let body = self.parse_statement(Some("while"), false, &mut None)?;
+        self.labels.pop();
+        Ok(WhileStatement {
+            span: self.finish_node(span),
+            test,
+            body,
+        })
+    }
+
+    fn parse_with_statement(&mut self, span: Span) -> Result<WithStatement, ParserError> {
+        if self.is_strict {
+            self.raise(
+                self.cur_token_start,
+                ErrorCode::StrictWith,
+                "'with' in strict mode",
+            )?;
+        }
+        self.next(false)?;
+        let object = self.parse_paren_expression()?;
+        let body = self.parse_statement(Some("with"), false, &mut None)?;
+        Ok(WithStatement {
+            span: self.finish_node(span),
+            object,
+            body,
+        })
+    }
+
+    fn parse_empty_statement(&mut self, span: Span) -> Result<EmptyStatement, ParserError> {
+        self.next(false)?;
+        Ok(EmptyStatement {
+            span: self.finish_node(span),
+        })
+    }
+
+    fn parse_labeled_statement(
+        &mut self,
+        span: Span,
+        maybe_name: &str,
+        label: Identifier,
+        context: Option<&str>,
+    ) -> Result<LabeledStatement, ParserError> {
+        let token_types = get_token_types();
+        let is_declared = self
+            .labels
+            .iter()
+            .any(|label| label.name.as_deref() == Some(maybe_name));
+        if is_declared {
+            self.raise(
+                label.span.start,
+                ErrorCode::DuplicateLabel,
+                &format!("Label '{:}' is already declared", maybe_name),
+            )?;
+        }
+        let cur_token_type = self.cur_token_type.clone();
+        let kind = if cur_token_type.is_loop {
+            Some(LabelKind::Loop)
+        } else if cur_token_type.eq(&token_types._switch) {
+            Some(LabelKind::Switch)
+        } else {
+            None
+        };
+        let statement_start = self.cur_token_start;
+        {
+            for label in self.labels.iter_mut().rev() {
+                if label.statement_start == span.start {
+                    // Update information about previous labels on this node
+                    label.statement_start = statement_start;
+                    label.kind = kind;
+                } else {
+                    break;
+                }
+            }
+            self.labels.push(Label {
+                name: Some(label.name.clone()),
+                kind,
+                statement_start,
+            });
+        }
+        let context = match context {
+            Some(context) if context.contains("label") => context.to_owned(),
+            Some(context) => format!("{:}label", context),
+            None => "label".to_owned(),
+        };
+        let body = self.parse_statement(Some(&context), false, &mut None)?;
+        self.labels.pop();
+        Ok(LabeledStatement {
+            span: self.finish_node(span),
+            body,
+            label,
+        })
+    }
+
+    fn parse_expression_statement(
+        &mut self,
+        span: Span,
+        expr: Expression,
+    ) -> Result<ExpressionStatement, ParserError> {
+        self.semicolon()?;
+        Ok(ExpressionStatement {
+            span: self.finish_node(span),
+            expression: expr,
+            directive: None,
+        })
+    }
+
+    /// Parse a brace-enclosed block of statements. `exit_strict` resets
+    /// strict mode once the block is closed, for function bodies that
+    /// switched it on with a directive.
+    fn parse_block(
+        &mut self,
+        create_new_lexical_scope: bool,
+        span: Option<Span>,
+        exit_strict: bool,
+    ) -> Result<BlockStatement, ParserError> {
+        let token_types = get_token_types();
+        let span = span.unwrap_or_else(|| self.start_node());
+        let mut body = vec![];
+        self.expect(&token_types.brace_l)?;
+        if create_new_lexical_scope {
+            self.enter_scope(0);
+        }
+        while self.cur_token_type.ne(&token_types.brace_r) {
+            let statement = self.parse_statement(None, false, &mut None)?;
+            body.push(statement);
+        }
+        if exit_strict {
+            self.is_strict = false;
+        }
+        self.next(false)?;
+        if create_new_lexical_scope {
+            self.exit_scope();
+        }
+        Ok(BlockStatement {
+            span: self.finish_node(span),
+            body,
+        })
+    }
+
+    /// Parse a regular `for` loop. The disambiguation code in
+    /// `parse_for_statement` will already have parsed the init statement or
+    /// expression.
+    fn parse_for(
+        &mut self,
+        span: Span,
+        init: Option<ForInit>,
+    ) -> Result<ForStatement, ParserError> {
+        let token_types = get_token_types();
+        self.expect(&token_types.semi)?;
+        let test = if self.cur_token_type.eq(&token_types.semi) {
+            None
+        } else {
+            Some(self.parse_expression(false, &mut None)?)
+        };
+        self.expect(&token_types.semi)?;
+        let update = if self.cur_token_type.eq(&token_types.paren_r) {
+            None
+        } else {
+            Some(self.parse_expression(false, &mut None)?)
+        };
+        self.expect(&token_types.paren_r)?;
+        let body = self.parse_statement(Some("for"), false, &mut None)?;
+        self.exit_scope();
+        self.labels.pop();
+        Ok(ForStatement {
+            span: self.finish_node(span),
+            init,
+            test,
+            update,
+            body,
+        })
+    }
+
+    /// Parse a `for`/`in` and `for`/`of` loop, which are almost
+    /// same from parser's perspective.
+    fn parse_for_in(
+        &mut self,
+        span: Span,
+        left: ForLeft,
+        is_await: bool,
+    ) -> Result<Statement, ParserError> {
+        let token_types = get_token_types();
+        let is_for_in = self.cur_token_type.eq(&token_types._in);
+        self.next(false)?;
+
+        if let ForLeft::VariableDeclaration(init) = &left {
+            if let Some(declaration) = init.declarations.first() {
+                let is_identifier = matches!(declaration.id, Pattern::Identifier(_));
+                if declaration.init.is_some()
+                    && (!is_for_in
+                        || self.options.get_ecma_version_number() < 8
+                        || self.is_strict
+                        || init.kind != VariableDeclarationKind::Var
+                        || !is_identifier)
+                {
+                    self.raise(
+                        init.span.start,
+                        ErrorCode::ForInOfInitializer,
+                        &format!(
+                            "{:} loop variable declaration may not have an initializer",
+                            if is_for_in { "for-in" } else { "for-of" }
+                        ),
+                    )?;
+                }
+            }
+        }
+        let right = if is_for_in {
+            self.parse_expression(false, &mut None)?
+        } else {
+            self.parse_maybe_assign(false, &mut None, None)?
+        };
+        self.expect(&token_types.paren_r)?;
+        let body = self.parse_statement(Some("for"), false, &mut None)?;
+        self.exit_scope();
+        self.labels.pop();
+        let span = self.finish_node(span);
+        Ok(if is_for_in {
+            Statement::ForInStatement(Box::new(ForInStatement {
+                span,
+                left,
+                right,
+                body,
+            }))
+        } else {
+            Statement::ForOfStatement(Box::new(ForOfStatement {
+                span,
+                is_await,
+                left,
+                right,
+                body,
+            }))
+        })
+    }
+
+    /// Parse a list of variable declarations.
+    fn parse_var(
+        &mut self,
+        is_for: bool,
+        kind: VariableDeclarationKind,
+    ) -> Result<Vec<VariableDeclarator>, ParserError> {
+        let token_types = get_token_types();
+        let mut declarations = vec![];
+        loop {
+            let span = self.start_node();
+            let id = self.parse_var_id(kind)?;
+            let is_for_in_or_of = self.cur_token_type.eq(&token_types._in)
+                || (self.options.get_ecma_version_number() >= 6 && self.is_contextual("of"));
+            let init = if self.eat(&token_types.eq)? {
+                Some(self.parse_maybe_assign(is_for, &mut None, None)?)
+            } else if kind == VariableDeclarationKind::Const && !is_for_in_or_of {
+                self.unexpected(None)?;
+                None
+            } else if !matches!(id, Pattern::Identifier(_))
+                && !(is_for
+                    && (self.cur_token_type.eq(&token_types._in) || self.is_contextual("of")))
+            {
+                self.raise(
+                    self.last_token_end,
+                    ErrorCode::InvalidDestructuring,
+                    "Complex binding patterns require an initialization value",
+                )?;
+                None
+            } else {
+                None
+            };
+            declarations.push(VariableDeclarator {
+                span: self.finish_node(span),
+                id,
+                init,
+            });
+            if !self.eat(&token_types.comma)? {
+                break;
+            }
+        }
+        Ok(declarations)
+    }
+
+    fn parse_var_id(&mut self, kind: VariableDeclarationKind) -> Result<Pattern, ParserError> {
+        let id = self.parse_binding_atom()?;
+        self.check_lval_pattern(
+            &id,
+            if kind == VariableDeclarationKind::Var {
+                BIND_VAR
+            } else {
+                BIND_LEXICAL
+            },
+            &mut None,
+        )?;
+        Ok(id)
+    }
+
+    /// Parse a function declaration or literal (depending on the
+    /// `statement & FUNC_STATEMENT`).
+    fn parse_function(
+        &mut self,
+        span: Span,
+        statement: i32,
+        is_async: bool,
+    ) -> Result<Function, ParserError> {
+        let token_types = get_token_types();
+        let ecma_version = self.options.get_ecma_version_number();
+        let mut generator = false;
+        if ecma_version >= 9 || ecma_version >= 6 && !is_async {
+            if self.cur_token_type.eq(&token_types.star) && (statement & FUNC_HANGING_STATEMENT) > 0
+            {
+                self.unexpected(None)?;
+            }
+            generator = self.eat(&token_types.star)?;
+        }
+        let is_async = ecma_version >= 8 && is_async;
+
+        let mut id = None;
+        if (statement & FUNC_STATEMENT) > 0 {
+            id = if (statement & FUNC_NULLABLE_ID) > 0 && self.cur_token_type.ne(&token_types.name)
+            {
+                None
+            } else {
+                Some(self.parse_ident(false)?)
+            };
+            if let Some(id) = &id {
+                if (statement & FUNC_HANGING_STATEMENT) == 0 {
+                    // If it is a regular function declaration in sloppy mode,
+                    // then it is subject to Annex B semantics
+                    // (BIND_FUNCTION). Otherwise, the binding mode depends on
+                    // properties of the current scope (see
+                    // `treat_functions_as_var_in_scope`).
+                    let binding_type = if self.is_strict || generator || is_async {
+                        let treat_functions_as_var = match self.current_scope() {
+                            Some(scope) => self.treat_functions_as_var_in_scope(scope),
+                            None => false,
+                        };
+                        if treat_functions_as_var {
+                            BIND_VAR
+                        } else {
+                            BIND_LEXICAL
+                        }
+                    } else {
+                        BIND_FUNCTION
+                    };
+                    self.check_lval_identifier(id, binding_type, &mut None)?;
+                }
+            }
+        }
+
+        let old_yield_pos = self.yield_pos;
+        let old_await_pos = self.await_pos;
+        let old_await_ident_pos = self.await_ident_pos;
+        self.yield_pos = None;
+        self.await_pos = None;
+        self.await_ident_pos = 0;
+        self.enter_scope(function_flags(is_async, generator));
+
+        if (statement & FUNC_STATEMENT) == 0 {
+            id = if self.cur_token_type.eq(&token_types.name) {
+                Some(self.parse_ident(false)?)
+            } else {
+                None
+            };
+        }
+
+        let params = self.parse_function_params()?;
+        let body = self.parse_function_body(span.start, id.as_ref(), &params, false, false)?;
+
+        self.yield_pos = old_yield_pos;
+        self.await_pos = old_await_pos;
+        self.await_ident_pos = old_await_ident_pos;
+        Ok(Function {
+            span: self.finish_node(span),
+            id,
+            generator,
+            is_async,
+            params,
+            body,
+        })
+    }
+
+    fn parse_function_params(&mut self) -> Result<Vec<Pattern>, ParserError> {
+        let token_types = get_token_types();
+        self.expect(&token_types.paren_l)?;
+        let params = self.parse_binding_list(
+            &token_types.paren_r,
+            false,
+            self.options.get_ecma_version_number() >= 8,
+        )?;
+        self.check_yield_await_in_default_params()?;
+        Ok(params.into_iter().flatten().collect())
+    }
+
+    /// Parse a class declaration or literal (depending on the
+    /// `is_statement` parameter).
+    fn parse_class(
+        &mut self,
+        span: Span,
+        is_statement: bool,
+        nullable_id: bool,
+    ) -> Result<Class, ParserError> {
+        let token_types = get_token_types();
+        self.next(false)?;
+
+        // ecma-262 14.6 Class Definitions
+        // A class definition is always strict mode code.
+        let old_strict = self.is_strict;
+        self.is_strict = true;
+
+        let id = self.parse_class_id(is_statement, nullable_id)?;
+        let super_class = self.parse_class_super()?;
+        self.enter_class_body();
+        let body_span = self.start_node();
+        let mut body = vec![];
+        let mut constructor_key: Option<Span> = None;
+        self.expect(&token_types.brace_l)?;
+        while self.cur_token_type.ne(&token_types.brace_r) {
+            let element = match self.parse_class_element(super_class.is_some())? {
+                Some(element) => element,
+                None => continue,
+            };
+            let key = match &element {
+                ClassElement::MethodDefinition(method)
+                    if method.kind == MethodDefinitionKind::Constructor =>
+                {
+                    match &constructor_key {
+                        Some(first) => self.recover(
+                            self.syntax_error(
+                                method.span.start,
+                                ErrorCode::DuplicateConstructor,
+                                "Duplicate constructor in the same class",
+                            )
+                            .with_end(method.key.span().end)
+                            .with_label(
+                                first.start,
+                                first.end,
+                                "first constructor here",
+                            ),
+                        )?,
+                        None => constructor_key = Some(method.key.span().clone()),
+                    }
+                    None
+                }
+                ClassElement::MethodDefinition(method) => Some(&method.key),
+                ClassElement::PropertyDefinition(field) => Some(&field.key),
+                ClassElement::StaticBlock(_) => None,
+            };
+            if let Some(Expression::PrivateIdentifier(key)) = key {
+                let is_conflicted = match self.private_name_stack.last_mut() {
+                    Some(scope) => is_private_name_conflicted(&mut scope.declared, &element),
+                    None => false,
+                };
+                if is_conflicted {
+                    self.raise_recoverable(
+                        key.span.start,
+                        ErrorCode::DuplicatePrivateName,
+                        &format!("Identifier '#{:}' has already been declared", key.name),
+                    )?;
+                }
+            }
+            body.push(element);
+        }
+        self.is_strict = old_strict;
+        self.next(false)?;
+        let body = ClassBody {
+            span: self.finish_node(body_span),
+            body,
+        };
+        self.exit_class_body()?;
+        Ok(Class {
+            span: self.finish_node(span),
+            id,
+            super_class,
+            body,
+        })
+    }
+
+    fn parse_class_element(
+        &mut self,
+        constructor_allows_super: bool,
+    ) -> Result<Option<ClassElement>, ParserError> {
+        let token_types = get_token_types();
+        if self.eat(&token_types.semi)? {
+            return Ok(None);
+        }
+
+        let ecma_version = self.options.get_ecma_version_number();
+        let span = self.start_node();
+        let mut key_name = "";
+        let mut is_generator = false;
+        let mut is_async = false;
+        let mut kind = MethodDefinitionKind::Method;
+        let mut is_static = false;
+
+        if self.eat_contextual("static")? {
+            // Parse static init block
+            if ecma_version >= 13 && self.eat(&token_types.brace_l)? {
+                return Ok(Some(ClassElement::StaticBlock(Box::new(
+                    self.parse_class_static_block(span)?,
+                ))));
+            }
+            if self.is_class_element_name_start() || self.cur_token_type.eq(&token_types.star) {
+                is_static = true;
+            } else {
+                key_name = "static";
+            }
+        }
+        if key_name.is_empty() && ecma_version >= 8 && self.eat_contextual("async")? {
+            if (self.is_class_element_name_start() || self.cur_token_type.eq(&token_types.star))
+                && !self.can_insert_semicolon()
+            {
+                is_async = true;
+            } else {
+                key_name = "async";
+            }
+        }
+        if key_name.is_empty() && (ecma_version >= 9 || !is_async) && self.eat(&token_types.star)? {
+            is_generator = true;
+        }
+        if key_name.is_empty() && !is_async && !is_generator {
+            let last_value = self.cur_token_value.as_str().to_owned();
+            if self.eat_contextual("get")? || self.eat_contextual("set")? {
+                let is_getter = last_value == "get";
+                if self.is_class_element_name_start() {
+                    kind = if is_getter {
+                        MethodDefinitionKind::Get
+                    } else {
+                        MethodDefinitionKind::Set
+                    };
+                } else {
+                    key_name = if is_getter { "get" } else { "set" };
+                }
+            }
+        }
+
+        // Parse element name
+        let (computed, key) = if !key_name.is_empty() {
+            // 'async', 'get', 'set', or 'static' were not a keyword
+            // contextually. The last token is any of those. Make it the
+            // element name.
+            let start_loc = self.last_token_start_loc.clone();
+            let key_span = self.start_node_at(self.last_token_start, &start_loc);
+            let end_loc = self.last_token_end_loc.clone();
+            let key = Identifier {
+                span: self.finish_node_at(key_span, self.last_token_end, &end_loc),
+                name: self.atoms.intern(key_name),
+            };
+            (false, Expression::Identifier(Box::new(key)))
+        } else {
+            self.parse_class_element_name()?
+        };
+
+        // Parse element value
+        if ecma_version < 13
+            || self.cur_token_type.eq(&token_types.paren_l)
+            || kind != MethodDefinitionKind::Method
+            || is_generator
+            || is_async
+        {
+            let is_constructor = !is_static && check_key_name(computed, &key, "constructor");
+            let allows_direct_super = is_constructor && constructor_allows_super;
+            // Couldn't move this check into the 'parse_class_method' method
+            // for backward compatibility.
+            if is_constructor && kind != MethodDefinitionKind::Method {
+                self.raise(
+                    key.span().start,
+                    ErrorCode::InvalidConstructor,
+                    "Constructor can't have get/set modifier",
+                )?;
+            }
+            let kind = if is_constructor {
+                MethodDefinitionKind::Constructor
+            } else {
+                kind
+            };
+            Ok(Some(ClassElement::MethodDefinition(Box::new(
+                self.parse_class_method(
+                    span,
+                    is_static,
+                    computed,
+                    key,
+                    kind,
+                    is_generator,
+                    is_async,
+                    allows_direct_super,
+                )?,
+            ))))
+        } else {
+            Ok(Some(ClassElement::PropertyDefinition(Box::new(
+                self.parse_class_field(span, is_static, computed, key)?,
+            ))))
+        }
+    }
+
+    fn is_class_element_name_start(&self) -> bool {
+        let token_types = get_token_types();
+        let cur_token_type = &self.cur_token_type;
+        cur_token_type.eq(&token_types.name)
+            || cur_token_type.eq(&token_types.private_id)
+            || cur_token_type.eq(&token_types.num)
+            || cur_token_type.eq(&token_types.string)
+            || cur_token_type.eq(&token_types.bracket_l)
+            || cur_token_type.keyword.is_some()
+    }
+
+    /// Parses the key of a class element, returning whether it is computed
+    /// along with the key itself.
+    fn parse_class_element_name(&mut self) -> Result<(bool, Expression), ParserError> {
+        if self.cur_token_type.eq(&get_token_types().private_id) {
+            if self.cur_token_value.as_str() == "constructor" {
+                self.raise(
+                    self.cur_token_start,
+                    ErrorCode::InvalidConstructor,
+                    "Classes can't have an element named '#constructor'",
+                )?;
+            }
+            Ok((
+                false,
+                Expression::PrivateIdentifier(Box::new(self.parse_private_ident()?)),
+            ))
+        } else {
+            self.parse_property_name()
+        }
+    }
+
+    fn parse_class_method(
+        &mut self,
+        span: Span,
+        is_static: bool,
+        computed: bool,
+        key: Expression,
+        kind: MethodDefinitionKind,
+        is_generator: bool,
+        is_async: bool,
+        allows_direct_super: bool,
+    ) -> Result<MethodDefinition, ParserError> {
+        // Check key and flags
+        let key_start = key.span().start;
+        if kind == MethodDefinitionKind::Constructor {
+            if is_generator {
+                self.raise(
+                    key_start,
+                    ErrorCode::InvalidConstructor,
+                    "Constructor can't be a generator",
+                )?;
+            }
+            if is_async {
+                self.raise(
+                    key_start,
+                    ErrorCode::InvalidConstructor,
+                    "Constructor can't be an async method",
+                )?;
+            }
+        } else if is_static && check_key_name(computed, &key, "prototype") {
+            self.raise(
+                key_start,
+                ErrorCode::StaticPrototype,
+                "Classes may not have a static property named prototype",
+            )?;
+        }
+
+        // Parse value
+        let value = self.parse_method(is_generator, is_async, allows_direct_super)?;
+
+        // Check value
+        if kind == MethodDefinitionKind::Get && !value.params.is_empty() {
+            self.raise_recoverable(
+                value.span.start,
+                ErrorCode::InvalidAccessorParams,
+                "getter should have no params",
+            )?;
+        }
+        if kind == MethodDefinitionKind::Set && value.params.len() != 1 {
+            self.raise_recoverable(
+                value.span.start,
+                ErrorCode::InvalidAccessorParams,
+                "setter should have exactly one param",
+            )?;
+        }
+        if kind == MethodDefinitionKind::Set
+            && matches!(value.params.first(), Some(Pattern::RestElement(_)))
+        {
+            self.raise_recoverable(
+                value.params[0].span().start,
+                ErrorCode::InvalidAccessorParams,
+                "Setter cannot use rest params",
+            )?;
+        }
+        Ok(MethodDefinition {
+            span: self.finish_node(span),
+            is_static,
+            computed,
+            key,
+            kind,
+            value,
+        })
+    }
+
+    fn parse_class_field(
+        &mut self,
+        span: Span,
+        is_static: bool,
+        computed: bool,
+        key: Expression,
+    ) -> Result<PropertyDefinition, ParserError> {
+        let key_start = key.span().start;
+        if check_key_name(computed, &key, "constructor") {
+            self.raise(
+                key_start,
+                ErrorCode::InvalidConstructor,
+                "Classes can't have a field named 'constructor'",
+            )?;
+        } else if is_static && check_key_name(computed, &key, "prototype") {
+            self.raise(
+                key_start,
+                ErrorCode::StaticPrototype,
+                "Classes can't have a static field named 'prototype'",
+            )?;
+        }
+
+        let value = if self.eat(&get_token_types().eq)? {
+            // To raise SyntaxError if 'arguments' exists in the initializer.
+            let in_class_field_init = self.set_in_class_field_init(true);
+            let value = self.parse_maybe_assign(false, &mut None, None);
+            self.set_in_class_field_init(in_class_field_init);
+            Some(value?)
+        } else {
+            None
+        };
+        self.semicolon()?;
+
+        Ok(PropertyDefinition {
+            span: self.finish_node(span),
+            is_static,
+            computed,
+            key,
+            value,
+        })
+    }
+
+    fn parse_class_static_block(&mut self, span: Span) -> Result<StaticBlock, ParserError> {
+        let token_types = get_token_types();
+        let old_labels = std::mem::take(&mut self.labels);
+        let mut body = vec![];
+        self.enter_scope(SCOPE_CLASS_STATIC_BLOCK | SCOPE_SUPER);
+        while self.cur_token_type.ne(&token_types.brace_r) {
+            let statement = self.parse_statement(None, false, &mut None)?;
+            body.push(statement);
+        }
+        self.next(false)?;
+        self.exit_scope();
+        self.labels = old_labels;
+
+        Ok(StaticBlock {
+            span: self.finish_node(span),
+            body,
+        })
+    }
+
+    /// A class declaration may only omit its name when `nullable_id` is set,
+    /// as in `export default class {}`.
+    fn parse_class_id(
+        &mut self,
+        is_statement: bool,
+        nullable_id: bool,
+    ) -> Result<Option<Identifier>, ParserError> {
+        if self.cur_token_type.eq(&get_token_types().name) {
+            let id = self.parse_ident(false)?;
+            if is_statement {
+                self.check_lval_identifier(&id, BIND_LEXICAL, &mut None)?;
+            }
+            Ok(Some(id))
+        } else {
+            if is_statement && !nullable_id {
+                self.unexpected(None)?;
+            }
+            Ok(None)
+        }
+    }
+
+    fn parse_class_super(&mut self) -> Result<Option<Expression>, ParserError> {
+        Ok(if self.eat(&get_token_types()._extends)? {
+            Some(self.parse_expr_subscripts(&mut None, false)?)
+        } else {
+            None
+        })
+    }
+
+    fn enter_class_body(&mut self) {
+        self.private_name_stack.push(PrivateNameScope::default());
+    }
+
+    /// Private names used in a class body must be declared by it or by an
+    /// enclosing class, so unresolved ones are handed over to the parent.
+    fn exit_class_body(&mut self) -> Result<(), ParserError> {
+        let PrivateNameScope { declared, used } = match self.private_name_stack.pop() {
+            Some(scope) => scope,
+            None => return Ok(()),
+        };
+        for id in used {
+            if declared.contains_key(&id.name) {
+                continue;
+            }
+            match self.private_name_stack.last_mut() {
+                Some(parent) => parent.used.push(id),
+                None => {
+                    return self.raise_recoverable(
+                        id.span.start,
+                        ErrorCode::UndeclaredPrivateName,
+                        &format!(
+                            "Private field '#{:}' must be declared in an enclosing class",
+                            id.name
+                        ),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses module export declaration.
+    fn parse_export(
+        &mut self,
+        span: Span,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<ModuleDeclaration, ParserError> {
+        let token_types = get_token_types();
+        self.next(false)?;
+        // export * from '...'
+        if self.eat(&token_types.star)? {
+            return Ok(ModuleDeclaration::ExportAllDeclaration(Box::new(
+                self.parse_export_all_declaration(span, exports)?,
+            )));
+        }
+        // export default ...
+        if self.eat(&token_types._default)? {
+            self.check_export(
+                exports,
+                "default",
+                self.last_token_start,
+                self.last_token_end,
+            )?;
+            let declaration = self.parse_export_default_declaration()?;
+            return Ok(ModuleDeclaration::ExportDefaultDeclaration(Box::new(
+                ExportDefaultDeclaration {
+                    span: self.finish_node(span),
+                    declaration,
+                },
+            )));
+        }
+        // export var|const|let|function|class ...
+        let (declaration, specifiers, source) = if self.should_parse_export_statement()? {
+            let declaration = self.parse_export_declaration()?;
+            match &declaration {
+                Declaration::VariableDeclaration(declaration) => {
+                    self.check_variable_export(exports, &declaration.declarations)?
+                }
+                Declaration::FunctionDeclaration(function) => {
+                    if let Some(id) = &function.id {
+                        self.check_export(exports, &id.name, id.span.start, id.span.end)?;
+                    }
+                }
+                Declaration::ClassDeclaration(class) => {
+                    if let Some(id) = &class.id {
+                        self.check_export(exports, &id.name, id.span.start, id.span.end)?;
+                    }
+                }
+            }
+            (Some(declaration), vec![], None)
+        } else {
+            // export { x, y as z } [from '...']
+            let specifiers = self.parse_export_specifiers(exports)?;
+            let source = if self.eat_contextual("from")? {
+                Some(self.parse_module_source()?)
+            } else {
+                for specifier in specifiers.iter() {
+                    match &specifier.local {
+                        ModuleExportName::Identifier(local) => {
+                            // check for keywords used as local names
+                            self.check_unreserved(local)?;
+                            // check if export is defined
+                            self.check_local_export(local);
+                        }
+                        ModuleExportName::Literal(local) => {
+                            self.raise(
+                                local.span.start,
+                                ErrorCode::InvalidExportName,
+                                "A string literal cannot be used as an exported binding without \
+                                 `from`.",
+                            )?;
+                        }
+                    }
+                }
+                None
+            };
+            self.semicolon()?;
+            (None, specifiers, source)
+        };
+        Ok(ModuleDeclaration::ExportNamedDeclaration(Box::new(
+            ExportNamedDeclaration {
+                span: self.finish_node(span),
+                declaration,
+                specifiers,
+                source,
+            },
+        )))
+    }
+
+    fn parse_export_declaration(&mut self) -> Result<Declaration, ParserError> {
+        Ok(match self.parse_statement(None, false, &mut None)? {
+            Statement::FunctionDeclaration(function) => Declaration::FunctionDeclaration(function),
+            Statement::VariableDeclaration(declaration) => {
+                Declaration::VariableDeclaration(declaration)
+            }
+            Statement::ClassDeclaration(class) => Declaration::ClassDeclaration(class),
+            // `should_parse_export_statement` only lets declarations through.
+            _ => unreachable!(),
+        })
+    }
+
+    fn parse_export_all_declaration(
+        &mut self,
+        span: Span,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<ExportAllDeclaration, ParserError> {
+        let mut exported = None;
+        if self.options.get_ecma_version_number() >= 11 && self.eat_contextual("as")? {
+            let name = self.parse_module_export_name()?;
+            self.check_export(exports, name.name(), name.span().start, name.span().end)?;
+            exported = Some(name);
+        }
+        self.expect_contextual("from")?;
+        let source = self.parse_module_source()?;
+        self.semicolon()?;
+        Ok(ExportAllDeclaration {
+            span: self.finish_node(span),
+            exported,
+            source,
+        })
+    }
+
+    fn parse_export_default_declaration(
+        &mut self,
+    ) -> Result<ExportDefaultDeclarationKind, ParserError> {
+        let token_types = get_token_types();
+        let is_function = self.cur_token_type.eq(&token_types._function);
+        let is_async = !is_function && self.is_async_function()?;
+        if is_function || is_async {
+            let span = self.start_node();
+            self.next(false)?;
+            if is_async {
+                self.next(false)?;
+            }
+            Ok(ExportDefaultDeclarationKind::FunctionDeclaration(Box::new(
+                self.parse_function(span, FUNC_STATEMENT | FUNC_NULLABLE_ID, is_async)?,
+            )))
+        } else if self.cur_token_type.eq(&token_types._class) {
+            Ok(ExportDefaultDeclarationKind::ClassDeclaration(Box::new(
+                self.parse_class(self.start_node(), true, true)?,
+            )))
+        } else {
+            let declaration = self.parse_maybe_assign(false, &mut None, None)?;
+            self.semicolon()?;
+            Ok(ExportDefaultDeclarationKind::Expression(declaration))
+        }
+    }
+
+    fn check_export(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+        name: &str,
+        start: i32,
+        end: i32,
+    ) -> Result<(), ParserError> {
+        if let Some(exports) = exports {
+            match exports.get(name) {
+                Some((first_start, first_end)) => self.recover(
+                    self.syntax_error(
+                        start,
+                        ErrorCode::DuplicateExport,
+                        &format!("Duplicate export '{:}'", name),
+                    )
+                    .with_end(end)
+                    .with_label(
+                        *first_start,
+                        *first_end,
+                        "first exported here",
+                    ),
+                )?,
+                None => {
+                    exports.insert(name.to_owned(), (start, end));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_pattern_export(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+        pattern: &Pattern,
+    ) -> Result<(), ParserError> {
+        match pattern {
+            Pattern::Identifier(id) => {
+                self.check_export(exports, &id.name, id.span.start, id.span.end)?
+            }
+            Pattern::ObjectPattern(object) => {
+                for property in object.properties.iter() {
+                    match property {
+                        PropertyOrRest::Property(property) => {
+                            self.check_pattern_export(exports, &property.value)?
+                        }
+                        PropertyOrRest::RestElement(rest) => {
+                            self.check_pattern_export(exports, &rest.argument)?
+                        }
+                    }
+                }
+            }
+            Pattern::ArrayPattern(array) => {
+                for element in array.elements.iter().flatten() {
+                    self.check_pattern_export(exports, element)?;
+                }
+            }
+            Pattern::AssignmentPattern(assign) => {
+                self.check_pattern_export(exports, &assign.left)?
+            }
+            Pattern::RestElement(rest) => self.check_pattern_export(exports, &rest.argument)?,
+            Pattern::ParenthesizedExpression(paren) => {
+                if let Expression::Identifier(id) = &paren.expression {
+                    self.check_export(exports, &id.name, id.span.start, id.span.end)?;
+                }
+            }
+            Pattern::MemberExpression(_) => {}
+        }
+        Ok(())
+    }
+
+    fn check_variable_export(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+        declarations: &[VariableDeclarator],
+    ) -> Result<(), ParserError> {
+        if exports.is_none() {
+            return Ok(());
+        }
+        for declaration in declarations {
+            self.check_pattern_export(exports, &declaration.id)?;
+        }
+        Ok(())
+    }
+
+    fn should_parse_export_statement(&self) -> Result<bool, ParserError> {
+        let is_declaration_keyword = matches!(
+            self.cur_token_type.keyword.as_deref(),
+            Some("var" | "const" | "class" | "function")
+        );
+        Ok(is_declaration_keyword || self.is_let(None)? || self.is_async_function()?)
+    }
+
+    fn parse_export_specifier(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<ExportSpecifier, ParserError> {
+        let span = self.start_node();
+        let local = self.parse_module_export_name()?;
+        let exported = if self.eat_contextual("as")? {
+            self.parse_module_export_name()?
+        } else {
+            local.clone()
+        };
+        self.check_export(
+            exports,
+            exported.name(),
+            exported.span().start,
+            exported.span().end,
+        )?;
+        Ok(ExportSpecifier {
+            span: self.finish_node(span),
+            local,
+            exported,
+        })
+    }
+
+    /// Parses a comma-separated list of module exports.
+    fn parse_export_specifiers(
+        &mut self,
+        exports: &mut Option<HashMap<String, (i32, i32)>>,
+    ) -> Result<Vec<ExportSpecifier>, ParserError> {
+        let token_types = get_token_types();
+        let mut nodes = vec![];
+        let mut is_first = true;
+        // export { x, y as z } [from '...']
+        self.expect(&token_types.brace_l)?;
+        while !self.eat(&token_types.brace_r)? {
+            if !is_first {
+                self.expect(&token_types.comma)?;
+                if self.after_trailing_comma(&token_types.brace_r, false)? {
+                    break;
+                }
+            } else {
+                is_first = false;
+            }
+            nodes.push(self.parse_export_specifier(exports)?);
+        }
+        Ok(nodes)
+    }
+
+    /// Parses import declaration.
+    fn parse_import(&mut self, span: Span) -> Result<ImportDeclaration, ParserError> {
+        let token_types = get_token_types();
+        self.next(false)?;
+
+        // import '...'
+        let specifiers = if self.cur_token_type.eq(&token_types.string) {
+            vec![]
+        } else {
+            let specifiers = self.parse_import_specifiers()?;
+            self.expect_contextual("from")?;
+            specifiers
+        };
+        let source = self.parse_module_source()?;
+        self.semicolon()?;
+        Ok(ImportDeclaration {
+            span: self.finish_node(span),
+            specifiers,
+            source,
+        })
+    }
+
+    fn parse_import_specifier(&mut self) -> Result<ImportSpecifier, ParserError> {
+        let span = self.start_node();
+        let imported = self.parse_module_export_name()?;
+        let local = if self.eat_contextual("as")? {
+            self.parse_ident(false)?
+        } else {
+            match &imported {
+                ModuleExportName::Identifier(imported) => {
+                    self.check_unreserved(imported)?;
+                    (**imported).clone()
+                }
+                // A string can only be imported under a local name. Acorn
+                // goes on with the string as the local binding, which an
+                // `Identifier` cannot hold.
+                ModuleExportName::Literal(imported) => {
+                    self.raise(
+                        imported.span.start,
+                        ErrorCode::InvalidAssignmentTarget,
+                        "Binding rvalue",
+                    )?;
+                    unreachable!()
+                }
+            }
+        };
+        self.check_lval_identifier(&local, BIND_LEXICAL, &mut None)?;
+        Ok(ImportSpecifier {
+            span: self.finish_node(span),
+            imported,
+            local,
+        })
+    }
+
+    fn parse_import_default_specifier(&mut self) -> Result<ImportDefaultSpecifier, ParserError> {
+        // import defaultObj, { x, y as z } from '...'
+        let span = self.start_node();
+        let local = self.parse_ident(false)?;
+        self.check_lval_identifier(&local, BIND_LEXICAL, &mut None)?;
+        Ok(ImportDefaultSpecifier {
+            span: self.finish_node(span),
+            local,
+        })
+    }
+
+    fn parse_import_namespace_specifier(
+        &mut self,
+    ) -> Result<ImportNamespaceSpecifier, ParserError> {
+        let span = self.start_node();
+        self.next(false)?;
+        self.expect_contextual("as")?;
+        let local = self.parse_ident(false)?;
+        self.check_lval_identifier(&local, BIND_LEXICAL, &mut None)?;
+        Ok(ImportNamespaceSpecifier {
+            span: self.finish_node(span),
+            local,
+        })
+    }
+
+    /// Parses a comma-separated list of module imports.
+    fn parse_import_specifiers(&mut self) -> Result<Vec<ImportDeclarationSpecifier>, ParserError> {
+        let token_types = get_token_types();
+        let mut nodes = vec![];
+        let mut is_first = true;
+        if self.cur_token_type.eq(&token_types.name) {
+            nodes.push(ImportDeclarationSpecifier::ImportDefaultSpecifier(
+                Box::new(self.parse_import_default_specifier()?),
+            ));
+            if !self.eat(&token_types.comma)? {
+                return Ok(nodes);
+            }
+        }
+        if self.cur_token_type.eq(&token_types.star) {
+            nodes.push(ImportDeclarationSpecifier::ImportNamespaceSpecifier(
+                Box::new(self.parse_import_namespace_specifier()?),
+            ));
+            return Ok(nodes);
+        }
+        self.expect(&token_types.brace_l)?;
+        while !self.eat(&token_types.brace_r)? {
+            if !is_first {
+                self.expect(&token_types.comma)?;
+                if self.after_trailing_comma(&token_types.brace_r, false)? {
+                    break;
+                }
+            } else {
+                is_first = false;
+            }
+            nodes.push(ImportDeclarationSpecifier::ImportSpecifier(Box::new(
+                self.parse_import_specifier()?,
+            )));
+        }
+        Ok(nodes)
+    }
+
+    fn parse_module_export_name(&mut self) -> Result<ModuleExportName, ParserError> {
+        if self.options.get_ecma_version_number() >= 13
+            && self.cur_token_type.eq(&get_token_types().string)
+        {
+            let value = self.atoms.intern(self.cur_token_value.as_str());
+            let string_literal = self.parse_literal(LiteralValue::String(value))?;
+            if has_lone_surrogate(&string_literal.raw) {
+                self.raise(
+                    string_literal.span.start,
+                    ErrorCode::InvalidExportName,
+                    "An export name cannot include a lone surrogate.",
+                )?;
+            }
+            return Ok(ModuleExportName::Literal(Box::new(string_literal)));
+        }
+        Ok(ModuleExportName::Identifier(Box::new(
+            self.parse_ident(true)?,
+        )))
+    }
+
+    /// Parses the string literal naming the module of an import or export.
+    fn parse_module_source(&mut self) -> Result<Literal, ParserError> {
+        if self.cur_token_type.ne(&get_token_types().string) {
+            self.unexpected(None)?;
+        }
+        let value = self.atoms.intern(self.cur_token_value.as_str());
+        self.parse_literal(LiteralValue::String(value))
+    }
+
+    /// Mark the leading string literal statements of a body with their
+    /// `directive` value.
+    fn adapt_directive_prologue(&self, statements: &mut [Statement]) {
+        for statement in statements.iter_mut() {
+            if !self.is_directive_candidate(statement) {
+                break;
+            }
+            if let Statement::ExpressionStatement(statement) = statement {
+                if let Expression::Literal(literal) = &statement.expression {
+                    // Strings recovered by the loose parser from unterminated
+                    // ones have no closing quote.
+                    let raw = &literal.raw;
+                    statement.directive = raw.get(1..raw.len() - 1).map(str::to_owned);
+                }
+            }
+        }
+    }
+
+    fn is_directive_candidate(&self, statement: &Statement) -> bool {
+        let is_string_literal = match statement {
+            Statement::ExpressionStatement(statement) => matches!(
+                &statement.expression,
+                Expression::Literal(literal) if matches!(literal.value, LiteralValue::String(_))
+            ),
+            _ => false,
+        };
+        let first_char = self.input[statement.span().start as usize..].chars().next();
+        self.options.get_ecma_version_number() >= 5
+            && is_string_literal
+            // Reject parenthesized strings.
+            && (first_char == Some('"') || first_char == Some('\''))
+    }
+}
@@ -1,19 +1,42 @@
-use std::borrow::Borrow;
-
 use crate::{
-    errors::ParserError,
-    location::{LocationParser, SourceLocation},
+    char_codes::*,
+    comment::{Comment, CommentKind},
+    errors::{ErrorCode, ParserError},
+    identifier::{is_identifier_char, is_identifier_start},
+    location::{LocationParser, Position, SourceLocation},
+    options::Sink,
     parser::Parser,
-    token::types::{get_token_types, TokenType},
+    regexp::{RegExpValidationState, RegexpParser},
+    token::{
+        context::{get_token_context_types, TokenContextParser},
+        types::{get_keyword_token_type, get_token_types, TokenType},
+    },
+    utils::{get_string_from_code, UtilsParser},
+    whitespace::{get_line_break_regex, is_new_line, is_non_ascii_white_space},
 };
 
 pub mod context;
+pub mod stream;
 pub mod types;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TokenValue {
     Null,
     String(String),
+    Number(f64),
+    BigInt(String),
+    Regex { pattern: String, flags: String },
+}
+
+impl TokenValue {
+    /// Returns the string value of a name, operator, string or template
+    /// token, or an empty string for any other value.
+    pub fn as_str(&self) -> &str {
+        match self {
+            TokenValue::String(value) => value,
+            _ => "",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -29,84 +52,1400 @@ pub struct Token {
 impl Token {
     pub fn new(parser: &Parser) -> Self {
         Token {
-            token_type: parser.cur_token_type.borrow().clone(),
-            value: parser.cur_token_value.borrow().clone(),
-            start: parser.cur_token_start.get(),
-            end: parser.cur_token_end.get(),
+            token_type: parser.cur_token_type.clone(),
+            value: parser.cur_token_value.clone(),
+            start: parser.cur_token_start,
+            end: parser.cur_token_end,
             loc: if parser.options.locations {
-                if let Some(loc) = parser.cur_token_start_loc.borrow().as_ref() {
-                    Some(SourceLocation::new(
-                        loc,
-                        &parser.cur_token_end_loc.borrow(),
-                        &parser.source_file,
-                    ))
-                } else {
-                    None
-                }
+                parser.cur_token_start_loc.as_ref().map(|loc| {
+                    SourceLocation::new(loc, &parser.cur_token_end_loc, &parser.source_file)
+                })
             } else {
                 None
             },
             range: if parser.options.ranges {
-                Some((parser.cur_token_start.get(), parser.cur_token_end.get()))
+                Some((parser.cur_token_start, parser.cur_token_end))
             } else {
                 None
             },
         }
     }
+
+    /// Converts the offsets of a token read by `parser` to the unit of its
+    /// options, for handing it out.
+    pub(crate) fn into_output(mut self, parser: &Parser) -> Self {
+        parser.output_range(
+            &mut self.start,
+            &mut self.end,
+            &mut self.loc,
+            &mut self.range,
+        );
+        self
+    }
+}
+
+/// Where the tokens consumed during a parse are reported.
+pub type OnToken = Sink<Token>;
+
+/// Returns the number of bytes the given code point occupies in the input.
+fn get_code_len(code: i32) -> i32 {
+    if code < 0x80 {
+        1
+    } else {
+        char::from_u32(code as u32)
+            .map(|v| v.len_utf8() as i32)
+            .unwrap_or(1)
+    }
+}
+
+/// Appends the given code point to an UTF-16 buffer. Lone surrogates, which
+/// can be produced by escape sequences, are kept as is so that a following
+/// escaped trail surrogate can complete the pair.
+fn push_code_point(out: &mut Vec<u16>, code: i32) {
+    match char::from_u32(code as u32) {
+        Some(ch) => {
+            let mut buffer = [0u16; 2];
+            out.extend_from_slice(ch.encode_utf16(&mut buffer));
+        }
+        None => out.push(code as u16),
+    }
+}
+
+fn string_to_number(value: &str, is_legacy_octal_numeric_literal: bool) -> f64 {
+    if is_legacy_octal_numeric_literal {
+        value
+            .chars()
+            .fold(0f64, |total, ch| total * 8.0 + (ch as i32 - DIGIT_0) as f64)
+    } else {
+        value.replace('_', "").parse::<f64>().unwrap_or(f64::NAN)
+    }
+}
+
+fn string_to_big_int(value: &str) -> String {
+    value.replace('_', "")
 }
 
 pub trait TokenParser {
-    fn next(&self, ignore_escape_sequence_in_keyword: bool) -> Result<(), ParserError>;
-    fn next_token(&self) -> Result<(), ParserError>;
-    fn get_token(&self) -> Result<Token, ParserError>;
+    fn next(&mut self, ignore_escape_sequence_in_keyword: bool) -> Result<(), ParserError>;
+    fn get_token(&mut self) -> Result<Token, ParserError>;
+    fn next_token(&mut self) -> Result<(), ParserError>;
+    fn read_token(&mut self, code: i32) -> Result<(), ParserError>;
+    fn char_code_at(&self, pos: i32) -> i32;
+    fn full_char_code_at_pos(&self) -> i32;
+    fn skip_block_comment(&mut self) -> Result<(), ParserError>;
+    fn skip_line_comment(&mut self, start_skip: i32);
+    fn push_comment(&self, kind: CommentKind, value: &str, start: i32, start_loc: Option<Position>);
+    fn skip_space(&mut self) -> Result<(), ParserError>;
+    fn finish_token(&mut self, token_type: &TokenType, value: TokenValue);
+    fn read_token_dot(&mut self) -> Result<(), ParserError>;
+    fn read_token_slash(&mut self) -> Result<(), ParserError>;
+    fn read_token_mult_modulo_exp(&mut self, code: i32);
+    fn read_token_pipe_amp(&mut self, code: i32);
+    fn read_token_caret(&mut self);
+    fn read_token_plus_min(&mut self, code: i32) -> Result<(), ParserError>;
+    fn read_token_lt_gt(&mut self, code: i32) -> Result<(), ParserError>;
+    fn read_token_eq_excl(&mut self, code: i32);
+    fn read_token_question(&mut self);
+    fn read_token_number_sign(&mut self) -> Result<(), ParserError>;
+    fn get_token_from_code(&mut self, code: i32) -> Result<(), ParserError>;
+    fn finish_op(&mut self, token_type: &TokenType, size: i32);
+    fn read_regexp(&mut self) -> Result<(), ParserError>;
+    fn read_int(
+        &mut self,
+        radix: i32,
+        len: Option<i32>,
+        maybe_legacy_octal_numeric_literal: bool,
+    ) -> Result<Option<f64>, ParserError>;
+    fn read_radix_number(&mut self, radix: i32) -> Result<(), ParserError>;
+    fn read_number(&mut self, starts_with_dot: bool) -> Result<(), ParserError>;
+    fn read_code_point(&mut self) -> Result<i32, ParserError>;
+    fn read_string(&mut self, quote: i32) -> Result<(), ParserError>;
+    fn try_read_template_token(&mut self) -> Result<(), ParserError>;
+    fn invalid_string_token(
+        &self,
+        pos: i32,
+        code: ErrorCode,
+        message: &str,
+    ) -> Result<(), ParserError>;
+    fn read_tmpl_token(&mut self) -> Result<(), ParserError>;
+    fn read_invalid_template_token(&mut self) -> Result<(), ParserError>;
+    fn read_escaped_char(
+        &mut self,
+        in_template: bool,
+        out: &mut Vec<u16>,
+    ) -> Result<(), ParserError>;
+    fn read_hex_char(&mut self, len: i32) -> Result<i32, ParserError>;
+    fn read_word1(&mut self) -> Result<String, ParserError>;
+    fn read_word(&mut self) -> Result<(), ParserError>;
 }
 
 impl TokenParser for Parser {
-    fn next(&self, ignore_escape_sequence_in_keyword: bool) -> Result<(), ParserError> {
-        let cur_token_type = self.cur_token_type.borrow();
-        if !ignore_escape_sequence_in_keyword
-            && cur_token_type.keyword.is_some()
-            && self.contains_esc
+    /// Move to the next token
+    fn next(&mut self, ignore_escape_sequence_in_keyword: bool) -> Result<(), ParserError> {
+        let keyword = self.cur_token_type.keyword.clone();
+        if !ignore_escape_sequence_in_keyword && self.contains_esc {
+            if let Some(keyword) = keyword {
+                self.raise_recoverable(
+                    self.cur_token_start,
+                    ErrorCode::EscapedKeyword,
+                    &format!("Escape sequence in keyword {:}", keyword),
+                )?;
+            }
+        }
+        if let Some(on_token) = &self.options.on_token {
+            on_token.push(Token::new(self).into_output(self));
+        }
+
+        self.last_token_end = self.cur_token_end;
+        self.last_token_start = self.cur_token_start;
+        self.last_token_end_loc = self.cur_token_end_loc.clone();
+        self.last_token_start_loc = self.cur_token_start_loc.clone();
+        self.next_token()
+    }
+
+    fn get_token(&mut self) -> Result<Token, ParserError> {
+        self.next(false)?;
+        Ok(Token::new(self))
+    }
+
+    /// Read a single token, updating the parser object's token-related
+    /// properties.
+    fn next_token(&mut self) -> Result<(), ParserError> {
+        let (preserve_space, in_template) = match self.current_context() {
+            Some(context) => (
+                context.preserve_space,
+                context.eq(&get_token_context_types().q_tmpl),
+            ),
+            None => (false, false),
+        };
+        if !preserve_space {
+            self.skip_space()?;
+        }
+
+        self.cur_token_start = self.cur_token_pos;
+        if self.options.locations {
+            self.cur_token_start_loc = self.get_cur_position();
+        }
+        if self.cur_token_pos >= self.input.len() as i32 {
+            self.finish_token(&get_token_types().eof, TokenValue::Null);
+            return Ok(());
+        }
+
+        if in_template {
+            self.try_read_template_token()
+        } else {
+            self.read_token(self.full_char_code_at_pos())
+        }
+    }
+
+    fn read_token(&mut self, code: i32) -> Result<(), ParserError> {
+        // Identifier or keyword. '\uXXXX' sequences are allowed in
+        // identifiers, so '\' also dispatches to that.
+        if is_identifier_start(code, self.options.get_ecma_version_number() >= 6)
+            || code == BACKSLASH
+        {
+            self.read_word()
+        } else {
+            self.get_token_from_code(code)
+        }
+    }
+
+    /// Returns the code point starting at the given byte offset, or -1 when
+    /// the offset is past the end of the input.
+    fn char_code_at(&self, pos: i32) -> i32 {
+        if pos < 0 {
+            return -1;
+        }
+        match self.input.as_bytes().get(pos as usize) {
+            Some(byte) if *byte < 0x80 => *byte as i32,
+            Some(_) => self
+                .input
+                .get(pos as usize..)
+                .and_then(|rest| rest.chars().next())
+                .map(|ch| ch as i32)
+                .unwrap_or(-1),
+            None => -1,
+        }
+    }
+
+    fn full_char_code_at_pos(&self) -> i32 {
+        self.char_code_at(self.cur_token_pos)
+    }
+
+    fn skip_block_comment(&mut self) -> Result<(), ParserError> {
+        let start_loc = self.get_cur_position();
+        let start = self.cur_token_pos;
+        let end = match self.input[start as usize + 2..].find("*/") {
+            Some(index) => start + 2 + index as i32,
+            None => {
+                return self.raise(
+                    start,
+                    ErrorCode::UnterminatedComment,
+                    "Unterminated comment",
+                )
+            }
+        };
+        self.cur_token_pos = end + 2;
+        if self.options.locations {
+            let comment = &self.input[start as usize..end as usize];
+            let mut chars = comment.char_indices().peekable();
+            while let Some((index, ch)) = chars.next() {
+                let code = ch as i32;
+                if !is_new_line(code) {
+                    continue;
+                }
+                let mut next_line_start = start + (index + ch.len_utf8()) as i32;
+                if code == CARRIAGE_RETURN {
+                    if let Some((_, '\n')) = chars.peek() {
+                        chars.next();
+                        next_line_start += 1;
+                    }
+                }
+                self.cur_token_line += 1;
+                self.cur_token_line_start = next_line_start;
+            }
+        }
+        self.push_comment(
+            CommentKind::Block,
+            &self.input[start as usize + 2..end as usize],
+            start,
+            start_loc,
+        );
+        Ok(())
+    }
+
+    fn skip_line_comment(&mut self, start_skip: i32) {
+        let start_loc = self.get_cur_position();
+        let start = self.cur_token_pos;
+        let mut pos = start + start_skip;
+        let len = self.input.len() as i32;
+        while pos < len {
+            let code = self.char_code_at(pos);
+            if is_new_line(code) {
+                break;
+            }
+            pos += get_code_len(code);
+        }
+        self.cur_token_pos = pos;
+        self.push_comment(
+            CommentKind::Line,
+            &self.input[(start + start_skip) as usize..pos as usize],
+            start,
+            start_loc,
+        );
+    }
+
+    /// Reports a comment that ends at the current position to
+    /// `on_comment`.
+    fn push_comment(
+        &self,
+        kind: CommentKind,
+        value: &str,
+        start: i32,
+        start_loc: Option<Position>,
+    ) {
+        if let Some(on_comment) = &self.options.on_comment {
+            let end = self.cur_token_pos;
+            let mut comment = Comment {
+                kind,
+                value: value.to_string(),
+                start,
+                end,
+                loc: start_loc.map(|loc| {
+                    SourceLocation::new(&loc, &self.get_cur_position(), &self.source_file)
+                }),
+                range: if self.options.ranges {
+                    Some((start, end))
+                } else {
+                    None
+                },
+            };
+            self.output_range(
+                &mut comment.start,
+                &mut comment.end,
+                &mut comment.loc,
+                &mut comment.range,
+            );
+            on_comment.push(comment);
+        }
+    }
+
+    /// Called at the start of the parse and after every token. Skips
+    /// whitespace and comments.
+    fn skip_space(&mut self) -> Result<(), ParserError> {
+        let len = self.input.len() as i32;
+        while self.cur_token_pos < len {
+            let pos = self.cur_token_pos;
+            let code = self.char_code_at(pos);
+            match code {
+                SPACE | NON_BREAKING_SPACE => self.cur_token_pos = pos + get_code_len(code),
+                CARRIAGE_RETURN | LINE_FEED | LINE_SEPARATOR | PARAGRAPH_SEPARATOR => {
+                    let mut next_pos = pos + get_code_len(code);
+                    if code == CARRIAGE_RETURN && self.char_code_at(next_pos) == LINE_FEED {
+                        next_pos += 1;
+                    }
+                    self.cur_token_pos = next_pos;
+                    if self.options.locations {
+                        self.cur_token_line += 1;
+                        self.cur_token_line_start = next_pos;
+                    }
+                }
+                SLASH => match self.char_code_at(pos + 1) {
+                    ASTERISK => self.skip_block_comment()?,
+                    SLASH => self.skip_line_comment(2),
+                    _ => break,
+                },
+                _ => {
+                    if code > BACK_SPACE && code < SHIFT_OUT
+                        || code >= OGHAM_SPACE_MARK && is_non_ascii_white_space(code)
+                    {
+                        self.cur_token_pos = pos + get_code_len(code);
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Called at the end of every token. Sets `end`, `value`, and
+    /// maintains `context` and `expr_allowed`.
+    fn finish_token(&mut self, token_type: &TokenType, value: TokenValue) {
+        self.cur_token_end = self.cur_token_pos;
+        if self.options.locations {
+            self.cur_token_end_loc = self.get_cur_position();
+        }
+        let prev_token_type = std::mem::replace(&mut self.cur_token_type, token_type.clone());
+        self.cur_token_value = value;
+        self.update_context(&prev_token_type);
+    }
+
+    /// ### Token reading
+    ///
+    /// This is the function that is called to fetch the next token. It
+    /// is somewhat obscure, because it works in character codes rather
+    /// than characters, and because operator parsing has been inlined
+    /// into it.
+    ///
+    /// All in the name of speed.
+    fn read_token_dot(&mut self) -> Result<(), ParserError> {
+        let pos = self.cur_token_pos;
+        let next = self.char_code_at(pos + 1);
+        if (DIGIT_0..=DIGIT_9).contains(&next) {
+            return self.read_number(true);
+        }
+        let next2 = self.char_code_at(pos + 2);
+        let token_types = get_token_types();
+        if self.options.get_ecma_version_number() >= 6 && next == DOT && next2 == DOT {
+            self.cur_token_pos = pos + 3;
+            self.finish_token(&token_types.ellipsis, TokenValue::Null);
+        } else {
+            self.cur_token_pos = pos + 1;
+            self.finish_token(&token_types.dot, TokenValue::Null);
+        }
+        Ok(())
+    }
+
+    fn read_token_slash(&mut self) -> Result<(), ParserError> {
+        let pos = self.cur_token_pos;
+        let next = self.char_code_at(pos + 1);
+        if self.expr_allowed {
+            self.cur_token_pos = pos + 1;
+            return self.read_regexp();
+        }
+        let token_types = get_token_types();
+        if next == EQUALS_TO {
+            self.finish_op(&token_types.assign, 2);
+        } else {
+            self.finish_op(&token_types.slash, 1);
+        }
+        Ok(())
+    }
+
+    fn read_token_mult_modulo_exp(&mut self, code: i32) {
+        let pos = self.cur_token_pos;
+        let mut next = self.char_code_at(pos + 1);
+        let mut size = 1;
+        let token_types = get_token_types();
+        let mut token_type = if code == ASTERISK {
+            &token_types.star
+        } else {
+            &token_types.modulo
+        };
+
+        // exponentiation operator ** and **=
+        if self.options.get_ecma_version_number() >= 7 && code == ASTERISK && next == ASTERISK {
+            size += 1;
+            token_type = &token_types.star_star;
+            next = self.char_code_at(pos + 2);
+        }
+
+        if next == EQUALS_TO {
+            self.finish_op(&token_types.assign, size + 1);
+        } else {
+            self.finish_op(token_type, size);
+        }
+    }
+
+    fn read_token_pipe_amp(&mut self, code: i32) {
+        let pos = self.cur_token_pos;
+        let next = self.char_code_at(pos + 1);
+        let token_types = get_token_types();
+        if next == code {
+            if self.options.get_ecma_version_number() >= 12
+                && self.char_code_at(pos + 2) == EQUALS_TO
+            {
+                return self.finish_op(&token_types.assign, 3);
+            }
+            return self.finish_op(
+                if code == VERTICAL_BAR {
+                    &token_types.logical_or
+                } else {
+                    &token_types.logical_and
+                },
+                2,
+            );
+        }
+        if next == EQUALS_TO {
+            return self.finish_op(&token_types.assign, 2);
+        }
+        self.finish_op(
+            if code == VERTICAL_BAR {
+                &token_types.bitwise_or
+            } else {
+                &token_types.bitwise_and
+            },
+            1,
+        )
+    }
+
+    fn read_token_caret(&mut self) {
+        let next = self.char_code_at(self.cur_token_pos + 1);
+        let token_types = get_token_types();
+        if next == EQUALS_TO {
+            self.finish_op(&token_types.assign, 2);
+        } else {
+            self.finish_op(&token_types.bitwise_xor, 1);
+        }
+    }
+
+    fn read_token_plus_min(&mut self, code: i32) -> Result<(), ParserError> {
+        let pos = self.cur_token_pos;
+        let next = self.char_code_at(pos + 1);
+        let token_types = get_token_types();
+        if next == code {
+            if next == DASH
+                && !self.is_in_module
+                && self.char_code_at(pos + 2) == GREATER_THAN
+                && (self.last_token_end == 0
+                    || get_line_break_regex()
+                        .is_match(&self.input[self.last_token_end as usize..pos as usize])
+                        .unwrap_or(false))
+            {
+                // A `-->` line comment
+                self.skip_line_comment(3);
+                self.skip_space()?;
+                return self.next_token();
+            }
+            self.finish_op(&token_types.inc_dec, 2);
+        } else if next == EQUALS_TO {
+            self.finish_op(&token_types.assign, 2);
+        } else {
+            self.finish_op(&token_types.plus_min, 1);
+        }
+        Ok(())
+    }
+
+    fn read_token_lt_gt(&mut self, code: i32) -> Result<(), ParserError> {
+        let pos = self.cur_token_pos;
+        let next = self.char_code_at(pos + 1);
+        let token_types = get_token_types();
+        let mut size = 1;
+        if next == code {
+            size = if code == GREATER_THAN && self.char_code_at(pos + 2) == GREATER_THAN {
+                3
+            } else {
+                2
+            };
+            if self.char_code_at(pos + size) == EQUALS_TO {
+                self.finish_op(&token_types.assign, size + 1);
+            } else {
+                self.finish_op(&token_types.bit_shift, size);
+            }
+            return Ok(());
+        }
+        if next == EXCLAMATION_MARK
+            && code == LESS_THAN
+            && !self.is_in_module
+            && self.char_code_at(pos + 2) == DASH
+            && self.char_code_at(pos + 3) == DASH
+        {
+            // `<!--`, an XML-style comment that should be interpreted as a line comment
+            self.skip_line_comment(4);
+            self.skip_space()?;
+            return self.next_token();
+        }
+        if next == EQUALS_TO {
+            size = 2;
+        }
+        self.finish_op(&token_types.relational, size);
+        Ok(())
+    }
+
+    fn read_token_eq_excl(&mut self, code: i32) {
+        let pos = self.cur_token_pos;
+        let next = self.char_code_at(pos + 1);
+        let token_types = get_token_types();
+        if next == EQUALS_TO {
+            let size = if self.char_code_at(pos + 2) == EQUALS_TO {
+                3
+            } else {
+                2
+            };
+            return self.finish_op(&token_types.equality, size);
+        }
+        if code == EQUALS_TO && next == GREATER_THAN && self.options.get_ecma_version_number() >= 6
         {
+            // '=>'
+            self.cur_token_pos = pos + 2;
+            return self.finish_token(&token_types.arrow, TokenValue::Null);
+        }
+        self.finish_op(
+            if code == EQUALS_TO {
+                &token_types.eq
+            } else {
+                &token_types.prefix
+            },
+            1,
+        )
+    }
+
+    fn read_token_question(&mut self) {
+        let pos = self.cur_token_pos;
+        let ecma_version = self.options.get_ecma_version_number();
+        let token_types = get_token_types();
+        if ecma_version >= 11 {
+            let next = self.char_code_at(pos + 1);
+            if next == DOT {
+                let next2 = self.char_code_at(pos + 2);
+                if !(DIGIT_0..=DIGIT_9).contains(&next2) {
+                    return self.finish_op(&token_types.question_dot, 2);
+                }
+            }
+            if next == QUESTION_MARK {
+                if ecma_version >= 12 && self.char_code_at(pos + 2) == EQUALS_TO {
+                    return self.finish_op(&token_types.assign, 3);
+                }
+                return self.finish_op(&token_types.coalesce, 2);
+            }
+        }
+        self.finish_op(&token_types.question, 1)
+    }
+
+    fn read_token_number_sign(&mut self) -> Result<(), ParserError> {
+        let mut code = NUMBER_SIGN;
+        if self.options.get_ecma_version_number() >= 13 {
+            self.cur_token_pos += 1;
+            code = self.full_char_code_at_pos();
+            if is_identifier_start(code, true) || code == BACKSLASH {
+                let word = self.read_word1()?;
+                self.finish_token(&get_token_types().private_id, TokenValue::String(word));
+                return Ok(());
+            }
+        }
+        self.raise(
+            self.cur_token_pos,
+            ErrorCode::UnexpectedCharacter,
+            &format!("Unexpected character '{:}'", get_string_from_code(code)),
+        )
+    }
+
+    fn get_token_from_code(&mut self, code: i32) -> Result<(), ParserError> {
+        let token_types = get_token_types();
+        let pos = self.cur_token_pos;
+        let punctuation = match code {
+            // The interpretation of a dot depends on whether it is followed
+            // by a digit or another two dots.
+            DOT => return self.read_token_dot(),
+
+            // Punctuation tokens.
+            LEFT_PARENTHESIS => Some(&token_types.paren_l),
+            RIGHT_PARENTHESIS => Some(&token_types.paren_r),
+            SEMICOLON => Some(&token_types.semi),
+            COMMA => Some(&token_types.comma),
+            LEFT_SQUARE_BRACKET => Some(&token_types.bracket_l),
+            RIGHT_SQUARE_BRACKET => Some(&token_types.bracket_r),
+            LEFT_CURLY_BRACE => Some(&token_types.brace_l),
+            RIGHT_CURLY_BRACE => Some(&token_types.brace_r),
+            COLON => Some(&token_types.colon),
+            GRAVE_ACCENT if self.options.get_ecma_version_number() >= 6 => {
+                Some(&token_types.back_quote)
+            }
+            _ => None,
+        };
+        if let Some(token_type) = punctuation {
+            self.cur_token_pos = pos + 1;
+            self.finish_token(token_type, TokenValue::Null);
+            return Ok(());
+        }
+
+        match code {
+            DIGIT_0 => {
+                let next = self.char_code_at(pos + 1);
+                // '0x', '0X' - hex number
+                if next == LOWERCASE_X || next == UPPERCASE_X {
+                    return self.read_radix_number(16);
+                }
+                if self.options.get_ecma_version_number() >= 6 {
+                    // '0o', '0O' - octal number
+                    if next == LOWERCASE_O || next == UPPERCASE_O {
+                        return self.read_radix_number(8);
+                    }
+                    // '0b', '0B' - binary number
+                    if next == LOWERCASE_B || next == UPPERCASE_B {
+                        return self.read_radix_number(2);
+                    }
+                }
+                self.read_number(false)
+            }
+            // Anything else beginning with a digit is an integer, octal
+            // number, or float.
+            DIGIT_1..=DIGIT_9 => self.read_number(false),
+            // Quotes produce strings.
+            QUOTATION_MARK | APOSTROPHE => self.read_string(code),
+            // Operators are parsed inline in tiny state machines. '=' (61) is
+            // often referred to. `finish_op` simply skips the amount of
+            // characters it is given as second argument, and returns a token
+            // of the type given by its first argument.
+            SLASH => self.read_token_slash(),
+            PERCENT_SIGN | ASTERISK => {
+                self.read_token_mult_modulo_exp(code);
+                Ok(())
+            }
+            VERTICAL_BAR | AMPERSAND => {
+                self.read_token_pipe_amp(code);
+                Ok(())
+            }
+            CARET => {
+                self.read_token_caret();
+                Ok(())
+            }
+            PLUS_SIGN | DASH => self.read_token_plus_min(code),
+            LESS_THAN | GREATER_THAN => self.read_token_lt_gt(code),
+            EQUALS_TO | EXCLAMATION_MARK => {
+                self.read_token_eq_excl(code);
+                Ok(())
+            }
+            QUESTION_MARK => {
+                self.read_token_question();
+                Ok(())
+            }
+            TILDE => {
+                self.finish_op(&token_types.prefix, 1);
+                Ok(())
+            }
+            NUMBER_SIGN => self.read_token_number_sign(),
+            _ => self.raise(
+                pos,
+                ErrorCode::UnexpectedCharacter,
+                &format!("Unexpected character '{:}'", get_string_from_code(code)),
+            ),
+        }
+    }
+
+    fn finish_op(&mut self, token_type: &TokenType, size: i32) {
+        let pos = self.cur_token_pos;
+        let value = self.input[pos as usize..(pos + size) as usize].to_owned();
+        self.cur_token_pos = pos + size;
+        self.finish_token(token_type, TokenValue::String(value));
+    }
+
+    fn read_regexp(&mut self) -> Result<(), ParserError> {
+        let start = self.cur_token_pos;
+        let len = self.input.len() as i32;
+        let mut escaped = false;
+        let mut in_class = false;
+        loop {
+            let pos = self.cur_token_pos;
+            if pos >= len {
+                return self.raise(
+                    start,
+                    ErrorCode::UnterminatedRegex,
+                    "Unterminated regular expression",
+                );
+            }
+            let code = self.char_code_at(pos);
+            if is_new_line(code) {
+                return self.raise(
+                    start,
+                    ErrorCode::UnterminatedRegex,
+                    "Unterminated regular expression",
+                );
+            }
+            if !escaped {
+                if code == LEFT_SQUARE_BRACKET {
+                    in_class = true;
+                } else if code == RIGHT_SQUARE_BRACKET && in_class {
+                    in_class = false;
+                } else if code == SLASH && !in_class {
+                    break;
+                }
+                escaped = code == BACKSLASH;
+            } else {
+                escaped = false;
+            }
+            self.cur_token_pos = pos + get_code_len(code);
+        }
+        let pattern = self.input[start as usize..self.cur_token_pos as usize].to_owned();
+        self.cur_token_pos += 1;
+        let flags_start = self.cur_token_pos;
+        let flags = self.read_word1()?;
+        if self.contains_esc {
+            self.unexpected(Some(flags_start))?;
+        }
+
+        // Validate pattern
+        let mut state = self
+            .regexp_state
+            .take()
+            .unwrap_or_else(|| RegExpValidationState::new(self.options.get_ecma_version_number()));
+        state.reset(start, &pattern, &flags);
+        let result = self
+            .validate_reg_exp_flags(&state)
+            .and_then(|_| self.validate_reg_exp_pattern(&mut state));
+        self.regexp_state = Some(state);
+        result?;
+
+        self.finish_token(
+            &get_token_types().regexp,
+            TokenValue::Regex { pattern, flags },
+        );
+        Ok(())
+    }
+
+    /// Read an integer in the given radix. Return None if zero digits
+    /// were read, the integer value otherwise. When `len` is given, this
+    /// will return None unless the integer has exactly `len` digits.
+    fn read_int(
+        &mut self,
+        radix: i32,
+        len: Option<i32>,
+        maybe_legacy_octal_numeric_literal: bool,
+    ) -> Result<Option<f64>, ParserError> {
+        // `len` is used for character escape sequences. In that case,
+        // disallow separators.
+        let allow_separators = self.options.get_ecma_version_number() >= 12 && len.is_none();
+
+        // `maybe_legacy_octal_numeric_literal` is true if it doesn't have prefix
+        // (0x,0o,0b) and isn't fraction part nor exponent part. In that case,
+        // if the first digit is zero then disallow separators.
+        let is_legacy_octal_numeric_literal =
+            maybe_legacy_octal_numeric_literal && self.char_code_at(self.cur_token_pos) == DIGIT_0;
+
+        let start = self.cur_token_pos;
+        let mut total = 0f64;
+        let mut last_code = 0;
+        let mut index = 0;
+        while len.map(|len| index < len).unwrap_or(true) {
+            let pos = self.cur_token_pos;
+            let code = self.char_code_at(pos);
+            if allow_separators && code == UNDERSCORE {
+                if is_legacy_octal_numeric_literal {
+                    self.raise_recoverable(
+                        pos,
+                        ErrorCode::InvalidNumericSeparator,
+                        "Numeric separator is not allowed in legacy octal numeric literals",
+                    )?;
+                }
+                if last_code == UNDERSCORE {
+                    self.raise_recoverable(
+                        pos,
+                        ErrorCode::InvalidNumericSeparator,
+                        "Numeric separator must be exactly one underscore",
+                    )?;
+                }
+                if index == 0 {
+                    self.raise_recoverable(
+                        pos,
+                        ErrorCode::InvalidNumericSeparator,
+                        "Numeric separator is not allowed at the first of digits",
+                    )?;
+                }
+                last_code = code;
+                index += 1;
+                self.cur_token_pos = pos + 1;
+                continue;
+            }
+
+            let value = if code >= LOWERCASE_A {
+                code - LOWERCASE_A + 10
+            } else if code >= UPPERCASE_A {
+                code - UPPERCASE_A + 10
+            } else if (DIGIT_0..=DIGIT_9).contains(&code) {
+                code - DIGIT_0
+            } else {
+                i32::MAX
+            };
+            if value >= radix {
+                break;
+            }
+            last_code = code;
+            total = total * radix as f64 + value as f64;
+            index += 1;
+            self.cur_token_pos = pos + 1;
+        }
+
+        let pos = self.cur_token_pos;
+        if allow_separators && last_code == UNDERSCORE {
             self.raise_recoverable(
-                self.cur_token_start.get(),
-                &format!(
-                    "Escape sequence in keyword {:}",
-                    cur_token_type.keyword.as_ref().unwrap()
-                ),
+                pos - 1,
+                ErrorCode::InvalidNumericSeparator,
+                "Numeric separator is not allowed at the last of digits",
             )?;
         }
+        if pos == start || len.map(|len| pos - start != len).unwrap_or(false) {
+            return Ok(None);
+        }
+        Ok(Some(total))
+    }
 
-        self.last_token_end.set(self.cur_token_end.get());
-        self.last_token_start.set(self.cur_token_start.get());
-        *self.last_token_end_loc.borrow_mut() = self.cur_token_end_loc.borrow().clone();
-        *self.last_token_start_loc.borrow_mut() = self.cur_token_start_loc.borrow().clone();
-        self.next_token()
+    fn read_radix_number(&mut self, radix: i32) -> Result<(), ParserError> {
+        let start = self.cur_token_pos;
+        // 0x
+        self.cur_token_pos = start + 2;
+        let value = self.read_int(radix, None, false)?;
+        if value.is_none() {
+            self.raise(
+                self.cur_token_start + 2,
+                ErrorCode::InvalidNumber,
+                &format!("Expected number in radix {:}", radix),
+            )?;
+        }
+        let pos = self.cur_token_pos;
+        let value = if self.options.get_ecma_version_number() >= 11
+            && self.char_code_at(pos) == LOWERCASE_N
+        {
+            let value = string_to_big_int(&self.input[start as usize..pos as usize]);
+            self.cur_token_pos = pos + 1;
+            TokenValue::BigInt(value)
+        } else {
+            if is_identifier_start(self.full_char_code_at_pos(), false) {
+                self.raise(
+                    pos,
+                    ErrorCode::IdentifierAfterNumber,
+                    "Identifier directly after number",
+                )?;
+            }
+            TokenValue::Number(value.unwrap_or(f64::NAN))
+        };
+        self.finish_token(&get_token_types().num, value);
+        Ok(())
     }
 
-    fn next_token(&self) -> Result<(), ParserError> {
-        todo!()
+    /// Read an integer, octal integer, or floating-point number.
+    fn read_number(&mut self, starts_with_dot: bool) -> Result<(), ParserError> {
+        let start = self.cur_token_pos;
+        if !starts_with_dot && self.read_int(10, None, true)?.is_none() {
+            self.raise(start, ErrorCode::InvalidNumber, "Invalid number")?;
+        }
+        let mut octal = self.cur_token_pos - start >= 2 && self.char_code_at(start) == DIGIT_0;
+        if octal && self.is_strict {
+            self.raise(start, ErrorCode::InvalidNumber, "Invalid number")?;
+        }
+        let mut next = self.full_char_code_at_pos();
+        if !octal
+            && !starts_with_dot
+            && self.options.get_ecma_version_number() >= 11
+            && next == LOWERCASE_N
+        {
+            let pos = self.cur_token_pos;
+            let value = string_to_big_int(&self.input[start as usize..pos as usize]);
+            self.cur_token_pos = pos + 1;
+            if is_identifier_start(self.full_char_code_at_pos(), false) {
+                self.raise(
+                    self.cur_token_pos,
+                    ErrorCode::IdentifierAfterNumber,
+                    "Identifier directly after number",
+                )?;
+            }
+            self.finish_token(&get_token_types().num, TokenValue::BigInt(value));
+            return Ok(());
+        }
+        if octal && self.input[start as usize..self.cur_token_pos as usize].contains(['8', '9']) {
+            octal = false;
+        }
+        if next == DOT && !octal {
+            self.cur_token_pos += 1;
+            self.read_int(10, None, false)?;
+            next = self.full_char_code_at_pos();
+        }
+        if (next == UPPERCASE_E || next == LOWERCASE_E) && !octal {
+            self.cur_token_pos += 1;
+            next = self.full_char_code_at_pos();
+            if next == PLUS_SIGN || next == DASH {
+                self.cur_token_pos += 1;
+            }
+            if self.read_int(10, None, false)?.is_none() {
+                self.raise(start, ErrorCode::InvalidNumber, "Invalid number")?;
+            }
+        }
+        if is_identifier_start(self.full_char_code_at_pos(), false) {
+            self.raise(
+                self.cur_token_pos,
+                ErrorCode::IdentifierAfterNumber,
+                "Identifier directly after number",
+            )?;
+        }
+
+        let value = string_to_number(
+            &self.input[start as usize..self.cur_token_pos as usize],
+            octal,
+        );
+        self.finish_token(&get_token_types().num, TokenValue::Number(value));
+        Ok(())
     }
 
-    fn get_token(&self) -> Result<Token, ParserError> {
-        self.next(false)?;
-        Ok(Token::new(self))
+    /// Read a string value, interpreting backslash-escapes.
+    fn read_code_point(&mut self) -> Result<i32, ParserError> {
+        let pos = self.cur_token_pos;
+        let code = self.char_code_at(pos);
+        if code == LEFT_CURLY_BRACE {
+            if self.options.get_ecma_version_number() < 6 {
+                self.unexpected(None)?;
+            }
+            let code_pos = pos + 1;
+            self.cur_token_pos = code_pos;
+            let len = self.input[code_pos as usize..]
+                .find('}')
+                .map(|index| index as i32)
+                .unwrap_or(-1);
+            let code = self.read_hex_char(len)?;
+            self.cur_token_pos += 1;
+            if code > 0x10ffff {
+                self.invalid_string_token(
+                    code_pos,
+                    ErrorCode::InvalidEscape,
+                    "Code point out of bounds",
+                )?;
+            }
+            Ok(code)
+        } else {
+            self.read_hex_char(4)
+        }
+    }
+
+    fn read_string(&mut self, quote: i32) -> Result<(), ParserError> {
+        let mut out: Vec<u16> = vec![];
+        let mut chunk_start = self.cur_token_pos + 1;
+        self.cur_token_pos = chunk_start;
+        let len = self.input.len() as i32;
+        loop {
+            let pos = self.cur_token_pos;
+            if pos >= len {
+                return self.raise(
+                    self.cur_token_start,
+                    ErrorCode::UnterminatedString,
+                    "Unterminated string constant",
+                );
+            }
+            let code = self.char_code_at(pos);
+            if code == quote {
+                break;
+            }
+            if code == BACKSLASH {
+                out.extend(self.input[chunk_start as usize..pos as usize].encode_utf16());
+                self.read_escaped_char(false, &mut out)?;
+                chunk_start = self.cur_token_pos;
+            } else if code == LINE_SEPARATOR || code == PARAGRAPH_SEPARATOR {
+                if self.options.get_ecma_version_number() < 10 {
+                    return self.raise(
+                        self.cur_token_start,
+                        ErrorCode::UnterminatedString,
+                        "Unterminated string constant",
+                    );
+                }
+                let next_pos = pos + get_code_len(code);
+                self.cur_token_pos = next_pos;
+                if self.options.locations {
+                    self.cur_token_line += 1;
+                    self.cur_token_line_start = next_pos;
+                }
+            } else {
+                if is_new_line(code) {
+                    return self.raise(
+                        self.cur_token_start,
+                        ErrorCode::UnterminatedString,
+                        "Unterminated string constant",
+                    );
+                }
+                self.cur_token_pos = pos + get_code_len(code);
+            }
+        }
+        let pos = self.cur_token_pos;
+        out.extend(self.input[chunk_start as usize..pos as usize].encode_utf16());
+        self.cur_token_pos = pos + 1;
+        self.finish_token(
+            &get_token_types().string,
+            TokenValue::String(String::from_utf16_lossy(&out)),
+        );
+        Ok(())
+    }
+
+    /// Reads template string tokens.
+    fn try_read_template_token(&mut self) -> Result<(), ParserError> {
+        self.in_template_element = true;
+        let result = match self.read_tmpl_token() {
+            Err(ParserError::InvalidTemplateEscape) => self.read_invalid_template_token(),
+            result => result,
+        };
+        self.in_template_element = false;
+        result
+    }
+
+    fn invalid_string_token(
+        &self,
+        pos: i32,
+        code: ErrorCode,
+        message: &str,
+    ) -> Result<(), ParserError> {
+        if self.in_template_element && self.options.get_ecma_version_number() >= 9 {
+            Err(ParserError::InvalidTemplateEscape)
+        } else {
+            self.raise(pos, code, message)
+        }
+    }
+
+    fn read_tmpl_token(&mut self) -> Result<(), ParserError> {
+        let token_types = get_token_types();
+        let mut out: Vec<u16> = vec![];
+        let mut chunk_start = self.cur_token_pos;
+        let len = self.input.len() as i32;
+        loop {
+            let pos = self.cur_token_pos;
+            if pos >= len {
+                return self.raise(
+                    self.cur_token_start,
+                    ErrorCode::UnterminatedTemplate,
+                    "Unterminated template",
+                );
+            }
+            let code = self.char_code_at(pos);
+            if code == GRAVE_ACCENT
+                || code == DOLLAR_SIGN && self.char_code_at(pos + 1) == LEFT_CURLY_BRACE
+            {
+                // '`', '${'
+                let is_template_token = {
+                    let cur_token_type = &self.cur_token_type;
+                    cur_token_type.eq(&token_types.template)
+                        || cur_token_type.eq(&token_types.invalid_template)
+                };
+                if pos == self.cur_token_start && is_template_token {
+                    if code == DOLLAR_SIGN {
+                        self.cur_token_pos = pos + 2;
+                        self.finish_token(&token_types.dollar_brace_l, TokenValue::Null);
+                    } else {
+                        self.cur_token_pos = pos + 1;
+                        self.finish_token(&token_types.back_quote, TokenValue::Null);
+                    }
+                    return Ok(());
+                }
+                out.extend(self.input[chunk_start as usize..pos as usize].encode_utf16());
+                self.finish_token(
+                    &token_types.template,
+                    TokenValue::String(String::from_utf16_lossy(&out)),
+                );
+                return Ok(());
+            }
+            if code == BACKSLASH {
+                out.extend(self.input[chunk_start as usize..pos as usize].encode_utf16());
+                self.read_escaped_char(true, &mut out)?;
+                chunk_start = self.cur_token_pos;
+            } else if is_new_line(code) {
+                out.extend(self.input[chunk_start as usize..pos as usize].encode_utf16());
+                let mut next_pos = pos + get_code_len(code);
+                if code == CARRIAGE_RETURN {
+                    if self.char_code_at(next_pos) == LINE_FEED {
+                        next_pos += 1;
+                    }
+                    out.push(LINE_FEED as u16);
+                } else {
+                    push_code_point(&mut out, code);
+                }
+                self.cur_token_pos = next_pos;
+                if self.options.locations {
+                    self.cur_token_line += 1;
+                    self.cur_token_line_start = next_pos;
+                }
+                chunk_start = next_pos;
+            } else {
+                self.cur_token_pos = pos + get_code_len(code);
+            }
+        }
+    }
+
+    /// Reads a template token to search for the end, without validating any
+    /// escape sequences
+    fn read_invalid_template_token(&mut self) -> Result<(), ParserError> {
+        let len = self.input.len() as i32;
+        while self.cur_token_pos < len {
+            let pos = self.cur_token_pos;
+            let code = self.char_code_at(pos);
+            if code == BACKSLASH {
+                self.cur_token_pos = pos + 1;
+            } else if code == GRAVE_ACCENT
+                || code == DOLLAR_SIGN && self.char_code_at(pos + 1) == LEFT_CURLY_BRACE
+            {
+                let value = self.input[self.cur_token_start as usize..pos as usize].to_owned();
+                self.finish_token(
+                    &get_token_types().invalid_template,
+                    TokenValue::String(value),
+                );
+                return Ok(());
+            }
+            let pos = self.cur_token_pos;
+            self.cur_token_pos = pos + get_code_len(self.char_code_at(pos)).max(1);
+        }
+        self.raise(
+            self.cur_token_start,
+            ErrorCode::UnterminatedTemplate,
+            "Unterminated template",
+        )
+    }
+
+    /// Used to read escaped characters
+    fn read_escaped_char(
+        &mut self,
+        in_template: bool,
+        out: &mut Vec<u16>,
+    ) -> Result<(), ParserError> {
+        let code = self.char_code_at(self.cur_token_pos + 1);
+        let pos = self.cur_token_pos + 1 + get_code_len(code);
+        self.cur_token_pos = pos;
+        match code {
+            // 'n' -> '\n'
+            LOWERCASE_N => out.push(LINE_FEED as u16),
+            // 'r' -> '\r'
+            LOWERCASE_R => out.push(CARRIAGE_RETURN as u16),
+            // 'x'
+            LOWERCASE_X => {
+                let code = self.read_hex_char(2)?;
+                out.push(code as u16);
+            }
+            // 'u'
+            LOWERCASE_U => {
+                let code = self.read_code_point()?;
+                push_code_point(out, code);
+            }
+            // 't' -> '\t'
+            LOWERCASE_T => out.push(TAB as u16),
+            // 'b' -> '\b'
+            LOWERCASE_B => out.push(BACK_SPACE as u16),
+            // 'v' -> '\u000b'
+            LOWERCASE_V => out.push(VERTICAL_TAB as u16),
+            // 'f' -> '\f'
+            LOWERCASE_F => out.push(FORM_FEED as u16),
+            CARRIAGE_RETURN | LINE_FEED => {
+                // '\r\n'
+                if code == CARRIAGE_RETURN && self.char_code_at(pos) == LINE_FEED {
+                    self.cur_token_pos = pos + 1;
+                }
+                if self.options.locations {
+                    self.cur_token_line_start = self.cur_token_pos;
+                    self.cur_token_line += 1;
+                }
+            }
+            DIGIT_8 | DIGIT_9 => {
+                if self.is_strict {
+                    self.invalid_string_token(
+                        pos - 1,
+                        ErrorCode::InvalidEscape,
+                        "Invalid escape sequence",
+                    )?;
+                }
+                if in_template {
+                    self.invalid_string_token(
+                        pos - 1,
+                        ErrorCode::InvalidEscape,
+                        "Invalid escape sequence in template string",
+                    )?;
+                }
+                out.push(code as u16);
+            }
+            DIGIT_0..=DIGIT_7 => {
+                let octal_str: String = self.input[(pos - 1) as usize..]
+                    .chars()
+                    .take(3)
+                    .take_while(|ch| ('0'..='7').contains(ch))
+                    .collect();
+                let mut octal_str = octal_str.as_str();
+                let mut octal = i32::from_str_radix(octal_str, 8).unwrap_or(0);
+                if octal > 255 {
+                    octal_str = &octal_str[..octal_str.len() - 1];
+                    octal = i32::from_str_radix(octal_str, 8).unwrap_or(0);
+                }
+                let pos = pos + octal_str.len() as i32 - 1;
+                self.cur_token_pos = pos;
+                let code = self.char_code_at(pos);
+                if (octal_str.ne("0") || code == DIGIT_8 || code == DIGIT_9)
+                    && (self.is_strict || in_template)
+                {
+                    self.invalid_string_token(
+                        pos - 1 - octal_str.len() as i32,
+                        ErrorCode::OctalEscape,
+                        if in_template {
+                            "Octal literal in template string"
+                        } else {
+                            "Octal literal in strict mode"
+                        },
+                    )?;
+                }
+                out.push(octal as u16);
+            }
+            _ => {
+                // Unicode new line characters after \ get removed from output in both
+                // template literals and strings
+                if is_new_line(code) {
+                    if self.options.locations {
+                        self.cur_token_line_start = self.cur_token_pos;
+                        self.cur_token_line += 1;
+                    }
+                } else if code >= 0 {
+                    push_code_point(out, code);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Used to read character escape sequences ('\x', '\u', '\U').
+    fn read_hex_char(&mut self, len: i32) -> Result<i32, ParserError> {
+        let code_pos = self.cur_token_pos;
+        match self.read_int(16, Some(len), false)? {
+            Some(value) => Ok(value as i32),
+            None => {
+                self.invalid_string_token(
+                    code_pos,
+                    ErrorCode::InvalidEscape,
+                    "Bad character escape sequence",
+                )?;
+                Ok(-1)
+            }
+        }
+    }
+
+    /// Read an identifier, and return it as a string. Sets `contains_esc`
+    /// to whether the word contained a '\u' escape.
+    ///
+    /// Incrementally adds only escaped chars, adding other chunks as-is
+    /// as a micro-optimization.
+    fn read_word1(&mut self) -> Result<String, ParserError> {
+        self.contains_esc = false;
+        let mut word = String::new();
+        let mut is_first = true;
+        let mut chunk_start = self.cur_token_pos;
+        let astral = self.options.get_ecma_version_number() >= 6;
+        let len = self.input.len() as i32;
+        while self.cur_token_pos < len {
+            let pos = self.cur_token_pos;
+            let code = self.full_char_code_at_pos();
+            if is_identifier_char(code, astral) {
+                self.cur_token_pos = pos + get_code_len(code);
+            } else if code == BACKSLASH {
+                self.contains_esc = true;
+                word.push_str(&self.input[chunk_start as usize..pos as usize]);
+                let esc_start = pos;
+                // "u"
+                if self.char_code_at(pos + 1) != LOWERCASE_U {
+                    self.invalid_string_token(
+                        pos + 1,
+                        ErrorCode::InvalidEscape,
+                        "Expecting Unicode escape sequence \\uXXXX",
+                    )?;
+                }
+                self.cur_token_pos = pos + 2;
+                let esc = self.read_code_point()?;
+                let is_valid = if is_first {
+                    is_identifier_start(esc, astral)
+                } else {
+                    is_identifier_char(esc, astral)
+                };
+                if !is_valid {
+                    self.invalid_string_token(
+                        esc_start,
+                        ErrorCode::InvalidEscape,
+                        "Invalid Unicode escape",
+                    )?;
+                }
+                word.push_str(&get_string_from_code(esc));
+                chunk_start = self.cur_token_pos;
+            } else {
+                break;
+            }
+            is_first = false;
+        }
+        word.push_str(&self.input[chunk_start as usize..self.cur_token_pos as usize]);
+        Ok(word)
+    }
+
+    /// Read an identifier or keyword token. Will check for reserved
+    /// words when necessary.
+    fn read_word(&mut self) -> Result<(), ParserError> {
+        let word = self.read_word1()?;
+        let keyword_token_type = if self.keywords.contains(&word) {
+            get_keyword_token_type(&word)
+        } else {
+            None
+        };
+        self.finish_token(
+            keyword_token_type.unwrap_or(&get_token_types().name),
+            TokenValue::String(word),
+        );
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct ParserIteratorItem {
-    pub value: Token,
-    pub done: bool,
+/// An iterator over the tokens of an input, as returned by
+/// [`tokenizer`](crate::tokenizer). It stops after the last token before
+/// the end of the input, or after yielding the first error.
+#[derive(Debug)]
+pub struct Tokenizer {
+    parser: Parser,
+    done: bool,
 }
 
-impl Iterator for Parser {
-    type Item = Result<ParserIteratorItem, ParserError>;
+impl Tokenizer {
+    pub(crate) fn new(parser: Parser) -> Self {
+        Tokenizer {
+            parser,
+            done: false,
+        }
+    }
+
+    /// Returns the errors collected so far with the `collect_errors`
+    /// option, like numeric separators out of place.
+    pub fn take_errors(&mut self) -> Vec<ParserError> {
+        self.parser.take_errors()
+    }
+}
+
+impl Iterator for Tokenizer {
+    type Item = Result<Token, ParserError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        Some(self.get_token().map(|token| ParserIteratorItem {
-            done: token.token_type.eq(&get_token_types().eof),
-            value: token,
-        }))
+        if self.done {
+            return None;
+        }
+        match self.parser.get_token() {
+            Ok(token) if token.token_type.eq(&get_token_types().eof) => {
+                self.done = true;
+                None
+            }
+            Ok(token) => Some(Ok(token.into_output(&self.parser))),
+            Err(error) => {
+                self.done = true;
+                Some(Err(self.parser.output_error(error)))
+            }
+        }
     }
 }
@@ -0,0 +1,438 @@
+// ### Streaming
+//
+// The streaming tokenizer is fed its input a chunk at a time, and hands out
+// each token once the input read so far shows where it ends. It reads a
+// token like the tokenizer does, but when that runs into the end of the
+// input read so far, it takes the token back and waits for more input, so
+// that tokens, templates and comments split across chunks are read as a
+// whole. Offsets and locations are those of the whole input, as with
+// `tokenizer`.
+
+use std::{
+    io::{self, Read},
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    comment::{Comment, CommentKind, OnComment},
+    errors::{ErrorCode, ParserError},
+    identifier::is_identifier_char,
+    location::{LocationParser, Position},
+    options::Options,
+    parser::Parser,
+    token::{
+        context::TokenContext,
+        types::{get_token_types, TokenType},
+        OnToken, Token, TokenParser, TokenValue,
+    },
+    utils::scan_strict_directive,
+    whitespace::is_new_line,
+};
+
+/// How far past its end the tokenizer may look to tell where a token ends,
+/// like after `<` in `<!--`.
+const LOOKAHEAD: i32 = 4;
+
+/// Whether a character of new input may complete a token taken back.
+type Wake = fn(char) -> bool;
+
+/// The size of the chunks read by a [`ReadTokenizer`].
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// What reading a token changes in the parser, to take the token back.
+struct Checkpoint {
+    contains_esc: bool,
+    in_template_element: bool,
+    cur_token_pos: i32,
+    cur_token_line_start: i32,
+    cur_token_line: i32,
+    cur_token_start: i32,
+    cur_token_end: i32,
+    cur_token_start_loc: Option<Position>,
+    cur_token_end_loc: Option<Position>,
+    cur_token_type: TokenType,
+    cur_token_value: TokenValue,
+    last_token_start: i32,
+    last_token_end: i32,
+    last_token_start_loc: Option<Position>,
+    last_token_end_loc: Option<Position>,
+    context: Vec<TokenContext>,
+    expr_allowed: bool,
+    errors: usize,
+}
+
+impl Checkpoint {
+    fn save(parser: &Parser) -> Self {
+        Checkpoint {
+            contains_esc: parser.contains_esc,
+            in_template_element: parser.in_template_element,
+            cur_token_pos: parser.cur_token_pos,
+            cur_token_line_start: parser.cur_token_line_start,
+            cur_token_line: parser.cur_token_line,
+            cur_token_start: parser.cur_token_start,
+            cur_token_end: parser.cur_token_end,
+            cur_token_start_loc: parser.cur_token_start_loc.clone(),
+            cur_token_end_loc: parser.cur_token_end_loc.clone(),
+            cur_token_type: parser.cur_token_type.clone(),
+            cur_token_value: parser.cur_token_value.clone(),
+            last_token_start: parser.last_token_start,
+            last_token_end: parser.last_token_end,
+            last_token_start_loc: parser.last_token_start_loc.clone(),
+            last_token_end_loc: parser.last_token_end_loc.clone(),
+            context: parser.context.clone(),
+            expr_allowed: parser.expr_allowed,
+            errors: parser.errors.len(),
+        }
+    }
+
+    fn restore(self, parser: &mut Parser) {
+        parser.contains_esc = self.contains_esc;
+        parser.in_template_element = self.in_template_element;
+        parser.cur_token_pos = self.cur_token_pos;
+        parser.cur_token_line_start = self.cur_token_line_start;
+        parser.cur_token_line = self.cur_token_line;
+        parser.cur_token_start = self.cur_token_start;
+        parser.cur_token_end = self.cur_token_end;
+        parser.cur_token_start_loc = self.cur_token_start_loc;
+        parser.cur_token_end_loc = self.cur_token_end_loc;
+        parser.cur_token_type = self.cur_token_type;
+        parser.cur_token_value = self.cur_token_value;
+        parser.last_token_start = self.last_token_start;
+        parser.last_token_end = self.last_token_end;
+        parser.last_token_start_loc = self.last_token_start_loc;
+        parser.last_token_end_loc = self.last_token_end_loc;
+        parser.context = self.context;
+        parser.expr_allowed = self.expr_allowed;
+        parser.errors.truncate(self.errors);
+    }
+}
+
+/// A tokenizer over an input given a chunk at a time, as returned by
+/// [`stream_tokenizer`](crate::stream_tokenizer). Feed it with `push` and
+/// `finish`; as an iterator, it yields the tokens complete so far and then
+/// `None` until more input is pushed. Like [`Tokenizer`](super::Tokenizer),
+/// it stops after the last token, or after yielding the first error.
+///
+/// Comments are reported to `on_comment` and tokens to `on_token` once
+/// they are complete, in source order.
+///
+/// The input pushed is kept for the life of the tokenizer, so it takes as
+/// much memory as the whole input: it spares the reads, not the memory.
+#[derive(Debug)]
+pub struct StreamTokenizer {
+    parser: Parser,
+    comments: Arc<Mutex<Vec<Comment>>>,
+    on_comment: Option<OnComment>,
+    on_token: Option<OnToken>,
+    started: bool,
+    finished: bool,
+    done: bool,
+    // When the token taken back is read again: as soon as new input has a
+    // character for which `wake` holds, since it may complete the token,
+    // or else once the input is `retry_at` long. Reading a long token
+    // anew only for such characters, or after the input has grown by the
+    // length of the token, keeps small chunks from making it quadratic.
+    retry_at: usize,
+    wake: Option<Wake>,
+    // How far the input has been searched for a `wake` character.
+    scanned: usize,
+}
+
+impl StreamTokenizer {
+    pub(crate) fn new(options: &Options) -> Self {
+        let comments = Arc::new(Mutex::new(vec![]));
+        let parser = Parser::new(
+            &Options {
+                on_comment: Some(OnComment::Collect(comments.clone())),
+                on_token: None,
+                ..options.clone()
+            },
+            "",
+            &None,
+        );
+        StreamTokenizer {
+            parser,
+            comments,
+            on_comment: options.on_comment.clone(),
+            on_token: options.on_token.clone(),
+            started: false,
+            finished: false,
+            done: false,
+            retry_at: 0,
+            wake: None,
+            scanned: 0,
+        }
+    }
+
+    /// Appends `chunk` to the input.
+    pub fn push(&mut self, chunk: &str) {
+        debug_assert!(!self.finished, "pushed input after `finish`");
+        self.parser.push_input(chunk);
+    }
+
+    /// Marks the end of the input, after which the remaining tokens can be
+    /// read.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Whether the last token or an error has been yielded.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// The input pushed so far, for rendering errors.
+    pub fn input(&self) -> &str {
+        &self.parser.input
+    }
+
+    /// Returns the errors collected so far with the `collect_errors`
+    /// option, like numeric separators out of place.
+    pub fn take_errors(&mut self) -> Vec<ParserError> {
+        self.parser.take_errors()
+    }
+
+    /// Skips a hashbang and finds out whether a script is strict, which
+    /// both need the start of the input. Returns whether that is done.
+    fn start(&mut self) -> bool {
+        let parser = &mut self.parser;
+        let input = parser.input.as_str();
+        if parser.options.allow_hash_bang && !self.finished {
+            let hash_bang_cut_short = "#!".starts_with(input)
+                || input.starts_with("#!") && !input[2..].contains(|ch| is_new_line(ch as i32));
+            if hash_bang_cut_short {
+                return false;
+            }
+        }
+        if parser.options.allow_hash_bang && input.starts_with("#!") {
+            parser.skip_line_comment(2);
+        }
+        if !parser.is_in_module && parser.options.get_ecma_version_number() >= 5 {
+            match scan_strict_directive(&parser.input, parser.cur_token_pos as usize, self.finished)
+            {
+                Some(is_strict) => parser.is_strict = is_strict,
+                None => {
+                    // Reads the hashbang again with the directives.
+                    parser.cur_token_pos = 0;
+                    self.comments.lock().unwrap().clear();
+                    return false;
+                }
+            }
+        }
+        self.started = true;
+        self.flush_comments();
+        true
+    }
+
+    /// Whether the input pushed since the last token was taken back may
+    /// complete it.
+    fn may_complete(&mut self) -> bool {
+        let input = self.parser.input.as_str();
+        if input.len() >= self.retry_at {
+            return true;
+        }
+        let woken = self
+            .wake
+            .is_some_and(|wake| input[self.scanned..].contains(wake));
+        self.scanned = input.len();
+        woken
+    }
+
+    /// Takes back the token just read from `checkpoint`, which the input
+    /// so far does not show the end of, and decides when to read it again.
+    fn take_back(&mut self, checkpoint: Checkpoint, result: &Result<Token, ParserError>) {
+        let len = self.parser.input.len() as i32;
+        let end = self.parser.cur_token_pos;
+        let unread = len - checkpoint.cur_token_pos;
+        let ends_in_line_comment = self.comments.lock().unwrap().last().is_some_and(|comment| {
+            comment.kind == CommentKind::Line && comment.end == self.parser.output_offset(len)
+        });
+        let (retry_at, wake): (i32, Option<Wake>) = match result {
+            Err(error) => match error.code() {
+                ErrorCode::UnterminatedString => (
+                    len + unread,
+                    Some(|ch| matches!(ch, '\'' | '"' | '\n' | '\r')),
+                ),
+                ErrorCode::UnterminatedTemplate => {
+                    (len + unread, Some(|ch| matches!(ch, '`' | '$')))
+                }
+                ErrorCode::UnterminatedComment => (len + unread, Some(|ch| ch == '/')),
+                ErrorCode::UnterminatedRegex => {
+                    (len + unread, Some(|ch| ch == '/' || is_new_line(ch as i32)))
+                }
+                _ => (len + 1, None),
+            },
+            // The token is known, only the characters after it are missing.
+            Ok(_) if end < len => (end + LOOKAHEAD, None),
+            Ok(_) if ends_in_line_comment => (len + unread, Some(|ch| is_new_line(ch as i32))),
+            Ok(token) if token.token_type.eq(&get_token_types().eof) => (len + 1, None),
+            // A name, a number or a punctuator up to the end of the input
+            // goes on while the characters that follow could be part of it.
+            Ok(_) => (
+                len + unread,
+                Some(|ch| !is_identifier_char(ch as i32, true) && ch != '\\'),
+            ),
+        };
+        checkpoint.restore(&mut self.parser);
+        self.comments.lock().unwrap().clear();
+        self.retry_at = retry_at as usize;
+        self.wake = wake;
+        self.scanned = len as usize;
+    }
+
+    /// Reports the comments read before the token just taken.
+    fn flush_comments(&mut self) {
+        let comments = mem::take(&mut *self.comments.lock().unwrap());
+        if let Some(on_comment) = &self.on_comment {
+            for comment in comments {
+                on_comment.push(comment);
+            }
+        }
+    }
+}
+
+impl Iterator for StreamTokenizer {
+    type Item = Result<Token, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || !self.finished && !self.may_complete() {
+            return None;
+        }
+        if !self.started && !self.start() {
+            return None;
+        }
+        let checkpoint = Checkpoint::save(&self.parser);
+        let result = self.parser.get_token();
+        let len = self.parser.input.len() as i32;
+        let complete = self.finished
+            || self.parser.cur_token_pos + LOOKAHEAD <= len
+                && !matches!(
+                    result.as_ref().map_err(ParserError::code),
+                    Err(ErrorCode::UnterminatedComment
+                        | ErrorCode::UnterminatedString
+                        | ErrorCode::UnterminatedTemplate
+                        | ErrorCode::UnterminatedRegex)
+                );
+        if !complete {
+            self.take_back(checkpoint, &result);
+            return None;
+        }
+        self.retry_at = 0;
+        self.wake = None;
+        self.flush_comments();
+        match result {
+            Ok(token) if token.token_type.eq(&get_token_types().eof) => {
+                self.done = true;
+                None
+            }
+            Ok(token) => {
+                let token = token.into_output(&self.parser);
+                if let Some(on_token) = &self.on_token {
+                    on_token.push(token.clone());
+                }
+                Some(Ok(token))
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(self.parser.output_error(error)))
+            }
+        }
+    }
+}
+
+/// A tokenizer over an input read from a `Read`, as returned by
+/// [`read_tokenizer`](crate::read_tokenizer). It yields each token once
+/// enough of the input has been read to show where it ends, and stops after
+/// the last token, or after yielding the first error. The input must be
+/// UTF-8.
+#[derive(Debug)]
+pub struct ReadTokenizer<R> {
+    reader: R,
+    tokenizer: StreamTokenizer,
+    // The bytes of a character split across two reads.
+    pending: Vec<u8>,
+}
+
+impl<R: Read> ReadTokenizer<R> {
+    pub(crate) fn new(reader: R, options: &Options) -> Self {
+        ReadTokenizer {
+            reader,
+            tokenizer: StreamTokenizer::new(options),
+            pending: vec![],
+        }
+    }
+
+    /// The input read so far, for rendering errors.
+    pub fn input(&self) -> &str {
+        self.tokenizer.input()
+    }
+
+    /// Returns the errors collected so far with the `collect_errors`
+    /// option, like numeric separators out of place.
+    pub fn take_errors(&mut self) -> Vec<ParserError> {
+        self.tokenizer.take_errors()
+    }
+
+    /// Reads the next chunk of the input into the tokenizer.
+    fn read_chunk(&mut self) -> Result<(), ParserError> {
+        let start = self.pending.len();
+        self.pending.resize(start + CHUNK_SIZE, 0);
+        let read = loop {
+            match self.reader.read(&mut self.pending[start..]) {
+                Ok(read) => break read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.pending.truncate(start);
+                    return Err(error.into());
+                }
+            }
+        };
+        self.pending.truncate(start + read);
+        if read == 0 {
+            if !self.pending.is_empty() {
+                return Err(invalid_utf8());
+            }
+            self.tokenizer.finish();
+            return Ok(());
+        }
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(chunk) => chunk.len(),
+            // A character cut at the end of the chunk is completed by the
+            // next one.
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(_) => return Err(invalid_utf8()),
+        };
+        let chunk = std::str::from_utf8(&self.pending[..valid]).unwrap();
+        self.tokenizer.push(chunk);
+        self.pending.drain(..valid);
+        Ok(())
+    }
+}
+
+fn invalid_utf8() -> ParserError {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "stream did not contain valid UTF-8",
+    )
+    .into()
+}
+
+impl<R: Read> Iterator for ReadTokenizer<R> {
+    type Item = Result<Token, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.tokenizer.next() {
+                return Some(token);
+            }
+            if self.tokenizer.is_done() {
+                return None;
+            }
+            if let Err(error) = self.read_chunk() {
+                self.tokenizer.done = true;
+                return Some(Err(error));
+            }
+        }
+    }
+}
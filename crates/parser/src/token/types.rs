@@ -1,5 +1,3 @@
-use std::{collections::HashMap, sync::RwLock};
-
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
@@ -32,12 +30,47 @@ impl TokenType {
     }
 }
 
-lazy_static! {
-    static ref KEYWORDS_LOCK: RwLock<HashMap<String, TokenType>> = RwLock::new(HashMap::new());
-}
-
-pub fn get_keywords() -> HashMap<String, TokenType> {
-    KEYWORDS_LOCK.read().unwrap().clone()
+/// The token type of the keyword `name`.
+pub fn get_keyword_token_type(name: &str) -> Option<&'static TokenType> {
+    let token_types = get_token_types();
+    Some(match name {
+        "break" => &token_types._break,
+        "case" => &token_types._case,
+        "catch" => &token_types._catch,
+        "continue" => &token_types._continue,
+        "debugger" => &token_types._debugger,
+        "default" => &token_types._default,
+        "do" => &token_types._do,
+        "else" => &token_types._else,
+        "finally" => &token_types._finally,
+        "for" => &token_types._for,
+        "function" => &token_types._function,
+        "if" => &token_types._if,
+        "return" => &token_types._return,
+        "switch" => &token_types._switch,
+        "throw" => &token_types._throw,
+        "try" => &token_types._try,
+        "var" => &token_types._var,
+        "const" => &token_types._const,
+        "while" => &token_types._while,
+        "with" => &token_types._with,
+        "new" => &token_types._new,
+        "this" => &token_types._this,
+        "super" => &token_types._super,
+        "class" => &token_types._class,
+        "extends" => &token_types._extends,
+        "export" => &token_types._export,
+        "import" => &token_types._import,
+        "null" => &token_types._null,
+        "true" => &token_types._true,
+        "false" => &token_types._false,
+        "in" => &token_types._in,
+        "instanceof" => &token_types._instanceof,
+        "typeof" => &token_types._typeof,
+        "void" => &token_types._void,
+        "delete" => &token_types._delete,
+        _ => return None,
+    })
 }
 
 fn create_binop(name: &str, binop: i32) -> TokenType {
@@ -50,13 +83,7 @@ fn create_binop(name: &str, binop: i32) -> TokenType {
 
 fn create_keyword(name: &str, mut options: JsonValue) -> TokenType {
     options["keyword"] = json!(name);
-    let token_type = TokenType::new(name, &options).unwrap();
-    let mut keywords_write_lock = KEYWORDS_LOCK.write().unwrap();
-    if keywords_write_lock.contains_key(name) {
-        keywords_write_lock.remove(name);
-    }
-    keywords_write_lock.insert(name.to_owned(), token_type.clone());
-    token_type
+    TokenType::new(name, &options).unwrap()
 }
 
 #[derive(Debug, Clone)]
@@ -246,5 +273,5 @@ lazy_static! {
 }
 
 pub fn get_token_types() -> &'static TokenTypes {
-    &(*TOKEN_TYPES)
+    &TOKEN_TYPES
 }
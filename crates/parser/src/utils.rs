@@ -1,24 +1,19 @@
-use std::borrow::Borrow;
-
 use fancy_regex::Regex;
 
 use crate::{
-    errors::ParserError,
+    errors::{ErrorCode, ParserError},
     location::LocationParser,
-    node::{Node, NodeType},
+    node::Expression,
     parser::Parser,
     token::{
         types::{get_token_types, TokenType},
-        TokenParser, TokenValue,
+        TokenParser,
     },
-    whitespace::{get_line_break_regex, get_skip_white_space_regex},
+    whitespace::{get_line_break_regex, is_new_line, skip_white_space},
 };
 
 pub fn get_string_from_codes(codes: Vec<i32>) -> String {
-    codes
-        .into_iter()
-        .map(|code| get_string_from_code(code))
-        .collect()
+    codes.into_iter().map(get_string_from_code).collect()
 }
 
 pub fn get_string_from_code(code: i32) -> String {
@@ -27,8 +22,45 @@ pub fn get_string_from_code(code: i32) -> String {
         .unwrap_or("".to_string())
 }
 
-pub fn get_codes_from_string(value: &str) -> Vec<i32> {
-    value.chars().map(|v| v as i32).collect()
+/// Formats a number the way JavaScript's `Number.prototype.toString` does:
+/// the shortest digits that round-trip, in plain notation for decimal
+/// exponents from -7 to 20 and in exponential notation otherwise.
+pub fn number_to_string(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    // Rust's exponential formatting already yields the shortest digits.
+    let formatted = format!("{:e}", value.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+    let body = if k <= n && n <= 21 {
+        digits + &"0".repeat((n - k) as usize)
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let exponent = if n > 0 {
+            format!("e+{}", n - 1)
+        } else {
+            format!("e-{}", 1 - n)
+        };
+        if k == 1 {
+            digits + &exponent
+        } else {
+            format!("{}.{}{}", &digits[..1], &digits[1..], exponent)
+        }
+    };
+    format!("{}{}", sign, body)
 }
 
 pub fn get_regex_from_words(words: &str) -> Regex {
@@ -37,25 +69,6 @@ pub fn get_regex_from_words(words: &str) -> Regex {
     Regex::new(&format!("{:}{:}{:}", r"^(?:", words, r")$")).unwrap()
 }
 
-pub fn get_sub_string(input: &str, start_index: usize, end_index: usize) -> String {
-    let length = input.len();
-    let start_index = if start_index > length {
-        length
-    } else {
-        start_index
-    };
-    let end_index = if end_index > length {
-        length
-    } else {
-        end_index
-    };
-    if start_index == end_index {
-        String::new()
-    } else {
-        String::from(&input[start_index..end_index])
-    }
-}
-
 pub struct DestructuringErrors {
     pub shorthand_assign: i32,
     pub trailing_comma: i32,
@@ -77,82 +90,108 @@ impl Default for DestructuringErrors {
 }
 
 pub trait UtilsParser {
-    fn strict_directive(&self, start: i32) -> Result<bool, ParserError>;
-    fn eat(&self, token_type: &TokenType) -> Result<bool, ParserError>;
-    fn is_contextual(&self, token_value: &TokenValue) -> bool;
-    fn eat_contextual(&self, token_value: &TokenValue) -> Result<bool, ParserError>;
-    fn expect_contextual(&self, token_value: &TokenValue) -> Result<(), ParserError>;
+    fn strict_directive(&self, start: i32) -> bool;
+    fn eat(&mut self, token_type: &TokenType) -> Result<bool, ParserError>;
+    fn is_contextual(&self, name: &str) -> bool;
+    fn eat_contextual(&mut self, name: &str) -> Result<bool, ParserError>;
+    fn expect_contextual(&mut self, name: &str) -> Result<(), ParserError>;
     fn can_insert_semicolon(&self) -> bool;
     fn insert_semicolon(&self) -> bool;
-    fn semicolon(&self) -> Result<(), ParserError>;
+    fn semicolon(&mut self) -> Result<(), ParserError>;
     fn after_trailing_comma(
-        &self,
+        &mut self,
         token_type: &TokenType,
         not_next: bool,
     ) -> Result<bool, ParserError>;
-    fn expect(&self, token_type: &TokenType) -> Result<(), ParserError>;
+    fn expect(&mut self, token_type: &TokenType) -> Result<(), ParserError>;
     fn unexpected(&self, pos: Option<i32>) -> Result<(), ParserError>;
     fn check_pattern_errors(
-        &self,
+        &mut self,
         destructuring_errors: &Option<DestructuringErrors>,
         is_assign: bool,
     ) -> Result<(), ParserError>;
     fn check_expression_errors(
-        &self,
+        &mut self,
         destructuring_errors: &Option<DestructuringErrors>,
         and_throw: bool,
     ) -> Result<bool, ParserError>;
     fn check_yield_await_in_default_params(&self) -> Result<(), ParserError>;
-    fn is_simple_assign_target(&self, node: &Node) -> bool;
+    fn is_simple_assign_target(&self, expr: &Expression) -> bool;
 }
 
-fn get_first_white_space(input: &str) -> Result<String, fancy_regex::Error> {
-    let skip_white_space_regex = get_skip_white_space_regex();
-    match skip_white_space_regex.captures(&input)? {
-        Some(captures) => Ok(captures
-            .get(0)
-            .map_or("".to_owned(), |m| m.as_str().to_owned())),
-        None => Ok("".to_owned()),
+/// Returns the length of the string literal at the start of `input`, and
+/// its content without the quotes, if there is one.
+fn read_directive_literal(input: &str) -> Option<(usize, &str)> {
+    let quote = input
+        .chars()
+        .next()
+        .filter(|ch| *ch == '\'' || *ch == '"')?;
+    let mut chars = input.char_indices().skip(1);
+    while let Some((index, ch)) = chars.next() {
+        if ch == '\\' {
+            chars.next();
+        } else if ch == quote {
+            return Some((index + 1, &input[1..index]));
+        }
     }
+    None
 }
 
-impl UtilsParser for Parser {
-    fn strict_directive(&self, start: i32) -> Result<bool, ParserError> {
-        let line_break_regex = get_line_break_regex();
-        let literal_regex = Regex::new(r#"^(?:'((?:\\.|[^'\\])*?)'|"((?:\\.|[^"\\])*?)")"#)?;
-        let input = self.input.as_str();
-        let mut start = start as usize;
-        loop {
-            start += get_first_white_space(&input[start..])?.len();
-            if let Some(captures) = literal_regex.captures(&input[start..])? {
-                let match_one = captures.get(0).map_or("", |m| m.as_str());
-                let match_two = captures.get(1).map_or("", |m| m.as_str());
-                let match_three = captures.get(2).map_or("", |m| m.as_str());
-                if match_two.eq("use strict") || match_three.eq("use strict") {
-                    let skip_white_space_last_index = start + match_one.len();
-                    let space_after = get_first_white_space(&input[skip_white_space_last_index..])?;
-                    let end = skip_white_space_last_index + space_after.len();
-                    let next = get_sub_string(&input, end, end + 1);
-                    return Ok(next.eq(";")
-                        || next.eq("}")
-                        || (line_break_regex.is_match(&space_after)?
-                            && !(Regex::new(r"[(`.\[+\-/*%<>=,?^&]")?.is_match(&next)?
-                                || next.eq("!")
-                                    && "=".eq(&get_sub_string(&input, end + 1, end + 2)))));
-                }
-                start += match_one.len();
-                // Skip semicolon, if any.
-                start += get_first_white_space(&input[start..])?.len();
-            } else {
-                return Ok(false);
+/// Whether the directive prologue of `input` from `start` has a `"use
+/// strict"` directive. When `input` is not `complete`, returns `None` if
+/// it ends before that is known.
+pub fn scan_strict_directive(input: &str, mut start: usize, complete: bool) -> Option<bool> {
+    // Whether more input could make `rest` read differently.
+    let cut_short =
+        |rest: &str| !complete && (rest.is_empty() || rest == "/" || rest.starts_with("/*"));
+    loop {
+        // Try to find string literal.
+        start += skip_white_space(input, start);
+        if cut_short(&input[start..]) {
+            return None;
+        }
+        let (len, content) = match read_directive_literal(&input[start..]) {
+            Some(literal) => literal,
+            None if !complete && input[start..].starts_with(['\'', '"']) => return None,
+            None => return Some(false),
+        };
+        if content == "use strict" {
+            let space_start = start + len;
+            let end = space_start + skip_white_space(input, space_start);
+            if cut_short(&input[end..]) || !complete && &input[end..] == "!" {
+                return None;
             }
+            let mut rest = input[end..].chars();
+            let next = rest.next();
+            return Some(match next {
+                Some(';' | '}') => true,
+                _ => {
+                    input[space_start..end].contains(|ch| is_new_line(ch as i32))
+                        && !(next.is_some_and(|ch| "(`.[+-/*%<>=,?^&".contains(ch))
+                            || next == Some('!') && rest.next() == Some('='))
+                }
+            });
+        }
+        start += len;
+
+        // Skip semicolon, if any.
+        start += skip_white_space(input, start);
+        if input[start..].starts_with(';') {
+            start += 1;
         }
     }
+}
+
+impl UtilsParser for Parser {
+    fn strict_directive(&self, start: i32) -> bool {
+        self.options.get_ecma_version_number() >= 5
+            && scan_strict_directive(&self.input, start as usize, true) == Some(true)
+    }
 
     /// Predicate that tests whether the next token is of the given
     /// type, and if yes, consumes it as a side effect.
-    fn eat(&self, token_type: &TokenType) -> Result<bool, ParserError> {
-        if self.cur_token_type.borrow().eq(token_type) {
+    fn eat(&mut self, token_type: &TokenType) -> Result<bool, ParserError> {
+        if self.cur_token_type.eq(token_type) {
             self.next(false)?;
             Ok(true)
         } else {
@@ -161,15 +200,15 @@ impl UtilsParser for Parser {
     }
 
     /// Tests whether parsed token is a contextual keyword.
-    fn is_contextual(&self, token_value: &TokenValue) -> bool {
-        self.cur_token_type.borrow().eq(&get_token_types().name)
-            && self.cur_token_value.borrow().eq(token_value)
+    fn is_contextual(&self, name: &str) -> bool {
+        self.cur_token_type.eq(&get_token_types().name)
+            && self.cur_token_value.as_str() == name
             && !self.contains_esc
     }
 
     /// Consumes contextual keyword if possible.
-    fn eat_contextual(&self, token_value: &TokenValue) -> Result<bool, ParserError> {
-        if self.is_contextual(token_value) {
+    fn eat_contextual(&mut self, name: &str) -> Result<bool, ParserError> {
+        if self.is_contextual(name) {
             self.next(false)?;
             Ok(true)
         } else {
@@ -178,8 +217,8 @@ impl UtilsParser for Parser {
     }
 
     /// Asserts that following token is given contextual keyword.
-    fn expect_contextual(&self, token_value: &TokenValue) -> Result<(), ParserError> {
-        if self.eat_contextual(token_value)? {
+    fn expect_contextual(&mut self, name: &str) -> Result<(), ParserError> {
+        if self.eat_contextual(name)? {
             Ok(())
         } else {
             self.unexpected(None)
@@ -189,21 +228,32 @@ impl UtilsParser for Parser {
     /// Test whether a semicolon can be inserted at the current position.
     fn can_insert_semicolon(&self) -> bool {
         let token_types = get_token_types();
-        let current_token_type = self.cur_token_type.borrow();
-        let input =
-            &self.input[self.last_token_end.get() as usize..self.cur_token_start.get() as usize];
+        let current_token_type = &self.cur_token_type;
+        let input = &self.input[self.last_token_end as usize..self.cur_token_start as usize];
         current_token_type.eq(&token_types.eof)
             || current_token_type.eq(&token_types.brace_r)
             || get_line_break_regex().is_match(input).unwrap_or(false)
     }
 
     fn insert_semicolon(&self) -> bool {
-        self.can_insert_semicolon()
+        if self.can_insert_semicolon() {
+            if let Some(on_insert_semicolon) = &self.options.on_insert_semicolon {
+                on_insert_semicolon.push((
+                    self.output_offset(self.last_token_end),
+                    self.last_token_end_loc
+                        .as_ref()
+                        .map(|loc| self.output_position(loc)),
+                ));
+            }
+            true
+        } else {
+            false
+        }
     }
 
     /// Consume a semicolon, or, failing that, see if we are allowed to
     /// pretend that there is a semicolon at this position.
-    fn semicolon(&self) -> Result<(), ParserError> {
+    fn semicolon(&mut self) -> Result<(), ParserError> {
         if self.eat(&get_token_types().semi)? || self.insert_semicolon() {
             Ok(())
         } else {
@@ -212,11 +262,19 @@ impl UtilsParser for Parser {
     }
 
     fn after_trailing_comma(
-        &self,
+        &mut self,
         token_type: &TokenType,
         not_next: bool,
     ) -> Result<bool, ParserError> {
-        if self.cur_token_type.borrow().eq(token_type) {
+        if self.cur_token_type.eq(token_type) {
+            if let Some(on_trailing_comma) = &self.options.on_trailing_comma {
+                on_trailing_comma.push((
+                    self.output_offset(self.last_token_start),
+                    self.last_token_start_loc
+                        .as_ref()
+                        .map(|loc| self.output_position(loc)),
+                ));
+            }
             if !not_next {
                 self.next(false)?;
             }
@@ -228,7 +286,7 @@ impl UtilsParser for Parser {
 
     /// Expect a token of a given type. If found, consume it, otherwise,
     /// raise an unexpected token error.
-    fn expect(&self, token_type: &TokenType) -> Result<(), ParserError> {
+    fn expect(&mut self, token_type: &TokenType) -> Result<(), ParserError> {
         if self.eat(token_type)? {
             Ok(())
         } else {
@@ -236,15 +294,17 @@ impl UtilsParser for Parser {
         }
     }
 
+    /// Raise an unexpected token error.
     fn unexpected(&self, pos: Option<i32>) -> Result<(), ParserError> {
-        self.raise_recoverable(
-            pos.unwrap_or(self.cur_token_start.get()),
+        self.raise(
+            pos.unwrap_or(self.cur_token_start),
+            ErrorCode::UnexpectedToken,
             "Unexpected token",
         )
     }
 
     fn check_pattern_errors(
-        &self,
+        &mut self,
         destructuring_errors: &Option<DestructuringErrors>,
         is_assign: bool,
     ) -> Result<(), ParserError> {
@@ -253,6 +313,7 @@ impl UtilsParser for Parser {
                 if destructuring_errors.trailing_comma > -1 {
                     self.raise_recoverable(
                         destructuring_errors.trailing_comma,
+                        ErrorCode::RestTrailingComma,
                         "Comma is not permitted after the rest element",
                     )
                 } else {
@@ -262,7 +323,11 @@ impl UtilsParser for Parser {
                         destructuring_errors.parenthesized_bind
                     };
                     if parens > -1 {
-                        self.raise_recoverable(parens, "Parenthesized pattern")
+                        self.raise_recoverable(
+                            parens,
+                            ErrorCode::InvalidAssignmentTarget,
+                            "Parenthesized pattern",
+                        )
                     } else {
                         Ok(())
                     }
@@ -273,7 +338,7 @@ impl UtilsParser for Parser {
     }
 
     fn check_expression_errors(
-        &self,
+        &mut self,
         destructuring_errors: &Option<DestructuringErrors>,
         and_throw: bool,
     ) -> Result<bool, ParserError> {
@@ -285,12 +350,17 @@ impl UtilsParser for Parser {
                     if shorthand_assign >= 0 {
                         self.raise(
                             shorthand_assign,
+                            ErrorCode::InvalidDestructuring,
                             "Shorthand property assignments are valid only in destructuring \
                              patterns",
                         )?;
                     }
                     if double_proto >= 0 {
-                        self.raise_recoverable(double_proto, "Redefinition of __proto__ property")?;
+                        self.raise_recoverable(
+                            double_proto,
+                            ErrorCode::DuplicateProto,
+                            "Redefinition of __proto__ property",
+                        )?;
                     }
                 }
                 Ok(shorthand_assign >= 0 || double_proto >= 0)
@@ -300,31 +370,38 @@ impl UtilsParser for Parser {
     }
 
     fn check_yield_await_in_default_params(&self) -> Result<(), ParserError> {
-        if let Some(yield_pos) = self.yield_pos.get() {
-            let need_raise_error = match self.await_pos.get() {
+        if let Some(yield_pos) = self.yield_pos {
+            let need_raise_error = match self.await_pos {
                 Some(await_pos) => yield_pos < await_pos,
                 None => true,
             };
             if need_raise_error {
-                return self.raise(yield_pos, "Yield expression cannot be a default value");
+                return self.raise(
+                    yield_pos,
+                    ErrorCode::InvalidParameterDefault,
+                    "Yield expression cannot be a default value",
+                );
             }
         }
 
-        if let Some(await_pos) = self.await_pos.get() {
-            self.raise(await_pos, "Await expression cannot be a default value")
+        if let Some(await_pos) = self.await_pos {
+            self.raise(
+                await_pos,
+                ErrorCode::InvalidParameterDefault,
+                "Await expression cannot be a default value",
+            )
         } else {
             Ok(())
         }
     }
 
-    fn is_simple_assign_target(&self, node: &Node) -> bool {
-        if node.node_type == NodeType::ParenthesizedExpression {
-            match node.expression.borrow() {
-                Some(v) => self.is_simple_assign_target(v),
-                None => false,
+    fn is_simple_assign_target(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::ParenthesizedExpression(paren) => {
+                self.is_simple_assign_target(&paren.expression)
             }
-        } else {
-            node.node_type == NodeType::Identifier || node.node_type == NodeType::MemberExpression
+            Expression::Identifier(_) | Expression::MemberExpression(_) => true,
+            _ => false,
         }
     }
 }
@@ -0,0 +1,445 @@
+// ### AST traversal
+//
+// `Visit` walks a tree by shared reference and `VisitMut` by mutable
+// reference. Both have one method per node type and per node enum, which
+// by default walks the children of the node in source order, by calling
+// the matching `walk_*` function. A visitor overrides the methods of the
+// nodes it is interested in and calls the `walk_*` function itself when
+// it wants to keep going down.
+//
+// Around the walk of every node, the default methods call the `enter`
+// and `leave` hooks with a `NodeRef` (or `NodeMut`) to the node. `enter`
+// decides whether the children are walked: when it returns
+// `VisitControl::Skip`, neither the children nor `leave` are visited.
+//
+// With `VisitMut`, a node is replaced by assigning to the reference the
+// visitor is given. A node is only replaced by one of the same type, so
+// replacing, say, a `BinaryExpression` by an `Identifier` is done from
+// `visit_expression`.
+//
+// Both traits are generated from the table in `for_each_node`, which
+// lists the child fields of every node type in `node`.
+
+use crate::node::*;
+
+/// Whether the walk goes into the children of the node just entered.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum VisitControl {
+    Continue,
+    Skip,
+}
+
+/// Calls `$callback` with a table of the node types and their children,
+/// and of the node enums and their variants.
+macro_rules! for_each_node {
+    ($callback:ident! { $($args:tt)* }) => {
+        $callback! {
+            $($args)*
+            nodes {
+                Program(visit_program, walk_program) { body }
+                ExpressionStatement(visit_expression_statement, walk_expression_statement) {
+                    expression
+                }
+                BlockStatement(visit_block_statement, walk_block_statement) { body }
+                EmptyStatement(visit_empty_statement, walk_empty_statement) {}
+                DebuggerStatement(visit_debugger_statement, walk_debugger_statement) {}
+                WithStatement(visit_with_statement, walk_with_statement) { object, body }
+                ReturnStatement(visit_return_statement, walk_return_statement) { argument }
+                LabeledStatement(visit_labeled_statement, walk_labeled_statement) { label, body }
+                BreakStatement(visit_break_statement, walk_break_statement) { label }
+                ContinueStatement(visit_continue_statement, walk_continue_statement) { label }
+                IfStatement(visit_if_statement, walk_if_statement) {
+                    test, consequent, alternate
+                }
+                SwitchStatement(visit_switch_statement, walk_switch_statement) {
+                    discriminant, cases
+                }
+                SwitchCase(visit_switch_case, walk_switch_case) { test, consequent }
+                ThrowStatement(visit_throw_statement, walk_throw_statement) { argument }
+                TryStatement(visit_try_statement, walk_try_statement) {
+                    block, handler, finalizer
+                }
+                CatchClause(visit_catch_clause, walk_catch_clause) { param, body }
+                WhileStatement(visit_while_statement, walk_while_statement) { test, body }
+                DoWhileStatement(visit_do_while_statement, walk_do_while_statement) {
+                    body, test
+                }
+                ForStatement(visit_for_statement, walk_for_statement) {
+                    init, test, update, body
+                }
+                ForInStatement(visit_for_in_statement, walk_for_in_statement) {
+                    left, right, body
+                }
+                ForOfStatement(visit_for_of_statement, walk_for_of_statement) {
+                    left, right, body
+                }
+                VariableDeclaration(visit_variable_declaration, walk_variable_declaration) {
+                    declarations
+                }
+                VariableDeclarator(visit_variable_declarator, walk_variable_declarator) {
+                    id, init
+                }
+                Function(visit_function, walk_function) { id, params, body }
+                ArrowFunctionExpression(
+                    visit_arrow_function_expression,
+                    walk_arrow_function_expression
+                ) { params, body }
+                Class(visit_class, walk_class) { id, super_class, body }
+                ClassBody(visit_class_body, walk_class_body) { body }
+                MethodDefinition(visit_method_definition, walk_method_definition) { key, value }
+                PropertyDefinition(visit_property_definition, walk_property_definition) {
+                    key, value
+                }
+                StaticBlock(visit_static_block, walk_static_block) { body }
+                Identifier(visit_identifier, walk_identifier) {}
+                PrivateIdentifier(visit_private_identifier, walk_private_identifier) {}
+                Literal(visit_literal, walk_literal) {}
+                ThisExpression(visit_this_expression, walk_this_expression) {}
+                Super(visit_super, walk_super) {}
+                ArrayExpression(visit_array_expression, walk_array_expression) { elements }
+                SpreadElement(visit_spread_element, walk_spread_element) { argument }
+                ObjectExpression(visit_object_expression, walk_object_expression) {
+                    properties
+                }
+                Property(visit_property, walk_property) { key, value }
+                TemplateLiteral(visit_template_literal, walk_template_literal) {
+                    expressions, quasis
+                }
+                TemplateElement(visit_template_element, walk_template_element) {}
+                TaggedTemplateExpression(
+                    visit_tagged_template_expression,
+                    walk_tagged_template_expression
+                ) { tag, quasi }
+                MemberExpression(visit_member_expression, walk_member_expression) {
+                    object, property
+                }
+                CallExpression(visit_call_expression, walk_call_expression) {
+                    callee, arguments
+                }
+                NewExpression(visit_new_expression, walk_new_expression) { callee, arguments }
+                ChainExpression(visit_chain_expression, walk_chain_expression) { expression }
+                SequenceExpression(visit_sequence_expression, walk_sequence_expression) {
+                    expressions
+                }
+                UnaryExpression(visit_unary_expression, walk_unary_expression) { argument }
+                UpdateExpression(visit_update_expression, walk_update_expression) { argument }
+                BinaryExpression(visit_binary_expression, walk_binary_expression) {
+                    left, right
+                }
+                LogicalExpression(visit_logical_expression, walk_logical_expression) {
+                    left, right
+                }
+                AssignmentExpression(visit_assignment_expression, walk_assignment_expression) {
+                    left, right
+                }
+                ConditionalExpression(
+                    visit_conditional_expression,
+                    walk_conditional_expression
+                ) { test, consequent, alternate }
+                YieldExpression(visit_yield_expression, walk_yield_expression) { argument }
+                AwaitExpression(visit_await_expression, walk_await_expression) { argument }
+                MetaProperty(visit_meta_property, walk_meta_property) { meta, property }
+                ImportExpression(visit_import_expression, walk_import_expression) { source }
+                ParenthesizedExpression(
+                    visit_parenthesized_expression,
+                    walk_parenthesized_expression
+                ) { expression }
+                ObjectPattern(visit_object_pattern, walk_object_pattern) { properties }
+                AssignmentProperty(visit_assignment_property, walk_assignment_property) {
+                    key, value
+                }
+                ArrayPattern(visit_array_pattern, walk_array_pattern) { elements }
+                RestElement(visit_rest_element, walk_rest_element) { argument }
+                AssignmentPattern(visit_assignment_pattern, walk_assignment_pattern) {
+                    left, right
+                }
+                ImportDeclaration(visit_import_declaration, walk_import_declaration) {
+                    specifiers, source
+                }
+                ImportSpecifier(visit_import_specifier, walk_import_specifier) {
+                    imported, local
+                }
+                ImportDefaultSpecifier(
+                    visit_import_default_specifier,
+                    walk_import_default_specifier
+                ) { local }
+                ImportNamespaceSpecifier(
+                    visit_import_namespace_specifier,
+                    walk_import_namespace_specifier
+                ) { local }
+                ExportNamedDeclaration(
+                    visit_export_named_declaration,
+                    walk_export_named_declaration
+                ) { declaration, specifiers, source }
+                ExportSpecifier(visit_export_specifier, walk_export_specifier) {
+                    local, exported
+                }
+                ExportDefaultDeclaration(
+                    visit_export_default_declaration,
+                    walk_export_default_declaration
+                ) { declaration }
+                ExportAllDeclaration(visit_export_all_declaration, walk_export_all_declaration) {
+                    exported, source
+                }
+            }
+            enums {
+                Statement(visit_statement, walk_statement) {
+                    ExpressionStatement,
+                    BlockStatement,
+                    EmptyStatement,
+                    DebuggerStatement,
+                    WithStatement,
+                    ReturnStatement,
+                    LabeledStatement,
+                    BreakStatement,
+                    ContinueStatement,
+                    IfStatement,
+                    SwitchStatement,
+                    ThrowStatement,
+                    TryStatement,
+                    WhileStatement,
+                    DoWhileStatement,
+                    ForStatement,
+                    ForInStatement,
+                    ForOfStatement,
+                    FunctionDeclaration,
+                    VariableDeclaration,
+                    ClassDeclaration,
+                    ModuleDeclaration
+                }
+                ForInit(visit_for_init, walk_for_init) { VariableDeclaration, Expression }
+                ForLeft(visit_for_left, walk_for_left) { VariableDeclaration, Pattern }
+                Declaration(visit_declaration, walk_declaration) {
+                    FunctionDeclaration,
+                    VariableDeclaration,
+                    ClassDeclaration
+                }
+                ArrowFunctionBody(visit_arrow_function_body, walk_arrow_function_body) {
+                    BlockStatement,
+                    Expression
+                }
+                ClassElement(visit_class_element, walk_class_element) {
+                    MethodDefinition,
+                    PropertyDefinition,
+                    StaticBlock
+                }
+                Expression(visit_expression, walk_expression) {
+                    Identifier,
+                    Literal,
+                    ThisExpression,
+                    Super,
+                    ArrayExpression,
+                    ObjectExpression,
+                    FunctionExpression,
+                    ArrowFunctionExpression,
+                    ClassExpression,
+                    TemplateLiteral,
+                    TaggedTemplateExpression,
+                    MemberExpression,
+                    CallExpression,
+                    NewExpression,
+                    ChainExpression,
+                    SequenceExpression,
+                    UnaryExpression,
+                    UpdateExpression,
+                    BinaryExpression,
+                    LogicalExpression,
+                    AssignmentExpression,
+                    ConditionalExpression,
+                    YieldExpression,
+                    AwaitExpression,
+                    MetaProperty,
+                    ImportExpression,
+                    ParenthesizedExpression,
+                    PrivateIdentifier
+                }
+                ExpressionOrSpread(visit_expression_or_spread, walk_expression_or_spread) {
+                    Expression,
+                    SpreadElement
+                }
+                PropertyOrSpread(visit_property_or_spread, walk_property_or_spread) {
+                    Property,
+                    SpreadElement
+                }
+                Pattern(visit_pattern, walk_pattern) {
+                    Identifier,
+                    ObjectPattern,
+                    ArrayPattern,
+                    RestElement,
+                    AssignmentPattern,
+                    MemberExpression,
+                    ParenthesizedExpression
+                }
+                PropertyOrRest(visit_property_or_rest, walk_property_or_rest) {
+                    Property,
+                    RestElement
+                }
+                ModuleDeclaration(visit_module_declaration, walk_module_declaration) {
+                    ImportDeclaration,
+                    ExportNamedDeclaration,
+                    ExportDefaultDeclaration,
+                    ExportAllDeclaration
+                }
+                ImportDeclarationSpecifier(
+                    visit_import_declaration_specifier,
+                    walk_import_declaration_specifier
+                ) { ImportSpecifier, ImportDefaultSpecifier, ImportNamespaceSpecifier }
+                ModuleExportName(visit_module_export_name, walk_module_export_name) {
+                    Identifier,
+                    Literal
+                }
+                ExportDefaultDeclarationKind(
+                    visit_export_default_declaration_kind,
+                    walk_export_default_declaration_kind
+                ) { FunctionDeclaration, ClassDeclaration, Expression }
+            }
+        }
+    };
+}
+
+/// Generates a visitor trait, its node reference enum and its walk
+/// functions from the table of `for_each_node`. `ref [mut]` makes the
+/// visitor go through mutable references.
+macro_rules! visitor {
+    (@ref $lifetime:lifetime [$($mutability:tt)*] $node:ty) => {
+        &$lifetime $($mutability)* $node
+    };
+    (@ref [$($mutability:tt)*] $node:ty) => {
+        &$($mutability)* $node
+    };
+    (@reborrow [$($mutability:tt)*] $node:ident) => {
+        &$($mutability)* *$node
+    };
+    (
+        $(#[$visit_meta:meta])*
+        pub trait $visit:ident;
+        $(#[$node_ref_meta:meta])*
+        pub enum $node_ref:ident;
+        trait $visit_with:ident::$visit_with_fn:ident;
+        ref $mutability:tt;
+        nodes {
+            $($node:ident($visit_node:ident, $walk_node:ident) { $($field:ident),* })*
+        }
+        enums {
+            $($enum:ident($visit_enum:ident, $walk_enum:ident) { $($variant:ident),* })*
+        }
+    ) => {
+        $(#[$node_ref_meta])*
+        pub enum $node_ref<'a> {
+            $($node(visitor!(@ref 'a $mutability $node)),)*
+        }
+
+        impl Spanned for $node_ref<'_> {
+            fn span(&self) -> &Span {
+                match self {
+                    $($node_ref::$node(node) => node.span(),)*
+                }
+            }
+        }
+
+        $(#[$visit_meta])*
+        pub trait $visit {
+            /// Called before the children of a node are walked.
+            fn enter(&mut self, _node: $node_ref<'_>) -> VisitControl {
+                VisitControl::Continue
+            }
+
+            /// Called after the children of a node have been walked.
+            fn leave(&mut self, _node: $node_ref<'_>) {}
+
+            $(
+                fn $visit_node(&mut self, node: visitor!(@ref $mutability $node)) {
+                    let entered = self.enter($node_ref::$node(visitor!(@reborrow $mutability node)));
+                    if entered == VisitControl::Continue {
+                        $walk_node(self, visitor!(@reborrow $mutability node));
+                        self.leave($node_ref::$node(node));
+                    }
+                }
+            )*
+
+            $(
+                fn $visit_enum(&mut self, node: visitor!(@ref $mutability $enum)) {
+                    $walk_enum(self, node)
+                }
+            )*
+        }
+
+        $(
+            #[allow(unused_variables)]
+            pub fn $walk_node<V: ?Sized + $visit>(
+                visitor: &mut V,
+                node: visitor!(@ref $mutability $node),
+            ) {
+                $(node.$field.$visit_with_fn(visitor);)*
+            }
+        )*
+
+        $(
+            pub fn $walk_enum<V: ?Sized + $visit>(
+                visitor: &mut V,
+                node: visitor!(@ref $mutability $enum),
+            ) {
+                match node {
+                    $($enum::$variant(node) => node.$visit_with_fn(visitor),)*
+                }
+            }
+        )*
+
+        /// Dispatches a child field, whatever its container, to the method
+        /// of the visitor for its type.
+        trait $visit_with<V: ?Sized> {
+            fn $visit_with_fn(self: visitor!(@ref $mutability Self), visitor: &mut V);
+        }
+
+        $(
+            impl<V: ?Sized + $visit> $visit_with<V> for $node {
+                fn $visit_with_fn(self: visitor!(@ref $mutability Self), visitor: &mut V) {
+                    visitor.$visit_node(self)
+                }
+            }
+        )*
+
+        $(
+            impl<V: ?Sized + $visit> $visit_with<V> for $enum {
+                fn $visit_with_fn(self: visitor!(@ref $mutability Self), visitor: &mut V) {
+                    visitor.$visit_enum(self)
+                }
+            }
+        )*
+
+        impl<V: ?Sized, T: $visit_with<V>> $visit_with<V> for Box<T> {
+            fn $visit_with_fn(self: visitor!(@ref $mutability Self), visitor: &mut V) {
+                (**self).$visit_with_fn(visitor)
+            }
+        }
+
+        impl<V: ?Sized, T: $visit_with<V>> $visit_with<V> for Option<T> {
+            fn $visit_with_fn(self: visitor!(@ref $mutability Self), visitor: &mut V) {
+                if let Some(node) = self {
+                    node.$visit_with_fn(visitor)
+                }
+            }
+        }
+
+        impl<V: ?Sized, T: $visit_with<V>> $visit_with<V> for Vec<T> {
+            fn $visit_with_fn(self: visitor!(@ref $mutability Self), visitor: &mut V) {
+                for node in self {
+                    node.$visit_with_fn(visitor)
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use for_each_node;
+pub(crate) use visitor;
+
+for_each_node!(visitor! {
+    /// A visitor over a tree borrowed immutably.
+    pub trait Visit;
+    /// A reference to any node, passed to `Visit::enter` and `Visit::leave`.
+    #[derive(Debug, Clone, Copy)]
+    pub enum NodeRef;
+    trait VisitWith::visit_with;
+    ref [];
+});
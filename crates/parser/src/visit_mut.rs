@@ -0,0 +1,38 @@
+// ### Mutable AST traversal
+//
+// The counterpart of `Visit` that walks a tree by mutable reference, so
+// that visitors can rewrite or replace nodes. See `visit` for how the
+// traversal works.
+
+use crate::{
+    node::*,
+    visit::{for_each_node, visitor, VisitControl},
+};
+
+for_each_node!(visitor! {
+    /// A visitor over a tree borrowed mutably.
+    pub trait VisitMut;
+    /// A mutable reference to any node, passed to `VisitMut::enter` and
+    /// `VisitMut::leave`.
+    #[derive(Debug)]
+    pub enum NodeMut;
+    trait VisitMutWith::visit_mut_with;
+    ref [mut];
+});
+
+macro_rules! node_mut_span {
+    (
+        nodes { $($node:ident($visit_node:ident, $walk_node:ident) { $($field:ident),* })* }
+        enums { $($enums:tt)* }
+    ) => {
+        impl NodeMut<'_> {
+            pub fn span_mut(&mut self) -> &mut Span {
+                match self {
+                    $(NodeMut::$node(node) => &mut node.span,)*
+                }
+            }
+        }
+    };
+}
+
+for_each_node!(node_mut_span! {});
@@ -1,7 +1,7 @@
 use fancy_regex::Regex;
 use lazy_static::lazy_static;
 
-use crate::{char_codes::*, utils::get_codes_from_string};
+use crate::char_codes::*;
 
 pub fn is_new_line(code: i32) -> bool {
     code == LINE_FEED
@@ -10,33 +10,50 @@ pub fn is_new_line(code: i32) -> bool {
         || code == PARAGRAPH_SEPARATOR
 }
 
-pub fn next_line_break(code: &str, start: i32, end: i32) -> i32 {
-    let codes = get_codes_from_string(code);
-    for index in start..end {
-        let next = codes[index as usize];
-        if is_new_line(next) {
-            return if index < end - 1
-                && next == CARRIAGE_RETURN
-                && codes[index as usize + 1] == LINE_FEED
-            {
-                index + 2
-            } else {
-                index + 1
-            };
-        }
-    }
-    -1
+pub fn is_non_ascii_white_space(code: i32) -> bool {
+    code == OGHAM_SPACE_MARK
+        || (0x2000..=0x200a).contains(&code)
+        || code == 0x202f
+        || code == 0x205f
+        || code == 0x3000
+        || code == 0xfeff
 }
 
 lazy_static! {
     static ref LINE_BREAK_REGEX: Regex = Regex::new(r"\r\n?|\n|\u2028|\u2029").unwrap();
-    static ref SKIP_WHITE_SPACE_REGEX: Regex = Regex::new(r"(?:\s|\/\/.*|\/\**?\*\/)*").unwrap();
 }
 
 pub fn get_line_break_regex() -> &'static Regex {
-    &(*LINE_BREAK_REGEX)
+    &LINE_BREAK_REGEX
+}
+
+/// Whether `ch` matches `\s` in JavaScript regular expressions.
+pub fn is_white_space(ch: char) -> bool {
+    matches!(ch, '\t' | '\u{b}' | '\u{c}' | ' ' | '\u{a0}')
+        || is_new_line(ch as i32)
+        || is_non_ascii_white_space(ch as i32)
 }
 
-pub fn get_skip_white_space_regex() -> &'static Regex {
-    &(*SKIP_WHITE_SPACE_REGEX)
+/// Returns the length of the whitespace and comments starting at `pos`.
+pub fn skip_white_space(input: &str, pos: usize) -> usize {
+    let mut rest = &input[pos..];
+    loop {
+        if let Some(comment) = rest.strip_prefix("//") {
+            let len = comment
+                .find(|ch| is_new_line(ch as i32))
+                .unwrap_or(comment.len());
+            rest = &comment[len..];
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            match comment.find("*/") {
+                Some(len) => rest = &comment[len + 2..],
+                None => break,
+            }
+        } else {
+            match rest.chars().next() {
+                Some(ch) if is_white_space(ch) => rest = &rest[ch.len_utf8()..],
+                _ => break,
+            }
+        }
+    }
+    input.len() - pos - rest.len()
 }
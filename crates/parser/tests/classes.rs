@@ -0,0 +1,116 @@
+mod common;
+
+use common::{assert_error, assert_parses, script};
+
+#[test]
+fn valid_classes() {
+    for input in [
+        "class A { get #a() {} set #a(v) {} }",
+        "class A { #a; m() { this?.#a } }",
+        "class A extends B { constructor() { super() } }",
+        "class A { static { super.x } }",
+        "class A { #a; static m(o) { return #a in o } }",
+        "class A { a\nb }",
+        "class A { static async *#m() {} }",
+        "class A { ['constructor']() {} ['constructor']() {} }",
+        "class A { m() { class B { [this.#x] } } #x }",
+        "class A { x = () => super.x }",
+    ] {
+        assert_parses(input, &script());
+    }
+}
+
+#[test]
+fn class_errors() {
+    for (input, message) in [
+        (
+            "class A { constructor() {} constructor() {} }",
+            "Duplicate constructor in the same class (1:27)",
+        ),
+        (
+            "class A { get constructor() {} }",
+            "Constructor can't have get/set modifier (1:14)",
+        ),
+        (
+            "class A { *constructor() {} }",
+            "Constructor can't be a generator (1:11)",
+        ),
+        (
+            "class A { async constructor() {} }",
+            "Constructor can't be an async method (1:16)",
+        ),
+        (
+            "class A { static prototype() {} }",
+            "Classes may not have a static property named prototype (1:17)",
+        ),
+        (
+            "class A { static prototype = 1 }",
+            "Classes can't have a static field named 'prototype' (1:17)",
+        ),
+        (
+            "class A { constructor = 1 }",
+            "Classes can't have a field named 'constructor' (1:10)",
+        ),
+        (
+            "class A { #constructor() {} }",
+            "Classes can't have an element named '#constructor' (1:10)",
+        ),
+        (
+            "class A { #a; #a; }",
+            "Identifier '#a' has already been declared (1:14)",
+        ),
+        (
+            "class A { get #a() {} static set #a(v) {} }",
+            "Identifier '#a' has already been declared (1:33)",
+        ),
+        (
+            "class A { m() { this.#b } }",
+            "Private field '#b' must be declared in an enclosing class (1:21)",
+        ),
+        (
+            "class A { #a; m() { delete this.#a } }",
+            "Private fields can not be deleted (1:20)",
+        ),
+        (
+            "class A { constructor() { super() } }",
+            "super() call outside constructor of a subclass (1:26)",
+        ),
+        (
+            "class A extends B { m() { super() } }",
+            "super() call outside constructor of a subclass (1:26)",
+        ),
+        (
+            "class A extends B { x = super() }",
+            "super() call outside constructor of a subclass (1:24)",
+        ),
+        (
+            "class A { x = arguments }",
+            "Cannot use 'arguments' in class field initializer (1:14)",
+        ),
+        (
+            "class A { static { return } }",
+            "'return' outside of function (1:19)",
+        ),
+        (
+            "class A { static { arguments } }",
+            "Cannot use arguments in class static initialization block (1:19)",
+        ),
+        (
+            "class A { static { var await } }",
+            "Cannot use await in class static initialization block (1:23)",
+        ),
+        (
+            "class A { m() { #a in this } }",
+            "Private field '#a' must be declared in an enclosing class (1:16)",
+        ),
+        ("class A { #a; m() { #a + 1 } }", "Unexpected token (1:23)"),
+        (
+            "class A { 'constructor'() {} 'constructor'() {} }",
+            "Duplicate constructor in the same class (1:29)",
+        ),
+        ("class let {}", "The keyword 'let' is reserved (1:6)"),
+        ("class A extends B, C {}", "Unexpected token (1:17)"),
+    ] {
+        assert_error(input, &script(), message);
+    }
+}
@@ -0,0 +1,47 @@
+use muse_parser::{parse, parse_with_errors, ErrorCode, Options, ParserError};
+
+fn collecting() -> Options {
+    Options {
+        collect_errors: true,
+        ..Options::default()
+    }
+}
+
+fn codes(errors: &[ParserError]) -> Vec<ErrorCode> {
+    errors.iter().map(ParserError::code).collect()
+}
+
+#[test]
+fn collects_recoverable_errors() {
+    let (program, errors) =
+        parse_with_errors("let a; let a; var b = 1__0;", &collecting()).unwrap();
+    assert_eq!(program.body.len(), 3);
+    assert_eq!(
+        codes(&errors),
+        [
+            ErrorCode::DuplicateBinding,
+            ErrorCode::InvalidNumericSeparator
+        ]
+    );
+}
+
+#[test]
+fn keeps_collected_errors_after_a_fatal_one() {
+    let errors = parse_with_errors("let a; let a; var b = 1__0; @", &collecting()).unwrap_err();
+    assert_eq!(
+        codes(&errors),
+        [
+            ErrorCode::DuplicateBinding,
+            ErrorCode::InvalidNumericSeparator,
+            ErrorCode::UnexpectedCharacter
+        ]
+    );
+}
+
+#[test]
+fn parse_fails_with_the_first_error() {
+    let error = parse("let a; let a; @", &collecting()).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::DuplicateBinding);
+    let error = parse("let a; let a; @", &Options::default()).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::DuplicateBinding);
+}
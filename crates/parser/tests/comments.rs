@@ -0,0 +1,216 @@
+use std::sync::{Arc, Mutex};
+
+use muse_parser::{
+    attach_comments, parse,
+    visit::{NodeRef, Visit, VisitControl},
+    Comment, CommentKind, OffsetUnit, OnComment, Options, Position, SourceLocation, Spanned,
+};
+
+/// The comments of `input`, collected while it is parsed with `options`.
+fn collect(input: &str, options: &Options) -> Vec<Comment> {
+    let comments = Arc::new(Mutex::new(vec![]));
+    let options = Options {
+        on_comment: Some(OnComment::Collect(comments.clone())),
+        ..options.clone()
+    };
+    parse(input, &options).unwrap();
+    let comments = comments.lock().unwrap().clone();
+    comments
+}
+
+/// Lists where the comments are attached, as the side, the node type and
+/// the source text of the node, and the value of the comment.
+struct Attachments<'a> {
+    input: &'a str,
+    found: Vec<String>,
+}
+
+impl Visit for Attachments<'_> {
+    fn enter(&mut self, node: NodeRef<'_>) -> VisitControl {
+        let span = node.span();
+        if let Some(comments) = &span.comments {
+            let debug = format!("{:?}", node);
+            let name = debug.split('(').next().unwrap();
+            let text = &self.input[span.start as usize..span.end as usize];
+            for (side, comments) in [
+                ("leading", &comments.leading),
+                ("trailing", &comments.trailing),
+            ] {
+                for comment in comments {
+                    self.found.push(format!(
+                        "{} {} {:?}: {}",
+                        side,
+                        name,
+                        text,
+                        comment.value.trim()
+                    ));
+                }
+            }
+        }
+        VisitControl::Continue
+    }
+}
+
+fn attached(input: &str) -> Vec<String> {
+    let options = Options::default();
+    let comments = collect(input, &options);
+    let mut program = parse(input, &options).unwrap();
+    attach_comments(&mut program, &comments, input, OffsetUnit::Utf8);
+    let mut attachments = Attachments {
+        input,
+        found: vec![],
+    };
+    attachments.visit_program(&program);
+    attachments.found
+}
+
+#[test]
+fn collects_comments_in_source_order() {
+    let input = "#!/usr/bin/env node\n/* a */ x; // b\r\n<!-- c\n--> d\n";
+    let options = Options {
+        allow_hash_bang: true,
+        ..Options::default()
+    };
+    let comments = collect(input, &options);
+    let described: Vec<_> = comments
+        .iter()
+        .map(|comment| {
+            (
+                comment.kind,
+                comment.value.as_str(),
+                comment.start,
+                comment.end,
+            )
+        })
+        .collect();
+    assert_eq!(
+        described,
+        [
+            (CommentKind::Line, "/usr/bin/env node", 0, 19),
+            (CommentKind::Block, " a ", 20, 27),
+            (CommentKind::Line, " b", 31, 35),
+            (CommentKind::Line, " c", 37, 43),
+            (CommentKind::Line, " d", 44, 49),
+        ]
+    );
+    assert!(comments
+        .iter()
+        .all(|comment| comment.loc.is_none() && comment.range.is_none()));
+}
+
+#[test]
+fn reports_comment_locations_and_ranges() {
+    let options = Options {
+        locations: true,
+        ranges: true,
+        offset_unit: OffsetUnit::Utf16,
+        source_file: Some("a.js".to_owned()),
+        ..Options::default()
+    };
+    let comments = collect("'😀'; /* a\nb */ x // c", &options);
+    assert_eq!(
+        comments[0],
+        Comment {
+            kind: CommentKind::Block,
+            value: " a\nb ".to_owned(),
+            start: 6,
+            end: 15,
+            loc: Some(SourceLocation {
+                start: Position::new(1, 6),
+                end: Some(Position::new(2, 4)),
+                source: Some("a.js".into()),
+            }),
+            range: Some((6, 15)),
+        }
+    );
+    assert_eq!(comments[1].start, 18);
+    assert_eq!(comments[1].end, 22);
+    assert_eq!(comments[1].range, Some((18, 22)));
+}
+
+#[test]
+fn callbacks_see_the_collected_comments() {
+    let input = "/* a */ f(/* b */) // c\n";
+    let seen = Arc::new(Mutex::new(vec![]));
+    let options = Options {
+        on_comment: Some(OnComment::callback({
+            let seen = seen.clone();
+            move |comment: &Comment| seen.lock().unwrap().push(comment.clone())
+        })),
+        ..Options::default()
+    };
+    parse(input, &options).unwrap();
+    assert_eq!(*seen.lock().unwrap(), collect(input, &Options::default()));
+}
+
+#[test]
+fn attaches_leading_comments_to_the_outermost_node() {
+    assert_eq!(
+        attached("// a\n/* b */ x = 1;\n"),
+        [
+            "leading ExpressionStatement \"x = 1;\": a",
+            "leading ExpressionStatement \"x = 1;\": b",
+        ]
+    );
+    assert_eq!(attached("f(/* a */ x, y)"), ["leading Identifier \"x\": a"]);
+}
+
+#[test]
+fn attaches_trailing_comments_to_the_innermost_node() {
+    assert_eq!(attached("f(x /* a */)"), ["trailing Identifier \"x\": a"]);
+    assert_eq!(
+        attached("x = 1; // a"),
+        ["trailing ExpressionStatement \"x = 1;\": a"]
+    );
+}
+
+#[test]
+fn attaches_comments_ending_a_line_to_the_node_before() {
+    assert_eq!(
+        attached("function f(){} // x\ny;"),
+        ["trailing BlockStatement \"{}\": x"]
+    );
+    assert_eq!(
+        attached("a; /* x */ // y\n// z\nb;"),
+        [
+            "trailing ExpressionStatement \"a;\": x",
+            "trailing ExpressionStatement \"a;\": y",
+            "leading ExpressionStatement \"b;\": z",
+        ]
+    );
+    assert_eq!(
+        attached("{ // x\n  a; }"),
+        ["leading ExpressionStatement \"a;\": x"]
+    );
+    assert_eq!(
+        attached("a; /* x */ b;"),
+        ["leading ExpressionStatement \"b;\": x"]
+    );
+}
+
+#[test]
+fn leaves_comments_in_empty_blocks() {
+    assert_eq!(attached("function f() { /* a */ }"), Vec::<String>::new());
+    assert_eq!(attached("f(/* a */)"), Vec::<String>::new());
+}
+
+#[test]
+fn attaches_with_utf16_offsets() {
+    let input = "'😀'; // a\nx; /* b */";
+    let options = Options {
+        offset_unit: OffsetUnit::Utf16,
+        ..Options::default()
+    };
+    let comments = collect(input, &options);
+    let mut program = parse(input, &options).unwrap();
+    attach_comments(&mut program, &comments, input, OffsetUnit::Utf16);
+    let counts = |index: usize| {
+        program.body[index]
+            .span()
+            .comments
+            .as_ref()
+            .map(|comments| (comments.leading.len(), comments.trailing.len()))
+    };
+    assert_eq!(counts(0), Some((0, 1)));
+    assert_eq!(counts(1), Some((0, 1)));
+}
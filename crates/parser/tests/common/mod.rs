@@ -0,0 +1,59 @@
+// Helpers shared by the tests, which check the programs and the messages
+// of acorn 8.10 for the same inputs.
+
+#![allow(dead_code)]
+
+use muse_parser::{parse, to_json, Options, Program, SourceType};
+use serde_json::Value;
+
+pub fn script() -> Options {
+    Options::default()
+}
+
+pub fn module() -> Options {
+    Options {
+        source_type: SourceType::Module,
+        ..Options::default()
+    }
+}
+
+#[track_caller]
+pub fn assert_parses(input: &str, options: &Options) -> Program {
+    match parse(input, options) {
+        Ok(program) => program,
+        Err(error) => panic!("{:?} does not parse: {}", input, error),
+    }
+}
+
+#[track_caller]
+pub fn assert_error(input: &str, options: &Options, message: &str) {
+    match parse(input, options) {
+        Ok(_) => panic!("{:?} parses", input),
+        Err(error) => assert_eq!(error.to_string(), message, "for {:?}", input),
+    }
+}
+
+/// The JSON of the program of `input`, without the positions, to compare
+/// the trees of inputs written differently.
+#[track_caller]
+pub fn shape(input: &str, options: &Options) -> Value {
+    positionless(&assert_parses(input, options))
+}
+
+/// The JSON of `program`, without the positions.
+pub fn positionless(program: &Program) -> Value {
+    fn strip(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.remove("start");
+                map.remove("end");
+                map.values_mut().for_each(strip);
+            }
+            Value::Array(values) => values.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+    let mut value = serde_json::from_str(&to_json(program).unwrap()).unwrap();
+    strip(&mut value);
+    value
+}
@@ -0,0 +1,26 @@
+use muse_parser::{parse, ErrorCode, Options, RenderStyle};
+
+#[test]
+fn displays_the_message() {
+    let error = parse("x = ;", &Options::default()).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::UnexpectedToken);
+    assert_eq!(error.to_string(), "Unexpected token (1:4)");
+}
+
+#[test]
+fn renders_the_source_and_labels() {
+    let input = "let a = 1;\nlet a = 2;\n";
+    let error = parse(input, &Options::default()).unwrap_err();
+    assert_eq!(
+        error.render(input, Some("a.js"), RenderStyle::Plain),
+        "\
+error[DuplicateBinding]: Identifier 'a' has already been declared
+ --> a.js:2:5
+  |
+1 | let a = 1;
+  |     - first declared here
+2 | let a = 2;
+  |     ^
+"
+    );
+}
@@ -0,0 +1,76 @@
+#[path = "../benches/common/mod.rs"]
+mod corpus;
+
+use muse_parser::{parse, to_json, Options, Program, SourceType};
+
+#[test]
+fn writes_acorn_json() {
+    let program = parse("a = 1.5;\n/x/g; `t${a}`", &Options::default()).unwrap();
+    assert_eq!(
+        to_json(&program).unwrap(),
+        concat!(
+            r#"{"type":"Program","start":0,"end":22,"body":["#,
+            r#"{"type":"ExpressionStatement","start":0,"end":8,"expression":"#,
+            r#"{"type":"AssignmentExpression","start":0,"end":7,"operator":"=","#,
+            r#""left":{"type":"Identifier","start":0,"end":1,"name":"a"},"#,
+            r#""right":{"type":"Literal","start":4,"end":7,"value":1.5,"raw":"1.5"}}},"#,
+            r#"{"type":"ExpressionStatement","start":9,"end":14,"expression":"#,
+            r#"{"type":"Literal","start":9,"end":13,"value":{},"raw":"/x/g","#,
+            r#""regex":{"pattern":"x","flags":"g"}}},"#,
+            r#"{"type":"ExpressionStatement","start":15,"end":22,"expression":"#,
+            r#"{"type":"TemplateLiteral","start":15,"end":22,"expressions":["#,
+            r#"{"type":"Identifier","start":19,"end":20,"name":"a"}],"quasis":["#,
+            r#"{"type":"TemplateElement","start":16,"end":17,"value":{"raw":"t","cooked":"t"},"tail":false},"#,
+            r#"{"type":"TemplateElement","start":21,"end":21,"value":{"raw":"","cooked":""},"tail":true}]}}],"#,
+            r#""sourceType":"script"}"#
+        )
+    );
+}
+
+#[test]
+fn writes_locations_and_ranges() {
+    let options = Options {
+        locations: true,
+        ranges: true,
+        source_file: Some("a.js".to_owned()),
+        ..Options::default()
+    };
+    let program = parse("a", &options).unwrap();
+    let position = r#""loc":{"start":{"line":1,"column":0},"end":{"line":1,"column":1},"source":"a.js"},"range":[0,1]"#;
+    assert_eq!(
+        to_json(&program).unwrap(),
+        format!(
+            r#"{{"type":"Program","start":0,"end":1,{0},"body":[{{"type":"ExpressionStatement","start":0,"end":1,{0},"expression":{{"type":"Identifier","start":0,"end":1,{0},"name":"a"}}}}],"sourceType":"script"}}"#,
+            position
+        )
+    );
+}
+
+#[test]
+fn round_trips() {
+    let input = format!(
+        "{}\nlabel: for (const [a, , ...b] of c) {{ if (a?.[b] ?? 1n) continue label; }}\nx = \
+         `a${{y}}b` + /re/giu.source + 0x10 + 1e21 + -0;",
+        corpus::source(3)
+    );
+    for options in [
+        Options {
+            source_type: SourceType::Module,
+            ..Options::default()
+        },
+        Options {
+            source_type: SourceType::Module,
+            locations: true,
+            ranges: true,
+            source_file: Some("a.js".to_owned()),
+            preserve_parens: true,
+            ..Options::default()
+        },
+    ] {
+        let program = parse(&input, &options).unwrap();
+        let json = to_json(&program).unwrap();
+        let read: Program = serde_json::from_str(&json).unwrap();
+        assert!(read == program);
+        assert_eq!(to_json(&read).unwrap(), json);
+    }
+}
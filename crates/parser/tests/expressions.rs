@@ -0,0 +1,128 @@
+mod common;
+
+use common::{assert_error, assert_parses, script, shape};
+
+#[test]
+fn precedence_and_associativity() {
+    let options = script();
+    for (input, grouped) in [
+        ("a + b * c", "a + (b * c)"),
+        ("a - b - c", "(a - b) - c"),
+        ("a ** b ** c", "a ** (b ** c)"),
+        ("a = b = c", "a = (b = c)"),
+        ("a || b && c", "a || (b && c)"),
+        ("a ? b : c ? d : e", "a ? b : (c ? d : e)"),
+        ("a, b = c", "a, (b = c)"),
+        ("!a in b", "(!a) in b"),
+        ("new a.b()", "new (a.b)()"),
+        ("new a().b", "(new a()).b"),
+        ("x = y => z, w", "(x = (y => z)), w"),
+    ] {
+        assert_eq!(
+            shape(input, &options),
+            shape(grouped, &options),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn valid_expressions() {
+    for input in [
+        "({a = 1} = x)",
+        "a?.b?.(c)",
+        "async => async",
+        "a ** -b",
+        "(a ?? b) || c",
+        "x = {__proto__: 1, __proto__}",
+        "(a, b) => c",
+        "async (a) => await a",
+        "() => {}",
+        "`${a}${b}`",
+        "a ? b : c = d",
+        "[a, , b = 1, ...c] = d",
+        "({a: [b], ...c} = d)",
+        "tag`\\unicode`",
+    ] {
+        assert_parses(input, &script());
+    }
+}
+
+#[test]
+fn expression_errors() {
+    for (input, message) in [
+        ("1 = 2", "Assigning to rvalue (1:0)"),
+        ("(a, b) = 1", "Assigning to rvalue (1:1)"),
+        ("++a++", "Assigning to rvalue (1:2)"),
+        (
+            "a ?? b || c",
+            "Logical expressions and coalesce expressions cannot be mixed. Wrap either by \
+             parentheses (1:7)",
+        ),
+        (
+            "a || b ?? c",
+            "Logical expressions and coalesce expressions cannot be mixed. Wrap either by \
+             parentheses (1:7)",
+        ),
+        ("-a ** 2", "Unexpected token (1:3)"),
+        (
+            "new.target",
+            "'new.target' can only be used in functions and class static block (1:0)",
+        ),
+        (
+            "a?.b = 1",
+            "Optional chaining cannot appear in left-hand side (1:0)",
+        ),
+        (
+            "new a?.b()",
+            "Optional chaining cannot appear in the callee of new expressions (1:5)",
+        ),
+        (
+            "a?.`x`",
+            "Optional chaining cannot appear in the tag of tagged template expressions (1:3)",
+        ),
+        (
+            "({a = 1})",
+            "Shorthand property assignments are valid only in destructuring patterns (1:4)",
+        ),
+        (
+            "\"use strict\"; delete x",
+            "Deleting local variable in strict mode (1:14)",
+        ),
+        (
+            "\"use strict\"; eval = 1",
+            "Assigning to eval in strict mode (1:14)",
+        ),
+        (
+            "\"use strict\"; arguments++",
+            "Assigning to arguments in strict mode (1:14)",
+        ),
+        (
+            "x = {__proto__: 1, __proto__: 2}",
+            "Redefinition of __proto__ property (1:19)",
+        ),
+        ("a => { super.x }", "'super' keyword outside a method (1:7)"),
+        (
+            "[...a, b] = c",
+            "Comma is not permitted after the rest element (1:5)",
+        ),
+        (
+            "({...a,} = c)",
+            "Comma is not permitted after the rest element (1:6)",
+        ),
+        (
+            "(...a, b) => c",
+            "Comma is not permitted after the rest element (1:5)",
+        ),
+        ("(a, a) => c", "Argument name clash (1:4)"),
+        ("a\n=> b", "Unexpected token (2:0)"),
+        (
+            "import.meta",
+            "Cannot use 'import.meta' outside a module (1:0)",
+        ),
+        ("new import(x)", "Cannot use new with import() (1:4)"),
+    ] {
+        assert_error(input, &script(), message);
+    }
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::{assert_error, assert_parses, script, shape};
+
+#[test]
+fn valid_functions() {
+    for input in [
+        "function f(a, a) {}",
+        "function f() { new.target }",
+        "({ m() { super.x } })",
+        "function f() { return }",
+        "async function* g() { yield await x; for await (const a of b); }",
+        "({ async *m() { yield await 1 } })",
+        "function f(a) { var a; }",
+        "a => { \"use strict\"; } ; ({a} = 1) => 1",
+    ] {
+        assert_parses(input, &script());
+    }
+}
+
+#[test]
+fn function_errors() {
+    for (input, message) in [
+        (
+            "function f(a, a) { \"use strict\" }",
+            "Argument name clash (1:14)",
+        ),
+        (
+            "function f(a = 1) { \"use strict\" }",
+            "Illegal 'use strict' directive in function with non-simple parameter list (1:0)",
+        ),
+        (
+            "\"use strict\"; function f(a, a) {}",
+            "Argument name clash (1:28)",
+        ),
+        ("(a, a) => 1", "Argument name clash (1:4)"),
+        (
+            "function f(...a,) {}",
+            "Comma is not permitted after the rest element (1:15)",
+        ),
+        ("function f(...a = 1) {}", "Unexpected token (1:16)"),
+        ("function* g() { yield\n* 1 }", "Unexpected token (2:0)"),
+        (
+            "function* g() { function yield() {} }",
+            "Cannot use 'yield' as identifier inside a generator (1:25)",
+        ),
+        (
+            "function* g() { var yield; }",
+            "Cannot use 'yield' as identifier inside a generator (1:20)",
+        ),
+        (
+            "function* g(x = yield) {}",
+            "Yield expression cannot be a default value (1:16)",
+        ),
+        ("async function f() { await }", "Unexpected token (1:27)"),
+        (
+            "async function f(x = await 1) {}",
+            "Await expression cannot be a default value (1:21)",
+        ),
+        (
+            "async function f() { function await() {} }",
+            "Cannot use 'await' as identifier inside an async function (1:30)",
+        ),
+        ("async () => await", "Unexpected token (1:17)"),
+        ("async (x = await 1) => x", "Unexpected token (1:17)"),
+        (
+            "function f() { super.x }",
+            "'super' keyword outside a method (1:15)",
+        ),
+        (
+            "({ m() { super() } })",
+            "super() call outside constructor of a subclass (1:9)",
+        ),
+        (
+            "function eval() { \"use strict\" }",
+            "Binding eval in strict mode (1:9)",
+        ),
+        (
+            "function f(eval) { \"use strict\" }",
+            "Binding eval in strict mode (1:11)",
+        ),
+        (
+            "function f() { for await (const a of b); }",
+            "Unexpected token (1:19)",
+        ),
+        ("function* g() { yield => 1 }", "Unexpected token (1:22)"),
+        (
+            "async function f() { (await) => 1 }",
+            "Unexpected token (1:27)",
+        ),
+        ("({ get x(a) {} })", "getter should have no params (1:8)"),
+        (
+            "({ set x() {} })",
+            "setter should have exactly one param (1:8)",
+        ),
+        (
+            "({ set x(...a) {} })",
+            "Setter cannot use rest params (1:9)",
+        ),
+        (
+            "function f() { let a; { var a; } }",
+            "Identifier 'a' has already been declared (1:28)",
+        ),
+        (
+            "function f(a) { let a; }",
+            "Identifier 'a' has already been declared (1:20)",
+        ),
+        (
+            "(a) => { let a; }",
+            "Identifier 'a' has already been declared (1:13)",
+        ),
+    ] {
+        assert_error(input, &script(), message);
+    }
+}
+
+#[test]
+fn function_flags() {
+    for (input, generator, is_async, expression) in [
+        ("function f() {}", false, false, false),
+        ("function* f() {}", true, false, false),
+        ("async function f() {}", false, true, false),
+        ("async function* f() {}", true, true, false),
+        ("(async () => 1)", false, true, true),
+        ("(() => {})", false, false, false),
+    ] {
+        let program = shape(input, &script());
+        let statement = &program["body"][0];
+        let function = if statement["type"] == "ExpressionStatement" {
+            &statement["expression"]
+        } else {
+            statement
+        };
+        assert_eq!(function["generator"], generator, "{}", input);
+        assert_eq!(function["async"], is_async, "{}", input);
+        assert_eq!(function["expression"], expression, "{}", input);
+    }
+}
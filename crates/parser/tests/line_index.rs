@@ -0,0 +1,64 @@
+use muse_parser::{LineIndex, Position};
+
+const INPUT: &str = "a\r\nb\rc\nd\u{2028}e\u{2029}f";
+
+fn line_starts(index: &LineIndex) -> Vec<i32> {
+    (1..=index.line_count() as i32)
+        .map(|line| index.line_start(line))
+        .collect()
+}
+
+#[test]
+fn counts_every_line_terminator() {
+    let index = LineIndex::new(INPUT);
+    assert_eq!(line_starts(&index), [0, 3, 5, 7, 11, 15]);
+    assert_eq!(line_starts(&LineIndex::new("")), [0]);
+    assert_eq!(line_starts(&LineIndex::new("a\n")), [0, 2]);
+    assert_eq!(line_starts(&LineIndex::new("\r\r\n\n")), [0, 1, 3, 4]);
+}
+
+#[test]
+fn finds_positions() {
+    let index = LineIndex::new(INPUT);
+    for (offset, line, column) in [
+        (0, 1, 0),
+        (1, 1, 1),
+        // Between the `\r` and the `\n` of a line break.
+        (2, 1, 2),
+        (3, 2, 0),
+        (4, 2, 1),
+        (5, 3, 0),
+        (8, 4, 1),
+        (11, 5, 0),
+        (15, 6, 0),
+        // The end of the input.
+        (16, 6, 1),
+    ] {
+        assert_eq!(
+            index.position(offset),
+            Position::new(line, column),
+            "at {}",
+            offset
+        );
+    }
+    assert_eq!(LineIndex::new("a\n").position(2), Position::new(2, 0));
+    assert_eq!(LineIndex::new("").position(0), Position::new(1, 0));
+}
+
+#[test]
+fn extends_like_a_new_index() {
+    for input in [INPUT, "\r\n\r\n", "a\r\r\nb"] {
+        let expected = line_starts(&LineIndex::new(input));
+        for from in (0..=input.len()).filter(|&from| input.is_char_boundary(from)) {
+            let mut index = LineIndex::new(&input[..from]);
+            index.extend(input, from);
+            assert_eq!(line_starts(&index), expected, "{:?} from {}", input, from);
+            if input.is_char_boundary(from / 2) {
+                let mut twice = LineIndex::new(&input[..from / 2]);
+                twice.extend(&input[..from], from / 2);
+                twice.extend(input, from);
+                assert_eq!(line_starts(&twice), expected, "{:?} from {}", input, from);
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+mod common;
+#[path = "../benches/common/mod.rs"]
+mod corpus;
+
+use common::{module, positionless, script, shape};
+use muse_parser::{parse, parse_loose, ErrorCode, Options, ParserError, DUMMY_VALUE};
+use serde_json::Value;
+
+/// The program `parse_loose` returns for `input`, without the positions,
+/// with `dummy` for the dummy identifiers, and the errors it recovered
+/// from.
+fn loose(input: &str, options: &Options) -> (Value, Vec<ParserError>) {
+    let (program, errors) = parse_loose(input, options).unwrap();
+    let json = positionless(&program)
+        .to_string()
+        .replace(DUMMY_VALUE, "dummy");
+    (serde_json::from_str(&json).unwrap(), errors)
+}
+
+fn messages(errors: &[ParserError]) -> Vec<String> {
+    errors.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn valid_input_parses_as_with_parse() {
+    for (input, options) in [
+        (corpus::source(2), module()),
+        (
+            "with (a) b; x = `a${b}c` / 2 / /re/g; if (a) { b } else c\nlabel: for (;;) break \
+             label"
+                .to_owned(),
+            script(),
+        ),
+    ] {
+        let (program, errors) = parse_loose(&input, &options).unwrap();
+        assert!(errors.is_empty(), "{:?}", messages(&errors));
+        assert!(program == parse(&input, &options).unwrap());
+    }
+}
+
+#[test]
+fn fills_in_dummy_identifiers() {
+    for (input, filled, error) in [
+        ("x = ;", "x = dummy;", "Unexpected token (1:4)"),
+        ("a.", "a.dummy", "Unexpected token (1:2)"),
+        ("var = 1;", "var dummy = 1;", "Unexpected token (1:4)"),
+    ] {
+        let (program, errors) = loose(input, &script());
+        assert_eq!(program, shape(filled, &script()), "{:?}", input);
+        assert_eq!(messages(&errors), [error], "{:?}", input);
+    }
+}
+
+#[test]
+fn closes_unclosed_lists() {
+    for (input, closed, error) in [
+        ("f(a, b", "f(a, b)", "Unexpected token (1:6)"),
+        (
+            "x = [1, 2\ny = 3",
+            "x = [1, 2]\ny = 3",
+            "Unexpected token (2:0)",
+        ),
+    ] {
+        let (program, errors) = loose(input, &script());
+        assert_eq!(program, shape(closed, &script()), "{:?}", input);
+        assert_eq!(messages(&errors), [error], "{:?}", input);
+    }
+    let (program, errors) = loose("x = 'abc\ny", &script());
+    let string = &program["body"][0]["expression"]["right"];
+    assert_eq!(
+        (&string["value"], &string["raw"]),
+        (&"abc".into(), &"'abc".into())
+    );
+    assert_eq!(program["body"][1]["expression"]["name"], "y");
+    assert_eq!(messages(&errors), ["Unterminated string constant (1:4)"]);
+}
+
+#[test]
+fn closes_blocks_by_indentation() {
+    for (input, closed) in [
+        ("if (a) {\n  b;\nc;", "if (a) {\n  b;\n}\nc;"),
+        (
+            "function f() {\n  if (a) {\n    b;\n  c;\n}\nd;",
+            "function f() {\n  if (a) {\n    b;\n  }\n  c;\n}\nd;",
+        ),
+    ] {
+        let (program, errors) = loose(input, &script());
+        assert_eq!(program, shape(closed, &script()), "{:?}", input);
+        assert_eq!(errors[0].code(), ErrorCode::UnexpectedToken);
+    }
+}
+
+#[test]
+fn reports_an_unterminated_template_once() {
+    for (input, expected) in [
+        ("x = `abc", &["Unterminated template (1:5)"][..]),
+        ("x = `abc${a}def", &["Unterminated template (1:12)"]),
+        ("x = `abc${a", &["Unexpected token (1:11)"]),
+    ] {
+        let (_, errors) = parse_loose(input, &script()).unwrap();
+        assert_eq!(messages(&errors), expected, "{:?}", input);
+    }
+    let (program, _) = loose("x = `abc", &script());
+    assert_eq!(
+        program["body"][0]["expression"]["right"]["quasis"][0]["value"]["raw"],
+        "abc"
+    );
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{assert_error, assert_parses, module, script};
+
+#[test]
+fn valid_modules() {
+    for input in [
+        "export {a}; var a;",
+        "import a, * as b from \"m\"; a = 1",
+        "export {\"a b\" as c} from \"m\"",
+        "import {\"a b\" as c} from \"m\"",
+        "export async function f() {}",
+        "export default async function () {}",
+        "export default class {}",
+        "await x",
+        "import.meta.url",
+        "x = import(\"m\")",
+        "export {default} from \"m\"",
+        "export * from \"m\"; export * from \"n\"",
+    ] {
+        assert_parses(input, &module());
+    }
+}
+
+#[test]
+fn module_errors() {
+    for (input, message) in [
+        ("export {a}", "Export 'a' is not defined (1:8)"),
+        (
+            "export {a as b, c as b}; var a, c;",
+            "Duplicate export 'b' (1:21)",
+        ),
+        (
+            "export default 1; export default 2;",
+            "Duplicate export 'default' (1:25)",
+        ),
+        (
+            "export var a; export let a;",
+            "Identifier 'a' has already been declared (1:25)",
+        ),
+        (
+            "export function f() {} export {f as default}; export default 1",
+            "Duplicate export 'default' (1:53)",
+        ),
+        (
+            "export {a as default}; export default 1; var a",
+            "Duplicate export 'default' (1:30)",
+        ),
+        (
+            "import {a} from \"m\"; import {a} from \"n\";",
+            "Identifier 'a' has already been declared (1:29)",
+        ),
+        (
+            "if (1) import a from \"m\"",
+            "'import' and 'export' may only appear at the top level (1:7)",
+        ),
+        (
+            "function f() { export {} }",
+            "'import' and 'export' may only appear at the top level (1:15)",
+        ),
+        (
+            "export * as ns from \"m\"; export {ns}",
+            "Duplicate export 'ns' (1:33)",
+        ),
+        (
+            "export {\"a b\"}",
+            "A string literal cannot be used as an exported binding without `from`. (1:8)",
+        ),
+        ("import {\"a b\"} from \"m\"", "Binding rvalue (1:8)"),
+        ("export class {}", "Unexpected token (1:13)"),
+        ("with (a) {}", "'with' in strict mode (1:0)"),
+        ("010", "Invalid number (1:0)"),
+        ("<!-- x", "Unexpected token (1:0)"),
+        ("export {default}", "Unexpected keyword 'default' (1:8)"),
+        (
+            "import {default} from \"m\"",
+            "Unexpected keyword 'default' (1:8)",
+        ),
+        (
+            "let await",
+            "Cannot use keyword 'await' outside an async function (1:4)",
+        ),
+        (
+            "export {a} from \"m\"; var b; export {b as a}",
+            "Duplicate export 'a' (1:41)",
+        ),
+        (
+            "import x from \"m\" assert { type: \"json\" }",
+            "Unexpected token (1:18)",
+        ),
+    ] {
+        assert_error(input, &module(), message);
+    }
+}
+
+#[test]
+fn import_and_export_need_a_module() {
+    for input in ["import a from \"m\"", "export {}"] {
+        assert_error(
+            input,
+            &script(),
+            "'import' and 'export' may appear only with 'sourceType: module' (1:0)",
+        );
+    }
+    assert_parses("x = import(\"m\")", &script());
+}
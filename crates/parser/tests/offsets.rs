@@ -0,0 +1,167 @@
+use muse_parser::{
+    parse, parse_expression_at, tokenizer, OffsetConverter, OffsetUnit, Options, ParserError,
+    Spanned,
+};
+
+const INPUT: &str = "\"😀é\"; x = 1;\n\"é\"; y";
+
+fn options(unit: OffsetUnit) -> Options {
+    Options {
+        offset_unit: unit,
+        locations: true,
+        ..Options::default()
+    }
+}
+
+/// The start and end of a node, and the line and column of each.
+type Place = (i32, i32, (i32, i32), (i32, i32));
+
+/// Where the statements of `INPUT` are.
+fn statements(unit: OffsetUnit) -> Vec<Place> {
+    let program = parse(INPUT, &options(unit)).unwrap();
+    program
+        .body
+        .iter()
+        .map(|statement| {
+            let span = statement.span();
+            let loc = span.loc.as_ref().unwrap();
+            let end = loc.end.as_ref().unwrap();
+            (
+                span.start,
+                span.end,
+                (loc.start.line, loc.start.column),
+                (end.line, end.column),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn nodes() {
+    assert_eq!(
+        statements(OffsetUnit::Utf8),
+        [
+            (0, 9, (1, 0), (1, 9)),
+            (10, 16, (1, 10), (1, 16)),
+            (17, 22, (2, 0), (2, 5)),
+            (23, 24, (2, 6), (2, 7)),
+        ]
+    );
+    assert_eq!(
+        statements(OffsetUnit::Utf16),
+        [
+            (0, 6, (1, 0), (1, 6)),
+            (7, 13, (1, 7), (1, 13)),
+            (14, 18, (2, 0), (2, 4)),
+            (19, 20, (2, 5), (2, 6)),
+        ]
+    );
+    assert_eq!(
+        statements(OffsetUnit::CodePoint),
+        [
+            (0, 5, (1, 0), (1, 5)),
+            (6, 12, (1, 6), (1, 12)),
+            (13, 17, (2, 0), (2, 4)),
+            (18, 19, (2, 5), (2, 6)),
+        ]
+    );
+}
+
+#[test]
+fn errors() {
+    for (unit, pos) in [
+        (OffsetUnit::Utf8, 8),
+        (OffsetUnit::Utf16, 6),
+        (OffsetUnit::CodePoint, 5),
+    ] {
+        match parse("\"😀\"; @", &options(unit)).unwrap_err() {
+            ParserError::SyntaxError {
+                message,
+                pos: error_pos,
+                loc,
+                unit: error_unit,
+                ..
+            } => {
+                assert_eq!(message, format!("Unexpected character '@' (1:{})", pos));
+                assert_eq!((error_pos, loc.column, error_unit), (pos, pos, unit));
+            }
+            error => panic!("{:?}", error),
+        }
+    }
+}
+
+#[test]
+fn tokens() {
+    for (unit, spans) in [
+        (OffsetUnit::Utf8, [(0, 6), (7, 8)]),
+        (OffsetUnit::Utf16, [(0, 4), (5, 6)]),
+        (OffsetUnit::CodePoint, [(0, 3), (4, 5)]),
+    ] {
+        let tokens: Vec<_> = tokenizer("\"😀\" a", &options(unit))
+            .map(|token| {
+                let token = token.unwrap();
+                (token.start, token.end)
+            })
+            .collect();
+        assert_eq!(tokens, spans, "{:?}", unit);
+    }
+}
+
+#[test]
+fn expression_offsets() {
+    let input = "\"😀\" + a + b";
+    for (unit, offset, span) in [
+        (OffsetUnit::Utf8, 9, (9, 14)),
+        (OffsetUnit::Utf16, 7, (7, 12)),
+        (OffsetUnit::CodePoint, 6, (6, 11)),
+    ] {
+        let expression = parse_expression_at(input, offset, &options(unit)).unwrap();
+        assert_eq!(
+            (expression.span().start, expression.span().end),
+            span,
+            "{:?}",
+            unit
+        );
+    }
+    // Offsets inside a character are rejected.
+    for (unit, offset) in [(OffsetUnit::Utf8, 3), (OffsetUnit::Utf16, 2)] {
+        assert!(matches!(
+            parse_expression_at(input, offset, &options(unit)),
+            Err(ParserError::InvalidOffset(invalid)) if invalid == offset
+        ));
+    }
+}
+
+#[test]
+fn checked_conversions_to_utf8() {
+    let input = "\"😀\" + a";
+    let offsets = OffsetConverter::new(input);
+    for (unit, offset, utf8) in [
+        (OffsetUnit::Utf8, 5, Ok(5)),
+        (OffsetUnit::Utf16, 3, Ok(5)),
+        (OffsetUnit::CodePoint, 2, Ok(5)),
+        (OffsetUnit::Utf16, 7, Ok(9)),
+        // The end of the input.
+        (OffsetUnit::Utf16, 8, Ok(10)),
+        (OffsetUnit::CodePoint, 7, Ok(10)),
+        // Inside the emoji, past the end, and before the start.
+        (OffsetUnit::Utf8, 2, Err(2)),
+        (OffsetUnit::Utf16, 2, Err(2)),
+        (OffsetUnit::Utf8, 11, Err(11)),
+        (OffsetUnit::Utf16, 9, Err(9)),
+        (OffsetUnit::CodePoint, -1, Err(-1)),
+    ] {
+        assert_eq!(
+            offsets
+                .to_utf8_checked(input, offset, unit)
+                .map_err(|error| match error {
+                    ParserError::InvalidOffset(offset) => offset,
+                    error => panic!("{}", error),
+                }),
+            utf8,
+            "{:?} {}",
+            unit,
+            offset
+        );
+    }
+}
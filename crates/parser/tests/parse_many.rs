@@ -0,0 +1,62 @@
+#[path = "../benches/common/mod.rs"]
+mod corpus;
+
+use muse_parser::{parse, parse_many, Options, ParserError, Program, SourceType};
+
+fn describe(result: &Result<Program, ParserError>) -> Result<&Program, String> {
+    result.as_ref().map_err(ToString::to_string)
+}
+
+#[track_caller]
+fn assert_parses_like_parse(sources: &[String], options: &Options) {
+    let results = parse_many(sources, options);
+    assert_eq!(results.len(), sources.len());
+    for (source, result) in sources.iter().zip(&results) {
+        assert!(
+            describe(result) == describe(&parse(source, options)),
+            "for {:?}",
+            source
+        );
+    }
+}
+
+#[test]
+fn parses_no_source() {
+    assert!(parse_many::<&str>(&[], &Options::default()).is_empty());
+}
+
+#[test]
+fn parses_one_source() {
+    assert_parses_like_parse(&["a = 1".to_owned()], &Options::default());
+    let results = parse_many(&["a = "], &Options::default());
+    assert_eq!(
+        results[0].as_ref().unwrap_err().to_string(),
+        "Unexpected token (1:4)"
+    );
+}
+
+#[test]
+fn keeps_the_order_and_errors_of_many_sources() {
+    let sources: Vec<String> = (0..64)
+        .map(|index| match index % 4 {
+            0 => corpus::source(index % 3 + 1),
+            1 => format!("let x{0} = {0};", index),
+            2 => format!("let x = 1; let x = {};", index),
+            _ => format!("{}\n@", "\n".repeat(index)),
+        })
+        .collect();
+    let options = Options {
+        source_type: SourceType::Module,
+        ..Options::default()
+    };
+    assert_parses_like_parse(&sources, &options);
+    let results = parse_many(&sources, &options);
+    assert_eq!(
+        describe(&results[63]).unwrap_err(),
+        "Unexpected character '@' (65:0)"
+    );
+    assert_eq!(
+        describe(&results[6]).unwrap_err(),
+        "Identifier 'x' has already been declared (1:15)"
+    );
+}
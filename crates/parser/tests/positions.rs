@@ -0,0 +1,90 @@
+use std::sync::{Arc, Mutex};
+
+use muse_parser::{parse, OffsetUnit, OnPosition, Options, Position, ReportedPosition, SourceType};
+
+/// The positions reported to `on_insert_semicolon` and to
+/// `on_trailing_comma` while `input` is parsed.
+fn reported(input: &str, options: &Options) -> (Vec<ReportedPosition>, Vec<ReportedPosition>) {
+    let semicolons = Arc::new(Mutex::new(vec![]));
+    let commas = Arc::new(Mutex::new(vec![]));
+    let options = Options {
+        on_insert_semicolon: Some(OnPosition::Collect(semicolons.clone())),
+        on_trailing_comma: Some(OnPosition::Collect(commas.clone())),
+        ..options.clone()
+    };
+    parse(input, &options).unwrap();
+    let semicolons = semicolons.lock().unwrap().clone();
+    let commas = commas.lock().unwrap().clone();
+    (semicolons, commas)
+}
+
+fn offsets(positions: &[ReportedPosition]) -> Vec<i32> {
+    positions.iter().map(|(pos, _)| *pos).collect()
+}
+
+#[test]
+fn reports_inserted_semicolons() {
+    let (semicolons, commas) = reported(
+        "a\nb; c\n{ d }\nreturn\ne",
+        &Options {
+            allow_return_outside_function: true,
+            ..Options::default()
+        },
+    );
+    assert_eq!(offsets(&semicolons), [1, 6, 10, 19, 21]);
+    assert!(commas.is_empty());
+    assert!(semicolons.iter().all(|(_, loc)| loc.is_none()));
+}
+
+#[test]
+fn reports_trailing_commas() {
+    let input = "f(a,); [b,]; ({ c, }); function g(d,) {} import { e, } from 'm'; export { e, };";
+    let options = Options {
+        source_type: SourceType::Module,
+        ..Options::default()
+    };
+    let (semicolons, commas) = reported(input, &options);
+    assert_eq!(offsets(&commas), [3, 9, 17, 35, 51, 75]);
+    assert!(semicolons.is_empty());
+    // The comma of an elision is not a trailing one.
+    assert_eq!(offsets(&reported("[a,,]", &options).1), [3]);
+}
+
+#[test]
+fn reports_positions_in_the_offset_unit() {
+    let options = Options {
+        locations: true,
+        offset_unit: OffsetUnit::Utf16,
+        ..Options::default()
+    };
+    let (semicolons, commas) = reported("'😀'\nf('😀',)", &options);
+    assert_eq!(
+        semicolons,
+        [
+            (4, Some(Position::new(1, 4))),
+            (13, Some(Position::new(2, 8)))
+        ]
+    );
+    assert_eq!(commas, [(11, Some(Position::new(2, 6)))]);
+}
+
+#[test]
+fn callbacks_see_the_collected_positions() {
+    let input = "a\nf(b,)";
+    let seen = Arc::new(Mutex::new(vec![]));
+    let sink = OnPosition::callback({
+        let seen = seen.clone();
+        move |position: &ReportedPosition| seen.lock().unwrap().push(position.clone())
+    });
+    let options = Options {
+        on_insert_semicolon: Some(sink.clone()),
+        on_trailing_comma: Some(sink),
+        ..Options::default()
+    };
+    parse(input, &options).unwrap();
+    assert_eq!(*seen.lock().unwrap(), [(1, None), (5, None), (7, None)]);
+    assert_eq!(
+        format!("{:?}", options.on_insert_semicolon.unwrap()),
+        "Callback(..)"
+    );
+}
@@ -0,0 +1,82 @@
+use muse_parser::{parse, ErrorCode, Options, ParserError};
+
+fn error(input: &str) -> (ErrorCode, String) {
+    match parse(input, &Options::default()) {
+        Ok(_) => panic!("{} parses", input),
+        Err(ParserError::SyntaxError { code, message, .. }) => (code, message),
+        Err(error) => panic!("{} fails with {:?}", input, error),
+    }
+}
+
+#[track_caller]
+fn assert_valid(input: &str) {
+    if let Err(error) = parse(input, &Options::default()) {
+        panic!("{} does not parse: {:?}", input, error);
+    }
+}
+
+#[track_caller]
+fn assert_invalid(input: &str, expected: &str) {
+    let (code, message) = error(input);
+    assert_eq!(code, ErrorCode::InvalidRegex, "{}", message);
+    assert_eq!(message, expected);
+}
+
+#[test]
+fn braced_quantifiers() {
+    assert_valid("x = /a{2}/");
+    assert_valid("/a{2,}/");
+    assert_valid("/a{2,10}/");
+    assert_valid("/\\d{4}/u");
+    assert_valid("/a{12}?/");
+    assert_invalid(
+        "/a{2,1}/",
+        "Invalid regular expression: /a{2,1}/: numbers out of order in {} quantifier (1:1)",
+    );
+    assert_invalid(
+        "/{2}/u",
+        "Invalid regular expression: /{2}/: Nothing to repeat (1:1)",
+    );
+}
+
+#[test]
+fn back_references() {
+    assert_valid("/(a)(a)(a)(a)(a)(a)(a)(a)(a)(a)\\10/u");
+    assert_invalid(
+        "/(a)\\2/u",
+        "Invalid regular expression: /(a)\\2/: Invalid escape (1:1)",
+    );
+}
+
+#[test]
+fn unicode_property_escapes() {
+    assert_valid("/\\p{L}/u");
+    assert_valid("/\\p{Lu}/u");
+    assert_valid("/\\P{ASCII}/u");
+    assert_valid("/\\p{Script=Greek}/u");
+    assert_valid("/\\p{General_Category=Letter}/u");
+    assert_invalid(
+        "/\\p{NotAProperty}/u",
+        "Invalid regular expression: /\\p{NotAProperty}/: Invalid property name or value (1:1)",
+    );
+    assert_invalid(
+        "/\\p{Script=NotAScript}/u",
+        "Invalid regular expression: /\\p{Script=NotAScript}/: Invalid property value (1:1)",
+    );
+    assert_invalid(
+        "/\\p{NotAName=Greek}/u",
+        "Invalid regular expression: /\\p{NotAName=Greek}/: Invalid property name (1:1)",
+    );
+}
+
+#[test]
+fn astral_characters() {
+    // Without the `u` flag, a pattern is made of UTF-16 code units.
+    assert_invalid(
+        "/[😀-😂]/",
+        "Invalid regular expression: /[😀-😂]/: Range out of order in character class (1:1)",
+    );
+    assert_valid("/[😀-😂]/u");
+    assert_valid("/😀{2}/");
+    assert_valid("/^.😀$/u");
+}
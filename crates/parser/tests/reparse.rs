@@ -0,0 +1,319 @@
+#[path = "../benches/common/mod.rs"]
+mod corpus;
+
+use muse_parser::{
+    parse, reparse, OffsetUnit, Options, ParserError, Program, SourceType, TextEdit,
+};
+
+const SCRIPT: &str = r#""use strict";
+var counter = 0;
+function outer(a, b) {
+  var x = a + b;
+  function* gen() {
+    yield x;
+    yield /re/g.test("s");
+    const o = { m() { return yield_ + 1; }, *g() { yield 2; }, get v() { return this._v; }, set v(z) { this._v = z; } };
+    return o;
+  }
+  const arrow = (p) => { return p * 2; };
+  label: for (let i = 0; i < 3; i++) {
+    if (i === 1) continue label;
+    counter += i;
+  }
+  return `${x} and ${arrow(x)}`;
+}
+class Base {
+  #secret = 1;
+  static #count = 0;
+  constructor(value) { this.value = value; Base.#count++; }
+  get secret() { return this.#secret; }
+  method() {
+    const inner = function () { return 1; };
+    return { class: "c", function: 2, run() { return this; } };
+  }
+  static create() { return new Base(#secret in this ? 1 : 2); }
+}
+class Derived extends Base {
+  constructor() {
+    super(1);
+    this.list = [1, 2, 3].map((n) => n * 2);
+  }
+  async load(url) {
+    const response = await fetch(url);
+    return response.json();
+  }
+  *[Symbol.iterator]() { yield* this.list; }
+}
+const f = async function* () { for await (const x of y) { yield x; } };
+const obj = {
+  a: 1,
+  b() { return 2; },
+  async c() { await null; },
+  "d": function () { "use strict"; return 3; },
+};
+if (counter > 1) {
+  console.log(counter / 2);
+} else {
+  console.log(/x/.source);
+}
+outer(1, 2);
+try { outer(); } catch (e) { console.error(e); } finally { counter = 0; }
+switch (counter) { case 0: break; default: counter++; }
+do { counter--; } while (counter > 0);
+while (false) {}
+new Derived();
+const greeting = "héllo 😀 wörld";
+function unicode() { const ç = "😀"; return ç + greeting; }
+"#;
+
+const SNIPPETS: &[&str] = &[
+    "x",
+    "1",
+    ";",
+    "{",
+    "}",
+    "(",
+    ")",
+    "\"a\"",
+    "/re/g",
+    " ",
+    "\n",
+    "function(){}",
+    "yield",
+    "await x",
+    "this.#a",
+    "#a in this",
+    "'use strict';",
+    "`",
+    "`${",
+    "${x}`",
+    "class",
+    "var q = 1;",
+    "let q;",
+    "else",
+    "/",
+    "/*",
+    "*/",
+    "//",
+    "{class: 1}",
+    "=>",
+    "() => {}",
+    "async ",
+    "*",
+    "super()",
+    "super.x",
+    "new.target",
+    "return",
+    "break;",
+    "label: ",
+    "\r\n",
+    "é",
+    "😀",
+    "\u{2028}",
+    "<!--",
+    "-->",
+    "import x from 'y';",
+    "export {x};",
+    "if (a) b;",
+    "static",
+    "get x() {}",
+    "let",
+    "07",
+];
+
+fn option_sets() -> [Options; 4] {
+    [
+        Options::default(),
+        Options {
+            source_type: SourceType::Module,
+            ..Options::default()
+        },
+        Options {
+            locations: true,
+            ranges: true,
+            offset_unit: OffsetUnit::Utf16,
+            ..Options::default()
+        },
+        Options {
+            source_type: SourceType::Module,
+            locations: true,
+            offset_unit: OffsetUnit::CodePoint,
+            collect_errors: true,
+            ..Options::default()
+        },
+    ]
+}
+
+fn count(text: &str, unit: OffsetUnit) -> i32 {
+    let count = match unit {
+        OffsetUnit::Utf8 => text.len(),
+        OffsetUnit::Utf16 => text.encode_utf16().count(),
+        OffsetUnit::CodePoint => text.chars().count(),
+    };
+    count as i32
+}
+
+/// Applies the edit of the UTF-8 range `start..end` to `program`, checks
+/// that the result is that of a full parse, and returns the new input.
+#[track_caller]
+fn check_edit(
+    program: &mut Program,
+    input: &str,
+    start: usize,
+    end: usize,
+    text: &str,
+    options: &Options,
+) -> Option<String> {
+    let new_input = format!("{}{}{}", &input[..start], text, &input[end..]);
+    let edit = TextEdit {
+        start: count(&input[..start], options.offset_unit),
+        end: count(&input[..end], options.offset_unit),
+        text: text.to_owned(),
+    };
+    let old = program.clone();
+    let result = reparse(program, input, &edit, options);
+    match (parse(&new_input, options), result) {
+        (Ok(expected), Ok(())) => {
+            assert!(
+                *program == expected,
+                "{:?} at {}..{} of {:?}",
+                text,
+                start,
+                end,
+                input
+            );
+            Some(new_input)
+        }
+        (Err(expected), Err(error)) => {
+            assert_eq!(format!("{:?}", error), format!("{:?}", expected));
+            assert!(*program == old, "the program changed on error");
+            None
+        }
+        (expected, result) => panic!(
+            "{:?} at {}..{} of {:?}: {:?} instead of {:?}",
+            text,
+            start,
+            end,
+            input,
+            result,
+            expected.err()
+        ),
+    }
+}
+
+#[test]
+fn edits_match_a_full_parse() {
+    let edits: &[(&str, &str, &str)] = &[
+        // In a function body, in a method and in a nested function.
+        ("var x = a + b;", "a + b", "a * b + 1"),
+        ("return response.json();", "json", "text"),
+        ("yield x;", "yield x;", "yield x; yield* [1, 2];"),
+        ("return this._v;", "this._v", "this.#secret"),
+        ("Base.#count++;", "#count", "#secret"),
+        ("super(1);", "super(1);", "super(2); this.x = 1;"),
+        ("return ç + greeting;", "ç", "ç + \"😀\""),
+        // In top-level statements.
+        ("outer(1, 2);", "1, 2", "3"),
+        ("new Derived();", "Derived", "Base"),
+        ("counter--;", "counter--", "counter -= 2"),
+        // Edits that change how the rest of the input reads.
+        ("var x = a + b;", "var x = a + b;", "var x = `"),
+        ("var x = a + b;", "a + b", "a + b; }"),
+        ("outer(1, 2);", "outer(1, 2);", "/*"),
+        (
+            "var counter = 0;",
+            "var counter = 0;",
+            "\"use strict\"; var counter = 0;",
+        ),
+        ("new Derived();", "new Derived();", "let counter;"),
+        ("while (false) {}", "{}", "{ var added; }"),
+        ("return `${x}", "return", "return\n"),
+        (
+            "const inner = function () { return 1; };",
+            "return 1;",
+            "return 1; }; {",
+        ),
+        // Errors, which leave the program as it was.
+        ("return this._v;", "this._v", "this.#undeclared"),
+        ("var x = a + b;", "var", "let let"),
+        ("outer(1, 2);", "1, 2", "1,, 2"),
+    ];
+    for options in option_sets() {
+        let mut program = parse(SCRIPT, &options).unwrap();
+        for (statement, old, new) in edits {
+            let start = SCRIPT.find(statement).unwrap() + statement.find(old).unwrap();
+            let mut edited = program.clone();
+            check_edit(&mut edited, SCRIPT, start, start + old.len(), new, &options);
+        }
+        // A deletion and an insertion.
+        check_edit(&mut program, SCRIPT, 0, 0, "", &options);
+        let end = SCRIPT.len();
+        check_edit(&mut program, SCRIPT, end, end, "\nx;", &options);
+    }
+}
+
+/// A xorshift generator, to pick the same edits on every run.
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+#[test]
+fn random_edits_match_a_full_parse() {
+    let module = corpus::source(2);
+    for (index, options) in option_sets().iter().enumerate() {
+        let source = if options.source_type == SourceType::Module {
+            &module
+        } else {
+            SCRIPT
+        };
+        let mut random = Random(7919 + index as u64);
+        let mut input = source.to_owned();
+        let mut program = parse(&input, options).unwrap();
+        for _ in 0..300 {
+            let boundaries: Vec<usize> = (0..=input.len())
+                .filter(|&offset| input.is_char_boundary(offset))
+                .collect();
+            let first = random.below(boundaries.len());
+            let last = (first + random.below(8)).min(boundaries.len() - 1);
+            let text = match random.below(4) {
+                0 => String::new(),
+                1 => {
+                    let from = random.below(boundaries.len());
+                    let to = (from + random.below(30)).min(boundaries.len() - 1);
+                    input[boundaries[from]..boundaries[to]].to_owned()
+                }
+                _ => SNIPPETS[random.below(SNIPPETS.len())].to_owned(),
+            };
+            let (start, end) = (boundaries[first], boundaries[last]);
+            if let Some(new_input) = check_edit(&mut program, &input, start, end, &text, options) {
+                input = new_input;
+            }
+        }
+    }
+}
+
+#[test]
+fn rejects_offsets_inside_characters() {
+    let input = "x = \"😀\";";
+    let options = Options {
+        offset_unit: OffsetUnit::Utf16,
+        ..Options::default()
+    };
+    let mut program = parse(input, &options).unwrap();
+    let edit = TextEdit {
+        start: 6,
+        end: 6,
+        text: "a".to_owned(),
+    };
+    assert!(matches!(
+        reparse(&mut program, input, &edit, &options),
+        Err(ParserError::InvalidOffset(6))
+    ));
+}
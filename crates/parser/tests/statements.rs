@@ -0,0 +1,141 @@
+mod common;
+
+use common::{assert_error, assert_parses, script, shape};
+
+#[test]
+fn automatic_semicolon_insertion() {
+    let options = script();
+    for (input, separated) in [
+        ("a\n++b", "a; ++b"),
+        ("a\n(b)", "a(b)"),
+        ("return\na", "return; a"),
+        ("x\n/re/g", "x / re / g"),
+        ("do x; while (0) y", "do x; while (0); y"),
+        ("let\na = 1", "let a = 1"),
+    ] {
+        let wrap = |input: &str| format!("function f() {{ {} }}", input);
+        assert_eq!(
+            shape(&wrap(input), &options),
+            shape(&wrap(separated), &options),
+            "{:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn valid_statements() {
+    for input in [
+        "a: while (1) { continue a; }",
+        "if (1) function f() {}",
+        "for (let x in y, z);",
+        "for (var x = 1 in y);",
+        "try {} catch (e) { var e; }",
+        "do x; while (0) y",
+        "function f() {} var f;",
+        "for (;;) break",
+        "x\n++y",
+        "yield = 1",
+        "await = 1",
+    ] {
+        assert_parses(input, &script());
+    }
+}
+
+#[test]
+fn statement_errors() {
+    for (input, message) in [
+        ("break", "Unsyntactic break (1:0)"),
+        ("continue", "Unsyntactic continue (1:0)"),
+        ("a: a: ;", "Label 'a' is already declared (1:3)"),
+        ("a: { break b; }", "Unsyntactic break (1:5)"),
+        ("a: { continue a; }", "Unsyntactic continue (1:5)"),
+        (
+            "\"use strict\"; if (1) function f() {}",
+            "Unexpected token (1:21)",
+        ),
+        ("while (1) function f() {}", "Unexpected token (1:10)"),
+        ("\"use strict\"; with (a) b", "'with' in strict mode (1:14)"),
+        ("for (let x of y, z);", "Unexpected token (1:15)"),
+        (
+            "for (var x = 1 of y);",
+            "for-of loop variable declaration may not have an initializer (1:5)",
+        ),
+        (
+            "\"use strict\"; for (var x = 1 in y);",
+            "for-in loop variable declaration may not have an initializer (1:19)",
+        ),
+        ("for (let x = 1, y of z);", "Unexpected token (1:18)"),
+        ("for (async of x);", "Unexpected token (1:14)"),
+        ("for (let of x);", "Unexpected token (1:12)"),
+        (
+            "let let = 1",
+            "let is disallowed as a lexically bound name (1:4)",
+        ),
+        (
+            "let [a, a] = b",
+            "Identifier 'a' has already been declared (1:8)",
+        ),
+        ("const a;", "Unexpected token (1:7)"),
+        ("const a = 1, b;", "Unexpected token (1:14)"),
+        (
+            "let a; var a;",
+            "Identifier 'a' has already been declared (1:11)",
+        ),
+        (
+            "var a; let a;",
+            "Identifier 'a' has already been declared (1:11)",
+        ),
+        (
+            "try {} catch (e) { let e; }",
+            "Identifier 'e' has already been declared (1:23)",
+        ),
+        (
+            "try {} catch ([e]) { var e; }",
+            "Identifier 'e' has already been declared (1:25)",
+        ),
+        ("try {}", "Missing catch or finally clause (1:0)"),
+        (
+            "switch (a) { default: default: }",
+            "Multiple default clauses (1:22)",
+        ),
+        (
+            "switch (a) { case 1: let b; case 2: let b; }",
+            "Identifier 'b' has already been declared (1:40)",
+        ),
+        ("return", "'return' outside of function (1:0)"),
+        ("throw\na", "Illegal newline after throw (1:5)"),
+        ("label: function* g() {}", "Unexpected token (1:15)"),
+        (
+            "\"use strict\"; label: function f() {}",
+            "Unexpected token (1:21)",
+        ),
+        (
+            "{ function f() {} var f; }",
+            "Identifier 'f' has already been declared (1:22)",
+        ),
+        ("\"use strict\"; 010", "Invalid number (1:14)"),
+        (
+            "\"use strict\"; \"\\08\"",
+            "Octal literal in strict mode (1:15)",
+        ),
+        (
+            "\"\\08\"; \"use strict\";",
+            "Octal literal in strict mode (1:1)",
+        ),
+        (
+            "function f() { \"use strict\"; 010 }",
+            "Invalid number (1:29)",
+        ),
+        (
+            "\"use strict\"; yield = 1",
+            "The keyword 'yield' is reserved (1:14)",
+        ),
+        (
+            "class A { static { await } }",
+            "Cannot use await in class static initialization block (1:19)",
+        ),
+    ] {
+        assert_error(input, &script(), message);
+    }
+}
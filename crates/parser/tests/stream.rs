@@ -0,0 +1,245 @@
+#[path = "../benches/common/mod.rs"]
+mod corpus;
+
+use std::{
+    io::{self, Read},
+    sync::{Arc, Mutex},
+};
+
+use muse_parser::{
+    read_tokenizer, stream_tokenizer, tokenizer, ErrorCode, OffsetUnit, OnComment, Options,
+    ParserError, SourceType, StreamTokenizer, Token,
+};
+
+const INPUTS: &[&str] = &[
+    "#!/usr/bin/env node\n'use strict'; var a = 010;",
+    "\"use strict\"\n + 1; 010",
+    "'a'; /* c */ 'use strict'; '\\01'",
+    "a ... b?.5:c ?.d <!-- x\n-->y\nz",
+    "x = `a${b}c${`d${e}`}f` / 2 / g; /re[/]x/giu.test(s)",
+    "// line\r\n/* block\r\n\u{2028} */ é = 'ü\\\n𝒳' + 1_000n + 0x1F + .5e-3 >>>= 2 **= 3",
+    "if (a) /b/.test(c); else { return; } let \\u0061b = 1; class A { #p = 1; static { } }",
+    "a\r\nb\rc\nd",
+    "x = 'unterminated",
+    "x = 1; /* unterminated",
+    "x = `unterminated ${a}",
+    "a = 12abc",
+];
+
+fn option_sets() -> [Options; 3] {
+    [
+        Options::default(),
+        Options {
+            source_type: SourceType::Module,
+            locations: true,
+            ranges: true,
+            offset_unit: OffsetUnit::Utf16,
+            ..Options::default()
+        },
+        Options {
+            locations: true,
+            collect_errors: true,
+            ..Options::default()
+        },
+    ]
+}
+
+fn describe(token: Result<Token, ParserError>) -> String {
+    format!(
+        "{:?}\n",
+        token.map(|token| (
+            token.token_type.label,
+            token.value,
+            token.start,
+            token.end,
+            token.loc,
+            token.range
+        ))
+    )
+}
+
+/// The tokens and comments `tokenizer` reads from `input`.
+fn whole(input: &str, options: &Options) -> String {
+    let comments = Arc::new(Mutex::new(vec![]));
+    let options = Options {
+        on_comment: Some(OnComment::Collect(comments.clone())),
+        ..options.clone()
+    };
+    let mut out: String = tokenizer(input, &options).map(describe).collect();
+    out += &format!("{:?}", comments.lock().unwrap());
+    out
+}
+
+/// The tokens and comments a streaming tokenizer reads from `input` pushed
+/// in chunks of the given sizes, in turn.
+fn chunked(input: &str, options: &Options, sizes: &[usize]) -> String {
+    let comments = Arc::new(Mutex::new(vec![]));
+    let options = Options {
+        on_comment: Some(OnComment::Collect(comments.clone())),
+        ..options.clone()
+    };
+    let mut tokens = stream_tokenizer(&options);
+    let mut out = String::new();
+    let mut rest = input;
+    for &size in sizes.iter().cycle() {
+        out.extend((&mut tokens).map(describe));
+        if rest.is_empty() {
+            break;
+        }
+        let mut size = size.min(rest.len());
+        while !rest.is_char_boundary(size) {
+            size += 1;
+        }
+        tokens.push(&rest[..size]);
+        rest = &rest[size..];
+    }
+    tokens.finish();
+    out.extend(tokens.map(describe));
+    out += &format!("{:?}", comments.lock().unwrap());
+    out
+}
+
+#[test]
+fn chunks_read_like_the_whole_input() {
+    for input in INPUTS {
+        for options in option_sets() {
+            let expected = whole(input, &options);
+            for sizes in [&[1][..], &[2], &[3, 1, 7], &[5, 11], &[1000]] {
+                assert_eq!(
+                    chunked(input, &options, sizes),
+                    expected,
+                    "{:?} in chunks of {:?}",
+                    input,
+                    sizes
+                );
+            }
+            for at in (0..=input.len()).filter(|&at| input.is_char_boundary(at)) {
+                let sizes = [at, input.len()];
+                assert_eq!(
+                    chunked(input, &options, &sizes),
+                    expected,
+                    "{:?} split at {}",
+                    input,
+                    at
+                );
+            }
+        }
+    }
+}
+
+/// A reader that hands out a few bytes at a time, splitting characters.
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    reads: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reads += 1;
+        let count = (self.reads % 7 + 1).min(self.bytes.len()).min(buf.len());
+        buf[..count].copy_from_slice(&self.bytes[..count]);
+        self.bytes = &self.bytes[count..];
+        Ok(count)
+    }
+}
+
+#[test]
+fn readers_read_like_the_whole_input() {
+    let input = format!("{}{}", corpus::source(20), INPUTS[5]);
+    let options = Options {
+        source_type: SourceType::Module,
+        locations: true,
+        ranges: true,
+        ..Options::default()
+    };
+    let expected: Vec<String> = tokenizer(&input, &options).map(describe).collect();
+    let trickled: Vec<String> = read_tokenizer(
+        Trickle {
+            bytes: input.as_bytes(),
+            reads: 0,
+        },
+        &options,
+    )
+    .map(describe)
+    .collect();
+    assert!(trickled == expected);
+    let read: Vec<String> = read_tokenizer(input.as_bytes(), &options)
+        .map(describe)
+        .collect();
+    assert!(read == expected);
+}
+
+#[test]
+fn readers_fail_on_invalid_utf8() {
+    let tokens: Vec<_> = read_tokenizer(&b"a = '\xff'"[..], &Options::default()).collect();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].as_ref().unwrap_err().code(), ErrorCode::Io);
+}
+
+/// The text of the tokens a streaming tokenizer yields after each chunk is
+/// pushed, and then after `finish`.
+fn yielded_after_each_push(chunks: &[&str]) -> Vec<Vec<String>> {
+    let mut tokens = stream_tokenizer(&Options::default());
+    let mut input = String::new();
+    let mut yielded = vec![];
+    let mut take = |tokens: &mut StreamTokenizer, input: &str| {
+        let texts: Vec<String> = tokens
+            .map(|token| {
+                let token = token.unwrap();
+                input[token.start as usize..token.end as usize].to_owned()
+            })
+            .collect();
+        yielded.push(texts);
+    };
+    for chunk in chunks {
+        tokens.push(chunk);
+        input += chunk;
+        take(&mut tokens, &input);
+    }
+    tokens.finish();
+    take(&mut tokens, &input);
+    yielded
+}
+
+#[test]
+fn yields_tokens_as_soon_as_they_are_complete() {
+    let string = format!("'{}'", "a".repeat(40));
+    assert_eq!(
+        yielded_after_each_push(&[
+            &format!("x = '{}", "a".repeat(40)),
+            "'; y = 1; z = 2;\n",
+            "w;"
+        ]),
+        [
+            vec!["x", "="],
+            vec![&string, ";", "y", "=", "1", ";", "z", "="],
+            vec!["2"],
+            vec![";", "w", ";"],
+        ]
+    );
+    let comment = format!("// {}", "c".repeat(40));
+    assert_eq!(
+        yielded_after_each_push(&[&comment, "c", "\nx;", " y + z"]),
+        [vec![], vec![], vec![], vec!["x", ";", "y"], vec!["+", "z"]]
+    );
+    let quasi = "t".repeat(40);
+    assert_eq!(
+        yielded_after_each_push(&[&format!("`{}", quasi), "${a} b`;", " c.d"]),
+        [
+            vec!["`"],
+            vec![&quasi, "${", "a", "}"],
+            vec![" b", "`", ";"],
+            vec!["c", ".", "d"],
+        ]
+    );
+    assert_eq!(
+        yielded_after_each_push(&[&format!("{} ", "n".repeat(40)), "+ m", "0 ", "+= 1;"]),
+        [
+            vec![],
+            vec![&"n".repeat(40)[..]],
+            vec!["+"],
+            vec!["m0"],
+            vec!["+=", "1", ";"]
+        ]
+    );
+}
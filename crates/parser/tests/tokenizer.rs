@@ -0,0 +1,203 @@
+use std::sync::{Arc, Mutex};
+
+use muse_parser::{
+    parse, tokenizer, ErrorCode, OffsetUnit, OnToken, Options, ParserError, SourceType, Token,
+    TokenValue,
+};
+
+fn tokens(input: &str) -> Vec<(String, TokenValue, i32, i32)> {
+    tokenizer(input, &Options::default())
+        .map(|token| {
+            let token = token.unwrap();
+            (token.token_type.label, token.value, token.start, token.end)
+        })
+        .collect()
+}
+
+fn labels(input: &str) -> Vec<String> {
+    tokens(input).into_iter().map(|(label, ..)| label).collect()
+}
+
+fn values(input: &str) -> Vec<TokenValue> {
+    tokens(input)
+        .into_iter()
+        .map(|(_, value, ..)| value)
+        .collect()
+}
+
+fn error(input: &str) -> (ErrorCode, String) {
+    match tokenizer(input, &Options::default()).find_map(Result::err) {
+        Some(ParserError::SyntaxError { code, message, .. }) => (code, message),
+        error => panic!("{} fails with {:?}", input, error),
+    }
+}
+
+fn string(value: &str) -> TokenValue {
+    TokenValue::String(value.to_owned())
+}
+
+#[test]
+fn punctuators_and_operators() {
+    assert_eq!(
+        labels("a?.b ?? c ** 2 >>>= 1 => ... #p"),
+        [
+            "name",
+            "?.",
+            "name",
+            "??",
+            "name",
+            "**",
+            "num",
+            "_=",
+            "num",
+            "=>",
+            "...",
+            "privateId"
+        ]
+    );
+    assert_eq!(
+        tokens("x >>>= 1"),
+        [
+            ("name".to_owned(), string("x"), 0, 1),
+            ("_=".to_owned(), string(">>>="), 2, 6),
+            ("num".to_owned(), TokenValue::Number(1.0), 7, 8),
+        ]
+    );
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(
+        values("1_000 .5e3 0x1F 0o17 0b11 017 1e-7"),
+        [1000.0, 500.0, 31.0, 15.0, 3.0, 15.0, 1e-7].map(TokenValue::Number)
+    );
+    assert_eq!(
+        values("0b11n 10n"),
+        [
+            TokenValue::BigInt("0b11".to_owned()),
+            TokenValue::BigInt("10".to_owned())
+        ]
+    );
+    assert_eq!(error("1__0").0, ErrorCode::InvalidNumericSeparator);
+    assert_eq!(error("3in x").1, "Identifier directly after number (1:1)");
+}
+
+#[test]
+fn strings_and_templates() {
+    assert_eq!(
+        values(
+            r#""A\x42\u{1F600}" 'a\
+b'"#
+        ),
+        [string("AB😀"), string("ab")]
+    );
+    assert_eq!(
+        labels("`a${b}c`"),
+        ["`", "template", "${", "name", "}", "template", "`"]
+    );
+    assert_eq!(error("'abc").0, ErrorCode::UnterminatedString);
+    assert_eq!(error("`abc").0, ErrorCode::UnterminatedTemplate);
+}
+
+#[test]
+fn comments_are_skipped() {
+    assert_eq!(
+        labels("a // b\n/* c */ d <!-- e\n--> f\ng"),
+        ["name", "name", "name"]
+    );
+    assert_eq!(error("a /* b").0, ErrorCode::UnterminatedComment);
+}
+
+#[test]
+fn slash_is_a_regex_where_an_expression_can_start() {
+    assert_eq!(
+        values("x = /a[/]b/gi"),
+        [
+            string("x"),
+            string("="),
+            TokenValue::Regex {
+                pattern: "a[/]b".to_owned(),
+                flags: "gi".to_owned()
+            }
+        ]
+    );
+    assert_eq!(labels("a / b / c"), ["name", "/", "name", "/", "name"]);
+    assert_eq!(
+        labels("`${a}` / b"),
+        ["`", "template", "${", "name", "}", "template", "`", "/", "name"]
+    );
+    assert_eq!(labels("if (a) /b/.test(c)")[4], "regexp");
+    assert_eq!(labels("a++ / 2")[2], "/");
+    assert_eq!(error("/a").0, ErrorCode::UnterminatedRegex);
+    assert_eq!(error("/a/gg").1, "Duplicate regular expression flag (1:1)");
+    assert_eq!(error("/a/x").1, "Invalid regular expression flag (1:1)");
+}
+
+#[test]
+fn identifiers_and_escapes() {
+    assert_eq!(values("\\u0061bc ünï"), [string("abc"), string("ünï")]);
+    assert_eq!(error("a\\u0020").0, ErrorCode::InvalidEscape);
+    assert_eq!(error("@").1, "Unexpected character '@' (1:0)");
+}
+
+#[test]
+fn parse_reports_the_tokens_of_the_tokenizer() {
+    let inputs = [
+        "x = a / b / c; y = /re/g.test(s) ? `a${b / 2}c${`d${/e/}`}` : a++ / 2;",
+        "if (a) /b/.test(c); else { return; }\n(a) / 2; a\n/b/g",
+        "class A { static #p = 1; m() { return this.#p ?? 'ü😀'; } } export default A;",
+    ];
+    for options in [
+        Options {
+            source_type: SourceType::Module,
+            allow_return_outside_function: true,
+            ..Options::default()
+        },
+        Options {
+            source_type: SourceType::Module,
+            allow_return_outside_function: true,
+            locations: true,
+            ranges: true,
+            offset_unit: OffsetUnit::Utf16,
+            ..Options::default()
+        },
+    ] {
+        for input in inputs {
+            let collected = Arc::new(Mutex::new(vec![]));
+            let called = Arc::new(Mutex::new(vec![]));
+            for on_token in [
+                OnToken::Collect(collected.clone()),
+                OnToken::callback({
+                    let called = called.clone();
+                    move |token: &Token| called.lock().unwrap().push(token.clone())
+                }),
+            ] {
+                let options = Options {
+                    on_token: Some(on_token),
+                    ..options.clone()
+                };
+                parse(input, &options).unwrap();
+            }
+            let mut expected: Vec<String> = tokenizer(input, &options)
+                .map(|token| format!("{:?}", token.unwrap()))
+                .collect();
+            let reported: Vec<String> = collected
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|token| format!("{:?}", token))
+                .collect();
+            let eof = reported.last().unwrap();
+            assert!(eof.contains("label: \"eof\""), "{}", eof);
+            expected.push(eof.clone());
+            assert_eq!(reported, expected, "for {:?}", input);
+            let called: Vec<String> = called
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|token| format!("{:?}", token))
+                .collect();
+            assert_eq!(called, reported, "for {:?}", input);
+        }
+    }
+}
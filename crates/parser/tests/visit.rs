@@ -0,0 +1,303 @@
+mod common;
+#[path = "../benches/common/mod.rs"]
+mod corpus;
+
+use std::collections::BTreeMap;
+
+use common::{assert_parses, module, positionless, script};
+use muse_parser::{
+    to_json,
+    visit::{NodeRef, Visit, VisitControl},
+    visit_mut::{walk_expression, VisitMut},
+    Expression, Identifier, Options, Program, Spanned,
+};
+use serde_json::Value;
+
+/// The node types of the table in `for_each_node`.
+const NODE_TYPES: &[&str] = &[
+    "Program",
+    "ExpressionStatement",
+    "BlockStatement",
+    "EmptyStatement",
+    "DebuggerStatement",
+    "WithStatement",
+    "ReturnStatement",
+    "LabeledStatement",
+    "BreakStatement",
+    "ContinueStatement",
+    "IfStatement",
+    "SwitchStatement",
+    "SwitchCase",
+    "ThrowStatement",
+    "TryStatement",
+    "CatchClause",
+    "WhileStatement",
+    "DoWhileStatement",
+    "ForStatement",
+    "ForInStatement",
+    "ForOfStatement",
+    "VariableDeclaration",
+    "VariableDeclarator",
+    "Function",
+    "ArrowFunctionExpression",
+    "Class",
+    "ClassBody",
+    "MethodDefinition",
+    "PropertyDefinition",
+    "StaticBlock",
+    "Identifier",
+    "PrivateIdentifier",
+    "Literal",
+    "ThisExpression",
+    "Super",
+    "ArrayExpression",
+    "SpreadElement",
+    "ObjectExpression",
+    "Property",
+    "TemplateLiteral",
+    "TemplateElement",
+    "TaggedTemplateExpression",
+    "MemberExpression",
+    "CallExpression",
+    "NewExpression",
+    "ChainExpression",
+    "SequenceExpression",
+    "UnaryExpression",
+    "UpdateExpression",
+    "BinaryExpression",
+    "LogicalExpression",
+    "AssignmentExpression",
+    "ConditionalExpression",
+    "YieldExpression",
+    "AwaitExpression",
+    "MetaProperty",
+    "ImportExpression",
+    "ParenthesizedExpression",
+    "ObjectPattern",
+    "AssignmentProperty",
+    "ArrayPattern",
+    "RestElement",
+    "AssignmentPattern",
+    "ImportDeclaration",
+    "ImportSpecifier",
+    "ImportDefaultSpecifier",
+    "ImportNamespaceSpecifier",
+    "ExportNamedDeclaration",
+    "ExportSpecifier",
+    "ExportDefaultDeclaration",
+    "ExportAllDeclaration",
+];
+
+/// Syntax the corpus does not use, with the statements only allowed in
+/// scripts.
+const SCRIPT: &str = r#"
+with (o) { debugger; ; }
+outer: for (var i = 0, j; i < 2; i++, j--) {
+    for (k in o) { if (k) continue outer; else break outer; }
+    while (i) do { i = i ? -i : +i; } while ((i));
+    switch (i) { case 1: throw new Error(i); default: }
+}
+try { f`a${b}c`; } catch ({ a = 1, ...b }) {} finally { a?.b(...c); }
+function* g() { yield [, a] = [1]; return new.target && import("m"); }
+class A extends B { static { super.x(); } #p; m() { return this.#p in this; } }
+"#;
+
+const MODULE: &str = r#"
+import d, * as ns from "a";
+import { x as y } from "b";
+export * as all from "c";
+export default class {}
+export { y as z };
+await import.meta.resolve("d");
+"#;
+
+/// Counts the nodes a visitor enters, by the name of their `NodeRef`
+/// variant, and keeps their spans.
+#[derive(Default)]
+struct Counter {
+    counts: BTreeMap<String, usize>,
+    spans: Vec<(i32, i32)>,
+}
+
+impl Visit for Counter {
+    fn enter(&mut self, node: NodeRef<'_>) -> VisitControl {
+        let debug = format!("{:?}", node);
+        let name = debug.split('(').next().unwrap().to_owned();
+        *self.counts.entry(name).or_default() += 1;
+        self.spans.push((node.span().start, node.span().end));
+        VisitControl::Continue
+    }
+}
+
+/// The spans of the nodes in the JSON of `program`, found from their
+/// `type` rather than by walking the tree.
+fn json_spans(program: &Program) -> Vec<(i32, i32)> {
+    fn collect(value: &Value, spans: &mut Vec<(i32, i32)>) {
+        match value {
+            Value::Object(map) => {
+                if map.contains_key("type") {
+                    spans.push((
+                        map["start"].as_i64().unwrap() as i32,
+                        map["end"].as_i64().unwrap() as i32,
+                    ));
+                }
+                map.values().for_each(|value| collect(value, spans));
+            }
+            Value::Array(values) => values.iter().for_each(|value| collect(value, spans)),
+            _ => {}
+        }
+    }
+    let mut spans = vec![];
+    collect(
+        &serde_json::from_str(&to_json(program).unwrap()).unwrap(),
+        &mut spans,
+    );
+    spans
+}
+
+#[test]
+fn reaches_every_node() {
+    let mut counts = BTreeMap::<String, usize>::new();
+    for (input, options) in [
+        (corpus::source(2), module()),
+        (SCRIPT.to_owned(), script()),
+        (MODULE.to_owned(), module()),
+    ] {
+        let options = Options {
+            preserve_parens: true,
+            ..options
+        };
+        let program = assert_parses(&input, &options);
+        let mut counter = Counter::default();
+        counter.visit_program(&program);
+        let mut expected = json_spans(&program);
+        expected.sort_unstable();
+        counter.spans.sort_unstable();
+        assert!(counter.spans == expected, "the nodes of {:?}", input);
+        for (name, count) in counter.counts {
+            *counts.entry(name).or_default() += count;
+        }
+    }
+    let missing: Vec<_> = NODE_TYPES
+        .iter()
+        .filter(|name| !counts.contains_key(**name))
+        .collect();
+    assert!(missing.is_empty(), "{:?} are not reached", missing);
+    assert_eq!(counts.len(), NODE_TYPES.len());
+}
+
+/// Records the nodes entered and left, with the source text of each.
+struct Recorder<'a> {
+    input: &'a str,
+    events: Vec<String>,
+    skip: &'a str,
+}
+
+impl Recorder<'_> {
+    fn record(&mut self, event: &str, node: NodeRef<'_>) {
+        let span = node.span();
+        let debug = format!("{:?}", node);
+        self.events.push(format!(
+            "{} {} {}",
+            event,
+            debug.split('(').next().unwrap(),
+            &self.input[span.start as usize..span.end as usize]
+        ));
+    }
+}
+
+impl Visit for Recorder<'_> {
+    fn enter(&mut self, node: NodeRef<'_>) -> VisitControl {
+        self.record("enter", node);
+        let debug = format!("{:?}", node);
+        if debug.starts_with(self.skip) {
+            VisitControl::Skip
+        } else {
+            VisitControl::Continue
+        }
+    }
+
+    fn leave(&mut self, node: NodeRef<'_>) {
+        self.record("leave", node);
+    }
+}
+
+fn events(input: &str, skip: &str) -> Vec<String> {
+    let mut recorder = Recorder {
+        input,
+        events: vec![],
+        skip,
+    };
+    recorder.visit_program(&assert_parses(input, &script()));
+    recorder.events
+}
+
+#[test]
+fn enters_and_leaves_in_source_order() {
+    assert_eq!(
+        events("a + b * c", "-"),
+        [
+            "enter Program a + b * c",
+            "enter ExpressionStatement a + b * c",
+            "enter BinaryExpression a + b * c",
+            "enter Identifier a",
+            "leave Identifier a",
+            "enter BinaryExpression b * c",
+            "enter Identifier b",
+            "leave Identifier b",
+            "enter Identifier c",
+            "leave Identifier c",
+            "leave BinaryExpression b * c",
+            "leave BinaryExpression a + b * c",
+            "leave ExpressionStatement a + b * c",
+            "leave Program a + b * c",
+        ]
+    );
+}
+
+#[test]
+fn skip_visits_neither_children_nor_leave() {
+    assert_eq!(
+        events("f(a + b, c)", "BinaryExpression"),
+        [
+            "enter Program f(a + b, c)",
+            "enter ExpressionStatement f(a + b, c)",
+            "enter CallExpression f(a + b, c)",
+            "enter Identifier f",
+            "leave Identifier f",
+            "enter BinaryExpression a + b",
+            "enter Identifier c",
+            "leave Identifier c",
+            "leave CallExpression f(a + b, c)",
+            "leave ExpressionStatement f(a + b, c)",
+            "leave Program f(a + b, c)",
+        ]
+    );
+}
+
+/// Replaces every binary expression by its left operand, and renames the
+/// identifiers.
+struct Simplifier;
+
+impl VisitMut for Simplifier {
+    fn visit_expression(&mut self, node: &mut Expression) {
+        walk_expression(self, node);
+        if let Expression::BinaryExpression(binary) = node {
+            *node = binary.left.clone();
+        }
+    }
+
+    fn visit_identifier(&mut self, node: &mut Identifier) {
+        node.name = format!("_{}", node.name).into();
+    }
+}
+
+#[test]
+fn replaces_nodes_through_their_enum() {
+    let mut program = assert_parses("x = (a + b) * c; f(a - 1, [b | c]);", &script());
+    Simplifier.visit_program(&mut program);
+    assert_eq!(
+        positionless(&program),
+        positionless(&assert_parses("_x = _a; _f(_a, [_b]);", &script()))
+    );
+}